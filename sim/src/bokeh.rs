@@ -0,0 +1,80 @@
+use std::f32::consts::TAU;
+
+use crate::rng::Rng;
+
+// Radians per frame of a mote's wobble and shimmer.
+const WOBBLE_SPEED: f32 = 0.004;
+
+/// Big, soft motes of dust drifting in front of the sky, as if out of
+/// focus close to the lens.
+#[derive(Clone, Copy)]
+pub struct BokehDust {
+    /// Smallest and largest mote diameters, in CSS pixels.
+    pub size: (f32, f32),
+    /// Drift of the largest motes, in CSS pixels per frame; smaller ones,
+    /// farther off, drift slower.
+    pub drift: (f32, f32),
+    /// Alpha of the largest motes at their brightest.
+    pub alpha: f32,
+}
+
+/// One mote, placed in fractions of the sky so a resize keeps the layout.
+pub struct Mote {
+    pub x: f32,
+    pub y: f32,
+    /// 0 for the smallest, farthest motes, 1 for the largest.
+    pub depth: f32,
+    /// Radians through its wobble and shimmer.
+    pub phase: f32,
+}
+
+impl BokehDust {
+    pub const DEFAULT: BokehDust = BokehDust { size: (24.0, 72.0), drift: (0.08, -0.03), alpha: 0.08 };
+
+    /// `count` motes spread over the sky, most of them small.
+    pub fn scatter(&self, rng: &mut Rng, count: usize) -> Vec<Mote> {
+        (0..count)
+            .map(|_| Mote {
+                x: rng.next_f32(),
+                y: rng.next_f32(),
+                depth: rng.next_f32() * rng.next_f32(),
+                phase: rng.range((0.0, TAU)),
+            })
+            .collect()
+    }
+
+    /// Moves the motes `dt` frames in a `width` x `height` sky with `scale`
+    /// device pixels per CSS pixel. Motes leaving one edge come back at the
+    /// other once fully out of sight.
+    pub fn step(&self, motes: &mut [Mote], (width, height): (f32, f32), scale: f32, dt: f32) {
+        if width <= 0.0 || height <= 0.0 {
+            return;
+        }
+        for mote in motes {
+            let speed = (0.3 + 0.7 * mote.depth) * scale * dt;
+            let radius = self.diameter(mote) * scale / 2.0;
+            let wrap = |position: f32, delta: f32, extent: f32| {
+                let margin = radius / extent;
+                (position + delta / extent + margin).rem_euclid(1.0 + 2.0 * margin) - margin
+            };
+            mote.x = wrap(mote.x, self.drift.0 * speed, width);
+            mote.y = wrap(mote.y, self.drift.1 * speed, height);
+            mote.phase = (mote.phase + WOBBLE_SPEED * dt) % TAU;
+        }
+    }
+
+    /// Diameter of `mote` in CSS pixels.
+    pub fn diameter(&self, mote: &Mote) -> f32 {
+        self.size.0 + (self.size.1 - self.size.0) * mote.depth
+    }
+
+    /// Where `mote` is in a `width` x `height` sky, with its slow wobble,
+    /// and its alpha.
+    pub fn place(&self, mote: &Mote, (width, height): (f32, f32), scale: f32) -> (f32, f32, f32) {
+        let wobble = self.diameter(mote) * scale * 0.15;
+        let x = mote.x * width + mote.phase.sin() * wobble;
+        let y = mote.y * height + (mote.phase * 2.0).cos() * wobble * 0.5;
+        let alpha = self.alpha * (0.4 + 0.6 * mote.depth) * (0.8 + 0.2 * (mote.phase * 3.0).sin());
+        (x, y, alpha)
+    }
+}
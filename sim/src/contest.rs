@@ -0,0 +1,41 @@
+/// Where a contest stands at a given moment.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ContestPhase {
+    Before,
+    Running,
+    /// The last minute before the end.
+    FinalMinute,
+    After,
+}
+
+/// A contest's start and end, in Unix milliseconds.
+#[derive(Clone, Copy, PartialEq)]
+pub struct ContestSchedule {
+    start_ms: f64,
+    end_ms: f64,
+}
+
+impl ContestSchedule {
+    /// Length of `ContestPhase::FinalMinute`, cut short for contests under
+    /// two minutes.
+    pub const FINAL_MINUTE_MS: f64 = 60_000.0;
+
+    /// `None` unless both are finite and the contest ends after it starts.
+    pub fn new(start_ms: f64, end_ms: f64) -> Option<ContestSchedule> {
+        (start_ms.is_finite() && end_ms.is_finite() && end_ms > start_ms)
+            .then_some(ContestSchedule { start_ms, end_ms })
+    }
+
+    pub fn phase(&self, now_ms: f64) -> ContestPhase {
+        let final_minute = Self::FINAL_MINUTE_MS.min((self.end_ms - self.start_ms) / 2.0);
+        if now_ms < self.start_ms {
+            ContestPhase::Before
+        } else if now_ms >= self.end_ms {
+            ContestPhase::After
+        } else if now_ms >= self.end_ms - final_minute {
+            ContestPhase::FinalMinute
+        } else {
+            ContestPhase::Running
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::f32::consts::TAU;
+
+use crate::rng::Rng;
+
+/// A band of specks orbiting along an elliptical arc, like an orbital
+/// debris ring or an asteroid belt.
+#[derive(Clone, Copy)]
+pub struct DebrisRing {
+    /// Center as fractions of the sky's width and height.
+    pub center: (f32, f32),
+    /// Half the ellipse's width, as a fraction of the sky's width.
+    pub radius: f32,
+    /// The ellipse's height relative to its width; 1 is a circle.
+    pub flatten: f32,
+    /// Radians clockwise.
+    pub rotation: f32,
+    /// The visible arc, in radians clockwise from the ellipse's long axis;
+    /// specks leaving one end come back at the other.
+    pub arc: (f32, f32),
+    /// Width of the band as a fraction of the radius.
+    pub thickness: f32,
+    /// Radians per frame of an average speck; negative orbits the other way.
+    pub speed: f32,
+}
+
+/// One speck's place on the ring.
+pub struct Speck {
+    pub angle: f32,
+    /// Across the band, from -0.5 to 0.5 of its thickness.
+    pub offset: f32,
+    /// A multiplier on the ring's speed.
+    pub speed: f32,
+}
+
+impl DebrisRing {
+    pub const DEFAULT: DebrisRing = DebrisRing {
+        center: (0.5, 0.5),
+        radius: 0.45,
+        flatten: 0.25,
+        rotation: -0.2,
+        arc: (0.0, TAU),
+        thickness: 0.08,
+        speed: 0.002,
+    };
+
+    /// `count` specks spread along the arc, denser toward the band's middle.
+    pub fn scatter(&self, rng: &mut Rng, count: usize) -> Vec<Speck> {
+        (0..count)
+            .map(|_| Speck {
+                angle: rng.range(self.arc),
+                offset: (rng.next_f32() + rng.next_f32()) / 2.0 - 0.5,
+                speed: rng.range((0.6, 1.4)),
+            })
+            .collect()
+    }
+
+    /// Moves the specks `dt` frames along the arc.
+    pub fn step(&self, specks: &mut [Speck], dt: f32) {
+        let (start, end) = self.arc;
+        let span = end - start;
+        if span <= 0.0 {
+            return;
+        }
+        for speck in specks {
+            speck.angle = start + (speck.angle + self.speed * speck.speed * dt - start).rem_euclid(span);
+        }
+    }
+
+    /// Where `speck` is in a `width` x `height` sky, and an alpha factor
+    /// fading it in and out at the ends of a partial arc.
+    pub fn place(&self, speck: &Speck, (width, height): (f32, f32)) -> (f32, f32, f32) {
+        let radius = self.radius * width * (1.0 + speck.offset * self.thickness);
+        let (sin, cos) = speck.angle.sin_cos();
+        let (ex, ey) = (radius * cos, radius * self.flatten * sin);
+        let (rotation_sin, rotation_cos) = self.rotation.sin_cos();
+        let x = self.center.0 * width + ex * rotation_cos - ey * rotation_sin;
+        let y = self.center.1 * height + ex * rotation_sin + ey * rotation_cos;
+        let span = self.arc.1 - self.arc.0;
+        let fade = if span >= TAU {
+            1.0
+        } else {
+            let edge = (speck.angle - self.arc.0).min(self.arc.1 - speck.angle);
+            (edge / (span * 0.1)).clamp(0.0, 1.0)
+        };
+        (x, y, fade)
+    }
+}
@@ -0,0 +1,75 @@
+use crate::particles::ParticleStore;
+
+/// A slowly evolving current: the curl of a noise field, which has no
+/// sources or sinks, so particles following it swirl without bunching up or
+/// thinning out.
+#[derive(Clone, Copy)]
+pub struct FlowField {
+    /// Rough width of one swirl, in the particles' units.
+    pub scale: f32,
+    /// Speed of a typical current, in units per frame.
+    pub speed: f32,
+    /// How fast the currents change shape, per unit of `time`.
+    pub evolution: f32,
+}
+
+impl FlowField {
+    pub const DEFAULT: FlowField = FlowField { scale: 400.0, speed: 0.1, evolution: 0.02 };
+
+    /// The current at (`x`, `y`) at `time`.
+    pub fn velocity(&self, x: f32, y: f32, time: f32) -> (f32, f32) {
+        if self.scale <= 0.0 {
+            return (0.0, 0.0);
+        }
+        let (dx, dy) = noise_gradient(x / self.scale, y / self.scale, time * self.evolution);
+        // Value noise gradients average about 1.
+        (dy * self.speed, -dx * self.speed)
+    }
+
+    /// Eases each particle's velocity toward the current where it is, over
+    /// `dt` frames, so it joins the flow within a second or two.
+    pub fn steer<T>(&self, particles: &mut ParticleStore<T>, time: f32, dt: f32) {
+        const EASE_PER_FRAME: f32 = 0.97;
+        let blend = 1.0 - EASE_PER_FRAME.powf(dt);
+        for i in 0..particles.len() {
+            let (vx, vy) = self.velocity(particles.x[i], particles.y[i], time);
+            particles.vx[i] += (vx - particles.vx[i]) * blend;
+            particles.vy[i] += (vy - particles.vy[i]) * blend;
+        }
+    }
+}
+
+// The x and y derivatives of smooth 3D value noise at (`x`, `y`, `z`).
+fn noise_gradient(x: f32, y: f32, z: f32) -> (f32, f32) {
+    let (ix, iy, iz) = (x.floor(), y.floor(), z.floor());
+    let (fx, fy, fz) = (x - ix, y - iy, z - iz);
+    let (ix, iy, iz) = (ix as i32, iy as i32, iz as i32);
+    let corner = |dx, dy, dz| lattice(ix.wrapping_add(dx), iy.wrapping_add(dy), iz.wrapping_add(dz));
+    let (c000, c100, c010, c110) = (corner(0, 0, 0), corner(1, 0, 0), corner(0, 1, 0), corner(1, 1, 0));
+    let (c001, c101, c011, c111) = (corner(0, 0, 1), corner(1, 0, 1), corner(0, 1, 1), corner(1, 1, 1));
+    // Quintic fade, whose first and second derivatives vanish at the
+    // lattice, keeping the curl smooth.
+    let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let fade_slope = |t: f32| 30.0 * t * t * (t * (t - 2.0) + 1.0);
+    let (u, v, w) = (fade(fx), fade(fy), fade(fz));
+    let k1 = c100 - c000;
+    let k2 = c010 - c000;
+    let k4 = c000 - c100 - c010 + c110;
+    let k5 = c000 - c010 - c001 + c011;
+    let k6 = c000 - c100 - c001 + c101;
+    let k7 = -c000 + c100 + c010 - c110 + c001 - c101 - c011 + c111;
+    let dx = fade_slope(fx) * (k1 + k4 * v + k6 * w + k7 * v * w);
+    let dy = fade_slope(fy) * (k2 + k4 * u + k5 * w + k7 * u * w);
+    (dx, dy)
+}
+
+// A pseudo-random value from -1 to 1 for a lattice point.
+fn lattice(x: i32, y: i32, z: i32) -> f32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ (z as u32).wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2c1b_3c6d);
+    h ^= h >> 12;
+    h as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
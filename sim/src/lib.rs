@@ -0,0 +1,23 @@
+//! The starfield's simulation without the browser: particle storage, star
+//! and meteor motion, the meteor-shower calendar, contest phases, observer
+//! sky positions, flow fields, debris rings, bokeh dust, plexus links,
+//! exclusion zones and the random source they draw from. Nothing here
+//! touches wasm-bindgen or WebGL, so it builds natively for benchmarks,
+//! fuzzing or a desktop screensaver; `soj-wasm` adds rendering and the
+//! JavaScript API on top.
+
+pub mod bokeh;
+pub mod contest;
+pub mod debris;
+pub mod flow;
+pub mod meteors;
+pub mod particles;
+pub mod plexus;
+pub mod rng;
+pub mod showers;
+pub mod sky;
+pub mod stars;
+pub mod zones;
+
+pub use particles::{ParticleStore, Spawn};
+pub use rng::Rng;
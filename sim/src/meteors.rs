@@ -0,0 +1,156 @@
+use crate::particles::{ParticleStore, Spawn};
+use crate::rng::Rng;
+
+/// Trail length in CSS pixels.
+pub const TRAIL_LENGTH: f32 = 300.0;
+/// Trail width in CSS pixels.
+pub const WIDTH: f32 = 0.5;
+const COLOR: [f32; 3] = [1.0, 1.0, 0.8];
+
+/// A meteor's `color` is its head's; its data is its tail's.
+pub type Meteors = ParticleStore<[f32; 3]>;
+
+/// The trail fades from the head color to the tail color.
+#[derive(Clone, Copy)]
+pub struct MeteorColors {
+    pub head: [f32; 3],
+    pub tail: [f32; 3],
+}
+
+impl MeteorColors {
+    pub const DEFAULT: MeteorColors = MeteorColors { head: COLOR, tail: COLOR };
+}
+
+impl Default for MeteorColors {
+    fn default() -> MeteorColors {
+        MeteorColors::DEFAULT
+    }
+}
+
+/// Which way meteors fly and where they enter.
+pub struct MeteorTrajectory {
+    /// Radians clockwise from rightward; within (0, PI) so meteors fall.
+    pub angle_range: (f32, f32),
+    /// CSS pixels per frame.
+    pub speed_range: (f32, f32),
+    pub from_top: bool,
+    pub from_side: bool,
+}
+
+impl MeteorTrajectory {
+    pub const DEFAULT: MeteorTrajectory = MeteorTrajectory {
+        angle_range: (std::f32::consts::PI / 6.0, std::f32::consts::PI / 3.0),
+        speed_range: (6.0, 12.0),
+        from_top: true,
+        from_side: true,
+    };
+}
+
+/// Relative meteor rates through the day, one weight per hour, so the sky
+/// can be quiet during the workday and lively in the evening.
+#[derive(Clone, Copy)]
+pub struct MeteorSchedule {
+    hourly: [f32; 24],
+}
+
+impl MeteorSchedule {
+    /// `hourly[h]` scales the rate from `h`:00 to `h + 1`:00. `None` if a
+    /// weight is negative or not finite.
+    pub fn new(hourly: [f32; 24]) -> Option<MeteorSchedule> {
+        hourly.iter().all(|weight| weight.is_finite() && *weight >= 0.0).then_some(MeteorSchedule { hourly })
+    }
+
+    /// The weight at `hour` (0 to 24, fractional), eased between the middles
+    /// of neighboring hours so the rate never jumps.
+    pub fn weight(&self, hour: f32) -> f32 {
+        let t = (hour - 0.5).rem_euclid(24.0);
+        let from = t.floor() as usize % 24;
+        let blend = t.fract();
+        self.hourly[from] + (self.hourly[(from + 1) % 24] - self.hourly[from]) * blend
+    }
+}
+
+/// A meteor starting just outside the top or side edge of a `resolution`
+/// sky, so it flies in rather than appearing mid-canvas, living until its
+/// tail has left. `size_scale` converts CSS to canvas pixels. `None` if the
+/// trajectory allows neither edge.
+pub fn launch_meteor(
+    rng: &mut Rng,
+    trajectory: &MeteorTrajectory,
+    colors: MeteorColors,
+    (width, height): (f32, f32),
+    size_scale: f32,
+) -> Option<Spawn<[f32; 3]>> {
+    let angle = rng.range(trajectory.angle_range);
+    let speed = rng.range(trajectory.speed_range) * size_scale;
+    let (vx, vy) = (speed * angle.cos(), speed * angle.sin());
+
+    // Pick an edge in proportion to its length so meteors are spread
+    // evenly along both; the side edge is whichever one vx points away from.
+    let top_weight = if trajectory.from_top { width } else { 0.0 };
+    let side_weight = if trajectory.from_side { height } else { 0.0 };
+    if top_weight + side_weight <= 0.0 {
+        return None;
+    }
+    let on_top = rng.next_f32() * (top_weight + side_weight) < top_weight;
+    let (x, y) = if on_top {
+        (rng.next_f32() * width, -1.0)
+    } else {
+        let side_x = if vx >= 0.0 { -1.0 } else { width + 1.0 };
+        (side_x, rng.next_f32() * height)
+    };
+
+    let exit_y = (height - y) / vy;
+    let exit_x = match vx {
+        vx if vx > 0.0 => (width - x) / vx,
+        vx if vx < 0.0 => -x / vx,
+        _ => f32::INFINITY,
+    };
+    let max_lifetime = exit_x.min(exit_y) + TRAIL_LENGTH * size_scale / speed;
+    Some(Spawn { x, y, vx, vy, color: colors.head, size: WIDTH, max_lifetime, data: colors.tail })
+}
+
+/// A curved flight between two points: a quadratic bezier bowed upward, so
+/// a meteor sent between two UI elements arcs over rather than sliding
+/// straight across.
+#[derive(Clone, Copy)]
+pub struct GuidedPath {
+    pub from: (f32, f32),
+    pub control: (f32, f32),
+    pub to: (f32, f32),
+}
+
+impl GuidedPath {
+    /// How far the middle of the arc bows out, as a fraction of the
+    /// distance flown.
+    pub const BEND: f32 = 0.25;
+
+    pub fn new(from: (f32, f32), to: (f32, f32)) -> GuidedPath {
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        // Of the two perpendiculars, the one pointing up the screen.
+        let (nx, ny) = if dx >= 0.0 { (dy, -dx) } else { (-dy, dx) };
+        let middle = ((from.0 + to.0) / 2.0, (from.1 + to.1) / 2.0);
+        GuidedPath { from, control: (middle.0 + nx * Self::BEND, middle.1 + ny * Self::BEND), to }
+    }
+
+    /// The point `t` of the way along, from 0 to 1.
+    pub fn point(&self, t: f32) -> (f32, f32) {
+        let (a, b, c) = ((1.0 - t) * (1.0 - t), 2.0 * (1.0 - t) * t, t * t);
+        (
+            a * self.from.0 + b * self.control.0 + c * self.to.0,
+            a * self.from.1 + b * self.control.1 + c * self.to.1,
+        )
+    }
+
+    /// Approximate length, summed over short chords.
+    pub fn length(&self) -> f32 {
+        const CHORDS: usize = 16;
+        (0..CHORDS)
+            .map(|i| {
+                let (x0, y0) = self.point(i as f32 / CHORDS as f32);
+                let (x1, y1) = self.point((i + 1) as f32 / CHORDS as f32);
+                ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt()
+            })
+            .sum()
+    }
+}
@@ -0,0 +1,200 @@
+/// Struct-of-arrays particle storage: one vector per component, so the
+/// per-frame loops walk contiguous memory instead of striding over whole
+/// structs. Stars, meteors and effect sparks each keep a store; `T` holds
+/// what only that effect needs (twinkle state, gravity, ...).
+pub struct ParticleStore<T> {
+    pub x: Vec<f32>,
+    pub y: Vec<f32>,
+    pub vx: Vec<f32>,
+    pub vy: Vec<f32>,
+    pub color: Vec<[f32; 3]>,
+    // In the owning effect's units: star radius, spark diameter in CSS pixels.
+    pub size: Vec<f32>,
+    // Frames lived and frames allowed; stars never expire.
+    pub lifetime: Vec<f32>,
+    pub max_lifetime: Vec<f32>,
+    pub data: Vec<T>,
+}
+
+/// One particle's components, for `ParticleStore::push`.
+pub struct Spawn<T> {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub color: [f32; 3],
+    pub size: f32,
+    pub max_lifetime: f32,
+    pub data: T,
+}
+
+impl<T> ParticleStore<T> {
+    pub fn with_capacity(capacity: usize) -> ParticleStore<T> {
+        ParticleStore {
+            x: Vec::with_capacity(capacity),
+            y: Vec::with_capacity(capacity),
+            vx: Vec::with_capacity(capacity),
+            vy: Vec::with_capacity(capacity),
+            color: Vec::with_capacity(capacity),
+            size: Vec::with_capacity(capacity),
+            lifetime: Vec::with_capacity(capacity),
+            max_lifetime: Vec::with_capacity(capacity),
+            data: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    pub fn push(&mut self, spawn: Spawn<T>) {
+        self.x.push(spawn.x);
+        self.y.push(spawn.y);
+        self.vx.push(spawn.vx);
+        self.vy.push(spawn.vy);
+        self.color.push(spawn.color);
+        self.size.push(spawn.size);
+        self.lifetime.push(0.0);
+        self.max_lifetime.push(spawn.max_lifetime);
+        self.data.push(spawn.data);
+    }
+
+    pub fn swap_remove(&mut self, index: usize) {
+        self.x.swap_remove(index);
+        self.y.swap_remove(index);
+        self.vx.swap_remove(index);
+        self.vy.swap_remove(index);
+        self.color.swap_remove(index);
+        self.size.swap_remove(index);
+        self.lifetime.swap_remove(index);
+        self.max_lifetime.swap_remove(index);
+        self.data.swap_remove(index);
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        self.x.truncate(len);
+        self.y.truncate(len);
+        self.vx.truncate(len);
+        self.vy.truncate(len);
+        self.color.truncate(len);
+        self.size.truncate(len);
+        self.lifetime.truncate(len);
+        self.max_lifetime.truncate(len);
+        self.data.truncate(len);
+    }
+
+    /// Keeps the particles `keep` accepts, in order.
+    pub fn retain(&mut self, mut keep: impl FnMut(&ParticleStore<T>, usize) -> bool) {
+        let mask: Vec<bool> = (0..self.len()).map(|i| keep(self, i)).collect();
+        if mask.iter().all(|&kept| kept) {
+            return;
+        }
+        compact(&mut self.x, &mask);
+        compact(&mut self.y, &mask);
+        compact(&mut self.vx, &mask);
+        compact(&mut self.vy, &mask);
+        compact(&mut self.color, &mask);
+        compact(&mut self.size, &mask);
+        compact(&mut self.lifetime, &mask);
+        compact(&mut self.max_lifetime, &mask);
+        compact(&mut self.data, &mask);
+    }
+
+    /// Moves every particle by its velocity and ages it by `dt` frames.
+    pub fn integrate(&mut self, dt: f32) {
+        for (x, vx) in self.x.iter_mut().zip(&self.vx) {
+            *x += vx * dt;
+        }
+        for (y, vy) in self.y.iter_mut().zip(&self.vy) {
+            *y += vy * dt;
+        }
+        for lifetime in &mut self.lifetime {
+            *lifetime += dt;
+        }
+    }
+
+    pub fn remove_expired(&mut self) {
+        self.retain(|store, i| store.lifetime[i] < store.max_lifetime[i]);
+    }
+
+    /// 1 when born, falling linearly to 0 at `max_lifetime`.
+    pub fn fade(&self, index: usize) -> f32 {
+        1.0 - self.lifetime[index] / self.max_lifetime[index]
+    }
+}
+
+fn compact<V>(values: &mut Vec<V>, mask: &[bool]) {
+    let mut index = 0;
+    values.retain(|_| {
+        index += 1;
+        mask[index - 1]
+    });
+}
+
+/// Effect particles (bursts, fountains, ...); `data` is each one's gravity
+/// in pixels per frame squared.
+pub type Sparks = ParticleStore<f32>;
+
+/// Pulls sparks down by their gravity, then moves and ages them by `dt`
+/// frames, dropping the expired.
+pub fn step_sparks(sparks: &mut Sparks, dt: f32) {
+    for (vy, gravity) in sparks.vy.iter_mut().zip(&sparks.data) {
+        *vy += gravity * dt;
+    }
+    sparks.integrate(dt);
+    sparks.remove_expired();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spark(x: f32, vx: f32, max_lifetime: f32) -> Spawn<f32> {
+        Spawn { x, y: 0.0, vx, vy: 1.0, color: [1.0; 3], size: 1.0, max_lifetime, data: 0.0 }
+    }
+
+    #[test]
+    fn integrates_and_ages() {
+        let mut store = ParticleStore::with_capacity(2);
+        store.push(spark(0.0, 2.0, 10.0));
+        store.push(spark(5.0, -1.0, 10.0));
+        store.integrate(0.5);
+        assert_eq!(store.x, [1.0, 4.5]);
+        assert_eq!(store.y, [0.5, 0.5]);
+        assert_eq!(store.lifetime, [0.5, 0.5]);
+        assert_eq!(store.fade(0), 0.95);
+    }
+
+    #[test]
+    fn removes_expired_in_order() {
+        let mut store = ParticleStore::with_capacity(4);
+        for (i, max_lifetime) in [3.0, 1.0, 5.0, 2.0].into_iter().enumerate() {
+            store.push(spark(i as f32, 0.0, max_lifetime));
+        }
+        store.integrate(2.0);
+        store.remove_expired();
+        assert_eq!(store.x, [0.0, 2.0]);
+        assert_eq!(store.max_lifetime, [3.0, 5.0]);
+        assert_eq!((store.len(), store.data.len(), store.color.len()), (2, 2, 2));
+        store.integrate(2.0);
+        store.remove_expired();
+        assert_eq!(store.x, [2.0]);
+        store.integrate(f32::INFINITY);
+        store.remove_expired();
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn sparks_fall_by_their_gravity() {
+        let mut sparks = Sparks::with_capacity(1);
+        sparks.push(Spawn { data: 0.5, ..spark(0.0, 0.0, 3.0) });
+        step_sparks(&mut sparks, 1.0);
+        assert_eq!((sparks.vy[0], sparks.y[0]), (1.5, 1.5));
+        step_sparks(&mut sparks, 2.0);
+        assert!(sparks.is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+/// A line between two stars, by index, and how strongly to draw it (0 to 1).
+pub struct Link {
+    pub a: usize,
+    pub b: usize,
+    pub strength: f32,
+}
+
+/// Finds the "plexus" links between stars near a point. Stars are bucketed
+/// into a grid of `link_distance` cells, so each star is only compared with
+/// those in its own and neighboring cells and a frame stays O(n).
+pub struct Plexus {
+    /// Only stars this close to the point are linked.
+    pub radius: f32,
+    /// Stars this close to each other are linked.
+    pub link_distance: f32,
+    // Kept between frames so the map's table is reused.
+    grid: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl Plexus {
+    pub fn new(radius: f32, link_distance: f32) -> Plexus {
+        Plexus { radius, link_distance, grid: HashMap::new() }
+    }
+
+    /// Replaces `links` with up to `max_links` links among the stars at
+    /// (`x`, `y`) within `radius` of `point`. A link fades as its stars get
+    /// farther apart and as its far end nears the edge of the radius.
+    pub fn links(&mut self, x: &[f32], y: &[f32], point: (f32, f32), max_links: usize, links: &mut Vec<Link>) {
+        links.clear();
+        self.grid.clear();
+        if self.radius <= 0.0 || self.link_distance <= 0.0 {
+            return;
+        }
+        let cell = |value: f32| (value / self.link_distance).floor() as i32;
+        let near_point = |i: usize| {
+            let (dx, dy) = (x[i] - point.0, y[i] - point.1);
+            dx * dx + dy * dy < self.radius * self.radius
+        };
+        for i in (0..x.len()).filter(|&i| near_point(i)) {
+            self.grid.entry((cell(x[i]), cell(y[i]))).or_default().push(i);
+        }
+        let point_fade = |i: usize| {
+            let (dx, dy) = (x[i] - point.0, y[i] - point.1);
+            1.0 - (dx * dx + dy * dy).sqrt() / self.radius
+        };
+        for (&(column, row), bucket) in &self.grid {
+            for &a in bucket {
+                for (dc, dr) in [(0, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                    // Half the neighbors, so each pair is visited once.
+                    let Some(neighbors) = self.grid.get(&(column + dc, row + dr)) else {
+                        continue;
+                    };
+                    for &b in neighbors {
+                        if (dc, dr) == (0, 0) && b <= a {
+                            continue;
+                        }
+                        let (dx, dy) = (x[b] - x[a], y[b] - y[a]);
+                        let distance = (dx * dx + dy * dy).sqrt();
+                        if distance >= self.link_distance {
+                            continue;
+                        }
+                        let strength = (1.0 - distance / self.link_distance) * point_fade(a).min(point_fade(b));
+                        links.push(Link { a, b, strength });
+                        if links.len() >= max_links {
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
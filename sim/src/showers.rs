@@ -0,0 +1,83 @@
+use crate::meteors::MeteorTrajectory;
+
+/// A yearly meteor shower, peaking on the same date each year.
+pub struct MeteorShower {
+    pub name: &'static str,
+    /// Month (1 to 12) and day of the peak.
+    pub peak: (u32, u32),
+    /// Days either side of the peak over which activity falls to about 60%.
+    pub width: f32,
+    /// Zenithal hourly rate at the peak.
+    pub zhr: f32,
+    /// Screen direction the meteors fly, in degrees clockwise from rightward,
+    /// as if the radiant sat up and behind the opposite edge.
+    pub direction: f32,
+    /// Entry speed in km/s; fast showers streak faster across the sky.
+    pub velocity: f32,
+}
+
+/// The major showers with their usual peaks.
+pub const SHOWERS: [MeteorShower; 10] = [
+    MeteorShower { name: "Quadrantids", peak: (1, 3), width: 1.0, zhr: 110.0, direction: 125.0, velocity: 41.0 },
+    MeteorShower { name: "Lyrids", peak: (4, 22), width: 1.5, zhr: 18.0, direction: 70.0, velocity: 49.0 },
+    MeteorShower { name: "Eta Aquariids", peak: (5, 6), width: 3.0, zhr: 50.0, direction: 45.0, velocity: 66.0 },
+    MeteorShower { name: "Delta Aquariids", peak: (7, 30), width: 4.0, zhr: 25.0, direction: 50.0, velocity: 41.0 },
+    MeteorShower { name: "Perseids", peak: (8, 12), width: 2.5, zhr: 100.0, direction: 130.0, velocity: 59.0 },
+    MeteorShower { name: "Draconids", peak: (10, 8), width: 1.0, zhr: 10.0, direction: 100.0, velocity: 20.0 },
+    MeteorShower { name: "Orionids", peak: (10, 21), width: 3.0, zhr: 20.0, direction: 55.0, velocity: 66.0 },
+    MeteorShower { name: "Leonids", peak: (11, 17), width: 1.5, zhr: 15.0, direction: 60.0, velocity: 71.0 },
+    MeteorShower { name: "Geminids", peak: (12, 14), width: 1.5, zhr: 150.0, direction: 75.0, velocity: 35.0 },
+    MeteorShower { name: "Ursids", peak: (12, 22), width: 1.0, zhr: 10.0, direction: 110.0, velocity: 33.0 },
+];
+
+// Sporadic meteors per hour on a night without a shower, which the base
+// spawn rate stands for.
+const SPORADIC_RATE: f32 = 10.0;
+// Below this, a shower is too weak to change the sky.
+const MIN_ACTIVITY: f32 = 0.05;
+// Degrees either side of a shower's direction its meteors spread over.
+const SPREAD: f32 = 8.0;
+// The entry speed `MeteorTrajectory::DEFAULT`'s speeds stand for.
+const TYPICAL_VELOCITY: f32 = 45.0;
+
+impl MeteorShower {
+    /// How much a shower at `activity` (0 to 1) multiplies the base rate.
+    pub fn rate(&self, activity: f32) -> f32 {
+        1.0 + self.zhr / SPORADIC_RATE * activity
+    }
+
+    /// `base` turned to fly from this shower's radiant, its speeds scaled
+    /// by the shower's velocity.
+    pub fn trajectory(&self, base: &MeteorTrajectory) -> MeteorTrajectory {
+        let (min, max) = (self.direction - SPREAD, self.direction + SPREAD);
+        let speed = self.velocity / TYPICAL_VELOCITY;
+        MeteorTrajectory {
+            angle_range: (min.max(1.0).to_radians(), max.min(179.0).to_radians()),
+            speed_range: (base.speed_range.0 * speed, base.speed_range.1 * speed),
+            from_top: true,
+            from_side: true,
+        }
+    }
+}
+
+/// The most active shower on `month` (1 to 12) and `day`, with its
+/// activity from 0 to 1, or `None` outside every shower's nights.
+pub fn active_shower(month: u32, day: u32) -> Option<(&'static MeteorShower, f32)> {
+    let today = day_of_year(month, day);
+    SHOWERS
+        .iter()
+        .map(|shower| {
+            let offset = (today - day_of_year(shower.peak.0, shower.peak.1)).rem_euclid(365.0);
+            // Days from the peak, either way round the new year.
+            let days = offset.min(365.0 - offset);
+            (shower, (-0.5 * (days / shower.width).powi(2)).exp())
+        })
+        .filter(|&(_, activity)| activity >= MIN_ACTIVITY)
+        .max_by(|a, b| a.0.rate(a.1).total_cmp(&b.0.rate(b.1)))
+}
+
+// Ignores leap days; showers drift by a day between years anyway.
+fn day_of_year(month: u32, day: u32) -> f32 {
+    const MONTH_STARTS: [u32; 12] = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+    (MONTH_STARTS[(month.clamp(1, 12) - 1) as usize] + day) as f32
+}
@@ -0,0 +1,186 @@
+/// Where on Earth the sky is seen from, in degrees: latitude north
+/// positive, longitude east positive.
+#[derive(Clone, Copy)]
+pub struct Observer {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Observer {
+    /// Altitude above the horizon and azimuth (from north, through east) in
+    /// degrees of a star at right ascension `ra` and declination `dec`
+    /// (degrees, J2000), at `unix_ms` milliseconds since the Unix epoch.
+    /// Ignores precession and refraction, which move stars by well under a
+    /// degree over decades.
+    pub fn horizontal(&self, ra: f64, dec: f64, unix_ms: f64) -> (f64, f64) {
+        let hour_angle = (local_sidereal_degrees(self.longitude, unix_ms) - ra).to_radians();
+        let (dec, latitude) = (dec.to_radians(), self.latitude.to_radians());
+        let altitude = (dec.sin() * latitude.sin() + dec.cos() * latitude.cos() * hour_angle.cos()).asin();
+        let azimuth = (-hour_angle.sin() * dec.cos())
+            .atan2(dec.sin() * latitude.cos() - dec.cos() * latitude.sin() * hour_angle.cos());
+        (altitude.to_degrees(), azimuth.to_degrees().rem_euclid(360.0))
+    }
+}
+
+/// The brightest stars, enough to trace the familiar constellations:
+/// right ascension and declination in degrees (J2000) and visual
+/// magnitude, brightest first.
+pub const BRIGHT_STARS: [(f64, f64, f32); 77] = [
+    (101.287, -16.716, -1.46), // Sirius
+    (95.988, -52.696, -0.74), // Canopus
+    (219.902, -60.834, -0.27), // Rigil Kentaurus
+    (213.915, 19.182, -0.05), // Arcturus
+    (279.235, 38.784, 0.03), // Vega
+    (79.172, 45.998, 0.08), // Capella
+    (78.634, -8.202, 0.13), // Rigel
+    (114.825, 5.225, 0.34), // Procyon
+    (24.429, -57.237, 0.46), // Achernar
+    (88.793, 7.407, 0.50), // Betelgeuse
+    (210.956, -60.373, 0.61), // Hadar
+    (297.696, 8.868, 0.76), // Altair
+    (186.650, -63.099, 0.76), // Acrux
+    (68.980, 16.509, 0.86), // Aldebaran
+    (247.352, -26.432, 0.96), // Antares
+    (201.298, -11.161, 0.97), // Spica
+    (116.329, 28.026, 1.14), // Pollux
+    (344.413, -29.622, 1.16), // Fomalhaut
+    (310.358, 45.280, 1.25), // Deneb
+    (191.930, -59.689, 1.25), // Mimosa
+    (152.093, 11.967, 1.35), // Regulus
+    (104.656, -28.972, 1.50), // Adhara
+    (113.650, 31.888, 1.58), // Castor
+    (263.402, -37.104, 1.62), // Shaula
+    (187.791, -57.113, 1.63), // Gacrux
+    (81.283, 6.350, 1.64), // Bellatrix
+    (81.573, 28.608, 1.65), // Elnath
+    (138.300, -69.717, 1.67), // Miaplacidus
+    (84.053, -1.202, 1.69), // Alnilam
+    (332.058, -46.961, 1.74), // Alnair
+    (85.190, -1.943, 1.77), // Alnitak
+    (193.507, 55.960, 1.77), // Alioth
+    (165.932, 61.751, 1.79), // Dubhe
+    (51.081, 49.861, 1.79), // Mirfak
+    (107.098, -26.393, 1.83), // Wezen
+    (276.043, -34.385, 1.85), // Kaus Australis
+    (125.628, -59.510, 1.86), // Avior
+    (206.885, 49.313, 1.86), // Alkaid
+    (264.330, -42.998, 1.87), // Sargas
+    (89.882, 44.948, 1.90), // Menkalinan
+    (252.166, -69.028, 1.91), // Atria
+    (99.428, 16.399, 1.93), // Alhena
+    (306.412, -56.735, 1.94), // Peacock
+    (37.955, 89.264, 1.98), // Polaris
+    (95.675, -17.956, 1.98), // Mirzam
+    (141.897, -8.659, 1.99), // Alphard
+    (31.793, 23.463, 2.00), // Hamal
+    (10.897, -17.987, 2.02), // Diphda
+    (283.816, -26.297, 2.05), // Nunki
+    (17.433, 35.621, 2.05), // Mirach
+    (211.671, -36.370, 2.06), // Menkent
+    (2.097, 29.091, 2.06), // Alpheratz
+    (86.939, -9.670, 2.06), // Saiph
+    (222.676, 74.156, 2.08), // Kochab
+    (263.734, 12.560, 2.08), // Rasalhague
+    (30.975, 42.330, 2.10), // Almach
+    (47.042, 40.956, 2.12), // Algol
+    (177.265, 14.572, 2.13), // Denebola
+    (83.002, -0.299, 2.23), // Mintaka
+    (305.557, 40.257, 2.23), // Sadr
+    (269.152, 51.489, 2.23), // Eltanin
+    (200.981, 54.925, 2.23), // Mizar
+    (10.127, 56.537, 2.24), // Schedar
+    (2.295, 59.150, 2.28), // Caph
+    (240.083, -22.622, 2.29), // Dschubba
+    (165.460, 56.383, 2.37), // Merak
+    (326.046, 9.875, 2.39), // Enif
+    (345.944, 28.083, 2.42), // Scheat
+    (178.458, 53.695, 2.44), // Phecda
+    (14.177, 60.717, 2.47), // Navi
+    (346.190, 15.205, 2.49), // Markab
+    (21.454, 60.235, 2.68), // Ruchbah
+    (222.720, -16.042, 2.75), // Zubenelgenubi
+    (3.309, 15.184, 2.83), // Algenib
+    (292.680, 27.960, 3.05), // Albireo
+    (183.857, 57.033, 3.31), // Megrez
+    (28.599, 63.670, 3.35), // Segin
+];
+
+/// Where a star at `altitude` and `azimuth` (degrees) lands on a star chart
+/// filling a `width` x `height` sky, as seen lying on one's back with head
+/// to the north: the zenith at the center, north up, east left and the
+/// horizon on the circle through the corners. `None` below the horizon;
+/// stars low in the north or south can land off the top or bottom.
+pub fn chart_position(altitude: f64, azimuth: f64, (width, height): (f32, f32)) -> Option<(f32, f32)> {
+    if altitude < 0.0 {
+        return None;
+    }
+    let horizon = (width as f64).hypot(height as f64) / 2.0;
+    let distance = (90.0 - altitude) / 90.0 * horizon;
+    let (sin, cos) = azimuth.to_radians().sin_cos();
+    Some(((width as f64 / 2.0 - distance * sin) as f32, (height as f64 / 2.0 - distance * cos) as f32))
+}
+
+// The sky's rotation over `longitude` at `unix_ms`: the right ascension on
+// the meridian, in degrees.
+fn local_sidereal_degrees(longitude: f64, unix_ms: f64) -> f64 {
+    // Days since the J2000 epoch.
+    let days = unix_ms / 86_400_000.0 + 2_440_587.5 - 2_451_545.0;
+    (280.460_618_37 + 360.985_647_366_29 * days + longitude).rem_euclid(360.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 0.05, "{} is not close to {}", actual, expected);
+    }
+
+    #[test]
+    fn matches_a_worked_ephemeris() {
+        // M13 from Birmingham at 1998-08-10 23:10 UT: altitude 49.169°,
+        // azimuth 269.146° (Burnett, "Converting RA and DEC to ALT and AZ").
+        let birmingham = Observer { latitude: 52.5, longitude: -1.9166667 };
+        let (altitude, azimuth) = birmingham.horizontal(250.425, 36.466667, 902_790_600_000.0);
+        assert_close(altitude, 49.169122);
+        assert_close(azimuth, 269.146340);
+        assert_close(local_sidereal_degrees(birmingham.longitude, 902_790_600_000.0), 304.80762);
+    }
+
+    #[test]
+    fn declination_is_altitude_at_the_pole() {
+        let pole = Observer { latitude: 90.0, longitude: 0.0 };
+        for unix_ms in [0.0, 1.0e12, 1.7e12] {
+            assert_close(pole.horizontal(101.287, -16.716, unix_ms).0, -16.716);
+            assert_close(pole.horizontal(37.95, 89.264, unix_ms).0, 89.264);
+        }
+    }
+
+    #[test]
+    fn charts_the_zenith_at_the_center() {
+        let sky = (800.0, 600.0);
+        assert_eq!(chart_position(90.0, 123.0, sky), Some((400.0, 300.0)));
+        // The horizon reaches the corners, 500 pixels out.
+        let (x, y) = chart_position(0.0, 0.0, sky).unwrap();
+        assert!((x - 400.0).abs() < 1e-3 && (y + 200.0).abs() < 1e-3);
+        let (x, y) = chart_position(45.0, 90.0, sky).unwrap();
+        assert!((x - 150.0).abs() < 1e-3 && (y - 300.0).abs() < 1e-3);
+        assert_eq!(chart_position(-0.1, 0.0, sky), None);
+    }
+
+    #[test]
+    fn catalog_is_in_range_and_brightest_first() {
+        assert!(BRIGHT_STARS.iter().all(|&(ra, dec, _)| (0.0..360.0).contains(&ra) && (-90.0..=90.0).contains(&dec)));
+        assert!(BRIGHT_STARS.windows(2).all(|pair| pair[0].2 <= pair[1].2));
+    }
+
+    #[test]
+    fn the_sky_turns_once_a_sidereal_day() {
+        let observer = Observer { latitude: 31.2, longitude: 121.5 };
+        let sidereal_day_ms = 86_164_090.5;
+        let (altitude, azimuth) = observer.horizontal(279.234, 38.784, 1.7e12);
+        let (later_altitude, later_azimuth) = observer.horizontal(279.234, 38.784, 1.7e12 + sidereal_day_ms);
+        assert_close(later_altitude, altitude);
+        assert_close(later_azimuth, azimuth);
+    }
+}
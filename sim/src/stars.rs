@@ -0,0 +1,569 @@
+use crate::particles::{ParticleStore, Spawn};
+use crate::rng::Rng;
+
+/// Star-only components. A star's `size` is its radius.
+pub struct Twinkle {
+    pub base_alpha: f32,
+    pub phase: f32,
+    pub speed: f32,
+    /// How far `alpha` swings either side of `base_alpha`.
+    pub amplitude: f32,
+    pub alpha: f32,
+    /// Picks this star's curve in `TwinkleMode::Noise`.
+    pub seed: u32,
+    /// How much of the twinkle shows, 0 to 1, while the star fades in or
+    /// out; 1 once settled.
+    pub visibility: f32,
+    /// `visibility` gained per frame: positive while fading in, negative
+    /// while fading out before the star is dropped.
+    pub fade: f32,
+}
+
+/// Frames a star takes to fade in when added or out when dropped.
+pub const STAR_FADE_FRAMES: f32 = 60.0;
+
+impl Twinkle {
+    /// Fades the star in from wherever it is, e.g. hidden for a new star or
+    /// half gone for one that was fading out.
+    pub fn fade_in(&mut self) {
+        self.fade = 1.0 / STAR_FADE_FRAMES;
+    }
+
+    /// Fades the star out; `step_stars` drops it once gone.
+    pub fn fade_out(&mut self) {
+        self.fade = -1.0 / STAR_FADE_FRAMES;
+    }
+
+    pub fn is_fading_out(&self) -> bool {
+        self.fade < 0.0
+    }
+}
+
+pub type Stars = ParticleStore<Twinkle>;
+
+/// How star brightness varies over time.
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum TwinkleMode {
+    /// A sine wave per star.
+    #[default]
+    Sine,
+    /// Smooth noise per star, so stars that happen to share a phase don't
+    /// breathe in step.
+    Noise,
+}
+
+/// How new stars twinkle.
+#[derive(Clone)]
+pub struct TwinkleConfig {
+    /// How far alpha swings either side of a star's base alpha.
+    pub amplitude: f32,
+    /// Radians of phase per frame, drawn evenly from this range.
+    pub speed: (f32, f32),
+    // Base alphas with cumulative weights, as in `StarPalette`.
+    alphas: Vec<(f32, f32)>,
+}
+
+impl TwinkleConfig {
+    /// The default base alphas, equally likely.
+    pub const DEFAULT_ALPHAS: [(f32, f32); 3] = [(0.5, 1.0), (0.7, 1.0), (0.9, 1.0)];
+
+    /// `alphas` are base alphas with their weights. `None` if it is empty, a
+    /// weight is not positive and finite, or an alpha is outside 0 to 1.
+    pub fn new(amplitude: f32, speed: (f32, f32), alphas: &[(f32, f32)]) -> Option<TwinkleConfig> {
+        if alphas.iter().any(|&(alpha, _)| !(0.0..=1.0).contains(&alpha)) {
+            return None;
+        }
+        Some(TwinkleConfig { amplitude, speed, alphas: cumulative_weights(alphas)? })
+    }
+}
+
+impl Default for TwinkleConfig {
+    /// A 0.3 swing, a slow sine period of 20 to 50 seconds and base alphas
+    /// of 0.5, 0.7 and 0.9 in equal parts.
+    fn default() -> TwinkleConfig {
+        TwinkleConfig::new(0.3, (0.002, 0.005), &TwinkleConfig::DEFAULT_ALPHAS).unwrap()
+    }
+}
+
+/// Weighted star colors. Weights are normalized, so only their ratios
+/// matter.
+#[derive(Clone)]
+pub struct StarPalette {
+    // Each color with the cumulative weight up to and including it; the
+    // last is 1.
+    entries: Vec<([f32; 3], f32)>,
+}
+
+impl StarPalette {
+    /// `None` if `colors` is empty or a weight is not positive and finite.
+    pub fn new(colors: &[([f32; 3], f32)]) -> Option<StarPalette> {
+        Some(StarPalette { entries: cumulative_weights(colors)? })
+    }
+
+    pub fn pick(&self, rng: &mut Rng) -> [f32; 3] {
+        pick_weighted(&self.entries, rng)
+    }
+}
+
+// Each item with the cumulative weight up to and including it, the last
+// exactly 1; `None` if `items` is empty or a weight is not positive and
+// finite.
+fn cumulative_weights<T: Copy>(items: &[(T, f32)]) -> Option<Vec<(T, f32)>> {
+    if items.is_empty() || items.iter().any(|&(_, weight)| !(weight.is_finite() && weight > 0.0)) {
+        return None;
+    }
+    let total: f32 = items.iter().map(|&(_, weight)| weight).sum();
+    let mut cumulative = 0.0;
+    let mut entries: Vec<(T, f32)> = items
+        .iter()
+        .map(|&(item, weight)| {
+            cumulative += weight / total;
+            (item, cumulative)
+        })
+        .collect();
+    // Rounding can leave the last bound just under 1.
+    entries.last_mut().unwrap().1 = 1.0;
+    Some(entries)
+}
+
+// An item from `cumulative_weights`' entries, picked in proportion to its
+// weight.
+fn pick_weighted<T: Copy>(entries: &[(T, f32)], rng: &mut Rng) -> T {
+    let r = rng.next_f32();
+    entries.iter().find(|&&(_, bound)| r < bound).unwrap_or(&entries[entries.len() - 1]).0
+}
+
+impl Default for StarPalette {
+    /// Warm, blue and white in equal parts.
+    fn default() -> StarPalette {
+        StarPalette::new(&[([1.0, 0.8, 0.5], 1.0), ([0.5, 0.8, 1.0], 1.0), ([1.0, 1.0, 1.0], 1.0)]).unwrap()
+    }
+}
+
+/// How star radii are distributed.
+#[derive(Clone)]
+pub struct StarSizes(SizeDistribution);
+
+#[derive(Clone)]
+enum SizeDistribution {
+    Power { min: f32, max: f32, exponent: f32 },
+    // Each radius with the cumulative weight up to and including it; the
+    // last is 1.
+    Histogram(Vec<(f32, f32)>),
+}
+
+impl StarSizes {
+    /// Mostly small stars, a few up to eight times their radius.
+    pub const DEFAULT: StarSizes = StarSizes(SizeDistribution::Power { min: 0.005, max: 0.04, exponent: 2.0 });
+
+    /// `min + (max - min) * r^exponent` for a uniform `r`: exponents above 1
+    /// make small stars commoner, 1 spreads radii evenly. `None` unless
+    /// `0 <= min <= max` and the exponent is positive, all finite.
+    pub fn power(min: f32, max: f32, exponent: f32) -> Option<StarSizes> {
+        let valid = min >= 0.0 && max.is_finite() && min <= max && exponent.is_finite() && exponent > 0.0;
+        valid.then_some(StarSizes(SizeDistribution::Power { min, max, exponent }))
+    }
+
+    /// Radii picked in proportion to their weights. `None` if `radii` is
+    /// empty, a radius is negative or a weight is not positive, or any is
+    /// not finite.
+    pub fn histogram(radii: &[(f32, f32)]) -> Option<StarSizes> {
+        if radii.iter().any(|&(radius, _)| !(radius.is_finite() && radius >= 0.0)) {
+            return None;
+        }
+        Some(StarSizes(SizeDistribution::Histogram(cumulative_weights(radii)?)))
+    }
+
+    /// A radius for a new star.
+    pub fn sample(&self, rng: &mut Rng) -> f32 {
+        match &self.0 {
+            SizeDistribution::Power { min, max, exponent } => min + (max - min) * rng.next_f32().powf(*exponent),
+            SizeDistribution::Histogram(entries) => pick_weighted(entries, rng),
+        }
+    }
+
+    /// The largest radius `sample` returns.
+    pub fn max(&self) -> f32 {
+        match &self.0 {
+            SizeDistribution::Power { max, .. } => *max,
+            SizeDistribution::Histogram(entries) => entries.iter().map(|&(radius, _)| radius).fold(0.0, f32::max),
+        }
+    }
+}
+
+impl Default for StarSizes {
+    fn default() -> StarSizes {
+        StarSizes::DEFAULT
+    }
+}
+
+/// A star at (`x`, `y`) with a random drift, brightness, twinkle and color.
+pub fn new_star(rng: &mut Rng, config: &StarConfig, x: f32, y: f32, radius: f32) -> Spawn<Twinkle> {
+    let vx = (rng.next_f32() - 0.5) * 0.1;
+    let vy = (rng.next_f32() - 0.5) * 0.1;
+    let twinkle = &config.twinkle;
+    let base_alpha = pick_weighted(&twinkle.alphas, rng);
+    let phase = rng.next_f32() * std::f32::consts::TAU;
+    let speed = rng.range(twinkle.speed);
+    let color = config.palette.pick(rng);
+    let seed = rng.next_u64() as u32;
+    Spawn {
+        x,
+        y,
+        vx,
+        vy,
+        color,
+        size: radius,
+        max_lifetime: f32::INFINITY,
+        data: Twinkle {
+            base_alpha,
+            phase,
+            speed,
+            amplitude: twinkle.amplitude,
+            alpha: base_alpha,
+            seed,
+            visibility: 1.0,
+            fade: 0.0,
+        },
+    }
+}
+
+/// A weight grid biasing where stars are placed: a cell gets stars in
+/// proportion to its weight, spread evenly within it.
+#[derive(Clone)]
+pub struct DensityMap {
+    width: usize,
+    height: usize,
+    // Running totals of the row-major weights.
+    cumulative: Vec<f64>,
+}
+
+impl DensityMap {
+    /// `weights` is `width` x `height`, row-major from the top left. `None`
+    /// if its size does not match, a weight is negative or not finite, or
+    /// all are 0.
+    pub fn new(width: usize, height: usize, weights: &[f32]) -> Option<DensityMap> {
+        if width == 0 || height == 0 || weights.len() != width * height {
+            return None;
+        }
+        if weights.iter().any(|&weight| !(weight.is_finite() && weight >= 0.0)) {
+            return None;
+        }
+        let mut total = 0.0;
+        let cumulative: Vec<f64> = weights
+            .iter()
+            .map(|&weight| {
+                total += weight as f64;
+                total
+            })
+            .collect();
+        (total > 0.0).then_some(DensityMap { width, height, cumulative })
+    }
+
+    /// A position as fractions (0 to 1) of the sky's width and height.
+    pub fn sample(&self, rng: &mut Rng) -> (f32, f32) {
+        let total = self.cumulative[self.cumulative.len() - 1];
+        let r = rng.next_f32() as f64 * total;
+        // Empty cells share their predecessor's total, so they are never picked.
+        let cell = self.cumulative.partition_point(|&c| c <= r).min(self.cumulative.len() - 1);
+        let (column, row) = (cell % self.width, cell / self.width);
+        ((column as f32 + rng.next_f32()) / self.width as f32, (row as f32 + rng.next_f32()) / self.height as f32)
+    }
+}
+
+/// An alpha image confining stars to a shape, stretched over the sky. A
+/// star lands on a cell with its alpha as the chance, so soft edges thin
+/// out; inverted, opaque cells keep stars out instead.
+#[derive(Clone)]
+pub struct StarMask {
+    width: usize,
+    height: usize,
+    // Chance a star placed in each cell stays, row-major.
+    acceptance: Vec<f32>,
+    // Draws from the cells directly once rejection keeps failing.
+    fallback: DensityMap,
+}
+
+impl StarMask {
+    /// `alphas` is `width` x `height`, row-major from the top left, each 0
+    /// to 1. `None` if its size does not match, an alpha is out of range or
+    /// the mask leaves nowhere for stars.
+    pub fn new(width: usize, height: usize, alphas: &[f32], invert: bool) -> Option<StarMask> {
+        if alphas.iter().any(|alpha| !(0.0..=1.0).contains(alpha)) {
+            return None;
+        }
+        let acceptance: Vec<f32> = alphas.iter().map(|&alpha| if invert { 1.0 - alpha } else { alpha }).collect();
+        let fallback = DensityMap::new(width, height, &acceptance)?;
+        Some(StarMask { width, height, acceptance, fallback })
+    }
+
+    /// The chance a star at (`u`, `v`), as fractions of the sky's width and
+    /// height, stays there.
+    pub fn acceptance(&self, u: f32, v: f32) -> f32 {
+        let column = ((u * self.width as f32) as usize).min(self.width - 1);
+        let row = ((v * self.height as f32) as usize).min(self.height - 1);
+        self.acceptance[row * self.width + column]
+    }
+}
+
+/// The dense "milky" band most initial stars fall in.
+#[derive(Clone, Copy)]
+pub struct StarBand {
+    /// Where the band crosses the sky's vertical center line, as a fraction
+    /// of its height from the top.
+    pub center: f32,
+    /// Radians clockwise from rightward.
+    pub angle: f32,
+    /// Standard deviation across the band, as a fraction of the sky's height.
+    pub sigma: f32,
+    /// Chance a star is placed in the band rather than anywhere.
+    pub probability: f32,
+}
+
+impl StarBand {
+    pub const DEFAULT: StarBand = StarBand { center: 0.5, angle: 0.0, sigma: 0.15, probability: 0.8 };
+}
+
+impl Default for StarBand {
+    fn default() -> StarBand {
+        StarBand::DEFAULT
+    }
+}
+
+/// A hotspot gathering big initial stars, such as behind a logo.
+#[derive(Clone, Copy)]
+pub struct StarCluster {
+    /// Center as fractions of the sky's width and height.
+    pub x: f32,
+    pub y: f32,
+    /// Half-extent as a fraction of the sky's width and height, so the
+    /// hotspot is an ellipse on a non-square sky.
+    pub radius: f32,
+    /// Chance a star big enough to join lands here.
+    pub density: f32,
+    /// Smallest star that joins, as a fraction of the largest star's radius;
+    /// 0 lets every star in.
+    pub size_bias: f32,
+    /// 0 spreads the cluster's stars evenly over it; up to 1 gathers them
+    /// ever closer to its center.
+    pub tightness: f32,
+}
+
+impl StarCluster {
+    /// The default hotspot at the center of the sky.
+    pub const CENTER: StarCluster =
+        StarCluster { x: 0.5, y: 0.5, radius: 0.1, density: 0.5, size_bias: 0.875, tightness: 0.0 };
+}
+
+/// How new stars are colored and placed.
+#[derive(Clone)]
+pub struct StarConfig {
+    pub palette: StarPalette,
+    pub sizes: StarSizes,
+    pub twinkle: TwinkleConfig,
+    pub band: StarBand,
+    /// Tried in order before the band.
+    pub clusters: Vec<StarCluster>,
+    /// Replaces the default band-and-center placement of the initial stars.
+    pub density: Option<DensityMap>,
+    /// Confines every new star, initial or added on resize.
+    pub mask: Option<StarMask>,
+}
+
+impl Default for StarConfig {
+    fn default() -> StarConfig {
+        StarConfig {
+            palette: StarPalette::default(),
+            sizes: StarSizes::DEFAULT,
+            twinkle: TwinkleConfig::default(),
+            band: StarBand::DEFAULT,
+            clusters: vec![StarCluster::CENTER],
+            density: None,
+            mask: None,
+        }
+    }
+}
+
+/// Adds `count` stars to a `width` x `height` sky, placed by the config's
+/// density map or else mostly in its band, some of the biggest gathered in
+/// its clusters and the rest anywhere.
+pub fn scatter_stars(rng: &mut Rng, config: &StarConfig, stars: &mut Stars, count: usize, width: f32, height: f32) {
+    for _ in 0..count {
+        let radius = config.sizes.sample(rng);
+        let (x, y) = place_star(rng, config, width, height, |rng| match &config.density {
+            Some(density) => {
+                let (u, v) = density.sample(rng);
+                (u * width, v * height)
+            }
+            None => default_position(rng, config, radius, width, height),
+        });
+        stars.push(new_star(rng, config, x, y, radius));
+    }
+}
+
+/// A position from `candidate` that the config's mask keeps, in a `width`
+/// x `height` sky. After a few rejections it draws from the mask itself.
+pub fn place_star(
+    rng: &mut Rng,
+    config: &StarConfig,
+    width: f32,
+    height: f32,
+    mut candidate: impl FnMut(&mut Rng) -> (f32, f32),
+) -> (f32, f32) {
+    const MASK_ATTEMPTS: usize = 32;
+    let Some(mask) = &config.mask else {
+        return candidate(rng);
+    };
+    for _ in 0..MASK_ATTEMPTS {
+        let (x, y) = candidate(rng);
+        if rng.next_f32() < mask.acceptance(x / width, y / height) {
+            return (x, y);
+        }
+    }
+    let (u, v) = mask.fallback.sample(rng);
+    (u * width, v * height)
+}
+
+fn default_position(rng: &mut Rng, config: &StarConfig, radius: f32, width: f32, height: f32) -> (f32, f32) {
+    // Tries before giving up on a band that lies mostly off-screen.
+    const BAND_ATTEMPTS: usize = 16;
+    for cluster in &config.clusters {
+        if radius >= cluster.size_bias * config.sizes.max() && rng.next_f32() < cluster.density {
+            // The square root is uniform over the ellipse; higher powers pull
+            // stars toward the center.
+            let distance = cluster.radius * rng.next_f32().powf(0.5 + 1.5 * cluster.tightness);
+            let (sin, cos) = (rng.next_f32() * std::f32::consts::TAU).sin_cos();
+            return ((cluster.x + distance * cos) * width, (cluster.y + distance * sin) * height);
+        }
+    }
+    let center_x = width / 2.0;
+    let band = &config.band;
+    if rng.next_f32() < band.probability {
+        // Anywhere along the band's center line, then a Gaussian step across it.
+        let (sin, cos) = band.angle.sin_cos();
+        let diagonal = (width * width + height * height).sqrt();
+        let sigma = height * band.sigma;
+        for _ in 0..BAND_ATTEMPTS {
+            let along = (rng.next_f32() - 0.5) * diagonal;
+            let across = sigma * gaussian(rng);
+            let x = center_x + along * cos - across * sin;
+            let y = band.center * height + along * sin + across * cos;
+            if (0.0..=width).contains(&x) && (0.0..=height).contains(&y) {
+                return (x, y);
+            }
+        }
+    }
+    (rng.next_f32() * width, rng.next_f32() * height)
+}
+
+// A standard normal sample (Box-Muller).
+fn gaussian(rng: &mut Rng) -> f32 {
+    let u1 = rng.next_f32().max(0.000001);
+    let u2 = rng.next_f32();
+    (-2.0 * u1.ln()).sqrt() * (std::f32::consts::TAU * u2).cos()
+}
+
+/// Drifts and twinkles the stars by `dt` frames within a `width` x `height`
+/// sky, their brightness following `twinkle`. `margin_per_radius` converts a radius to half the drawn point size:
+/// stars wrap only once wholly off-screen, so big ones slide out and back in
+/// instead of popping at the edge.
+pub fn step_stars(
+    stars: &mut Stars,
+    dt: f32,
+    (width, height): (f32, f32),
+    margin_per_radius: f32,
+    twinkle: TwinkleMode,
+) {
+    stars.integrate(dt);
+    let damping = 0.995f32.powf(dt);
+    for v in stars.vx.iter_mut().chain(stars.vy.iter_mut()) {
+        *v *= damping;
+    }
+    for i in 0..stars.len() {
+        let margin = stars.size[i] * margin_per_radius;
+        let (x, y) = (&mut stars.x[i], &mut stars.y[i]);
+        if *x > width + margin { *x = -margin; }
+        if *x < -margin { *x = width + margin; }
+        if *y > height + margin { *y = -margin; }
+        if *y < -margin { *y = height + margin; }
+    }
+    let mut faded_out = false;
+    for star in &mut stars.data {
+        if star.fade != 0.0 {
+            star.visibility = (star.visibility + star.fade * dt).clamp(0.0, 1.0);
+            if star.visibility >= 1.0 {
+                star.fade = 0.0;
+            }
+            faded_out |= star.visibility <= 0.0 && star.is_fading_out();
+        }
+        star.phase += star.speed * dt;
+        let wave = match twinkle {
+            TwinkleMode::Sine => star.phase.sin(),
+            // Half the rate keeps noise swings about as slow as the sine's.
+            TwinkleMode::Noise => twinkle_noise(star.phase * 0.5, star.seed),
+        };
+        star.alpha = (star.base_alpha + star.amplitude * wave).clamp(0.0, 1.0) * star.visibility;
+    }
+    if faded_out {
+        stars.retain(|stars, i| !(stars.data[i].visibility <= 0.0 && stars.data[i].is_fading_out()));
+    }
+}
+
+// Smooth 1D gradient noise from about -1 to 1, a different curve per `seed`.
+fn twinkle_noise(x: f32, seed: u32) -> f32 {
+    let cell = x.floor();
+    let t = x - cell;
+    let cell = cell as i32 as u32;
+    let gradient = |i: u32| {
+        let mut h = i.wrapping_mul(0x9e37_79b1) ^ seed.wrapping_mul(0x85eb_ca77);
+        h ^= h >> 16;
+        h = h.wrapping_mul(0x7feb_352d);
+        h ^= h >> 15;
+        h as f32 / u32::MAX as f32 * 2.0 - 1.0
+    };
+    let fade = t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let (a, b) = (gradient(cell) * t, gradient(cell.wrapping_add(1)) * (t - 1.0));
+    (a + (b - a) * fade) * 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_mask_rejects_bad_images() {
+        assert!(StarMask::new(2, 2, &[1.0; 3], false).is_none());
+        assert!(StarMask::new(0, 0, &[], false).is_none());
+        assert!(StarMask::new(2, 1, &[0.5, 1.5], false).is_none());
+        assert!(StarMask::new(2, 1, &[0.5, -0.1], false).is_none());
+        assert!(StarMask::new(2, 1, &[0.5, f32::NAN], false).is_none());
+        assert!(StarMask::new(2, 1, &[0.0, 0.0], false).is_none());
+        assert!(StarMask::new(2, 1, &[1.0, 1.0], true).is_none());
+    }
+
+    #[test]
+    fn star_mask_keeps_stars_in_its_shape() {
+        let mask = StarMask::new(2, 1, &[0.25, 1.0], false).unwrap();
+        assert_eq!((mask.acceptance(0.1, 0.5), mask.acceptance(0.9, 0.5)), (0.25, 1.0));
+        assert_eq!(mask.acceptance(1.0, 1.0), 1.0);
+        let inverted = StarMask::new(2, 1, &[0.25, 1.0], true).unwrap();
+        assert_eq!((inverted.acceptance(0.1, 0.5), inverted.acceptance(0.9, 0.5)), (0.75, 0.0));
+
+        let config = StarConfig { mask: Some(inverted), ..StarConfig::default() };
+        let mut rng = Rng::new(7);
+        for _ in 0..200 {
+            let (x, _) = place_star(&mut rng, &config, 100.0, 100.0, |rng| (rng.next_f32() * 100.0, 50.0));
+            assert!(x < 50.0);
+        }
+    }
+
+    #[test]
+    fn scattered_stars_stay_on_the_sky() {
+        let mut rng = Rng::new(1);
+        let mut stars = Stars::with_capacity(500);
+        scatter_stars(&mut rng, &StarConfig::default(), &mut stars, 500, 300.0, 200.0);
+        assert_eq!(stars.len(), 500);
+        assert!(stars.x.iter().all(|x| (0.0..=300.0).contains(x)));
+        assert!(stars.y.iter().all(|y| (0.0..=200.0).contains(y)));
+    }
+}
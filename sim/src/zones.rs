@@ -0,0 +1,74 @@
+/// A screen rectangle, such as a login card, that bright effects keep away
+/// from.
+#[derive(Clone, Copy)]
+pub struct ExclusionZone {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    /// Width of the soft edge around the rectangle over which effects fade
+    /// back in.
+    pub feather: f32,
+    /// How much effects inside are dimmed, from 0 (not at all) to 1
+    /// (hidden).
+    pub strength: f32,
+}
+
+impl ExclusionZone {
+    /// The zone with its position and sizes multiplied by `scale`.
+    pub fn scaled(self, scale: f32) -> ExclusionZone {
+        ExclusionZone {
+            x: self.x * scale,
+            y: self.y * scale,
+            width: self.width * scale,
+            height: self.height * scale,
+            feather: self.feather * scale,
+            strength: self.strength,
+        }
+    }
+
+    /// The alpha multiplier at (`x`, `y`): `1 - strength` inside, easing to
+    /// 1 across the feather.
+    pub fn attenuation(&self, x: f32, y: f32) -> f32 {
+        let dx = (self.x - x).max(x - self.x - self.width).max(0.0);
+        let dy = (self.y - y).max(y - self.y - self.height).max(0.0);
+        let distance = (dx * dx + dy * dy).sqrt();
+        let inside = if self.feather > 0.0 {
+            let t = 1.0 - (distance / self.feather).min(1.0);
+            t * t * (3.0 - 2.0 * t)
+        } else if distance > 0.0 {
+            0.0
+        } else {
+            1.0
+        };
+        1.0 - self.strength * inside
+    }
+
+    /// Whether the segment from `from` to `to` passes through the zone or
+    /// its feather.
+    pub fn crosses(&self, from: (f32, f32), to: (f32, f32)) -> bool {
+        let f = self.feather;
+        let (min_x, min_y) = (self.x - f, self.y - f);
+        let (max_x, max_y) = (self.x + self.width + f, self.y + self.height + f);
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        // Liang-Barsky: clip the segment's parameter range to each slab.
+        let (mut enter, mut exit) = (0.0_f32, 1.0_f32);
+        for (p, q) in [(-dx, from.0 - min_x), (dx, max_x - from.0), (-dy, from.1 - min_y), (dy, max_y - from.1)] {
+            if p == 0.0 {
+                if q < 0.0 {
+                    return false;
+                }
+            } else if p < 0.0 {
+                enter = enter.max(q / p);
+            } else {
+                exit = exit.min(q / p);
+            }
+        }
+        enter <= exit
+    }
+}
+
+/// The combined alpha multiplier of `zones` at (`x`, `y`).
+pub fn attenuation(zones: &[ExclusionZone], x: f32, y: f32) -> f32 {
+    zones.iter().map(|zone| zone.attenuation(x, y)).product()
+}
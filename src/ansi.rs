@@ -0,0 +1,195 @@
+use wasm_bindgen::prelude::*;
+
+use crate::highlight::escape_html;
+
+#[derive(Clone, Copy, PartialEq, Default)]
+enum Color {
+    #[default]
+    Default,
+    Palette(u8),
+    Rgb(u8, u8, u8),
+}
+
+#[derive(Clone, Copy, PartialEq, Default)]
+struct Style {
+    fg: Color,
+    bg: Color,
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    inverse: bool,
+}
+
+const COLOR_NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+impl Style {
+    fn apply_sgr(&mut self, params: &[u32]) {
+        if params.is_empty() {
+            *self = Style::default();
+            return;
+        }
+        let mut i = 0;
+        while i < params.len() {
+            match params[i] {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                2 => self.dim = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.inverse = true,
+                22 => {
+                    self.bold = false;
+                    self.dim = false;
+                }
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.inverse = false,
+                n @ 30..=37 => self.fg = Color::Palette((n - 30) as u8),
+                n @ 90..=97 => self.fg = Color::Palette((n - 90 + 8) as u8),
+                n @ 40..=47 => self.bg = Color::Palette((n - 40) as u8),
+                n @ 100..=107 => self.bg = Color::Palette((n - 100 + 8) as u8),
+                39 => self.fg = Color::Default,
+                49 => self.bg = Color::Default,
+                n @ (38 | 48) => {
+                    let (color, consumed) = parse_extended_color(&params[i + 1..]);
+                    if let Some(color) = color {
+                        if n == 38 {
+                            self.fg = color;
+                        } else {
+                            self.bg = color;
+                        }
+                    }
+                    i += consumed;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+
+    fn open_tag(&self) -> Option<String> {
+        if *self == Style::default() {
+            return None;
+        }
+        let (fg, bg) = if self.inverse { (self.bg, self.fg) } else { (self.fg, self.bg) };
+        let mut classes = Vec::new();
+        let mut styles = Vec::new();
+        if self.bold {
+            classes.push("ansi-bold".to_string());
+        }
+        if self.dim {
+            classes.push("ansi-dim".to_string());
+        }
+        if self.italic {
+            classes.push("ansi-italic".to_string());
+        }
+        if self.underline {
+            classes.push("ansi-underline".to_string());
+        }
+        if self.inverse {
+            classes.push("ansi-inverse".to_string());
+        }
+        color_attr(fg, "fg", "color", &mut classes, &mut styles);
+        color_attr(bg, "bg", "background-color", &mut classes, &mut styles);
+
+        let mut tag = String::from("<span");
+        if !classes.is_empty() {
+            tag.push_str(&format!(" class=\"{}\"", classes.join(" ")));
+        }
+        if !styles.is_empty() {
+            tag.push_str(&format!(" style=\"{}\"", styles.join(";")));
+        }
+        tag.push('>');
+        Some(tag)
+    }
+}
+
+fn parse_extended_color(params: &[u32]) -> (Option<Color>, usize) {
+    match params {
+        [5, n, ..] => (Some(Color::Palette((*n).min(255) as u8)), 2),
+        [2, r, g, b, ..] => (Some(Color::Rgb((*r).min(255) as u8, (*g).min(255) as u8, (*b).min(255) as u8)), 4),
+        _ => (None, params.len()),
+    }
+}
+
+fn color_attr(color: Color, prefix: &str, property: &str, classes: &mut Vec<String>, styles: &mut Vec<String>) {
+    match color {
+        Color::Default => {}
+        Color::Palette(n) if n < 8 => classes.push(format!("ansi-{}-{}", prefix, COLOR_NAMES[n as usize])),
+        Color::Palette(n) if n < 16 => {
+            classes.push(format!("ansi-{}-bright-{}", prefix, COLOR_NAMES[(n - 8) as usize]))
+        }
+        Color::Palette(n) => {
+            let (r, g, b) = palette_256(n);
+            styles.push(format!("{}:rgb({},{},{})", property, r, g, b));
+        }
+        Color::Rgb(r, g, b) => styles.push(format!("{}:rgb({},{},{})", property, r, g, b)),
+    }
+}
+
+fn palette_256(n: u8) -> (u8, u8, u8) {
+    if n >= 232 {
+        let level = 8 + (n - 232) * 10;
+        return (level, level, level);
+    }
+    let n = n - 16;
+    let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+    (level(n / 36), level((n / 6) % 6), level(n % 6))
+}
+
+/// Converts compiler or checker output containing ANSI escape codes into
+/// HTML. SGR styles become `<span>`s with `ansi-*` classes (16-color
+/// palette, bold, dim, italic, underline, inverse) or inline colors for
+/// 256-color and truecolor codes; other escape sequences are dropped and
+/// all text is HTML-escaped.
+#[wasm_bindgen]
+pub fn ansi_to_html(text: &str) -> String {
+    let mut html = String::with_capacity(text.len() + text.len() / 4);
+    let mut style = Style::default();
+    let mut open = false;
+    let mut rest = text;
+
+    while let Some(esc) = rest.find('\x1b') {
+        escape_html(&rest[..esc], &mut html);
+        rest = &rest[esc + 1..];
+        if let Some(csi) = rest.strip_prefix('[') {
+            let end = csi.find(|c: char| ('\x40'..='\x7e').contains(&c)).unwrap_or(csi.len());
+            let final_byte = csi[end..].chars().next();
+            if final_byte == Some('m') {
+                let params: Vec<u32> = csi[..end]
+                    .split([';', ':'])
+                    .filter(|p| !p.is_empty())
+                    .map(|p| p.parse().unwrap_or(0))
+                    .collect();
+                let mut next = style;
+                next.apply_sgr(&params);
+                if next != style {
+                    if open {
+                        html.push_str("</span>");
+                        open = false;
+                    }
+                    if let Some(tag) = next.open_tag() {
+                        html.push_str(&tag);
+                        open = true;
+                    }
+                    style = next;
+                }
+            }
+            rest = &csi[(end + final_byte.map_or(0, char::len_utf8)).min(csi.len())..];
+        } else if let Some(osc) = rest.strip_prefix(']') {
+            // Operating system commands end with BEL or ESC \.
+            let end = osc.find(['\x07', '\x1b']).unwrap_or(osc.len());
+            rest = &osc[end..];
+            rest = rest.strip_prefix('\x07').or_else(|| rest.strip_prefix("\x1b\\")).unwrap_or(rest);
+        } else {
+            let skip = rest.chars().next().map_or(0, char::len_utf8);
+            rest = &rest[skip..];
+        }
+    }
+    escape_html(rest, &mut html);
+    if open {
+        html.push_str("</span>");
+    }
+    html
+}
@@ -0,0 +1,226 @@
+use std::cell::RefCell;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    window, AudioBuffer, AudioContext, AudioContextState, BiquadFilterType, GainNode, OscillatorType,
+};
+
+use crate::options::invalid_option;
+
+const DEFAULT_VOLUME: f32 = 0.6;
+const NOISE_SECONDS: f32 = 1.0;
+const WHOOSH_SECONDS: f64 = 0.7;
+const CHIME_NOTES: [f32; 3] = [1046.5, 1318.5, 1568.0];
+const CHIME_STAGGER: f64 = 0.09;
+const CHIME_SECONDS: f64 = 1.4;
+const TICK_SECONDS: f64 = 0.04;
+// Autoplay policies only let a context start inside a user gesture.
+const UNLOCK_EVENTS: [&str; 3] = ["pointerdown", "keydown", "touchend"];
+
+#[derive(Clone, Copy)]
+pub enum Sound {
+    Meteor,
+    Accepted,
+    Tick,
+}
+
+impl Sound {
+    pub fn from_name(name: &str) -> Option<Sound> {
+        match name.to_ascii_lowercase().as_str() {
+            "meteor" | "whoosh" => Some(Sound::Meteor),
+            "ac" | "accepted" | "chime" => Some(Sound::Accepted),
+            "tick" => Some(Sound::Tick),
+            _ => None,
+        }
+    }
+}
+
+struct Audio {
+    context: AudioContext,
+    master: GainNode,
+    noise: AudioBuffer,
+    muted: bool,
+    volume: f32,
+}
+
+thread_local! {
+    static AUDIO: RefCell<Option<Audio>> = const { RefCell::new(None) };
+}
+
+impl Audio {
+    fn new() -> Result<Audio, JsValue> {
+        let context = AudioContext::new()?;
+        let master = context.create_gain()?;
+        master.gain().set_value(DEFAULT_VOLUME);
+        master.connect_with_audio_node(&context.destination())?;
+
+        let sample_rate = context.sample_rate();
+        let length = (sample_rate * NOISE_SECONDS) as u32;
+        let noise = context.create_buffer(1, length, sample_rate)?;
+        let samples: Vec<f32> = (0..length).map(|_| js_sys::Math::random() as f32 * 2.0 - 1.0).collect();
+        noise.copy_to_channel(&samples, 0)?;
+
+        Ok(Audio { context, master, noise, muted: false, volume: DEFAULT_VOLUME })
+    }
+
+    fn apply_volume(&self) {
+        let gain = if self.muted { 0.0 } else { self.volume };
+        self.master.gain().set_value(gain);
+    }
+
+    fn play(&self, sound: Sound) -> Result<(), JsValue> {
+        // A suspended context would queue every sound and play them all at
+        // once on unlock, so sounds before the first gesture are dropped.
+        if self.muted || self.context.state() != AudioContextState::Running {
+            return Ok(());
+        }
+        let now = self.context.current_time();
+        match sound {
+            Sound::Meteor => self.whoosh(now),
+            Sound::Accepted => self.chime(now),
+            Sound::Tick => self.tick(now),
+        }
+    }
+
+    // Band-passed noise sweeping downward, like air rushing past.
+    fn whoosh(&self, now: f64) -> Result<(), JsValue> {
+        let source = self.context.create_buffer_source()?;
+        source.set_buffer(Some(&self.noise));
+        let filter = self.context.create_biquad_filter()?;
+        filter.set_type(BiquadFilterType::Bandpass);
+        filter.q().set_value(1.2);
+        filter.frequency().set_value_at_time(2400.0, now)?;
+        filter.frequency().exponential_ramp_to_value_at_time(300.0, now + WHOOSH_SECONDS)?;
+        let envelope = self.context.create_gain()?;
+        envelope.gain().set_value_at_time(0.0001, now)?;
+        envelope.gain().exponential_ramp_to_value_at_time(0.12, now + 0.12)?;
+        envelope.gain().exponential_ramp_to_value_at_time(0.0001, now + WHOOSH_SECONDS)?;
+
+        source.connect_with_audio_node(&filter)?;
+        filter.connect_with_audio_node(&envelope)?;
+        envelope.connect_with_audio_node(&self.master)?;
+        source.start_with_when(now)?;
+        Ok(())
+    }
+
+    // A rising major arpeggio of soft sines.
+    fn chime(&self, now: f64) -> Result<(), JsValue> {
+        for (i, frequency) in CHIME_NOTES.iter().enumerate() {
+            let start = now + i as f64 * CHIME_STAGGER;
+            let oscillator = self.context.create_oscillator()?;
+            oscillator.set_type(OscillatorType::Sine);
+            oscillator.frequency().set_value(*frequency);
+            let envelope = self.context.create_gain()?;
+            envelope.gain().set_value_at_time(0.0001, start)?;
+            envelope.gain().exponential_ramp_to_value_at_time(0.15, start + 0.01)?;
+            envelope.gain().exponential_ramp_to_value_at_time(0.0001, start + CHIME_SECONDS)?;
+
+            oscillator.connect_with_audio_node(&envelope)?;
+            envelope.connect_with_audio_node(&self.master)?;
+            oscillator.start_with_when(start)?;
+            oscillator.stop_with_when(start + CHIME_SECONDS)?;
+        }
+        Ok(())
+    }
+
+    fn tick(&self, now: f64) -> Result<(), JsValue> {
+        let oscillator = self.context.create_oscillator()?;
+        oscillator.set_type(OscillatorType::Triangle);
+        oscillator.frequency().set_value(1800.0);
+        let envelope = self.context.create_gain()?;
+        envelope.gain().set_value_at_time(0.06, now)?;
+        envelope.gain().exponential_ramp_to_value_at_time(0.0001, now + TICK_SECONDS)?;
+
+        oscillator.connect_with_audio_node(&envelope)?;
+        envelope.connect_with_audio_node(&self.master)?;
+        oscillator.start_with_when(now)?;
+        oscillator.stop_with_when(now + TICK_SECONDS)?;
+        Ok(())
+    }
+}
+
+fn install_unlock_listeners() {
+    let window = window().unwrap();
+    let unlock = Closure::wrap(Box::new(move || {
+        AUDIO.with(|audio| {
+            if let Some(audio) = audio.borrow().as_ref() {
+                if audio.context.state() == AudioContextState::Suspended {
+                    let _ = audio.context.resume();
+                }
+            }
+        });
+    }) as Box<dyn FnMut()>);
+    for event in UNLOCK_EVENTS {
+        window
+            .add_event_listener_with_callback(event, unlock.as_ref().unchecked_ref())
+            .unwrap();
+    }
+    unlock.forget();
+}
+
+/// Plays `sound` if sound effects are enabled, unmuted and allowed to play.
+pub(crate) fn play(sound: Sound) {
+    AUDIO.with(|audio| {
+        if let Some(audio) = audio.borrow().as_ref() {
+            if let Err(e) = audio.play(sound) {
+                web_sys::console::warn_1(&e);
+            }
+        }
+    });
+}
+
+/// Turns on the synthesized sound effects (meteor whoosh, AC chime and
+/// countdown ticks). Sounds stay silent until the page receives its first
+/// click, tap or key press, as browsers' autoplay policies require.
+#[wasm_bindgen]
+pub fn enable_sound_effects() -> Result<(), JsValue> {
+    let created = AUDIO.with(|audio| -> Result<bool, JsValue> {
+        let mut audio = audio.borrow_mut();
+        if audio.is_some() {
+            return Ok(false);
+        }
+        *audio = Some(Audio::new()?);
+        Ok(true)
+    })?;
+    if created {
+        install_unlock_listeners();
+    }
+    Ok(())
+}
+
+/// Master mute for all sound effects; takes effect immediately, including
+/// sounds already playing.
+#[wasm_bindgen]
+pub fn set_sound_muted(muted: bool) {
+    AUDIO.with(|audio| {
+        if let Some(audio) = audio.borrow_mut().as_mut() {
+            audio.muted = muted;
+            audio.apply_volume();
+        }
+    });
+}
+
+/// Sets the master volume (0 to 1).
+#[wasm_bindgen]
+pub fn set_sound_volume(volume: f32) -> Result<(), JsValue> {
+    if !(0.0..=1.0).contains(&volume) {
+        return Err(invalid_option("volume", "a number from 0 to 1", &volume.into()));
+    }
+    AUDIO.with(|audio| {
+        if let Some(audio) = audio.borrow_mut().as_mut() {
+            audio.volume = volume;
+            audio.apply_volume();
+        }
+    });
+    Ok(())
+}
+
+/// Plays a sound effect by name: `"meteor"`, `"ac"` or `"tick"`.
+#[wasm_bindgen]
+pub fn play_sound(name: &str) -> Result<(), JsValue> {
+    let sound = Sound::from_name(name)
+        .ok_or_else(|| invalid_option("name", "\"meteor\", \"ac\" or \"tick\"", &name.into()))?;
+    play(sound);
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use std::io::Cursor;
+
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, ImageReader, ImageResult, Limits};
+use wasm_bindgen::prelude::*;
+use web_sys::{Blob, BlobPropertyBag};
+
+const AVATAR_MAX_SIZE: u32 = 1024;
+// Uploads are checked against these before any pixel buffer is allocated.
+const UPLOAD_MAX_SIDE: u32 = 4096;
+const UPLOAD_MAX_ALLOC: u64 = 128 * 1024 * 1024;
+
+/// Decodes an uploaded image, refusing ones too large to handle in the browser.
+pub fn decode_upload(bytes: &[u8]) -> ImageResult<DynamicImage> {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(UPLOAD_MAX_SIDE);
+    limits.max_image_height = Some(UPLOAD_MAX_SIDE);
+    limits.max_alloc = Some(UPLOAD_MAX_ALLOC);
+    let mut reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format()?;
+    reader.limits(limits);
+    reader.decode()
+}
+
+/// Center-crops `image` to a square and scales it to `size` pixels.
+pub fn crop_avatar(image: &DynamicImage, size: u32) -> DynamicImage {
+    let side = image.width().min(image.height());
+    let x = (image.width() - side) / 2;
+    let y = (image.height() - side) / 2;
+    image.crop_imm(x, y, side, side).resize_exact(size, size, FilterType::CatmullRom)
+}
+
+/// Decodes an uploaded image (PNG, JPEG or WebP), center-crops and resizes
+/// it to a `size`×`size` avatar and re-encodes it as `"png"` or `"webp"`
+/// (lossless), returning a `Blob` ready to upload.
+#[wasm_bindgen]
+pub fn resize_avatar(bytes: &[u8], size: u32, format: &str) -> Result<Blob, JsValue> {
+    if size == 0 || size > AVATAR_MAX_SIZE {
+        return Err(JsValue::from_str(&format!("Avatar size must be between 1 and {}", AVATAR_MAX_SIZE)));
+    }
+    let (format, mime) = match format.to_ascii_lowercase().as_str() {
+        "png" => (ImageFormat::Png, "image/png"),
+        "webp" => (ImageFormat::WebP, "image/webp"),
+        _ => return Err(JsValue::from_str(&format!("Unsupported avatar format: {}", format))),
+    };
+    let image = decode_upload(bytes)
+        .map_err(|e| JsValue::from_str(&format!("Failed to decode image: {}", e)))?;
+    // Both encoders want 8-bit RGBA; this also drops 16-bit channels.
+    let avatar = DynamicImage::ImageRgba8(crop_avatar(&image, size).to_rgba8());
+    let mut encoded = Cursor::new(Vec::new());
+    avatar
+        .write_to(&mut encoded, format)
+        .map_err(|e| JsValue::from_str(&format!("Failed to encode avatar: {}", e)))?;
+
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(encoded.get_ref().as_slice()));
+    let options = BlobPropertyBag::new();
+    options.set_type(mime);
+    Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::error::ImageError;
+    use image::RgbaImage;
+
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut encoded = Cursor::new(Vec::new());
+        DynamicImage::ImageRgba8(RgbaImage::new(width, height)).write_to(&mut encoded, ImageFormat::Png).unwrap();
+        encoded.into_inner()
+    }
+
+    #[test]
+    fn crops_to_a_centered_square() {
+        let image = decode_upload(&png(30, 20)).unwrap();
+        let avatar = crop_avatar(&image, 8);
+        assert_eq!((avatar.width(), avatar.height()), (8, 8));
+    }
+
+    #[test]
+    fn rejects_oversized_uploads_before_decoding() {
+        for (width, height) in [(UPLOAD_MAX_SIDE + 1, 1), (1, UPLOAD_MAX_SIDE + 1)] {
+            assert!(matches!(decode_upload(&png(width, height)), Err(ImageError::Limits(_))));
+        }
+        assert!(decode_upload(&png(UPLOAD_MAX_SIDE, 1)).is_ok());
+    }
+}
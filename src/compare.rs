@@ -0,0 +1,267 @@
+use wasm_bindgen::prelude::*;
+
+use crate::options::{get_option, get_option_string, invalid_option};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CompareMode {
+    Exact,
+    Lines,
+    Tokens,
+    Float { absolute: f64, relative: f64 },
+}
+
+#[derive(Debug)]
+pub struct Mismatch {
+    pub line: usize,
+    pub token: Option<usize>,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+    pub message: String,
+}
+
+const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+impl CompareMode {
+    fn parse(name: &str, absolute: f64, relative: f64) -> Option<CompareMode> {
+        match name {
+            "exact" => Some(CompareMode::Exact),
+            "lines" => Some(CompareMode::Lines),
+            "tokens" => Some(CompareMode::Tokens),
+            "float" => Some(CompareMode::Float { absolute, relative }),
+            _ => None,
+        }
+    }
+}
+
+/// Compares contestant output against the expected answer the way the judge
+/// does. Line endings are normalized in every mode; `Lines` also ignores
+/// trailing whitespace and trailing blank lines, `Tokens` ignores all
+/// whitespace layout, and `Float` additionally accepts numeric tokens within
+/// the absolute or relative tolerance.
+pub fn compare(expected: &str, actual: &str, mode: CompareMode) -> Result<(), Mismatch> {
+    match mode {
+        CompareMode::Exact => compare_lines(expected, actual, false),
+        CompareMode::Lines => compare_lines(expected, actual, true),
+        CompareMode::Tokens => compare_tokens(expected, actual, None),
+        CompareMode::Float { absolute, relative } => compare_tokens(expected, actual, Some((absolute, relative))),
+    }
+}
+
+fn split_lines(text: &str, trim: bool) -> Vec<&str> {
+    let mut lines: Vec<&str> = text
+        .split('\n')
+        .map(|line| if trim { line.trim_end() } else { line.strip_suffix('\r').unwrap_or(line) })
+        .collect();
+    if trim {
+        while lines.last() == Some(&"") {
+            lines.pop();
+        }
+    }
+    lines
+}
+
+fn compare_lines(expected: &str, actual: &str, trim: bool) -> Result<(), Mismatch> {
+    let expected_lines = split_lines(expected, trim);
+    let actual_lines = split_lines(actual, trim);
+    for i in 0..expected_lines.len().max(actual_lines.len()) {
+        let left = expected_lines.get(i);
+        let right = actual_lines.get(i);
+        if left == right {
+            continue;
+        }
+        let message = match (left, right) {
+            (Some(_), None) => format!("output ended early at line {}", i + 1),
+            (None, Some(_)) => format!("extra output at line {}", i + 1),
+            _ => format!("line {} differs", i + 1),
+        };
+        return Err(Mismatch {
+            line: i + 1,
+            token: None,
+            expected: left.map(|s| s.to_string()),
+            actual: right.map(|s| s.to_string()),
+            message,
+        });
+    }
+    Ok(())
+}
+
+fn tokens(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .flat_map(|(line, content)| content.split_whitespace().map(move |token| (line + 1, token)))
+}
+
+fn floats_match(expected: &str, actual: &str, absolute: f64, relative: f64) -> bool {
+    let (Ok(e), Ok(a)) = (expected.parse::<f64>(), actual.parse::<f64>()) else {
+        return false;
+    };
+    if !e.is_finite() || !a.is_finite() {
+        return false;
+    }
+    let error = (e - a).abs();
+    error <= absolute || error <= relative * e.abs()
+}
+
+fn compare_tokens(expected: &str, actual: &str, tolerance: Option<(f64, f64)>) -> Result<(), Mismatch> {
+    let mut expected_tokens = tokens(expected);
+    let mut actual_tokens = tokens(actual);
+    let mut index = 0;
+    loop {
+        index += 1;
+        let (left, right) = match (expected_tokens.next(), actual_tokens.next()) {
+            (None, None) => return Ok(()),
+            (Some((_, token)), None) => {
+                // Point at where the actual output stops, not at the missing token.
+                return Err(Mismatch {
+                    line: actual.lines().count().max(1),
+                    token: Some(index),
+                    expected: Some(token.to_string()),
+                    actual: None,
+                    message: format!("output ended early; expected token {} \"{}\"", index, token),
+                });
+            }
+            (None, Some((line, token))) => {
+                return Err(Mismatch {
+                    line,
+                    token: Some(index),
+                    expected: None,
+                    actual: Some(token.to_string()),
+                    message: format!("extra output starting at token {} \"{}\"", index, token),
+                });
+            }
+            (Some(left), Some(right)) => (left, right),
+        };
+        if left.1 == right.1 {
+            continue;
+        }
+        if let Some((absolute, relative)) = tolerance {
+            if floats_match(left.1, right.1, absolute, relative) {
+                continue;
+            }
+        }
+        return Err(Mismatch {
+            line: right.0,
+            token: Some(index),
+            expected: Some(left.1.to_string()),
+            actual: Some(right.1.to_string()),
+            message: format!("token {} differs: expected \"{}\", found \"{}\"", index, left.1, right.1),
+        });
+    }
+}
+
+// A NaN tolerance would quietly fail every numeric comparison.
+fn tolerance_option(options: &JsValue, key: &str) -> Result<f64, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(DEFAULT_TOLERANCE);
+    };
+    match value.as_f64() {
+        Some(tolerance) if tolerance.is_finite() && tolerance >= 0.0 => Ok(tolerance),
+        _ => Err(invalid_option(key, "a non-negative number", &value)),
+    }
+}
+
+/// Pre-checks sample output in the browser using the judge's comparison
+/// rules. `options` may set `mode` (`exact`, `lines` (default), `tokens` or
+/// `float`) and, for `float`, `absTolerance` / `relTolerance` (both default
+/// to `1e-6`). Returns `{ accepted, line, token, expected, actual, message }`
+/// where the location fields are `null` when accepted; `line` is 1-based in
+/// the actual output.
+#[wasm_bindgen]
+pub fn compare_output(expected: &str, actual: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let absolute = tolerance_option(&options, "absTolerance")?;
+    let relative = tolerance_option(&options, "relTolerance")?;
+    let name = get_option_string(&options, "mode").unwrap_or_else(|| "lines".to_string());
+    let mode = CompareMode::parse(&name, absolute, relative)
+        .ok_or_else(|| JsValue::from_str(&format!("Unknown compare mode: {}", name)))?;
+
+    let result = js_sys::Object::new();
+    let set = |key: &str, value: JsValue| {
+        js_sys::Reflect::set(&result, &JsValue::from_str(key), &value).unwrap();
+    };
+    let optional = |value: Option<String>| value.map(JsValue::from).unwrap_or(JsValue::NULL);
+    match compare(expected, actual, mode) {
+        Ok(()) => {
+            set("accepted", JsValue::TRUE);
+            for key in ["line", "token", "expected", "actual", "message"] {
+                set(key, JsValue::NULL);
+            }
+        }
+        Err(mismatch) => {
+            set("accepted", JsValue::FALSE);
+            set("line", JsValue::from(mismatch.line as u32));
+            set("token", mismatch.token.map(|t| JsValue::from(t as u32)).unwrap_or(JsValue::NULL));
+            set("expected", optional(mismatch.expected));
+            set("actual", optional(mismatch.actual));
+            set("message", JsValue::from_str(&mismatch.message));
+        }
+    }
+    Ok(result.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOAT: CompareMode = CompareMode::Float { absolute: 1e-6, relative: 1e-6 };
+
+    #[test]
+    fn exact_only_normalizes_line_endings() {
+        assert!(compare("1 2\n3\n", "1 2\r\n3\r\n", CompareMode::Exact).is_ok());
+        let mismatch = compare("1 2\n3\n", "1 2 \n3\n", CompareMode::Exact).unwrap_err();
+        assert_eq!((mismatch.line, mismatch.token), (1, None));
+        assert!(compare("1\n", "1\n\n", CompareMode::Exact).is_err());
+    }
+
+    #[test]
+    fn lines_ignores_trailing_whitespace() {
+        assert!(compare("1 2\n3\n", "1 2  \r\n3\n\n\n", CompareMode::Lines).is_ok());
+        assert!(compare("1 2\n3", "1  2\n3", CompareMode::Lines).is_err());
+        let mismatch = compare("1\n2\n3\n", "1\n2\n", CompareMode::Lines).unwrap_err();
+        assert_eq!(mismatch.line, 3);
+        assert_eq!(mismatch.expected.as_deref(), Some("3"));
+        assert_eq!(mismatch.actual, None);
+        let mismatch = compare("1\n", "1\n2\n", CompareMode::Lines).unwrap_err();
+        assert_eq!(mismatch.line, 2);
+        assert_eq!(mismatch.actual.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn tokens_ignore_layout() {
+        assert!(compare("1 2\n3\n", "  1\n\n2 3", CompareMode::Tokens).is_ok());
+        let mismatch = compare("1 2 3", "1\n2\n4", CompareMode::Tokens).unwrap_err();
+        assert_eq!((mismatch.line, mismatch.token), (3, Some(3)));
+        assert_eq!(mismatch.expected.as_deref(), Some("3"));
+        assert_eq!(mismatch.actual.as_deref(), Some("4"));
+        assert!(compare("1.0", "1", CompareMode::Tokens).is_err());
+    }
+
+    #[test]
+    fn tokens_report_where_output_ends() {
+        let mismatch = compare("1\n2\n3 4\n", "1\n2\n", CompareMode::Tokens).unwrap_err();
+        assert_eq!((mismatch.line, mismatch.token), (2, Some(3)));
+        assert_eq!(mismatch.actual, None);
+        assert_eq!(compare("1", "", CompareMode::Tokens).unwrap_err().line, 1);
+        let mismatch = compare("1", "1\n\n5", CompareMode::Tokens).unwrap_err();
+        assert_eq!((mismatch.line, mismatch.token), (3, Some(2)));
+    }
+
+    #[test]
+    fn float_accepts_either_tolerance() {
+        assert!(compare("0.333333", "0.3333333333", FLOAT).is_ok());
+        assert!(compare("1000000", "1000000.5", FLOAT).is_ok());
+        assert!(compare("1", "1.00001", FLOAT).is_err());
+        assert!(compare("yes 1", "yes 1.0000000001", FLOAT).is_ok());
+        assert!(compare("yes", "Yes", FLOAT).is_err());
+        assert!(compare("nan", "nan", FLOAT).is_ok());
+        assert!(compare("inf", "1e309", FLOAT).is_err());
+    }
+
+    #[test]
+    fn parses_mode_names() {
+        assert_eq!(CompareMode::parse("exact", 0.0, 0.0), Some(CompareMode::Exact));
+        assert_eq!(CompareMode::parse("lines", 0.0, 0.0), Some(CompareMode::Lines));
+        assert_eq!(CompareMode::parse("tokens", 0.0, 0.0), Some(CompareMode::Tokens));
+        assert_eq!(CompareMode::parse("float", 0.1, 0.2), Some(CompareMode::Float { absolute: 0.1, relative: 0.2 }));
+        assert_eq!(CompareMode::parse("fuzzy", 0.0, 0.0), None);
+    }
+}
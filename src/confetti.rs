@@ -0,0 +1,261 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{window, HtmlCanvasElement, WebGlBuffer, WebGlProgram, WebGlRenderingContext as GL};
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use crate::stars::{compile_shader, link_program, FrameCallback};
+
+pub struct ConfettiField {
+    gl: GL,
+    canvas: HtmlCanvasElement,
+    resolution: (f32, f32),
+    pieces: Vec<ConfettiPiece>,
+    buffer: WebGlBuffer,
+    program: WebGlProgram,
+}
+
+struct ConfettiPiece {
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    angle: f32,
+    spin: f32,
+    flip_phase: f32,
+    flip_speed: f32,
+    width: f32,
+    height: f32,
+    lifetime: f32,
+    max_lifetime: f32,
+    color: [f32; 3],
+}
+
+const CONFETTI_GRAVITY: f32 = 0.12;
+const CONFETTI_DRAG: f32 = 0.985;
+const CONFETTI_MAX_PIECES: usize = 2000;
+const CONFETTI_COLORS: [[f32; 3]; 6] = [
+    [1.0, 0.35, 0.4],
+    [1.0, 0.8, 0.3],
+    [0.4, 0.9, 0.5],
+    [0.35, 0.7, 1.0],
+    [0.75, 0.5, 1.0],
+    [1.0, 1.0, 1.0],
+];
+
+thread_local! {
+    static CONFETTI_FIELD: RefCell<Option<Rc<RefCell<ConfettiField>>>> = const { RefCell::new(None) };
+}
+
+impl ConfettiField {
+    pub fn new(canvas_id: &str) -> ConfettiField {
+        let document = window().unwrap().document().unwrap();
+        let canvas = document
+            .get_element_by_id(canvas_id)
+            .expect("Canvas element not found")
+            .dyn_into::<HtmlCanvasElement>()
+            .unwrap();
+
+        let gl: GL = canvas
+            .get_context("webgl")
+            .unwrap()
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+
+        let vertex_shader_source = r#"
+            attribute vec2 a_position;
+            attribute vec4 a_color;
+            uniform vec2 u_resolution;
+            varying vec4 v_color;
+            void main() {
+                vec2 zeroToOne = a_position / u_resolution;
+                vec2 zeroToTwo = zeroToOne * 2.0;
+                vec2 clipSpace = zeroToTwo - 1.0;
+                clipSpace.y = -clipSpace.y;
+                gl_Position = vec4(clipSpace, 0.0, 1.0);
+                v_color = a_color;
+            }
+        "#;
+        let fragment_shader_source = r#"
+            precision mediump float;
+            varying vec4 v_color;
+            void main() {
+                gl_FragColor = v_color;
+            }
+        "#;
+        let vertex_shader = compile_shader(&gl, GL::VERTEX_SHADER, vertex_shader_source)
+            .expect("Confetti vertex shader compile error");
+        let fragment_shader = compile_shader(&gl, GL::FRAGMENT_SHADER, fragment_shader_source)
+            .expect("Confetti fragment shader compile error");
+        let program = link_program(&gl, &vertex_shader, &fragment_shader)
+            .expect("Confetti program link error");
+        let buffer = gl.create_buffer().expect("Failed to create confetti buffer");
+
+        let mut confetti = ConfettiField {
+            gl,
+            canvas,
+            resolution: (0.0, 0.0),
+            pieces: Vec::new(),
+            buffer,
+            program,
+        };
+        confetti.resize();
+        confetti
+    }
+
+    fn resize(&mut self) {
+        let dpr = window().unwrap().device_pixel_ratio() as f32;
+        let width = self.canvas.client_width() as f32 * dpr;
+        let height = self.canvas.client_height() as f32 * dpr;
+        self.canvas.set_width(width as u32);
+        self.canvas.set_height(height as u32);
+        self.resolution = (width, height);
+    }
+
+    fn fire(&mut self, x: f32, y: f32, count: usize) {
+        let (width, height) = self.resolution;
+        let origin_x = x * width;
+        let origin_y = y * height;
+        let scale = height.max(1.0) / 600.0;
+        let count = count.min(CONFETTI_MAX_PIECES.saturating_sub(self.pieces.len()));
+        for _ in 0..count {
+            let angle = -std::f32::consts::FRAC_PI_2
+                + ((js_sys::Math::random() as f32) - 0.5) * std::f32::consts::FRAC_PI_2 * 1.4;
+            let speed = (6.0 + (js_sys::Math::random() as f32) * 10.0) * scale;
+            let color_index = ((js_sys::Math::random() as f32) * CONFETTI_COLORS.len() as f32) as usize;
+            self.pieces.push(ConfettiPiece {
+                x: origin_x,
+                y: origin_y,
+                vx: speed * angle.cos(),
+                vy: speed * angle.sin(),
+                angle: (js_sys::Math::random() as f32) * std::f32::consts::TAU,
+                spin: ((js_sys::Math::random() as f32) - 0.5) * 0.3,
+                flip_phase: (js_sys::Math::random() as f32) * std::f32::consts::TAU,
+                flip_speed: 0.05 + (js_sys::Math::random() as f32) * 0.15,
+                width: (6.0 + (js_sys::Math::random() as f32) * 6.0) * scale,
+                height: (3.0 + (js_sys::Math::random() as f32) * 4.0) * scale,
+                lifetime: 0.0,
+                max_lifetime: 180.0 + (js_sys::Math::random() as f32) * 120.0,
+                color: CONFETTI_COLORS[color_index.min(CONFETTI_COLORS.len() - 1)],
+            });
+        }
+    }
+
+    fn update(&mut self) {
+        let dt: f32 = 1.0;
+        let scale = self.resolution.1.max(1.0) / 600.0;
+        for piece in &mut self.pieces {
+            piece.vy += CONFETTI_GRAVITY * scale * dt;
+            piece.vx *= CONFETTI_DRAG;
+            piece.vy *= CONFETTI_DRAG;
+            piece.x += piece.vx * dt;
+            piece.y += piece.vy * dt;
+            piece.angle += piece.spin * dt;
+            piece.flip_phase += piece.flip_speed * dt;
+            piece.lifetime += dt;
+        }
+        let height = self.resolution.1;
+        self.pieces.retain(|p| p.lifetime < p.max_lifetime && p.y < height + 50.0);
+
+        let mut data = Vec::with_capacity(self.pieces.len() * 6 * 6);
+        for piece in &self.pieces {
+            let fade = 1.0 - (piece.lifetime / piece.max_lifetime).powi(3);
+            let (sin, cos) = piece.angle.sin_cos();
+            let half_w = piece.width / 2.0;
+            let half_h = piece.height / 2.0 * piece.flip_phase.cos();
+            let corner = |dx: f32, dy: f32| (piece.x + dx * cos - dy * sin, piece.y + dx * sin + dy * cos);
+            let c0 = corner(-half_w, -half_h);
+            let c1 = corner(half_w, -half_h);
+            let c2 = corner(half_w, half_h);
+            let c3 = corner(-half_w, half_h);
+            for (vx, vy) in [c0, c1, c2, c0, c2, c3] {
+                data.push(vx);
+                data.push(vy);
+                data.push(piece.color[0]);
+                data.push(piece.color[1]);
+                data.push(piece.color[2]);
+                data.push(fade);
+            }
+        }
+        self.gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.buffer));
+        unsafe {
+            let array = js_sys::Float32Array::view(&data);
+            self.gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &array, GL::DYNAMIC_DRAW);
+        }
+    }
+
+    fn draw(&self) {
+        let gl = &self.gl;
+        gl.viewport(0, 0, self.resolution.0 as i32, self.resolution.1 as i32);
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(GL::COLOR_BUFFER_BIT);
+        if self.pieces.is_empty() {
+            return;
+        }
+        gl.use_program(Some(&self.program));
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.buffer));
+        let stride = 6 * std::mem::size_of::<f32>() as i32;
+        let pos_loc = gl.get_attrib_location(&self.program, "a_position") as u32;
+        let color_loc = gl.get_attrib_location(&self.program, "a_color") as u32;
+        gl.enable_vertex_attrib_array(pos_loc);
+        gl.vertex_attrib_pointer_with_i32(pos_loc, 2, GL::FLOAT, false, stride, 0);
+        gl.enable_vertex_attrib_array(color_loc);
+        gl.vertex_attrib_pointer_with_i32(color_loc, 4, GL::FLOAT, false, stride, 2 * std::mem::size_of::<f32>() as i32);
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_resolution") {
+            gl.uniform2f(Some(&loc), self.resolution.0, self.resolution.1);
+        }
+        gl.enable(GL::BLEND);
+        gl.blend_func(GL::SRC_ALPHA, GL::ONE_MINUS_SRC_ALPHA);
+        gl.draw_arrays(GL::TRIANGLES, 0, (self.pieces.len() * 6) as i32);
+        gl.disable(GL::BLEND);
+    }
+}
+
+/// Starts the confetti renderer on its own (usually transparent, overlaid)
+/// canvas. Bursts are triggered with `fire_confetti`.
+#[wasm_bindgen]
+pub fn start_confetti(canvas_id: &str) {
+    let confetti = Rc::new(RefCell::new(ConfettiField::new(canvas_id)));
+    CONFETTI_FIELD.with(|cell| *cell.borrow_mut() = Some(confetti.clone()));
+
+    {
+        let confetti_clone = confetti.clone();
+        let resize_closure = Closure::wrap(Box::new(move || {
+            confetti_clone.borrow_mut().resize();
+        }) as Box<dyn FnMut()>);
+        window().unwrap()
+            .add_event_listener_with_callback("resize", resize_closure.as_ref().unchecked_ref())
+            .unwrap();
+        resize_closure.forget();
+    }
+
+    let f: FrameCallback = Rc::new(RefCell::new(None));
+    let g = f.clone();
+
+    *g.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+        {
+            let mut cf = confetti.borrow_mut();
+            cf.update();
+            cf.draw();
+        }
+        window().unwrap()
+            .request_animation_frame(f.borrow().as_ref().unwrap().as_ref().unchecked_ref())
+            .unwrap();
+    }) as Box<dyn FnMut()>));
+
+    window().unwrap()
+        .request_animation_frame(g.borrow().as_ref().unwrap().as_ref().unchecked_ref())
+        .unwrap();
+}
+
+/// Fires `count` pieces of confetti from `(x, y)`, given as fractions of the
+/// canvas width and height.
+#[wasm_bindgen]
+pub fn fire_confetti(x: f32, y: f32, count: usize) {
+    CONFETTI_FIELD.with(|cell| {
+        if let Some(confetti) = cell.borrow().as_ref() {
+            confetti.borrow_mut().fire(x, y, count);
+        }
+    });
+}
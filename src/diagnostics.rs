@@ -0,0 +1,88 @@
+#[cfg(feature = "diagnostics")]
+use std::cell::Cell;
+
+#[cfg(feature = "diagnostics")]
+use wasm_bindgen::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+#[cfg(feature = "diagnostics")]
+impl Level {
+    fn from_name(name: &str) -> Option<Option<Level>> {
+        match name.to_ascii_lowercase().as_str() {
+            "off" | "none" => Some(None),
+            "error" => Some(Some(Level::Error)),
+            "warn" => Some(Some(Level::Warn)),
+            "info" => Some(Some(Level::Info)),
+            "debug" => Some(Some(Level::Debug)),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+        }
+    }
+}
+
+#[cfg(feature = "diagnostics")]
+thread_local! {
+    // Off until the page asks for logs.
+    static LEVEL: Cell<Option<Level>> = const { Cell::new(None) };
+}
+
+/// Logs `message` to the console if diagnostics are enabled at `level` or
+/// more verbose. The message is only built when it will be printed.
+#[cfg(feature = "diagnostics")]
+pub(crate) fn log(level: Level, message: impl FnOnce() -> String) {
+    if LEVEL.with(|current| current.get()).is_none_or(|current| level > current) {
+        return;
+    }
+    let line = JsValue::from_str(&format!("[soj-wasm {:>9.1}ms] [{}] {}", now_ms(), level.label(), message()));
+    match level {
+        Level::Error => web_sys::console::error_1(&line),
+        Level::Warn => web_sys::console::warn_1(&line),
+        Level::Info => web_sys::console::info_1(&line),
+        Level::Debug => web_sys::console::debug_1(&line),
+    }
+}
+
+#[cfg(not(feature = "diagnostics"))]
+#[inline(always)]
+pub(crate) fn log(_level: Level, _message: impl FnOnce() -> String) {}
+
+/// Milliseconds since page load, for timing milestones.
+#[cfg(feature = "diagnostics")]
+pub(crate) fn now_ms() -> f64 {
+    web_sys::window().and_then(|window| window.performance()).map_or(0.0, |performance| performance.now())
+}
+
+#[cfg(not(feature = "diagnostics"))]
+#[inline(always)]
+pub(crate) fn now_ms() -> f64 {
+    0.0
+}
+
+/// Turns console diagnostics on at `level` (`"error"`, `"warn"`, `"info"`
+/// or `"debug"`) or off with `"off"`. Covers shader compile logs, WebGL
+/// context attributes, buffer sizes and startup timing; paste the output
+/// into bug reports about blank or broken canvases.
+#[cfg(feature = "diagnostics")]
+#[wasm_bindgen]
+pub fn set_log_level(level: &str) -> Result<(), JsValue> {
+    let level = Level::from_name(level).ok_or_else(|| {
+        crate::options::invalid_option("level", "\"off\", \"error\", \"warn\", \"info\" or \"debug\"", &level.into())
+    })?;
+    LEVEL.with(|current| current.set(level));
+    Ok(())
+}
@@ -0,0 +1,519 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{window, CanvasRenderingContext2d, HtmlCanvasElement};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DiffOp {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+enum LineDiff<'a> {
+    Equal(usize, usize, &'a str),
+    Delete(usize, &'a str),
+    Insert(usize, &'a str),
+    Change(usize, usize, Vec<WordSpan<'a>>, Vec<WordSpan<'a>>),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SpanKind {
+    Equal,
+    Delete,
+    Insert,
+}
+
+struct WordSpan<'a> {
+    kind: SpanKind,
+    text: &'a str,
+}
+
+// Bounds the work `myers` does, about the edit distance times the length
+// of the inputs' differing middle; beyond it that middle is reported as a
+// wholesale replacement.
+const MAX_DIFF_WORK: usize = 100_000_000;
+const DIFF_LINE_HEIGHT: f64 = 18.0;
+const DIFF_FONT: &str = "13px monospace";
+const DIFF_MAX_CANVAS_HEIGHT: f64 = 32000.0;
+
+/// Computes a shortest edit script between `a` and `b` with Myers' algorithm.
+pub fn myers<T: PartialEq>(a: &[T], b: &[T]) -> Vec<DiffOp> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+
+    let mut ops: Vec<DiffOp> = (0..prefix).map(|i| DiffOp::Equal(i, i)).collect();
+    match myers_middle(a_mid, b_mid) {
+        Some(middle) => ops.extend(middle.into_iter().map(|op| match op {
+            DiffOp::Equal(i, j) => DiffOp::Equal(i + prefix, j + prefix),
+            DiffOp::Delete(i) => DiffOp::Delete(i + prefix),
+            DiffOp::Insert(j) => DiffOp::Insert(j + prefix),
+        })),
+        None => {
+            ops.extend((0..a_mid.len()).map(|i| DiffOp::Delete(i + prefix)));
+            ops.extend((0..b_mid.len()).map(|j| DiffOp::Insert(j + prefix)));
+        }
+    }
+    let a_tail = a.len() - suffix;
+    let b_tail = b.len() - suffix;
+    ops.extend((0..suffix).map(|k| DiffOp::Equal(a_tail + k, b_tail + k)));
+    ops
+}
+
+// The middle section's edit script, or None when it needs so many edits
+// that finding them would exceed `MAX_DIFF_WORK`.
+fn myers_middle<T: PartialEq>(a: &[T], b: &[T]) -> Option<Vec<DiffOp>> {
+    let limit = MAX_DIFF_WORK / (a.len() + b.len()).max(1);
+    let mut ops = Vec::new();
+    diff_range(a, b, (0, 0), limit, &mut ops).then_some(ops)
+}
+
+// Appends the edit script from `a` to `b`, which start at `a_start` and
+// `b_start` in the full inputs, splitting at the middle snake so memory
+// stays linear. Returns false, having appended nothing past the common
+// prefix, when the script needs more than `limit` edits.
+fn diff_range<T: PartialEq>(
+    a: &[T],
+    b: &[T],
+    (a_start, b_start): (usize, usize),
+    limit: usize,
+    ops: &mut Vec<DiffOp>,
+) -> bool {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    ops.extend((0..prefix).map(|i| DiffOp::Equal(a_start + i, b_start + i)));
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let (a_start, b_start) = (a_start + prefix, b_start + prefix);
+    let suffix = a.iter().rev().zip(b.iter().rev()).take_while(|(x, y)| x == y).count();
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+
+    if a.is_empty() || b.is_empty() {
+        ops.extend((0..a.len()).map(|i| DiffOp::Delete(a_start + i)));
+        ops.extend((0..b.len()).map(|j| DiffOp::Insert(b_start + j)));
+    } else {
+        let Some((x, y, u, v)) = middle_snake(a, b, limit) else {
+            return false;
+        };
+        // Both halves need fewer edits than the whole, so only the top
+        // level can run over the limit.
+        diff_range(&a[..x], &b[..y], (a_start, b_start), usize::MAX, ops);
+        ops.extend((0..u - x).map(|i| DiffOp::Equal(a_start + x + i, b_start + y + i)));
+        diff_range(&a[u..], &b[v..], (a_start + u, b_start + v), usize::MAX, ops);
+    }
+    let (a_end, b_end) = (a_start + a.len(), b_start + b.len());
+    ops.extend((0..suffix).map(|k| DiffOp::Equal(a_end + k, b_end + k)));
+    true
+}
+
+// Finds the snake in the middle of a shortest edit script from `a` to `b`
+// by searching from both ends until the paths overlap, keeping one row of
+// furthest-reaching x values per direction. Returns the points (x, y) and
+// (u, v) the snake runs between, or None past `limit` edits.
+fn middle_snake<T: PartialEq>(a: &[T], b: &[T], limit: usize) -> Option<(usize, usize, usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    let offset = max + 1;
+    let at = |k: isize| (k + offset) as usize;
+    // Backward x values count from the ends of `a` and `b`; backward
+    // diagonal k meets forward diagonal `delta - k`.
+    let mut forward = vec![0isize; 2 * max as usize + 3];
+    let mut backward = forward.clone();
+    for d in 0..=limit.div_ceil(2).min(max as usize) as isize {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[at(k - 1)] < forward[at(k + 1)]) {
+                forward[at(k + 1)]
+            } else {
+                forward[at(k - 1)] + 1
+            };
+            let (x0, y0) = (x, x - k);
+            let mut y = y0;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[at(k)] = x;
+            if odd && (delta - k).abs() < d && x + backward[at(delta - k)] >= n {
+                return Some((x0 as usize, y0 as usize, x as usize, y as usize));
+            }
+        }
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[at(k - 1)] < backward[at(k + 1)]) {
+                backward[at(k + 1)]
+            } else {
+                backward[at(k - 1)] + 1
+            };
+            let (x0, y0) = (x, x - k);
+            let mut y = y0;
+            while x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[at(k)] = x;
+            if !odd && (delta - k).abs() <= d && x + forward[at(delta - k)] >= n {
+                return Some(((n - x) as usize, (m - y) as usize, (n - x0) as usize, (m - y0) as usize));
+            }
+        }
+    }
+    None
+}
+
+fn split_words(line: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut in_space = None;
+    for (i, c) in line.char_indices() {
+        let is_space = c.is_whitespace();
+        if in_space.is_some_and(|s| s != is_space) {
+            words.push(&line[start..i]);
+            start = i;
+        }
+        in_space = Some(is_space);
+    }
+    if start < line.len() {
+        words.push(&line[start..]);
+    }
+    words
+}
+
+fn word_diff<'a>(expected: &'a str, actual: &'a str) -> (Vec<WordSpan<'a>>, Vec<WordSpan<'a>>) {
+    let a = split_words(expected);
+    let b = split_words(actual);
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    for op in myers(&a, &b) {
+        match op {
+            DiffOp::Equal(i, j) => {
+                left.push(WordSpan { kind: SpanKind::Equal, text: a[i] });
+                right.push(WordSpan { kind: SpanKind::Equal, text: b[j] });
+            }
+            DiffOp::Delete(i) => left.push(WordSpan { kind: SpanKind::Delete, text: a[i] }),
+            DiffOp::Insert(j) => right.push(WordSpan { kind: SpanKind::Insert, text: b[j] }),
+        }
+    }
+    (left, right)
+}
+
+fn line_diff<'a>(expected: &'a str, actual: &'a str) -> Vec<LineDiff<'a>> {
+    let a: Vec<&str> = expected.lines().collect();
+    let b: Vec<&str> = actual.lines().collect();
+    let ops = myers(&a, &b);
+
+    let mut result = Vec::new();
+    let mut i = 0;
+    while i < ops.len() {
+        if let DiffOp::Equal(x, y) = ops[i] {
+            result.push(LineDiff::Equal(x, y, a[x]));
+            i += 1;
+            continue;
+        }
+        let mut deletes = Vec::new();
+        let mut inserts = Vec::new();
+        while i < ops.len() {
+            match ops[i] {
+                DiffOp::Delete(x) => deletes.push(x),
+                DiffOp::Insert(y) => inserts.push(y),
+                DiffOp::Equal(..) => break,
+            }
+            i += 1;
+        }
+        let paired = deletes.len().min(inserts.len());
+        for (x, y) in deletes.iter().zip(&inserts) {
+            let (left, right) = word_diff(a[*x], b[*y]);
+            result.push(LineDiff::Change(*x, *y, left, right));
+        }
+        for x in &deletes[paired..] {
+            result.push(LineDiff::Delete(*x, a[*x]));
+        }
+        for y in &inserts[paired..] {
+            result.push(LineDiff::Insert(*y, b[*y]));
+        }
+    }
+    result
+}
+
+fn span_kind_name(kind: SpanKind) -> &'static str {
+    match kind {
+        SpanKind::Equal => "equal",
+        SpanKind::Delete => "delete",
+        SpanKind::Insert => "insert",
+    }
+}
+
+fn set(object: &js_sys::Object, key: &str, value: &JsValue) {
+    js_sys::Reflect::set(object, &JsValue::from_str(key), value).unwrap();
+}
+
+fn spans_to_js(spans: &[WordSpan]) -> js_sys::Array {
+    spans
+        .iter()
+        .map(|span| {
+            let object = js_sys::Object::new();
+            set(&object, "kind", &JsValue::from_str(span_kind_name(span.kind)));
+            set(&object, "text", &JsValue::from_str(span.text));
+            JsValue::from(object)
+        })
+        .collect()
+}
+
+/// Diffs judge output line by line (Myers), refining changed line pairs to
+/// word level. Returns an array of `{ kind, expectedLine?, actualLine?, ... }`
+/// entries where `kind` is `equal`, `delete`, `insert` (with `text`) or
+/// `change` (with `expected`/`actual` arrays of `{ kind, text }` spans).
+/// Line numbers are 1-based.
+#[wasm_bindgen]
+pub fn diff_output(expected: &str, actual: &str) -> js_sys::Array {
+    line_diff(expected, actual)
+        .iter()
+        .map(|entry| {
+            let object = js_sys::Object::new();
+            match entry {
+                LineDiff::Equal(x, y, text) => {
+                    set(&object, "kind", &JsValue::from_str("equal"));
+                    set(&object, "expectedLine", &JsValue::from(*x as u32 + 1));
+                    set(&object, "actualLine", &JsValue::from(*y as u32 + 1));
+                    set(&object, "text", &JsValue::from_str(text));
+                }
+                LineDiff::Delete(x, text) => {
+                    set(&object, "kind", &JsValue::from_str("delete"));
+                    set(&object, "expectedLine", &JsValue::from(*x as u32 + 1));
+                    set(&object, "text", &JsValue::from_str(text));
+                }
+                LineDiff::Insert(y, text) => {
+                    set(&object, "kind", &JsValue::from_str("insert"));
+                    set(&object, "actualLine", &JsValue::from(*y as u32 + 1));
+                    set(&object, "text", &JsValue::from_str(text));
+                }
+                LineDiff::Change(x, y, left, right) => {
+                    set(&object, "kind", &JsValue::from_str("change"));
+                    set(&object, "expectedLine", &JsValue::from(*x as u32 + 1));
+                    set(&object, "actualLine", &JsValue::from(*y as u32 + 1));
+                    set(&object, "expected", &spans_to_js(left));
+                    set(&object, "actual", &spans_to_js(right));
+                }
+            }
+            JsValue::from(object)
+        })
+        .collect()
+}
+
+/// Renders the diff of `expected` and `actual` into a 2D canvas as a
+/// unified view, for outputs too large to lay out as DOM nodes. Canvases
+/// stop at 32000 device pixels tall, so returns how many rows past that
+/// were left out, 0 when the whole diff fits.
+#[wasm_bindgen]
+pub fn render_diff(canvas_id: &str, expected: &str, actual: &str) -> Result<u32, JsValue> {
+    let document = window().unwrap().document().unwrap();
+    let canvas = document
+        .get_element_by_id(canvas_id)
+        .ok_or_else(|| JsValue::from_str("Canvas element not found"))?
+        .dyn_into::<HtmlCanvasElement>()?;
+    let ctx = canvas
+        .get_context("2d")?
+        .ok_or_else(|| JsValue::from_str("2D context unavailable"))?
+        .dyn_into::<CanvasRenderingContext2d>()?;
+
+    let rows: Vec<(char, Vec<WordSpan>)> = line_diff(expected, actual)
+        .into_iter()
+        .flat_map(|entry| match entry {
+            LineDiff::Equal(_, _, text) => vec![(' ', vec![WordSpan { kind: SpanKind::Equal, text }])],
+            LineDiff::Delete(_, text) => vec![('-', vec![WordSpan { kind: SpanKind::Delete, text }])],
+            LineDiff::Insert(_, text) => vec![('+', vec![WordSpan { kind: SpanKind::Insert, text }])],
+            LineDiff::Change(_, _, left, right) => vec![('-', left), ('+', right)],
+        })
+        .collect();
+
+    let dpr = window().unwrap().device_pixel_ratio();
+    let width = canvas.client_width() as f64;
+    let shown = rows.len().min((DIFF_MAX_CANVAS_HEIGHT / dpr / DIFF_LINE_HEIGHT) as usize);
+    let height = shown as f64 * DIFF_LINE_HEIGHT;
+    canvas.set_width((width * dpr) as u32);
+    canvas.set_height((height * dpr) as u32);
+    canvas.style().set_property("height", &format!("{}px", height))?;
+    ctx.scale(dpr, dpr)?;
+    ctx.set_font(DIFF_FONT);
+    ctx.set_text_baseline("middle");
+
+    let gutter = ctx.measure_text("+ ")?.width();
+    for (row, (marker, spans)) in rows.iter().take(shown).enumerate() {
+        let y = row as f64 * DIFF_LINE_HEIGHT;
+        let (line_bg, word_bg) = match marker {
+            '-' => ("rgba(248, 81, 73, 0.15)", "rgba(248, 81, 73, 0.45)"),
+            '+' => ("rgba(63, 185, 80, 0.15)", "rgba(63, 185, 80, 0.45)"),
+            _ => ("rgba(0, 0, 0, 0)", "rgba(0, 0, 0, 0)"),
+        };
+        ctx.set_fill_style_str(line_bg);
+        ctx.fill_rect(0.0, y, width, DIFF_LINE_HEIGHT);
+        ctx.set_fill_style_str("#8b949e");
+        ctx.fill_text(&marker.to_string(), 2.0, y + DIFF_LINE_HEIGHT / 2.0)?;
+
+        let mut x = gutter;
+        for span in spans {
+            let span_width = ctx.measure_text(span.text)?.width();
+            if span.kind != SpanKind::Equal {
+                ctx.set_fill_style_str(word_bg);
+                ctx.fill_rect(x, y, span_width, DIFF_LINE_HEIGHT);
+            }
+            ctx.set_fill_style_str("#c9d1d9");
+            ctx.fill_text(span.text, x, y + DIFF_LINE_HEIGHT / 2.0)?;
+            x += span_width;
+            if x > width {
+                break;
+            }
+        }
+    }
+    Ok((rows.len() - shown) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Replays `ops` over `a`, checking every index is visited in order, and
+    // returns the rebuilt `b` with the number of edits made.
+    fn apply<T: PartialEq + Clone + std::fmt::Debug>(a: &[T], b: &[T], ops: &[DiffOp]) -> (Vec<T>, usize) {
+        let (mut next_a, mut next_b, mut edits) = (0, 0, 0);
+        let mut rebuilt = Vec::new();
+        for op in ops {
+            match *op {
+                DiffOp::Equal(i, j) => {
+                    assert_eq!((i, j), (next_a, next_b));
+                    assert_eq!(a[i], b[j]);
+                    rebuilt.push(a[i].clone());
+                    (next_a, next_b) = (i + 1, j + 1);
+                }
+                DiffOp::Delete(i) => {
+                    assert_eq!(i, next_a);
+                    next_a += 1;
+                    edits += 1;
+                }
+                DiffOp::Insert(j) => {
+                    assert_eq!(j, next_b);
+                    rebuilt.push(b[j].clone());
+                    next_b += 1;
+                    edits += 1;
+                }
+            }
+        }
+        assert_eq!((next_a, next_b), (a.len(), b.len()));
+        (rebuilt, edits)
+    }
+
+    fn check(a: &str, b: &str, distance: usize) {
+        let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+        let (rebuilt, edits) = apply(&a, &b, &myers(&a, &b));
+        assert_eq!(rebuilt, b);
+        assert_eq!(edits, distance);
+    }
+
+    #[test]
+    fn finds_shortest_edit_scripts() {
+        check("", "", 0);
+        check("abc", "abc", 0);
+        check("", "abc", 3);
+        check("abc", "", 3);
+        check("ABCABBA", "CBABAC", 5);
+        check("kitten", "sitting", 5);
+        check("1 2 3\n4 5 6", "1 2 3\n4 5 7", 2);
+    }
+
+    #[test]
+    fn rebuilds_pseudo_random_inputs() {
+        let mut seed = 0x2545_f491_u32;
+        let mut next = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed % 4
+        };
+        for _ in 0..200 {
+            let a: Vec<u32> = (0..next() * 5).map(|_| next()).collect();
+            let b: Vec<u32> = (0..next() * 5).map(|_| next()).collect();
+            let (rebuilt, edits) = apply(&a, &b, &myers(&a, &b));
+            assert_eq!(rebuilt, b);
+            assert!(edits <= a.len() + b.len());
+        }
+    }
+
+    // Edit distance by the textbook quadratic LCS table.
+    fn distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+        let mut row = vec![0; b.len() + 1];
+        for x in a {
+            let mut diagonal = 0;
+            for (j, y) in b.iter().enumerate() {
+                let above = row[j + 1];
+                row[j + 1] = if x == y { diagonal + 1 } else { above.max(row[j]) };
+                diagonal = above;
+            }
+        }
+        a.len() + b.len() - 2 * row[b.len()]
+    }
+
+    #[test]
+    fn matches_the_quadratic_distance() {
+        let mut seed = 0x9e37_79b9_u32;
+        let mut next = |range: u32| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed % range
+        };
+        for _ in 0..500 {
+            let alphabet = 2 + next(4);
+            let a: Vec<u32> = (0..next(40)).map(|_| next(alphabet)).collect();
+            let b: Vec<u32> = (0..next(40)).map(|_| next(alphabet)).collect();
+            let (rebuilt, edits) = apply(&a, &b, &myers(&a, &b));
+            assert_eq!(rebuilt, b);
+            assert_eq!(edits, distance(&a, &b), "{:?} -> {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn diffs_long_inputs_with_few_changes() {
+        let a: Vec<u32> = (0..500_000).collect();
+        let mut b = a.clone();
+        b[1_000] = 0;
+        b.remove(250_000);
+        b.insert(400_000, 7);
+        let (rebuilt, edits) = apply(&a, &b, &myers(&a, &b));
+        assert_eq!(rebuilt, b);
+        assert_eq!(edits, 4);
+    }
+
+    #[test]
+    fn replaces_wholesale_beyond_the_work_limit() {
+        let a: Vec<u32> = (0..8_000).collect();
+        let b: Vec<u32> = a.iter().map(|x| x + 1_000_000).collect();
+        assert!(myers_middle(&a, &b).is_none());
+        let ops = myers(&a, &b);
+        let (rebuilt, edits) = apply(&a, &b, &ops);
+        assert_eq!(rebuilt, b);
+        assert_eq!(edits, a.len() + b.len());
+    }
+
+    fn spans<'a>(spans: &[WordSpan<'a>]) -> Vec<(bool, &'a str)> {
+        spans.iter().map(|span| (span.kind == SpanKind::Equal, span.text)).collect()
+    }
+
+    #[test]
+    fn pairs_changed_lines_into_word_spans() {
+        let diff = line_diff("1 2\n3 4\n5", "1 2\n3 5\n5\n6");
+        assert!(matches!(diff[0], LineDiff::Equal(0, 0, "1 2")));
+        match &diff[1] {
+            LineDiff::Change(1, 1, left, right) => {
+                assert_eq!(spans(left), [(true, "3"), (true, " "), (false, "4")]);
+                assert_eq!(spans(right), [(true, "3"), (true, " "), (false, "5")]);
+            }
+            _ => panic!("expected a changed line"),
+        }
+        assert!(matches!(diff[2], LineDiff::Equal(2, 2, "5")));
+        assert!(matches!(diff[3], LineDiff::Insert(3, "6")));
+        assert_eq!(diff.len(), 4);
+    }
+}
@@ -0,0 +1,197 @@
+use wasm_bindgen::prelude::*;
+
+use crate::highlight::{comment_syntax, tokenize, TokenKind};
+
+// Textarea selection offsets are UTF-16 code units; Rust strings are UTF-8.
+fn utf16_to_byte(code: &str, offset: u32) -> usize {
+    let mut units = 0;
+    for (byte, c) in code.char_indices() {
+        if units >= offset as usize {
+            return byte;
+        }
+        units += c.len_utf16();
+    }
+    code.len()
+}
+
+fn byte_to_utf16(code: &str, byte: usize) -> u32 {
+    code[..byte].chars().map(char::len_utf16).sum::<usize>() as u32
+}
+
+fn closer_for(open: char) -> Option<char> {
+    match open {
+        '(' => Some(')'),
+        '[' => Some(']'),
+        '{' => Some('}'),
+        _ => None,
+    }
+}
+
+/// Byte offsets of matched bracket pairs, ignoring brackets inside strings,
+/// comments and preprocessor lines.
+pub fn bracket_pairs(code: &str, language: &str) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    let mut stack: Vec<(usize, char)> = Vec::new();
+    let mut offset = 0;
+    for token in tokenize(code, language) {
+        let start = offset;
+        offset += token.text.len();
+        if !matches!(token.kind, TokenKind::Punctuation | TokenKind::Plain) {
+            continue;
+        }
+        for (i, c) in token.text.char_indices() {
+            if closer_for(c).is_some() {
+                stack.push((start + i, c));
+            } else if matches!(c, ')' | ']' | '}') {
+                // A mismatched closer is left unmatched instead of
+                // unwinding the stack, so one typo does not break every pair.
+                if let Some(&(open, open_char)) = stack.last() {
+                    if closer_for(open_char) == Some(c) {
+                        stack.pop();
+                        pairs.push((open, start + i));
+                    }
+                }
+            }
+        }
+    }
+    pairs
+}
+
+/// Finds the bracket matching the one at the cursor (the character after
+/// it, or else the one before it) and returns its offset.
+pub fn match_bracket_at(code: &str, language: &str, cursor: usize) -> Option<usize> {
+    let pairs = bracket_pairs(code, language);
+    let before = code[..cursor].chars().next_back().map(|c| cursor - c.len_utf8());
+    [Some(cursor), before].into_iter().flatten().find_map(|position| {
+        pairs.iter().find_map(|&(open, close)| match position {
+            p if p == open => Some(close),
+            p if p == close => Some(open),
+            _ => None,
+        })
+    })
+}
+
+const PYTHON_DEDENT_KEYWORDS: [&str; 5] = ["return", "pass", "break", "continue", "raise"];
+
+/// Indentation for a new line inserted at `cursor`: the current line's
+/// indentation, one `unit` deeper after an opening bracket (or a Python
+/// `:`), one shallower after a Python block-ending statement. When the
+/// cursor sits between a bracket pair, also returns the indentation for the
+/// line holding the closer.
+pub fn indent_for_newline(code: &str, language: &str, cursor: usize, unit: &str) -> (String, Option<String>) {
+    let line_start = code[..cursor].rfind('\n').map_or(0, |i| i + 1);
+    let line = &code[line_start..cursor];
+    let current: String = line.chars().take_while(|c| *c == ' ' || *c == '\t').collect();
+    let before = line.trim_end();
+    let after = code[cursor..].trim_start_matches([' ', '\t']);
+    let python = comment_syntax(language).is_some_and(|(marker, _)| marker == "#");
+
+    let last = before.chars().next_back();
+    let opens = last.and_then(closer_for).is_some() || (python && last == Some(':'));
+    if opens {
+        let deeper = format!("{}{}", current, unit);
+        let closes = last.and_then(closer_for).is_some_and(|closer| after.starts_with(closer));
+        return (deeper, closes.then_some(current));
+    }
+    let statement = before.trim_start();
+    let word = statement.split(|c: char| !c.is_alphanumeric() && c != '_').next().unwrap_or("");
+    if python && PYTHON_DEDENT_KEYWORDS.contains(&word) {
+        let dedented = current.strip_suffix(unit).unwrap_or(&current).to_string();
+        return (dedented, None);
+    }
+    (current, None)
+}
+
+/// Wraps the selection `start..end` in `/* */`, or unwraps it if it already
+/// is. Languages without block comments toggle their line comment on every
+/// selected line instead. Returns the new code and selection.
+pub fn toggle_comment(code: &str, language: &str, start: usize, end: usize) -> (String, usize, usize) {
+    let (line_marker, block) = comment_syntax(language).unwrap_or(("//", true));
+    if block {
+        let selected = &code[start..end];
+        let trimmed = selected.trim();
+        let lead = selected.len() - selected.trim_start().len();
+        if let Some(inner) = trimmed.strip_prefix("/*").and_then(|s| s.strip_suffix("*/")) {
+            let inner = inner.strip_prefix(' ').unwrap_or(inner);
+            let inner = inner.strip_suffix(' ').unwrap_or(inner);
+            let new_start = start + lead;
+            let code = format!("{}{}{}", &code[..new_start], inner, &code[new_start + trimmed.len()..]);
+            return (code, new_start, new_start + inner.len());
+        }
+        let code = format!("{}/* {} */{}", &code[..start], selected, &code[end..]);
+        return (code, start, end + 6);
+    }
+
+    let first_line = code[..start].rfind('\n').map_or(0, |i| i + 1);
+    // A selection ending right after a newline does not include that line.
+    let last_end = if end > start && code[..end].ends_with('\n') { end - 1 } else { end };
+    let last_line = code[last_end..].find('\n').map_or(code.len(), |i| last_end + i);
+    let lines: Vec<&str> = code[first_line..last_line].split('\n').collect();
+    let commented = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .all(|l| l.trim_start().starts_with(line_marker));
+    let indent = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let toggled: Vec<String> = lines
+        .iter()
+        .map(|l| {
+            if l.trim().is_empty() {
+                l.to_string()
+            } else if commented {
+                let (lead, rest) = l.split_at(l.len() - l.trim_start().len());
+                let rest = rest.strip_prefix(line_marker).unwrap_or(rest);
+                format!("{}{}", lead, rest.strip_prefix(' ').unwrap_or(rest))
+            } else {
+                format!("{}{} {}", &l[..indent], line_marker, &l[indent..])
+            }
+        })
+        .collect();
+    let block_text = toggled.join("\n");
+    let new_code = format!("{}{}{}", &code[..first_line], block_text, &code[last_line..]);
+    (new_code, first_line, first_line + block_text.len())
+}
+
+/// Returns the offset of the bracket matching the one at (or just before)
+/// `cursor`, or `undefined`. Offsets are UTF-16 like `selectionStart`.
+#[wasm_bindgen]
+pub fn match_bracket(code: &str, language: &str, cursor: u32) -> Option<u32> {
+    match_bracket_at(code, language, utf16_to_byte(code, cursor)).map(|byte| byte_to_utf16(code, byte))
+}
+
+/// Computes the indentation for pressing Enter at `cursor`, using
+/// `indent_unit` (e.g. four spaces or a tab) per level. Returns
+/// `{ indent, closingIndent }`; `closingIndent` is non-null when the cursor
+/// is between a bracket pair and the closer should move to its own line.
+#[wasm_bindgen]
+pub fn auto_indent(code: &str, language: &str, cursor: u32, indent_unit: &str) -> JsValue {
+    let (indent, closing) = indent_for_newline(code, language, utf16_to_byte(code, cursor), indent_unit);
+    let result = js_sys::Object::new();
+    let set = |key: &str, value: JsValue| {
+        js_sys::Reflect::set(&result, &JsValue::from_str(key), &value).unwrap();
+    };
+    set("indent", JsValue::from_str(&indent));
+    set("closingIndent", closing.map(JsValue::from).unwrap_or(JsValue::NULL));
+    result.into()
+}
+
+/// Toggles a block comment around the selection `start..end` (UTF-16
+/// offsets), or line comments for languages without block comments.
+/// Returns `{ code, start, end }` with the updated selection.
+#[wasm_bindgen]
+pub fn toggle_block_comment(code: &str, language: &str, start: u32, end: u32) -> JsValue {
+    let (start, end) = (utf16_to_byte(code, start.min(end)), utf16_to_byte(code, start.max(end)));
+    let (new_code, new_start, new_end) = toggle_comment(code, language, start, end);
+    let result = js_sys::Object::new();
+    let set = |key: &str, value: JsValue| {
+        js_sys::Reflect::set(&result, &JsValue::from_str(key), &value).unwrap();
+    };
+    set("start", JsValue::from(byte_to_utf16(&new_code, new_start)));
+    set("end", JsValue::from(byte_to_utf16(&new_code, new_end)));
+    set("code", new_code.into());
+    result.into()
+}
@@ -0,0 +1,37 @@
+use std::cell::RefCell;
+
+use wasm_bindgen::prelude::*;
+
+thread_local! {
+    static ERROR_CALLBACK: RefCell<Option<js_sys::Function>> = const { RefCell::new(None) };
+}
+
+/// Passes an internal error to the host's `on_error` callback as a JS
+/// `Error` with a `kind` property (`"panic"`, `"gl"` or `"fallback"`).
+/// Must not be called while holding a borrow the callback could re-enter.
+pub(crate) fn report(kind: &str, message: &str) {
+    let Some(callback) = ERROR_CALLBACK.with(|callback| callback.borrow().clone()) else {
+        return;
+    };
+    let error: JsValue = JsError::new(message).into();
+    js_sys::Reflect::set(&error, &"kind".into(), &kind.into()).unwrap();
+    let _ = callback.call1(&JsValue::NULL, &error);
+}
+
+pub(crate) fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        #[cfg(feature = "panic-hook")]
+        console_error_panic_hook::hook(info);
+        report("panic", &info.to_string());
+    }));
+}
+
+/// Registers `callback` to receive internal failures — panics, WebGL
+/// errors and the switch to the non-WebGL fallback — as `Error` objects
+/// with a `kind` property, e.g. to forward them to an error tracker. Pass
+/// `null` to unregister. After a panic the module is unusable, so the
+/// callback should not call back into it.
+#[wasm_bindgen]
+pub fn on_error(callback: Option<js_sys::Function>) {
+    ERROR_CALLBACK.with(|slot| *slot.borrow_mut() = callback);
+}
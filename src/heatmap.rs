@@ -0,0 +1,298 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    window, HtmlCanvasElement, MouseEvent, WebGlBuffer, WebGlProgram, WebGlRenderingContext as GL,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::stars::{compile_shader, link_program};
+
+struct ActivityHeatmap {
+    gl: GL,
+    canvas: HtmlCanvasElement,
+    resolution: (f32, f32),
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+    counts: Vec<u32>,
+    start_weekday: usize,
+    hovered: Option<usize>,
+    on_hover: Option<js_sys::Function>,
+}
+
+const HEATMAP_LEVELS: [[f32; 3]; 5] = [
+    [0.16, 0.2, 0.32],
+    [0.05, 0.27, 0.16],
+    [0.0, 0.43, 0.2],
+    [0.15, 0.65, 0.25],
+    [0.22, 0.83, 0.33],
+];
+const HEATMAP_HOVER_COLOR: [f32; 3] = [0.9, 0.95, 1.0];
+const HEATMAP_GAP_RATIO: f32 = 0.18;
+
+thread_local! {
+    static HEATMAPS: RefCell<HashMap<String, Rc<RefCell<ActivityHeatmap>>>> = RefCell::new(HashMap::new());
+}
+
+impl ActivityHeatmap {
+    fn new(canvas_id: &str) -> ActivityHeatmap {
+        let document = window().unwrap().document().unwrap();
+        let canvas = document
+            .get_element_by_id(canvas_id)
+            .expect("Canvas element not found")
+            .dyn_into::<HtmlCanvasElement>()
+            .unwrap();
+
+        let gl: GL = canvas
+            .get_context("webgl")
+            .unwrap()
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+
+        let vertex_shader_source = r#"
+            attribute vec2 a_position;
+            attribute vec3 a_color;
+            uniform vec2 u_resolution;
+            varying vec3 v_color;
+            void main() {
+                vec2 zeroToOne = a_position / u_resolution;
+                vec2 zeroToTwo = zeroToOne * 2.0;
+                vec2 clipSpace = zeroToTwo - 1.0;
+                clipSpace.y = -clipSpace.y;
+                gl_Position = vec4(clipSpace, 0.0, 1.0);
+                v_color = a_color;
+            }
+        "#;
+        let fragment_shader_source = r#"
+            precision mediump float;
+            varying vec3 v_color;
+            void main() {
+                gl_FragColor = vec4(v_color, 1.0);
+            }
+        "#;
+        let vertex_shader = compile_shader(&gl, GL::VERTEX_SHADER, vertex_shader_source)
+            .expect("Heatmap vertex shader compile error");
+        let fragment_shader = compile_shader(&gl, GL::FRAGMENT_SHADER, fragment_shader_source)
+            .expect("Heatmap fragment shader compile error");
+        let program = link_program(&gl, &vertex_shader, &fragment_shader)
+            .expect("Heatmap program link error");
+        let buffer = gl.create_buffer().expect("Failed to create heatmap buffer");
+
+        let mut heatmap = ActivityHeatmap {
+            gl,
+            canvas,
+            resolution: (0.0, 0.0),
+            program,
+            buffer,
+            counts: Vec::new(),
+            start_weekday: 0,
+            hovered: None,
+            on_hover: None,
+        };
+        heatmap.resize();
+        heatmap
+    }
+
+    fn resize(&mut self) {
+        let dpr = window().unwrap().device_pixel_ratio() as f32;
+        let width = self.canvas.client_width() as f32 * dpr;
+        let height = self.canvas.client_height() as f32 * dpr;
+        self.canvas.set_width(width as u32);
+        self.canvas.set_height(height as u32);
+        self.resolution = (width, height);
+    }
+
+    fn columns(&self) -> usize {
+        (self.start_weekday + self.counts.len()).div_ceil(7).max(1)
+    }
+
+    fn cell_size(&self) -> f32 {
+        (self.resolution.0 / self.columns() as f32).min(self.resolution.1 / 7.0)
+    }
+
+    fn cell_origin(&self, index: usize) -> (f32, f32) {
+        let slot = self.start_weekday + index;
+        let cell = self.cell_size();
+        ((slot / 7) as f32 * cell, (slot % 7) as f32 * cell)
+    }
+
+    fn hit_test(&self, x: f32, y: f32) -> Option<usize> {
+        let cell = self.cell_size();
+        if cell <= 0.0 || x < 0.0 || y < 0.0 {
+            return None;
+        }
+        let column = (x / cell) as usize;
+        let row = (y / cell) as usize;
+        if row >= 7 || column >= self.columns() {
+            return None;
+        }
+        let inset = cell * HEATMAP_GAP_RATIO;
+        if x - column as f32 * cell > cell - inset || y - row as f32 * cell > cell - inset {
+            return None;
+        }
+        (column * 7 + row)
+            .checked_sub(self.start_weekday)
+            .filter(|index| *index < self.counts.len())
+    }
+
+    fn level(&self, count: u32, max: u32) -> usize {
+        if count == 0 || max == 0 {
+            return 0;
+        }
+        let quartile = (count as f32 / max as f32 * 4.0).ceil() as usize;
+        quartile.clamp(1, HEATMAP_LEVELS.len() - 1)
+    }
+
+    fn draw(&self) {
+        let gl = &self.gl;
+        gl.viewport(0, 0, self.resolution.0 as i32, self.resolution.1 as i32);
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(GL::COLOR_BUFFER_BIT);
+        if self.counts.is_empty() {
+            return;
+        }
+
+        let max = self.counts.iter().copied().max().unwrap_or(0);
+        let cell = self.cell_size();
+        let size = cell * (1.0 - HEATMAP_GAP_RATIO);
+        let mut data = Vec::with_capacity(self.counts.len() * 6 * 5);
+        for (index, count) in self.counts.iter().enumerate() {
+            let (x0, y0) = self.cell_origin(index);
+            let (x1, y1) = (x0 + size, y0 + size);
+            let color = if self.hovered == Some(index) {
+                HEATMAP_HOVER_COLOR
+            } else {
+                HEATMAP_LEVELS[self.level(*count, max)]
+            };
+            for (x, y) in [(x0, y0), (x1, y0), (x0, y1), (x1, y0), (x1, y1), (x0, y1)] {
+                data.push(x);
+                data.push(y);
+                data.push(color[0]);
+                data.push(color[1]);
+                data.push(color[2]);
+            }
+        }
+
+        gl.use_program(Some(&self.program));
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.buffer));
+        unsafe {
+            let array = js_sys::Float32Array::view(&data);
+            gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &array, GL::DYNAMIC_DRAW);
+        }
+        let stride = 5 * std::mem::size_of::<f32>() as i32;
+        let pos_loc = gl.get_attrib_location(&self.program, "a_position") as u32;
+        let color_loc = gl.get_attrib_location(&self.program, "a_color") as u32;
+        gl.enable_vertex_attrib_array(pos_loc);
+        gl.vertex_attrib_pointer_with_i32(pos_loc, 2, GL::FLOAT, false, stride, 0);
+        gl.enable_vertex_attrib_array(color_loc);
+        gl.vertex_attrib_pointer_with_i32(color_loc, 3, GL::FLOAT, false, stride, 2 * std::mem::size_of::<f32>() as i32);
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_resolution") {
+            gl.uniform2f(Some(&loc), self.resolution.0, self.resolution.1);
+        }
+        gl.draw_arrays(GL::TRIANGLES, 0, (self.counts.len() * 6) as i32);
+    }
+
+    fn set_hovered(&mut self, hovered: Option<usize>) -> Option<HoverEvent> {
+        if hovered == self.hovered {
+            return None;
+        }
+        self.hovered = hovered;
+        self.draw();
+        let callback = self.on_hover.clone()?;
+        let (index, count) = match hovered {
+            Some(index) => (JsValue::from(index as u32), JsValue::from(self.counts[index])),
+            None => (JsValue::NULL, JsValue::NULL),
+        };
+        Some(HoverEvent { callback, index, count })
+    }
+}
+
+// Dispatched after the heatmap borrow is released so the callback may call
+// back into `render_activity_heatmap`.
+struct HoverEvent {
+    callback: js_sys::Function,
+    index: JsValue,
+    count: JsValue,
+}
+
+impl HoverEvent {
+    fn dispatch(self) {
+        let _ = self.callback.call2(&JsValue::NULL, &self.index, &self.count);
+    }
+}
+
+fn attach_listeners(canvas: &HtmlCanvasElement, heatmap: &Rc<RefCell<ActivityHeatmap>>) {
+    {
+        let heatmap = heatmap.clone();
+        let move_closure = Closure::wrap(Box::new(move |event: MouseEvent| {
+            let dpr = window().unwrap().device_pixel_ratio() as f32;
+            let hover = {
+                let mut hm = heatmap.borrow_mut();
+                let hit = hm.hit_test(event.offset_x() as f32 * dpr, event.offset_y() as f32 * dpr);
+                hm.set_hovered(hit)
+            };
+            if let Some(hover) = hover {
+                hover.dispatch();
+            }
+        }) as Box<dyn FnMut(MouseEvent)>);
+        canvas
+            .add_event_listener_with_callback("mousemove", move_closure.as_ref().unchecked_ref())
+            .unwrap();
+        move_closure.forget();
+    }
+    {
+        let heatmap = heatmap.clone();
+        let leave_closure = Closure::wrap(Box::new(move || {
+            let hover = heatmap.borrow_mut().set_hovered(None);
+            if let Some(hover) = hover {
+                hover.dispatch();
+            }
+        }) as Box<dyn FnMut()>);
+        canvas
+            .add_event_listener_with_callback("mouseleave", leave_closure.as_ref().unchecked_ref())
+            .unwrap();
+        leave_closure.forget();
+    }
+    {
+        let heatmap = heatmap.clone();
+        let resize_closure = Closure::wrap(Box::new(move || {
+            let mut hm = heatmap.borrow_mut();
+            hm.resize();
+            hm.draw();
+        }) as Box<dyn FnMut()>);
+        window().unwrap()
+            .add_event_listener_with_callback("resize", resize_closure.as_ref().unchecked_ref())
+            .unwrap();
+        resize_closure.forget();
+    }
+}
+
+/// Renders a GitHub-style activity calendar into `canvas_id`. `counts` holds
+/// one submission count per day, oldest first; `start_weekday` is the row of
+/// the first day (0 = Sunday). `on_hover` is called as `(dayIndex, count)`
+/// when the pointer enters a cell and `(null, null)` when it leaves.
+/// Calling this again for the same canvas replaces the data.
+#[wasm_bindgen]
+pub fn render_activity_heatmap(
+    canvas_id: &str,
+    counts: Vec<u32>,
+    start_weekday: u32,
+    on_hover: Option<js_sys::Function>,
+) {
+    let existing = HEATMAPS.with(|heatmaps| heatmaps.borrow().get(canvas_id).cloned());
+    let heatmap = existing.unwrap_or_else(|| {
+        let heatmap = Rc::new(RefCell::new(ActivityHeatmap::new(canvas_id)));
+        let canvas = heatmap.borrow().canvas.clone();
+        attach_listeners(&canvas, &heatmap);
+        HEATMAPS.with(|heatmaps| heatmaps.borrow_mut().insert(canvas_id.to_string(), heatmap.clone()));
+        heatmap
+    });
+    let mut hm = heatmap.borrow_mut();
+    hm.counts = counts;
+    hm.start_weekday = (start_weekday % 7) as usize;
+    hm.hovered = None;
+    hm.on_hover = on_hover;
+    hm.draw();
+}
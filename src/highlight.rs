@@ -0,0 +1,445 @@
+use wasm_bindgen::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TokenKind {
+    Plain,
+    Keyword,
+    Type,
+    Literal,
+    String,
+    Number,
+    Comment,
+    Preprocessor,
+    Attribute,
+    Function,
+    Punctuation,
+}
+
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+}
+
+struct Language {
+    keywords: &'static [&'static str],
+    types: &'static [&'static str],
+    literals: &'static [&'static str],
+    line_comment: &'static str,
+    block_comments: bool,
+    hash_preprocessor: bool,
+    triple_quotes: bool,
+    at_attributes: bool,
+    rust_attributes: bool,
+    char_literals: bool,
+}
+
+const CPP: Language = Language {
+    keywords: &[
+        "alignas", "alignof", "auto", "break", "case", "catch", "class", "const", "constexpr",
+        "const_cast", "continue", "decltype", "default", "delete", "do", "dynamic_cast", "else",
+        "enum", "explicit", "export", "extern", "for", "friend", "goto", "if", "inline",
+        "mutable", "namespace", "new", "noexcept", "operator", "private", "protected", "public",
+        "register", "reinterpret_cast", "return", "sizeof", "static", "static_assert",
+        "static_cast", "struct", "switch", "template", "this", "throw", "try", "typedef",
+        "typename", "union", "using", "virtual", "volatile", "while",
+    ],
+    types: &[
+        "bool", "char", "double", "float", "int", "long", "short", "signed", "unsigned", "void",
+        "size_t", "int64_t", "uint64_t", "int32_t", "uint32_t", "string", "vector", "map", "set",
+        "pair", "array", "deque", "queue", "stack", "priority_queue", "unordered_map",
+        "unordered_set", "bitset",
+    ],
+    literals: &["true", "false", "nullptr", "NULL"],
+    line_comment: "//",
+    block_comments: true,
+    hash_preprocessor: true,
+    triple_quotes: false,
+    at_attributes: false,
+    rust_attributes: false,
+    char_literals: true,
+};
+
+const PYTHON: Language = Language {
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is",
+        "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with",
+        "yield", "match", "case",
+    ],
+    types: &[
+        "int", "float", "str", "bool", "list", "dict", "set", "tuple", "bytes", "object",
+        "range", "print", "len", "input", "map", "sorted", "enumerate", "zip", "min", "max", "sum",
+    ],
+    literals: &["True", "False", "None"],
+    line_comment: "#",
+    block_comments: false,
+    hash_preprocessor: false,
+    triple_quotes: true,
+    at_attributes: true,
+    rust_attributes: false,
+    char_literals: false,
+};
+
+const RUST: Language = Language {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "type",
+        "unsafe", "use", "where", "while",
+    ],
+    types: &[
+        "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16",
+        "u32", "u64", "u128", "usize", "str", "String", "Vec", "Option", "Result", "Box",
+        "HashMap", "HashSet", "BTreeMap", "BTreeSet", "VecDeque", "BinaryHeap",
+    ],
+    literals: &["true", "false", "None", "Some", "Ok", "Err"],
+    line_comment: "//",
+    block_comments: true,
+    hash_preprocessor: false,
+    triple_quotes: false,
+    at_attributes: false,
+    rust_attributes: true,
+    char_literals: true,
+};
+
+const JAVA: Language = Language {
+    keywords: &[
+        "abstract", "assert", "break", "case", "catch", "class", "const", "continue", "default",
+        "do", "else", "enum", "extends", "final", "finally", "for", "goto", "if", "implements",
+        "import", "instanceof", "interface", "native", "new", "package", "private", "protected",
+        "public", "return", "static", "strictfp", "super", "switch", "synchronized", "this",
+        "throw", "throws", "transient", "try", "var", "volatile", "while", "record",
+    ],
+    types: &[
+        "boolean", "byte", "char", "double", "float", "int", "long", "short", "void", "String",
+        "Integer", "Long", "Double", "Object", "List", "ArrayList", "Map", "HashMap", "Set",
+        "HashSet", "Scanner", "StringBuilder",
+    ],
+    literals: &["true", "false", "null"],
+    line_comment: "//",
+    block_comments: true,
+    hash_preprocessor: false,
+    triple_quotes: false,
+    at_attributes: true,
+    rust_attributes: false,
+    char_literals: true,
+};
+
+fn language_for(name: &str) -> Option<&'static Language> {
+    match name.to_ascii_lowercase().as_str() {
+        "c" | "cpp" | "c++" | "cc" | "cxx" => Some(&CPP),
+        "python" | "py" | "python3" => Some(&PYTHON),
+        "rust" | "rs" => Some(&RUST),
+        "java" => Some(&JAVA),
+        _ => None,
+    }
+}
+
+/// The line comment marker of `language` and whether it has `/* */` block
+/// comments.
+pub(crate) fn comment_syntax(language: &str) -> Option<(&'static str, bool)> {
+    language_for(language).map(|lang| (lang.line_comment, lang.block_comments))
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+struct Scanner<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn peek_at(&self, n: usize) -> Option<char> {
+        self.src[self.pos..].chars().nth(n)
+    }
+
+    fn starts_with(&self, s: &str) -> bool {
+        self.src[self.pos..].starts_with(s)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat_while(&mut self, f: impl Fn(char) -> bool) {
+        while self.peek().is_some_and(&f) {
+            self.bump();
+        }
+    }
+
+    fn eat_until_line_end(&mut self) {
+        self.eat_while(|c| c != '\n');
+    }
+
+    fn eat_until(&mut self, end: &str) {
+        match self.src[self.pos..].find(end) {
+            Some(i) => self.pos += i + end.len(),
+            None => self.pos = self.src.len(),
+        }
+    }
+
+    fn eat_quoted(&mut self, quote: char) {
+        self.bump();
+        while let Some(c) = self.bump() {
+            if c == '\\' {
+                self.bump();
+            } else if c == quote || c == '\n' {
+                break;
+            }
+        }
+    }
+}
+
+/// Splits `code` into highlight tokens. Unknown languages yield a single
+/// plain token.
+pub fn tokenize<'a>(code: &'a str, language: &str) -> Vec<Token<'a>> {
+    let lang = match language_for(language) {
+        Some(lang) => lang,
+        None => return vec![Token { kind: TokenKind::Plain, text: code }],
+    };
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut s = Scanner { src: code, pos: 0 };
+    let mut line_start = true;
+
+    while let Some(c) = s.peek() {
+        let start = s.pos;
+        let kind = if c.is_whitespace() {
+            s.eat_while(|c| c.is_whitespace() && c != '\n');
+            if s.peek() == Some('\n') {
+                s.bump();
+                line_start = true;
+                tokens.push(Token { kind: TokenKind::Plain, text: &code[start..s.pos] });
+                continue;
+            }
+            TokenKind::Plain
+        } else if s.starts_with(lang.line_comment) {
+            s.eat_until_line_end();
+            TokenKind::Comment
+        } else if lang.block_comments && s.starts_with("/*") {
+            s.pos += 2;
+            s.eat_until("*/");
+            TokenKind::Comment
+        } else if lang.hash_preprocessor && c == '#' && line_start {
+            s.eat_until_line_end();
+            TokenKind::Preprocessor
+        } else if lang.rust_attributes && c == '#' && matches!(s.peek_at(1), Some('[') | Some('!')) {
+            s.eat_until("]");
+            TokenKind::Attribute
+        } else if lang.at_attributes && c == '@' {
+            s.bump();
+            s.eat_while(|c| is_ident_continue(c) || c == '.');
+            TokenKind::Attribute
+        } else if lang.triple_quotes && (s.starts_with("\"\"\"") || s.starts_with("'''")) {
+            let delimiter = &code[s.pos..s.pos + 3];
+            s.pos += 3;
+            s.eat_until(delimiter);
+            TokenKind::String
+        } else if c == '"' || (c == '\'' && !lang.char_literals) {
+            s.eat_quoted(c);
+            TokenKind::String
+        } else if c == '\'' && lang.char_literals {
+            // Rust lifetimes (`'a`) look like an unterminated char literal.
+            let is_lifetime = lang.rust_attributes
+                && s.peek_at(1).is_some_and(is_ident_start)
+                && s.peek_at(2) != Some('\'');
+            if is_lifetime {
+                s.bump();
+                s.eat_while(is_ident_continue);
+                TokenKind::Type
+            } else {
+                s.eat_quoted('\'');
+                TokenKind::String
+            }
+        } else if c.is_ascii_digit() || (c == '.' && s.peek_at(1).is_some_and(|d| d.is_ascii_digit())) {
+            s.eat_while(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '\'');
+            TokenKind::Number
+        } else if is_ident_start(c) {
+            s.eat_while(is_ident_continue);
+            let word = &code[start..s.pos];
+            if lang.rust_attributes && s.peek() == Some('!') {
+                s.bump();
+                TokenKind::Function
+            } else if lang.keywords.contains(&word) {
+                TokenKind::Keyword
+            } else if lang.types.contains(&word) {
+                TokenKind::Type
+            } else if lang.literals.contains(&word) {
+                TokenKind::Literal
+            } else if s.src[s.pos..].trim_start_matches([' ', '\t']).starts_with('(') {
+                TokenKind::Function
+            } else {
+                TokenKind::Plain
+            }
+        } else {
+            s.bump();
+            TokenKind::Punctuation
+        };
+        if !c.is_whitespace() {
+            line_start = false;
+        }
+
+        let text = &code[start..s.pos];
+        match tokens.last_mut() {
+            Some(last) if last.kind == kind && matches!(kind, TokenKind::Plain | TokenKind::Punctuation) => {
+                last.text = &code[start - last.text.len()..s.pos];
+            }
+            _ => tokens.push(Token { kind, text }),
+        }
+    }
+    tokens
+}
+
+fn kind_name(kind: TokenKind) -> &'static str {
+    match kind {
+        TokenKind::Plain => "plain",
+        TokenKind::Keyword => "keyword",
+        TokenKind::Type => "type",
+        TokenKind::Literal => "literal",
+        TokenKind::String => "string",
+        TokenKind::Number => "number",
+        TokenKind::Comment => "comment",
+        TokenKind::Preprocessor => "preprocessor",
+        TokenKind::Attribute => "attribute",
+        TokenKind::Function => "function",
+        TokenKind::Punctuation => "punctuation",
+    }
+}
+
+pub fn escape_html(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// Highlights `code` as HTML: every non-plain token is wrapped in
+/// `<span class="tok-KIND">` and all text is escaped. Supported languages
+/// are C/C++, Python, Rust and Java; anything else is returned escaped.
+#[wasm_bindgen]
+pub fn highlight(code: &str, language: &str) -> String {
+    let mut html = String::with_capacity(code.len() * 2);
+    for token in tokenize(code, language) {
+        if token.kind == TokenKind::Plain {
+            escape_html(token.text, &mut html);
+        } else {
+            html.push_str("<span class=\"tok-");
+            html.push_str(kind_name(token.kind));
+            html.push_str("\">");
+            escape_html(token.text, &mut html);
+            html.push_str("</span>");
+        }
+    }
+    html
+}
+
+/// Returns the tokens of `code` as an array of `{ kind, text }` objects.
+#[wasm_bindgen]
+pub fn highlight_tokens(code: &str, language: &str) -> js_sys::Array {
+    tokenize(code, language)
+        .into_iter()
+        .map(|token| {
+            let object = js_sys::Object::new();
+            js_sys::Reflect::set(&object, &"kind".into(), &kind_name(token.kind).into()).unwrap();
+            js_sys::Reflect::set(&object, &"text".into(), &token.text.into()).unwrap();
+            JsValue::from(object)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The tokens other than plain text, which is everything in between.
+    fn marked<'a>(code: &'a str, language: &str) -> Vec<(TokenKind, &'a str)> {
+        tokenize(code, language)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Plain)
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    fn strings<'a>(code: &'a str, language: &str) -> Vec<&'a str> {
+        marked(code, language)
+            .into_iter()
+            .filter(|(kind, _)| *kind == TokenKind::String)
+            .map(|(_, text)| text)
+            .collect()
+    }
+
+    #[test]
+    fn tokens_cover_the_code() {
+        let code = "#include <cstdio>\nint main() { printf(\"%d\\n\", 'x'); } // done\n";
+        for language in ["cpp", "python", "rust", "java", "brainfuck"] {
+            let joined: String = tokenize(code, language).iter().map(|token| token.text).collect();
+            assert_eq!(joined, code, "{}", language);
+        }
+    }
+
+    #[test]
+    fn strings_keep_escaped_quotes() {
+        assert_eq!(strings(r#"s = "a\"b" + "\\";"#, "cpp"), [r#""a\"b""#, r#""\\""#]);
+        assert_eq!(strings(r#"s = 'it\'s' + "x""#, "python"), [r"'it\'s'", "\"x\""]);
+        assert_eq!(strings("s = \"\"\"two\nlines\"\"\" + 1", "python"), ["\"\"\"two\nlines\"\"\""]);
+        // An unterminated string stops at the end of its line.
+        assert_eq!(strings("\"open\nint x;", "java"), ["\"open\n"]);
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(marked("x; // note \"q\"\ny", "cpp")[1], (TokenKind::Comment, "// note \"q\""));
+        assert_eq!(marked("x # note\ny", "python"), [(TokenKind::Comment, "# note")]);
+        let block = marked("a /* one\ntwo */ b", "java");
+        assert_eq!(block, [(TokenKind::Comment, "/* one\ntwo */")]);
+        assert_eq!(marked("a /* open", "rust"), [(TokenKind::Comment, "/* open")]);
+        // Python has no block comments.
+        assert!(!marked("a /* b */", "python").iter().any(|(kind, _)| *kind == TokenKind::Comment));
+    }
+
+    #[test]
+    fn lifetimes_and_char_literals() {
+        let tokens = marked("fn f<'a>(s: &'a str) -> char { 'b' }", "rust");
+        assert!(tokens.contains(&(TokenKind::Type, "'a")));
+        assert_eq!(strings("fn f<'a>(s: &'a str) -> char { 'b' }", "rust"), ["'b'"]);
+        assert_eq!(strings(r"let c = '\n'; let q = '\'';", "rust"), [r"'\n'", r"'\''"]);
+        // Other languages have no lifetimes.
+        assert_eq!(strings("char c = 'a';", "cpp"), ["'a'"]);
+    }
+
+    #[test]
+    fn preprocessor_lines() {
+        let tokens = marked("#include <vector>\n  #define N 10\nint x = a # b;", "cpp");
+        assert_eq!(tokens[0], (TokenKind::Preprocessor, "#include <vector>"));
+        assert_eq!(tokens[1], (TokenKind::Preprocessor, "#define N 10"));
+        // Only a `#` opening a line starts a directive.
+        assert!(tokens[2..].iter().all(|(kind, _)| *kind != TokenKind::Preprocessor));
+        assert_eq!(marked("#[derive(Debug)]\nstruct S;", "rust")[0], (TokenKind::Attribute, "#[derive(Debug)]"));
+    }
+
+    #[test]
+    fn highlight_escapes_html() {
+        assert_eq!(
+            highlight("a < \"<b>\"", "cpp"),
+            "a <span class=\"tok-punctuation\">&lt;</span> <span class=\"tok-string\">&quot;&lt;b&gt;&quot;</span>"
+        );
+        assert_eq!(highlight("<&>", "cobol"), "&lt;&amp;&gt;");
+    }
+}
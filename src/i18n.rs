@@ -0,0 +1,232 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+const PLURAL_CATEGORIES: [&str; 6] = ["zero", "one", "two", "few", "many", "other"];
+const FALLBACK_LOCALE: &str = "en";
+
+enum Message {
+    Text(String),
+    Plural(HashMap<String, String>),
+}
+
+struct Catalogs {
+    locale: String,
+    messages: HashMap<String, HashMap<String, Message>>,
+}
+
+thread_local! {
+    static CATALOGS: RefCell<Catalogs> = RefCell::new(Catalogs {
+        locale: FALLBACK_LOCALE.to_string(),
+        messages: HashMap::new(),
+    });
+}
+
+/// CLDR plural category of `n` for `locale`, covering the languages the site
+/// ships; unknown languages use the English rules.
+pub fn plural_category(locale: &str, n: f64) -> &'static str {
+    let language = locale.split(['-', '_']).next().unwrap_or("").to_ascii_lowercase();
+    let integer = n.fract() == 0.0;
+    let i = n.abs().trunc() as u64;
+    let (mod10, mod100) = (i % 10, i % 100);
+    match language.as_str() {
+        "zh" | "ja" | "ko" | "vi" | "th" | "id" | "ms" => "other",
+        "fr" | "hy" => {
+            if i <= 1 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+        "ru" | "uk" | "be" => match (integer, mod10, mod100) {
+            (false, _, _) => "other",
+            (_, 1, m) if m != 11 => "one",
+            (_, 2..=4, m) if !(12..=14).contains(&m) => "few",
+            _ => "many",
+        },
+        "pl" => match (integer, i, mod10, mod100) {
+            (false, _, _, _) => "other",
+            (_, 1, _, _) => "one",
+            (_, _, 2..=4, m) if !(12..=14).contains(&m) => "few",
+            _ => "many",
+        },
+        "cs" | "sk" => match (integer, i) {
+            (false, _) => "many",
+            (_, 1) => "one",
+            (_, 2..=4) => "few",
+            _ => "other",
+        },
+        "ar" => match (integer, i, mod100) {
+            (false, _, _) => "other",
+            (_, 0, _) => "zero",
+            (_, 1, _) => "one",
+            (_, 2, _) => "two",
+            (_, _, 3..=10) => "few",
+            (_, _, 11..=99) => "many",
+            _ => "other",
+        },
+        _ => {
+            if n == 1.0 {
+                "one"
+            } else {
+                "other"
+            }
+        }
+    }
+}
+
+/// Replaces `{name}` placeholders with `args`; `{{` and `}}` are literal
+/// braces and unknown placeholders are kept as written.
+pub fn interpolate(template: &str, args: &[(String, String)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find(['{', '}']) {
+        out.push_str(&rest[..open]);
+        let tail = &rest[open..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        let placeholder = tail.strip_prefix('{').and_then(|inner| inner.find('}').map(|end| &inner[..end]));
+        match placeholder.and_then(|name| args.iter().find(|(key, _)| key == name.trim())) {
+            Some((_, value)) => {
+                out.push_str(value);
+                rest = &tail[placeholder.unwrap().len() + 2..];
+            }
+            None => {
+                out.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// `zh-CN` falls back to `zh`, then to the fallback locale.
+fn locale_chain(locale: &str) -> Vec<String> {
+    let mut chain = vec![locale.to_string()];
+    if let Some((language, _)) = locale.split_once(['-', '_']) {
+        chain.push(language.to_string());
+    }
+    if !chain.iter().any(|l| l == FALLBACK_LOCALE) {
+        chain.push(FALLBACK_LOCALE.to_string());
+    }
+    chain
+}
+
+/// Looks `key` up in the active locale (with fallbacks) and formats it.
+/// A numeric `count` argument selects the plural form. Returns `None` when
+/// no catalog has the key.
+pub(crate) fn translate(key: &str, args: &[(String, String)]) -> Option<String> {
+    CATALOGS.with(|catalogs| {
+        let catalogs = catalogs.borrow();
+        for locale in locale_chain(&catalogs.locale) {
+            let Some(message) = catalogs.messages.get(&locale).and_then(|messages| messages.get(key)) else {
+                continue;
+            };
+            let template = match message {
+                Message::Text(text) => text,
+                Message::Plural(forms) => {
+                    let count = args
+                        .iter()
+                        .find(|(name, _)| name == "count")
+                        .and_then(|(_, value)| value.parse::<f64>().ok())
+                        .unwrap_or(0.0);
+                    let category = plural_category(&locale, count);
+                    forms.get(category).or_else(|| forms.get("other"))?
+                }
+            };
+            return Some(interpolate(template, args));
+        }
+        None
+    })
+}
+
+fn flatten(prefix: &str, value: &JsValue, messages: &mut HashMap<String, Message>) -> Result<(), JsValue> {
+    if let Some(text) = value.as_string() {
+        messages.insert(prefix.to_string(), Message::Text(text));
+        return Ok(());
+    }
+    let Some(object) = value.dyn_ref::<js_sys::Object>() else {
+        return Err(JsValue::from_str(&format!("Message {} must be a string or object", prefix)));
+    };
+    let entries: Vec<(String, JsValue)> = js_sys::Object::entries(object)
+        .iter()
+        .map(|entry| {
+            let pair: js_sys::Array = entry.unchecked_into();
+            (pair.get(0).as_string().unwrap_or_default(), pair.get(1))
+        })
+        .collect();
+    // An object whose keys are all plural categories holding strings is a
+    // plural message; anything else is a namespace.
+    let plural = !entries.is_empty()
+        && entries.iter().all(|(k, v)| PLURAL_CATEGORIES.contains(&k.as_str()) && v.is_string());
+    if plural {
+        let forms = entries.into_iter().map(|(k, v)| (k, v.as_string().unwrap())).collect();
+        messages.insert(prefix.to_string(), Message::Plural(forms));
+        return Ok(());
+    }
+    for (key, value) in entries {
+        let path = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+        flatten(&path, &value, messages)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn args_from_js(args: &JsValue) -> Vec<(String, String)> {
+    let Some(object) = args.dyn_ref::<js_sys::Object>() else {
+        return Vec::new();
+    };
+    js_sys::Object::entries(object)
+        .iter()
+        .filter_map(|entry| {
+            let pair: js_sys::Array = entry.unchecked_into();
+            let key = pair.get(0).as_string()?;
+            let value = pair.get(1);
+            let text = match value.as_f64() {
+                Some(number) => number.to_string(),
+                None => value.as_string().or_else(|| value.as_bool().map(|b| b.to_string()))?,
+            };
+            Some((key, text))
+        })
+        .collect()
+}
+
+/// Loads (or replaces) the catalog for `locale`. `messages` is an object, or
+/// its JSON text, mapping keys to strings; nested objects become dotted
+/// keys and objects keyed by plural categories (`one`, `few`, `other`, ...)
+/// are plural messages selected by the `count` argument.
+#[wasm_bindgen]
+pub fn load_catalog(locale: &str, messages: JsValue) -> Result<(), JsValue> {
+    let messages = match messages.as_string() {
+        Some(json) => js_sys::JSON::parse(&json)?,
+        None => messages,
+    };
+    let mut catalog = HashMap::new();
+    flatten("", &messages, &mut catalog)?;
+    CATALOGS.with(|catalogs| catalogs.borrow_mut().messages.insert(locale.to_string(), catalog));
+    Ok(())
+}
+
+/// Switches the active locale. Lookups fall back to the base language
+/// (`zh-CN` → `zh`) and then to English.
+#[wasm_bindgen]
+pub fn set_locale(locale: &str) {
+    CATALOGS.with(|catalogs| catalogs.borrow_mut().locale = locale.to_string());
+}
+
+#[wasm_bindgen]
+pub fn get_locale() -> String {
+    CATALOGS.with(|catalogs| catalogs.borrow().locale.clone())
+}
+
+/// Formats message `key` with `args` (an object of placeholder values).
+/// Missing keys return the key itself so untranslated text stays visible.
+#[wasm_bindgen]
+pub fn t(key: &str, args: JsValue) -> String {
+    translate(key, &args_from_js(&args)).unwrap_or_else(|| key.to_string())
+}
@@ -1,18 +1,35 @@
 use wasm_bindgen::prelude::*;
 
+pub mod ansi;
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod avatar;
+pub mod compare;
+pub mod confetti;
+mod diagnostics;
+pub mod diff;
+pub mod editor;
+pub mod errors;
+pub mod hash;
+pub mod heatmap;
+pub mod highlight;
+pub mod i18n;
+pub mod math;
+pub mod normalize;
+mod options;
+pub mod playground;
+pub mod rating_chart;
+pub mod recording;
+pub mod spinner;
 pub mod stars;
+pub mod stats_chart;
+pub mod status_stream;
+mod text;
+pub mod theme;
+pub mod virtual_table;
+pub mod zip;
 
 #[wasm_bindgen(start)]
 pub fn main() {
-    console_error_panic_hook::set_once();
-}
-
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_namespace = console)]
-    fn log(s: &str);
-}
-
-macro_rules! console_log {
-    ($($t:tt)*) => (log(&format_args!($($t)*).to_string()))
+    errors::install_panic_hook();
 }
\ No newline at end of file
@@ -0,0 +1,288 @@
+use wasm_bindgen::prelude::*;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SegmentKind {
+    Text,
+    Inline,
+    Display,
+}
+
+pub struct Segment {
+    pub kind: SegmentKind,
+    pub content: String,
+    pub source: String,
+    pub error: Option<String>,
+    pub fallback: String,
+}
+
+const FALLBACK_SYMBOLS: &[(&str, &str)] = &[
+    ("\\leq", "≤"), ("\\le", "≤"), ("\\geq", "≥"), ("\\ge", "≥"), ("\\neq", "≠"), ("\\ne", "≠"),
+    ("\\times", "×"), ("\\cdot", "·"), ("\\cdots", "⋯"), ("\\ldots", "…"), ("\\dots", "…"),
+    ("\\infty", "∞"), ("\\sum", "Σ"), ("\\prod", "Π"), ("\\pm", "±"), ("\\to", "→"),
+    ("\\rightarrow", "→"), ("\\leftarrow", "←"), ("\\in", "∈"), ("\\notin", "∉"),
+    ("\\subseteq", "⊆"), ("\\cup", "∪"), ("\\cap", "∩"), ("\\forall", "∀"), ("\\exists", "∃"),
+    ("\\lfloor", "⌊"), ("\\rfloor", "⌋"), ("\\lceil", "⌈"), ("\\rceil", "⌉"), ("\\oplus", "⊕"),
+    ("\\alpha", "α"), ("\\beta", "β"), ("\\gamma", "γ"), ("\\delta", "δ"), ("\\epsilon", "ε"),
+    ("\\lambda", "λ"), ("\\mu", "μ"), ("\\pi", "π"), ("\\sigma", "σ"), ("\\phi", "φ"),
+    ("\\omega", "ω"), ("\\Delta", "Δ"), ("\\Sigma", "Σ"), ("\\Omega", "Ω"), ("\\log", "log"),
+    ("\\max", "max"), ("\\min", "min"), ("\\gcd", "gcd"), ("\\bmod", "mod"), ("\\mod", "mod"),
+    ("\\left", ""), ("\\right", ""), ("\\,", " "), ("\\;", " "), ("\\quad", "  "), ("\\{", "{"),
+    ("\\}", "}"), ("\\|", "‖"),
+];
+
+/// Splits problem statement text into plain text and `$...$` / `$$...$$`
+/// math segments. `\$` is a literal dollar sign and an unterminated
+/// delimiter is kept as text.
+pub fn extract(text: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut plain = String::new();
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&b'$') {
+            plain.push('$');
+            i += 2;
+            continue;
+        }
+        if bytes[i] != b'$' {
+            let c = text[i..].chars().next().unwrap();
+            plain.push(c);
+            i += c.len_utf8();
+            continue;
+        }
+        let display = bytes.get(i + 1) == Some(&b'$');
+        let delimiter = if display { "$$" } else { "$" };
+        let body_start = i + delimiter.len();
+        match find_closing(text, body_start, delimiter) {
+            Some(body_end) => {
+                if !plain.is_empty() {
+                    segments.push(text_segment(std::mem::take(&mut plain)));
+                }
+                let raw = &text[body_start..body_end];
+                let content = normalize(raw);
+                let error = validate(&content);
+                segments.push(Segment {
+                    kind: if display { SegmentKind::Display } else { SegmentKind::Inline },
+                    fallback: fallback(&content),
+                    source: text[i..body_end + delimiter.len()].to_string(),
+                    content,
+                    error,
+                });
+                i = body_end + delimiter.len();
+            }
+            None => {
+                plain.push_str(delimiter);
+                i = body_start;
+            }
+        }
+    }
+    if !plain.is_empty() {
+        segments.push(text_segment(plain));
+    }
+    segments
+}
+
+fn text_segment(text: String) -> Segment {
+    Segment {
+        kind: SegmentKind::Text,
+        content: text.clone(),
+        source: text.clone(),
+        error: None,
+        fallback: text,
+    }
+}
+
+fn find_closing(text: &str, from: usize, delimiter: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let mut i = from;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            // Inline math never spans a blank line; treat it as a stray `$`.
+            b'\n' if delimiter == "$" && bytes.get(i + 1) == Some(&b'\n') => return None,
+            b'$' if text[i..].starts_with(delimiter) => {
+                if i == from {
+                    return None;
+                }
+                return Some(i);
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+fn normalize(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut last_space = false;
+    for c in raw.trim().chars() {
+        let c = match c {
+            '\u{00a0}' | '\u{3000}' => ' ',
+            '＜' => '<',
+            '＞' => '>',
+            '，' => ',',
+            '（' => '(',
+            '）' => ')',
+            _ => c,
+        };
+        if c.is_whitespace() {
+            if !last_space {
+                out.push(' ');
+            }
+            last_space = true;
+        } else {
+            out.push(c);
+            last_space = false;
+        }
+    }
+    out
+}
+
+fn validate(content: &str) -> Option<String> {
+    if content.is_empty() {
+        return Some("empty math segment".to_string());
+    }
+    let mut depth: i32 = 0;
+    let mut left_right: i32 = 0;
+    let mut environments: Vec<&str> = Vec::new();
+    let mut i = 0;
+    let bytes = content.as_bytes();
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => {
+                let rest = &content[i + 1..];
+                let name_len = rest.bytes().take_while(|b| b.is_ascii_alphabetic()).count();
+                let name = &rest[..name_len];
+                match name {
+                    "left" => left_right += 1,
+                    "right" => left_right -= 1,
+                    "begin" | "end" => {
+                        let after = &rest[name_len..];
+                        let env = after
+                            .strip_prefix('{')
+                            .and_then(|s| s.split_once('}'))
+                            .map(|(env, _)| env);
+                        let Some(env) = env else {
+                            return Some(format!("\\{} without an environment name", name));
+                        };
+                        if name == "begin" {
+                            environments.push(env);
+                        } else if environments.pop() != Some(env) {
+                            return Some(format!("unmatched \\end{{{}}}", env));
+                        }
+                    }
+                    _ => {}
+                }
+                i += 1 + name_len.max(1);
+                continue;
+            }
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth < 0 {
+                    return Some("unbalanced '}'".to_string());
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    if depth != 0 {
+        return Some("unbalanced '{'".to_string());
+    }
+    if left_right != 0 {
+        return Some("unmatched \\left/\\right".to_string());
+    }
+    if let Some(env) = environments.pop() {
+        return Some(format!("unterminated \\begin{{{}}}", env));
+    }
+    None
+}
+
+fn fallback(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut rest = content;
+    while let Some(c) = rest.chars().next() {
+        if c == '\\' {
+            let name_len = rest[1..].bytes().take_while(|b| b.is_ascii_alphabetic()).count();
+            let command_len = 1 + match name_len {
+                0 => rest[1..].chars().next().map_or(0, char::len_utf8),
+                n => n,
+            };
+            let command = &rest[..command_len];
+            rest = &rest[command_len..];
+            if command == "\\frac" {
+                if let Some((num, after)) = take_group(rest) {
+                    if let Some((den, after)) = take_group(after) {
+                        out.push_str(&format!("({})/({})", fallback(num), fallback(den)));
+                        rest = after;
+                        continue;
+                    }
+                }
+            }
+            match FALLBACK_SYMBOLS.iter().find(|(name, _)| *name == command) {
+                Some((_, replacement)) => out.push_str(replacement),
+                // Unknown command: drop the backslash and keep its name.
+                None => out.push_str(&command[1..]),
+            }
+            continue;
+        }
+        if c != '{' && c != '}' {
+            out.push(c);
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+fn take_group(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_start();
+    let inner = s.strip_prefix('{')?;
+    let mut depth = 1;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((&inner[..i], &inner[i + 1..]));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn kind_name(kind: SegmentKind) -> &'static str {
+    match kind {
+        SegmentKind::Text => "text",
+        SegmentKind::Inline => "inline",
+        SegmentKind::Display => "display",
+    }
+}
+
+/// Scans statement text for math and returns an array of
+/// `{ kind, content, source, valid, error, fallback }` segments where
+/// `kind` is `text`, `inline` or `display`. `content` is the normalized TeX
+/// to feed the math renderer, `fallback` a plain-text approximation for
+/// when it fails or the segment is invalid.
+#[wasm_bindgen]
+pub fn extract_math(text: &str) -> js_sys::Array {
+    extract(text)
+        .into_iter()
+        .map(|segment| {
+            let object = js_sys::Object::new();
+            let set = |key: &str, value: JsValue| {
+                js_sys::Reflect::set(&object, &JsValue::from_str(key), &value).unwrap();
+            };
+            set("kind", kind_name(segment.kind).into());
+            set("content", segment.content.into());
+            set("source", segment.source.into());
+            set("valid", segment.error.is_none().into());
+            set("error", segment.error.map(JsValue::from).unwrap_or(JsValue::NULL));
+            set("fallback", segment.fallback.into());
+            JsValue::from(object)
+        })
+        .collect()
+}
@@ -0,0 +1,203 @@
+use wasm_bindgen::prelude::*;
+
+use crate::options::{get_option, invalid_option};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ChangeKind {
+    Bom,
+    LineEndings,
+    SmartQuotes,
+    Dashes,
+    InvisibleCharacters,
+    NonBreakingSpaces,
+    Tabs,
+}
+
+pub struct Change {
+    pub kind: ChangeKind,
+    pub count: usize,
+    pub first_line: usize,
+}
+
+// Wider tabs are surely a mistake, and huge ones would exhaust memory.
+const MAX_TAB_WIDTH: f64 = 16.0;
+
+// Languages where indentation is significant get their leading tabs
+// expanded; elsewhere tabs are left alone unless a width is requested.
+fn default_tab_width(language: &str) -> Option<usize> {
+    match language.to_ascii_lowercase().as_str() {
+        "python" | "py" | "python3" => Some(4),
+        _ => None,
+    }
+}
+
+// A whole number of columns from 0 (keep tabs) to `MAX_TAB_WIDTH`.
+fn is_valid_tab_width(width: f64) -> bool {
+    width.fract() == 0.0 && (0.0..=MAX_TAB_WIDTH).contains(&width)
+}
+
+fn replacement(c: char) -> Option<(ChangeKind, Option<char>)> {
+    match c {
+        '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}' | '\u{2032}' => Some((ChangeKind::SmartQuotes, Some('\''))),
+        '\u{201c}' | '\u{201d}' | '\u{201e}' | '\u{201f}' | '\u{2033}' => Some((ChangeKind::SmartQuotes, Some('"'))),
+        '\u{2013}' | '\u{2014}' | '\u{2212}' => Some((ChangeKind::Dashes, Some('-'))),
+        '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{2060}' | '\u{feff}' | '\u{00ad}' => {
+            Some((ChangeKind::InvisibleCharacters, None))
+        }
+        '\u{00a0}' | '\u{2007}' | '\u{202f}' | '\u{3000}' => Some((ChangeKind::NonBreakingSpaces, Some(' '))),
+        _ => None,
+    }
+}
+
+fn record(changes: &mut Vec<Change>, kind: ChangeKind, line: usize) {
+    match changes.iter_mut().find(|change| change.kind == kind) {
+        Some(change) => change.count += 1,
+        None => changes.push(Change { kind, count: 1, first_line: line }),
+    }
+}
+
+/// Cleans up pasted source: strips a leading BOM, converts CRLF/CR to LF,
+/// replaces smart quotes, typographic dashes and non-breaking spaces with
+/// their ASCII forms, drops zero-width characters and, when `tab_width` is
+/// set, expands tabs in leading indentation. Returns the new code and one
+/// `Change` per kind of fix, in order of first occurrence.
+pub fn normalize(code: &str, tab_width: Option<usize>) -> (String, Vec<Change>) {
+    let mut changes = Vec::new();
+    let mut out = String::with_capacity(code.len());
+    let mut rest = code;
+    if let Some(stripped) = rest.strip_prefix('\u{feff}') {
+        record(&mut changes, ChangeKind::Bom, 1);
+        rest = stripped;
+    }
+
+    let mut line = 1;
+    let mut column = 0;
+    let mut indenting = true;
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\r' || c == '\n' {
+            if c == '\r' {
+                if chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                record(&mut changes, ChangeKind::LineEndings, line);
+            }
+            out.push('\n');
+            line += 1;
+            column = 0;
+            indenting = true;
+            continue;
+        }
+        if let (true, '\t', Some(width)) = (indenting, c, tab_width) {
+            let spaces = width - column % width;
+            out.extend(std::iter::repeat_n(' ', spaces));
+            column += spaces;
+            record(&mut changes, ChangeKind::Tabs, line);
+            continue;
+        }
+        let c = match replacement(c) {
+            Some((kind, replaced)) => {
+                record(&mut changes, kind, line);
+                match replaced {
+                    Some(replaced) => replaced,
+                    None => continue,
+                }
+            }
+            None => c,
+        };
+        out.push(c);
+        column += 1;
+        indenting &= c == ' ' || c == '\t';
+    }
+    (out, changes)
+}
+
+fn kind_name(kind: ChangeKind) -> &'static str {
+    match kind {
+        ChangeKind::Bom => "bom",
+        ChangeKind::LineEndings => "line_endings",
+        ChangeKind::SmartQuotes => "smart_quotes",
+        ChangeKind::Dashes => "dashes",
+        ChangeKind::InvisibleCharacters => "invisible_characters",
+        ChangeKind::NonBreakingSpaces => "non_breaking_spaces",
+        ChangeKind::Tabs => "tabs",
+    }
+}
+
+/// Normalizes pasted code before submission to avoid "invisible character"
+/// compile errors. `language` picks the default tab handling (Python
+/// indentation is expanded to 4 spaces, other languages keep tabs);
+/// `options.tabWidth` overrides it with a width up to 16, `0` keeping tabs.
+/// Returns `{ code, changed, changes }` where `changes` is an array of
+/// `{ kind, count, firstLine }`.
+#[wasm_bindgen]
+pub fn normalize_source(code: &str, language: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let tab_width = match get_option(&options, "tabWidth") {
+        Some(value) => match value.as_f64() {
+            Some(width) if is_valid_tab_width(width) => Some(width as usize).filter(|&width| width > 0),
+            _ => return Err(invalid_option("tabWidth", "a whole number from 0 to 16", &value)),
+        },
+        None => default_tab_width(language),
+    };
+    let (normalized, changes) = normalize(code, tab_width);
+
+    let result = js_sys::Object::new();
+    let set = |object: &js_sys::Object, key: &str, value: JsValue| {
+        js_sys::Reflect::set(object, &JsValue::from_str(key), &value).unwrap();
+    };
+    let list: js_sys::Array = changes
+        .iter()
+        .map(|change| {
+            let object = js_sys::Object::new();
+            set(&object, "kind", kind_name(change.kind).into());
+            set(&object, "count", JsValue::from(change.count as u32));
+            set(&object, "firstLine", JsValue::from(change.first_line as u32));
+            JsValue::from(object)
+        })
+        .collect();
+    set(&result, "code", normalized.into());
+    set(&result, "changed", (!changes.is_empty()).into());
+    set(&result, "changes", list.into());
+    Ok(result.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixes_pasted_characters() {
+        let (code, changes) = normalize("\u{feff}a = \u{201c}x\u{201d}\r\nb\u{200b} \u{2013}1\r", None);
+        assert_eq!(code, "a = \"x\"\nb -1\n");
+        let kinds: Vec<(ChangeKind, usize, usize)> =
+            changes.iter().map(|change| (change.kind, change.count, change.first_line)).collect();
+        assert_eq!(
+            kinds,
+            [
+                (ChangeKind::Bom, 1, 1),
+                (ChangeKind::SmartQuotes, 2, 1),
+                (ChangeKind::LineEndings, 2, 1),
+                (ChangeKind::InvisibleCharacters, 1, 2),
+                (ChangeKind::Dashes, 1, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn expands_only_leading_tabs() {
+        let (code, changes) = normalize("\tif x:\n  \ty\tz\n", Some(4));
+        assert_eq!(code, "    if x:\n    y\tz\n");
+        assert_eq!((changes[0].kind, changes[0].count), (ChangeKind::Tabs, 2));
+        assert_eq!(normalize("\tx", None).0, "\tx");
+    }
+
+    #[test]
+    fn rejects_unreasonable_tab_widths() {
+        for width in [0.0, 1.0, 4.0, 16.0] {
+            assert!(is_valid_tab_width(width), "{}", width);
+        }
+        for width in [-1.0, 2.5, 17.0, 1e9, f64::INFINITY, f64::NAN] {
+            assert!(!is_valid_tab_width(width), "{}", width);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use wasm_bindgen::prelude::*;
+
+pub(crate) fn get_option(options: &JsValue, key: &str) -> Option<JsValue> {
+    if !options.is_object() {
+        return None;
+    }
+    js_sys::Reflect::get(options, &JsValue::from_str(key))
+        .ok()
+        .filter(|value| !value.is_undefined() && !value.is_null())
+}
+
+pub(crate) fn get_option_string(options: &JsValue, key: &str) -> Option<String> {
+    get_option(options, key).and_then(|value| value.as_string())
+}
+
+fn describe_value(value: &JsValue) -> String {
+    if let Some(number) = value.as_f64() {
+        number.to_string()
+    } else if let Some(text) = value.as_string() {
+        format!("{:?}", text)
+    } else if let Some(flag) = value.as_bool() {
+        flag.to_string()
+    } else {
+        value.js_typeof().as_string().unwrap_or_default()
+    }
+}
+
+/// A JS `Error` for a bad option value, naming the field and what it
+/// accepts. The `field` and `expected` properties are also set on the error
+/// so callers can highlight the offending input.
+pub(crate) fn invalid_option(field: &str, expected: &str, value: &JsValue) -> JsValue {
+    let message = format!("Invalid {}: expected {}, got {}", field, expected, describe_value(value));
+    let error: JsValue = JsError::new(&message).into();
+    js_sys::Reflect::set(&error, &"field".into(), &field.into()).unwrap();
+    js_sys::Reflect::set(&error, &"expected".into(), &expected.into()).unwrap();
+    error
+}
+
+/// A finite number option within `min..=max`, if present.
+pub(crate) fn number_option(options: &JsValue, key: &str, min: f32, max: f32) -> Result<Option<f32>, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(None);
+    };
+    match value.as_f64().map(|v| v as f32) {
+        Some(number) if number.is_finite() && (min..=max).contains(&number) => Ok(Some(number)),
+        _ => Err(invalid_option(key, &format!("a number from {} to {}", min, max), &value)),
+    }
+}
+
+pub(crate) fn bool_option(options: &JsValue, key: &str) -> Result<Option<bool>, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(None);
+    };
+    value.as_bool().map(Some).ok_or_else(|| invalid_option(key, "true or false", &value))
+}
+
+pub(crate) fn color_option(options: &JsValue, key: &str) -> Result<Option<[f32; 3]>, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(None);
+    };
+    value
+        .as_string()
+        .and_then(|hex| parse_hex_color(&hex))
+        .map(Some)
+        .ok_or_else(|| invalid_option(key, HEX_COLOR, &value))
+}
+
+pub(crate) const HEX_COLOR: &str = "a hex color such as \"#rgb\" or \"#rrggbb\"";
+
+pub(crate) fn parse_hex_color(hex: &str) -> Option<[f32; 3]> {
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+    if !digits.is_ascii() {
+        return None;
+    }
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok().map(|v| v as f32 / 255.0);
+    match digits.len() {
+        3 => {
+            let expand = |i: usize| channel(&digits[i..i + 1].repeat(2));
+            Some([expand(0)?, expand(1)?, expand(2)?])
+        }
+        6 => Some([channel(&digits[0..2])?, channel(&digits[2..4])?, channel(&digits[4..6])?]),
+        _ => None,
+    }
+}
@@ -0,0 +1,582 @@
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+const BRAINFUCK_TAPE_SIZE: usize = 30000;
+const MAX_OUTPUT_BYTES: usize = 1 << 20;
+
+#[derive(Debug, PartialEq)]
+pub enum Stop {
+    StepLimit,
+    Error(String),
+}
+
+pub struct RunResult {
+    pub output: String,
+    pub steps: u64,
+    pub stop: Option<Stop>,
+}
+
+/// Runs a Brainfuck program on a 30000-cell wrapping byte tape. `,` reads
+/// the next byte of `input` (0 at end of input).
+pub fn run_brainfuck(source: &str, input: &str, max_steps: u64) -> RunResult {
+    let code: Vec<u8> = source.bytes().filter(|b| b"+-<>[].,".contains(b)).collect();
+    let mut result = RunResult { output: String::new(), steps: 0, stop: None };
+
+    let mut jumps = vec![0; code.len()];
+    let mut open = Vec::new();
+    for (i, &op) in code.iter().enumerate() {
+        match op {
+            b'[' => open.push(i),
+            b']' => match open.pop() {
+                Some(start) => {
+                    jumps[start] = i;
+                    jumps[i] = start;
+                }
+                None => {
+                    result.stop = Some(Stop::Error("unmatched ']'".to_string()));
+                    return result;
+                }
+            },
+            _ => {}
+        }
+    }
+    if !open.is_empty() {
+        result.stop = Some(Stop::Error("unmatched '['".to_string()));
+        return result;
+    }
+
+    let mut tape = vec![0u8; BRAINFUCK_TAPE_SIZE];
+    let mut input = input.bytes();
+    let mut output = Vec::new();
+    let mut pointer = 0;
+    let mut pc = 0;
+    while pc < code.len() {
+        if result.steps >= max_steps {
+            result.stop = Some(Stop::StepLimit);
+            break;
+        }
+        result.steps += 1;
+        match code[pc] {
+            b'+' => tape[pointer] = tape[pointer].wrapping_add(1),
+            b'-' => tape[pointer] = tape[pointer].wrapping_sub(1),
+            b'>' => pointer = (pointer + 1) % BRAINFUCK_TAPE_SIZE,
+            b'<' => pointer = (pointer + BRAINFUCK_TAPE_SIZE - 1) % BRAINFUCK_TAPE_SIZE,
+            b'[' if tape[pointer] == 0 => pc = jumps[pc],
+            b']' if tape[pointer] != 0 => pc = jumps[pc],
+            b'.' => {
+                if output.len() >= MAX_OUTPUT_BYTES {
+                    result.stop = Some(Stop::Error("output limit exceeded".to_string()));
+                    break;
+                }
+                output.push(tape[pointer]);
+            }
+            b',' => tape[pointer] = input.next().unwrap_or(0),
+            _ => {}
+        }
+        pc += 1;
+    }
+    result.output = String::from_utf8_lossy(&output).into_owned();
+    result
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(i64),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: [&str; 21] = [
+    "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "<", ">", "!", "=", "(", ")", "{", "}", ",", ";",
+];
+
+fn lex(source: &str) -> Result<Vec<(Token, usize)>, String> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        if c == '\n' {
+            line += 1;
+        }
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        if c == '#' {
+            rest = rest.find('\n').map_or("", |end| &rest[end..]);
+            continue;
+        }
+        if c.is_ascii_digit() {
+            let len = rest.bytes().take_while(u8::is_ascii_digit).count();
+            let value = rest[..len]
+                .parse()
+                .map_err(|_| format!("line {}: number {} is too large", line, &rest[..len]))?;
+            tokens.push((Token::Number(value), line));
+            rest = &rest[len..];
+            continue;
+        }
+        if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest.bytes().take_while(|b| b.is_ascii_alphanumeric() || *b == b'_').count();
+            tokens.push((Token::Ident(rest[..len].to_string()), line));
+            rest = &rest[len..];
+            continue;
+        }
+        match SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            Some(symbol) => {
+                tokens.push((Token::Symbol(symbol), line));
+                rest = &rest[symbol.len()..];
+            }
+            None => return Err(format!("line {}: unexpected character '{}'", line, c)),
+        }
+    }
+    Ok(tokens)
+}
+
+enum Expr {
+    Number(i64),
+    Var(String),
+    Unary(&'static str, Box<Expr>),
+    // Operands of one precedence level, applied left to right; kept flat
+    // so long chains like `a + b + ...` need no deep recursion.
+    Chain(Box<Expr>, Vec<(&'static str, Expr)>),
+}
+
+enum Stmt {
+    Assign(String, Expr),
+    Print(Vec<Expr>),
+    Read(String),
+    While(Expr, Vec<Stmt>),
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+}
+
+// Binary operators from loosest to tightest binding.
+const PRECEDENCE: [&[&str]; 5] = [&["||"], &["&&"], &["==", "!=", "<", "<=", ">", ">="], &["+", "-"], &["*", "/", "%"]];
+
+const KEYWORDS: [&str; 6] = ["let", "print", "read", "while", "if", "else"];
+
+// Deepest nesting of parentheses, unary operators and blocks the parser
+// accepts; parsing and evaluation recurse, and the wasm stack is small.
+const MAX_NESTING: usize = 256;
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn error<T>(&self, message: &str) -> Result<T, String> {
+        Err(format!("line {}: {}", self.line(), message))
+    }
+
+    // Goes one level deeper; callers step back out with `depth -= 1` once
+    // the nested part has parsed.
+    fn enter(&mut self, what: &str) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_NESTING {
+            return self.error(&format!("{} nested too deeply", what));
+        }
+        Ok(())
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            self.error(&format!("expected '{}'", symbol))
+        }
+    }
+
+    fn keyword(&mut self, name: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Ident(ident)) if ident == name) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(Token::Ident(name)) if !KEYWORDS.contains(&name.as_str()) => {
+                let name = name.clone();
+                self.position += 1;
+                Ok(name)
+            }
+            _ => self.error("expected a variable name"),
+        }
+    }
+
+    fn program(&mut self) -> Result<Vec<Stmt>, String> {
+        let mut statements = Vec::new();
+        while self.peek().is_some() {
+            statements.push(self.statement()?);
+        }
+        Ok(statements)
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, String> {
+        self.expect("{")?;
+        self.enter("blocks")?;
+        let mut statements = Vec::new();
+        while !self.eat("}") {
+            if self.peek().is_none() {
+                return self.error("expected '}'");
+            }
+            statements.push(self.statement()?);
+        }
+        self.depth -= 1;
+        Ok(statements)
+    }
+
+    fn statement(&mut self) -> Result<Stmt, String> {
+        let statement = if self.keyword("print") {
+            let mut values = vec![self.expression(0)?];
+            while self.eat(",") {
+                values.push(self.expression(0)?);
+            }
+            Stmt::Print(values)
+        } else if self.keyword("read") {
+            Stmt::Read(self.ident()?)
+        } else if self.keyword("while") {
+            let condition = self.expression(0)?;
+            Stmt::While(condition, self.block()?)
+        } else if self.keyword("if") {
+            self.if_rest()?
+        } else {
+            self.keyword("let");
+            let name = self.ident()?;
+            self.expect("=")?;
+            Stmt::Assign(name, self.expression(0)?)
+        };
+        self.eat(";");
+        Ok(statement)
+    }
+
+    fn if_rest(&mut self) -> Result<Stmt, String> {
+        let condition = self.expression(0)?;
+        let then = self.block()?;
+        let otherwise = if !self.keyword("else") {
+            Vec::new()
+        } else if self.keyword("if") {
+            self.enter("blocks")?;
+            let chained = self.if_rest()?;
+            self.depth -= 1;
+            vec![chained]
+        } else {
+            self.block()?
+        };
+        Ok(Stmt::If(condition, then, otherwise))
+    }
+
+    fn expression(&mut self, level: usize) -> Result<Expr, String> {
+        if level == PRECEDENCE.len() {
+            return self.unary();
+        }
+        let first = self.expression(level + 1)?;
+        let mut rest = Vec::new();
+        while let Some(&Token::Symbol(op)) = self.peek() {
+            if !PRECEDENCE[level].contains(&op) {
+                break;
+            }
+            self.position += 1;
+            rest.push((op, self.expression(level + 1)?));
+        }
+        Ok(if rest.is_empty() { first } else { Expr::Chain(Box::new(first), rest) })
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        for op in ["-", "!"] {
+            if self.eat(op) {
+                self.enter("expression")?;
+                let operand = self.unary()?;
+                self.depth -= 1;
+                return Ok(Expr::Unary(op, Box::new(operand)));
+            }
+        }
+        match self.peek().cloned() {
+            Some(Token::Number(value)) => {
+                self.position += 1;
+                Ok(Expr::Number(value))
+            }
+            Some(Token::Symbol("(")) => {
+                self.position += 1;
+                self.enter("expression")?;
+                let inner = self.expression(0)?;
+                self.expect(")")?;
+                self.depth -= 1;
+                Ok(inner)
+            }
+            Some(Token::Ident(_)) => Ok(Expr::Var(self.ident()?)),
+            _ => self.error("expected an expression"),
+        }
+    }
+}
+
+struct Machine<'a> {
+    variables: HashMap<String, i64>,
+    input: std::str::SplitWhitespace<'a>,
+    output: String,
+    steps: u64,
+    max_steps: u64,
+}
+
+impl Machine<'_> {
+    fn tick(&mut self) -> Result<(), Stop> {
+        if self.steps >= self.max_steps {
+            return Err(Stop::StepLimit);
+        }
+        self.steps += 1;
+        Ok(())
+    }
+
+    fn run(&mut self, statements: &[Stmt]) -> Result<(), Stop> {
+        for statement in statements {
+            self.tick()?;
+            match statement {
+                Stmt::Assign(name, value) => {
+                    let value = self.eval(value)?;
+                    self.variables.insert(name.clone(), value);
+                }
+                Stmt::Print(values) => {
+                    let mut line = Vec::with_capacity(values.len());
+                    for value in values {
+                        line.push(self.eval(value)?.to_string());
+                    }
+                    if self.output.len() >= MAX_OUTPUT_BYTES {
+                        return Err(Stop::Error("output limit exceeded".to_string()));
+                    }
+                    self.output.push_str(&line.join(" "));
+                    self.output.push('\n');
+                }
+                Stmt::Read(name) => {
+                    let token = self.input.next().ok_or_else(|| Stop::Error("read past end of input".to_string()))?;
+                    let value = token
+                        .parse()
+                        .map_err(|_| Stop::Error(format!("input \"{}\" is not an integer", token)))?;
+                    self.variables.insert(name.clone(), value);
+                }
+                Stmt::While(condition, body) => {
+                    while self.eval(condition)? != 0 {
+                        self.run(body)?;
+                        self.tick()?;
+                    }
+                }
+                Stmt::If(condition, then, otherwise) => {
+                    if self.eval(condition)? != 0 {
+                        self.run(then)?;
+                    } else {
+                        self.run(otherwise)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, op: &str, a: i64, right: &Expr) -> Result<i64, Stop> {
+        let overflow = || Stop::Error("integer overflow".to_string());
+        let b = self.eval(right)?;
+        match op {
+            "+" => a.checked_add(b).ok_or_else(overflow),
+            "-" => a.checked_sub(b).ok_or_else(overflow),
+            "*" => a.checked_mul(b).ok_or_else(overflow),
+            "/" | "%" if b == 0 => Err(Stop::Error("division by zero".to_string())),
+            "/" => a.checked_div(b).ok_or_else(overflow),
+            "%" => a.checked_rem(b).ok_or_else(overflow),
+            "==" => Ok((a == b) as i64),
+            "!=" => Ok((a != b) as i64),
+            "<" => Ok((a < b) as i64),
+            "<=" => Ok((a <= b) as i64),
+            ">" => Ok((a > b) as i64),
+            ">=" => Ok((a >= b) as i64),
+            _ => Ok((b != 0) as i64),
+        }
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Result<i64, Stop> {
+        self.tick()?;
+        let overflow = || Stop::Error("integer overflow".to_string());
+        match expr {
+            Expr::Number(value) => Ok(*value),
+            Expr::Var(name) => self
+                .variables
+                .get(name)
+                .copied()
+                .ok_or_else(|| Stop::Error(format!("undefined variable {}", name))),
+            Expr::Unary(op, operand) => {
+                let value = self.eval(operand)?;
+                match *op {
+                    "-" => value.checked_neg().ok_or_else(overflow),
+                    _ => Ok((value == 0) as i64),
+                }
+            }
+            Expr::Chain(first, rest) => {
+                let mut a = self.eval(first)?;
+                for (op, right) in rest {
+                    self.tick()?;
+                    // `&&` and `||` short-circuit.
+                    a = match (*op, a != 0) {
+                        ("&&", false) => 0,
+                        ("||", true) => 1,
+                        _ => self.apply(op, a, right)?,
+                    };
+                }
+                Ok(a)
+            }
+        }
+    }
+}
+
+/// Runs a program in the playground's expression language: 64-bit integer
+/// variables, `let x = ...` / `x = ...`, `print a, b`, `read x` (next
+/// whitespace-separated integer of `input`), `while cond { ... }` and
+/// `if cond { ... } else { ... }`, with C-like operators and `#` comments.
+pub fn run_expression(source: &str, input: &str, max_steps: u64) -> RunResult {
+    let program = lex(source).and_then(|tokens| Parser { tokens, position: 0, depth: 0 }.program());
+    let program = match program {
+        Ok(program) => program,
+        Err(error) => return RunResult { output: String::new(), steps: 0, stop: Some(Stop::Error(error)) },
+    };
+    let mut machine = Machine {
+        variables: HashMap::new(),
+        input: input.split_whitespace(),
+        output: String::new(),
+        steps: 0,
+        max_steps,
+    };
+    let stop = machine.run(&program).err();
+    RunResult { output: machine.output, steps: machine.steps, stop }
+}
+
+/// Runs `source` client-side for the playground page. `language` is
+/// `brainfuck` (or `bf`) or `expr`; execution stops after `max_steps`
+/// instructions. Returns `{ output, steps, status, error }` where `status`
+/// is `ok`, `step_limit` or `error`.
+#[wasm_bindgen]
+pub fn run_program(language: &str, source: &str, input: &str, max_steps: u32) -> Result<JsValue, JsValue> {
+    let result = match language.to_ascii_lowercase().as_str() {
+        "brainfuck" | "bf" => run_brainfuck(source, input, max_steps as u64),
+        "expr" => run_expression(source, input, max_steps as u64),
+        _ => return Err(JsValue::from_str(&format!("Unknown playground language: {}", language))),
+    };
+    let object = js_sys::Object::new();
+    let set = |key: &str, value: JsValue| {
+        js_sys::Reflect::set(&object, &JsValue::from_str(key), &value).unwrap();
+    };
+    let (status, error) = match result.stop {
+        None => ("ok", JsValue::NULL),
+        Some(Stop::StepLimit) => ("step_limit", JsValue::from_str("step limit exceeded")),
+        Some(Stop::Error(message)) => ("error", JsValue::from_str(&message)),
+    };
+    set("output", result.output.into());
+    set("steps", JsValue::from(result.steps as f64));
+    set("status", status.into());
+    set("error", error);
+    Ok(object.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(source: &str) -> RunResult {
+        run_expression(source, "", 1_000_000)
+    }
+
+    #[test]
+    fn runs_nested_expressions() {
+        let source = format!("print {}1{}", "(".repeat(100), ")".repeat(100));
+        let result = run(&source);
+        assert_eq!(result.stop, None);
+        assert_eq!(result.output, "1\n");
+    }
+
+    #[test]
+    fn rejects_deep_parentheses() {
+        let source = format!("print {}1{}", "(".repeat(200_000), ")".repeat(200_000));
+        match run(&source).stop {
+            Some(Stop::Error(message)) => assert!(message.ends_with("expression nested too deeply"), "{}", message),
+            stop => panic!("expected a nesting error, got {:?}", stop),
+        }
+    }
+
+    #[test]
+    fn runs_long_operator_chains() {
+        let result = run(&format!("print 1{}", "+1".repeat(256)));
+        assert_eq!((result.output.as_str(), result.stop), ("257\n", None));
+        let result = run(&format!("print 1{}", "+1".repeat(200_000)));
+        assert_eq!((result.output.as_str(), result.stop), ("200001\n", None));
+        let result = run("print 10 - 4 - 3, 2 * 3 % 4, 0 && 1 / 0, 1 || 1 / 0, 1 && 2 && 3");
+        assert_eq!(result.output, "3 2 0 1 1\n");
+    }
+
+    #[test]
+    fn rejects_deep_unary_operators_and_blocks() {
+        let negations = format!("print {}1", "-".repeat(200_000));
+        assert!(matches!(run(&negations).stop, Some(Stop::Error(_))));
+        let blocks = format!("{}{}", "if 1 {".repeat(200_000), "}".repeat(200_000));
+        assert!(matches!(run(&blocks).stop, Some(Stop::Error(_))));
+    }
+
+    #[test]
+    fn stops_at_the_step_limit() {
+        let result = run_expression("x = 0 while 1 { x = x + 1 }", "", 1_000);
+        assert_eq!((result.steps, result.stop), (1_000, Some(Stop::StepLimit)));
+        let result = run_expression("print 1 print 2", "", 3);
+        assert_eq!((result.output.as_str(), result.stop), ("1\n", Some(Stop::StepLimit)));
+    }
+
+    #[test]
+    fn reads_input_and_reports_errors() {
+        let result = run_expression("read a read b print a * b", " 6\n7 ", 100);
+        assert_eq!((result.output.as_str(), result.stop), ("42\n", None));
+        assert_eq!(run("read a").stop, Some(Stop::Error("read past end of input".to_string())));
+        assert_eq!(run("print 1 / 0").stop, Some(Stop::Error("division by zero".to_string())));
+        assert_eq!(run("print y").stop, Some(Stop::Error("undefined variable y".to_string())));
+    }
+
+    #[test]
+    fn brainfuck_runs_programs() {
+        let hello = concat!(
+            "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]",
+            ">>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.",
+        );
+        let result = run_brainfuck(hello, "", 100_000);
+        assert_eq!((result.output.as_str(), result.stop), ("Hello World!", None));
+        // Echoes input, reading 0 once it runs out; cells wrap.
+        assert_eq!(run_brainfuck(",[.,]", "abc", 1_000).output, "abc");
+        assert_eq!(run_brainfuck("-.", "", 10).output, "\u{fffd}");
+    }
+
+    #[test]
+    fn brainfuck_limits_and_errors() {
+        let result = run_brainfuck("+[]", "", 500);
+        assert_eq!((result.steps, result.stop), (500, Some(Stop::StepLimit)));
+        assert_eq!(run_brainfuck("[", "", 10).stop, Some(Stop::Error("unmatched '['".to_string())));
+        assert_eq!(run_brainfuck("]", "", 10).stop, Some(Stop::Error("unmatched ']'".to_string())));
+        // The tape wraps at both ends.
+        assert_eq!(run_brainfuck("<+.", "", 10).output, "\u{1}");
+    }
+}
@@ -0,0 +1,443 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    window, HtmlCanvasElement, MouseEvent, WebGlBuffer, WebGlProgram, WebGlRenderingContext as GL,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::stars::{compile_shader, link_program, DIFFICULTY_SCALE};
+
+struct RatingChart {
+    gl: GL,
+    canvas: HtmlCanvasElement,
+    resolution: (f32, f32),
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+    times: Vec<f64>,
+    ratings: Vec<f32>,
+    time_range: (f64, f64),
+    rating_range: (f32, f32),
+    hovered: Option<usize>,
+    on_hover: Option<js_sys::Function>,
+}
+
+const CHART_PADDING: f32 = 16.0;
+const CHART_RATING_MARGIN: f32 = 100.0;
+const CHART_BAND_ALPHA: f32 = 0.18;
+const CHART_LINE_WIDTH: f32 = 2.0;
+const CHART_LINE_COLOR: [f32; 4] = [0.85, 0.9, 1.0, 0.9];
+const CHART_POINT_SIZE: f32 = 6.0;
+const CHART_HOVER_POINT_SIZE: f32 = 11.0;
+const CHART_HOVER_RADIUS: f32 = 12.0;
+const CHART_GUIDE_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.25];
+// Catmull-Rom subdivisions per segment of the smoothed line.
+const CHART_CURVE_STEPS: usize = 8;
+// Beyond this many points the line is drawn straight; segments are already
+// shorter than the subdivision would make visible.
+const CHART_SMOOTH_LIMIT: usize = 1000;
+
+thread_local! {
+    static RATING_CHARTS: RefCell<HashMap<String, Rc<RefCell<RatingChart>>>> = RefCell::new(HashMap::new());
+}
+
+fn push_vertex(data: &mut Vec<f32>, x: f32, y: f32, color: [f32; 4]) {
+    data.extend_from_slice(&[x, y, color[0], color[1], color[2], color[3]]);
+}
+
+fn push_rect(data: &mut Vec<f32>, x0: f32, y0: f32, x1: f32, y1: f32, color: [f32; 4]) {
+    for (x, y) in [(x0, y0), (x1, y0), (x0, y1), (x1, y0), (x1, y1), (x0, y1)] {
+        push_vertex(data, x, y, color);
+    }
+}
+
+fn catmull_rom(p0: (f32, f32), p1: (f32, f32), p2: (f32, f32), p3: (f32, f32), t: f32) -> (f32, f32) {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    let blend = |a: f32, b: f32, c: f32, d: f32| {
+        0.5 * (2.0 * b + (c - a) * t + (2.0 * a - 5.0 * b + 4.0 * c - d) * t2 + (3.0 * b - a - 3.0 * c + d) * t3)
+    };
+    (blend(p0.0, p1.0, p2.0, p3.0), blend(p0.1, p1.1, p2.1, p3.1))
+}
+
+impl RatingChart {
+    fn new(canvas_id: &str) -> RatingChart {
+        let document = window().unwrap().document().unwrap();
+        let canvas = document
+            .get_element_by_id(canvas_id)
+            .expect("Canvas element not found")
+            .dyn_into::<HtmlCanvasElement>()
+            .unwrap();
+
+        let gl: GL = canvas
+            .get_context("webgl")
+            .unwrap()
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+
+        let vertex_shader_source = r#"
+            attribute vec2 a_position;
+            attribute vec4 a_color;
+            uniform vec2 u_resolution;
+            uniform float u_pointSize;
+            varying vec4 v_color;
+            void main() {
+                vec2 zeroToOne = a_position / u_resolution;
+                vec2 zeroToTwo = zeroToOne * 2.0;
+                vec2 clipSpace = zeroToTwo - 1.0;
+                clipSpace.y = -clipSpace.y;
+                gl_Position = vec4(clipSpace, 0.0, 1.0);
+                gl_PointSize = u_pointSize;
+                v_color = a_color;
+            }
+        "#;
+        let fragment_shader_source = r#"
+            precision mediump float;
+            uniform bool u_round;
+            varying vec4 v_color;
+            void main() {
+                if (u_round && length(gl_PointCoord - 0.5) > 0.5) {
+                    discard;
+                }
+                gl_FragColor = v_color;
+            }
+        "#;
+        let vertex_shader = compile_shader(&gl, GL::VERTEX_SHADER, vertex_shader_source)
+            .expect("Rating chart vertex shader compile error");
+        let fragment_shader = compile_shader(&gl, GL::FRAGMENT_SHADER, fragment_shader_source)
+            .expect("Rating chart fragment shader compile error");
+        let program = link_program(&gl, &vertex_shader, &fragment_shader)
+            .expect("Rating chart program link error");
+        let buffer = gl.create_buffer().expect("Failed to create rating chart buffer");
+
+        let mut chart = RatingChart {
+            gl,
+            canvas,
+            resolution: (0.0, 0.0),
+            program,
+            buffer,
+            times: Vec::new(),
+            ratings: Vec::new(),
+            time_range: (0.0, 1.0),
+            rating_range: (0.0, 1.0),
+            hovered: None,
+            on_hover: None,
+        };
+        chart.resize();
+        chart
+    }
+
+    fn resize(&mut self) {
+        let dpr = window().unwrap().device_pixel_ratio() as f32;
+        let width = self.canvas.client_width() as f32 * dpr;
+        let height = self.canvas.client_height() as f32 * dpr;
+        self.canvas.set_width(width as u32);
+        self.canvas.set_height(height as u32);
+        self.resolution = (width, height);
+    }
+
+    fn set_data(&mut self, times: Vec<f64>, ratings: Vec<f32>) {
+        let first = times.first().copied().unwrap_or(0.0);
+        let last = times.last().copied().unwrap_or(0.0);
+        self.time_range = if last > first { (first, last) } else { (first - 1.0, first + 1.0) };
+        let min = ratings.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = ratings.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        self.rating_range = (min - CHART_RATING_MARGIN, max + CHART_RATING_MARGIN);
+        self.times = times;
+        self.ratings = ratings;
+        self.hovered = None;
+    }
+
+    fn to_screen(&self, index: usize) -> (f32, f32) {
+        let (t0, t1) = self.time_range;
+        let (x0, x1) = (CHART_PADDING, self.resolution.0 - CHART_PADDING);
+        let x = x0 + ((self.times[index] - t0) / (t1 - t0)) as f32 * (x1 - x0);
+        (x, self.rating_to_y(self.ratings[index]))
+    }
+
+    fn rating_to_y(&self, rating: f32) -> f32 {
+        let (lo, hi) = self.rating_range;
+        let (y0, y1) = (CHART_PADDING, self.resolution.1 - CHART_PADDING);
+        y1 - (rating - lo) / (hi - lo) * (y1 - y0)
+    }
+
+    fn hit_test(&self, x: f32, y: f32) -> Option<usize> {
+        if self.times.is_empty() {
+            return None;
+        }
+        // Points are sorted by time, so only neighbours of the insertion
+        // point along x can be closest.
+        let mut lo = 0;
+        let mut hi = self.times.len();
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.to_screen(mid).0 < x {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let radius = CHART_HOVER_RADIUS * window().unwrap().device_pixel_ratio() as f32;
+        let mut best: Option<(usize, f32)> = None;
+        let mut consider = |index: usize| {
+            let (px, py) = self.to_screen(index);
+            let distance = ((px - x).powi(2) + (py - y).powi(2)).sqrt();
+            if distance <= radius && best.is_none_or(|(_, d)| distance < d) {
+                best = Some((index, distance));
+            }
+        };
+        for index in (0..lo).rev() {
+            if x - self.to_screen(index).0 > radius {
+                break;
+            }
+            consider(index);
+        }
+        for index in lo..self.times.len() {
+            if self.to_screen(index).0 - x > radius {
+                break;
+            }
+            consider(index);
+        }
+        best.map(|(index, _)| index)
+    }
+
+    fn line_points(&self) -> Vec<(f32, f32)> {
+        let points: Vec<(f32, f32)> = (0..self.times.len()).map(|i| self.to_screen(i)).collect();
+        if points.len() < 3 || points.len() > CHART_SMOOTH_LIMIT {
+            return points;
+        }
+        let mut smooth = Vec::with_capacity(points.len() * CHART_CURVE_STEPS);
+        for i in 0..points.len() - 1 {
+            let p0 = points[i.saturating_sub(1)];
+            let p3 = points[(i + 2).min(points.len() - 1)];
+            for step in 0..CHART_CURVE_STEPS {
+                let t = step as f32 / CHART_CURVE_STEPS as f32;
+                smooth.push(catmull_rom(p0, points[i], points[i + 1], p3, t));
+            }
+        }
+        smooth.push(points[points.len() - 1]);
+        smooth
+    }
+
+    fn draw(&self) {
+        let gl = &self.gl;
+        gl.viewport(0, 0, self.resolution.0 as i32, self.resolution.1 as i32);
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(GL::COLOR_BUFFER_BIT);
+        if self.ratings.is_empty() {
+            return;
+        }
+        let dpr = window().unwrap().device_pixel_ratio() as f32;
+
+        let mut triangles = Vec::new();
+        let (lo, hi) = self.rating_range;
+        for (i, (_, color)) in DIFFICULTY_SCALE.iter().enumerate() {
+            // The first tier also covers everything below the second threshold.
+            let bottom = if i == 0 { lo } else { DIFFICULTY_SCALE[i].0 as f32 }.max(lo);
+            let top = DIFFICULTY_SCALE.get(i + 1).map_or(hi, |(rating, _)| *rating as f32).min(hi);
+            if bottom < top {
+                let band = [color[0], color[1], color[2], CHART_BAND_ALPHA];
+                push_rect(&mut triangles, 0.0, self.rating_to_y(top), self.resolution.0, self.rating_to_y(bottom), band);
+            }
+        }
+        if let Some(index) = self.hovered {
+            let x = self.to_screen(index).0;
+            push_rect(&mut triangles, x - 0.5 * dpr, 0.0, x + 0.5 * dpr, self.resolution.1, CHART_GUIDE_COLOR);
+        }
+        let half_width = CHART_LINE_WIDTH * dpr * 0.5;
+        for segment in self.line_points().windows(2) {
+            let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+            let length = ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt().max(f32::EPSILON);
+            let (nx, ny) = (-(y1 - y0) / length * half_width, (x1 - x0) / length * half_width);
+            for (x, y) in [
+                (x0 + nx, y0 + ny),
+                (x1 + nx, y1 + ny),
+                (x0 - nx, y0 - ny),
+                (x1 + nx, y1 + ny),
+                (x1 - nx, y1 - ny),
+                (x0 - nx, y0 - ny),
+            ] {
+                push_vertex(&mut triangles, x, y, CHART_LINE_COLOR);
+            }
+        }
+
+        let mut points = Vec::with_capacity(self.ratings.len() * 6);
+        for (index, rating) in self.ratings.iter().enumerate() {
+            if self.hovered == Some(index) {
+                continue;
+            }
+            let (x, y) = self.to_screen(index);
+            let color = difficulty_rgb(*rating);
+            push_vertex(&mut points, x, y, [color[0], color[1], color[2], 1.0]);
+        }
+
+        gl.enable(GL::BLEND);
+        gl.blend_func(GL::SRC_ALPHA, GL::ONE_MINUS_SRC_ALPHA);
+        self.draw_vertices(&triangles, GL::TRIANGLES, 1.0);
+        self.draw_vertices(&points, GL::POINTS, CHART_POINT_SIZE * dpr);
+        if let Some(index) = self.hovered {
+            let (x, y) = self.to_screen(index);
+            let mut hovered = Vec::with_capacity(12);
+            push_vertex(&mut hovered, x, y, [1.0, 1.0, 1.0, 1.0]);
+            self.draw_vertices(&hovered, GL::POINTS, CHART_HOVER_POINT_SIZE * dpr);
+            let color = difficulty_rgb(self.ratings[index]);
+            hovered.clear();
+            push_vertex(&mut hovered, x, y, [color[0], color[1], color[2], 1.0]);
+            self.draw_vertices(&hovered, GL::POINTS, (CHART_HOVER_POINT_SIZE - 4.0) * dpr);
+        }
+        gl.disable(GL::BLEND);
+    }
+
+    fn draw_vertices(&self, data: &[f32], mode: u32, point_size: f32) {
+        if data.is_empty() {
+            return;
+        }
+        let gl = &self.gl;
+        gl.use_program(Some(&self.program));
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.buffer));
+        unsafe {
+            let array = js_sys::Float32Array::view(data);
+            gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &array, GL::DYNAMIC_DRAW);
+        }
+        let stride = 6 * std::mem::size_of::<f32>() as i32;
+        let pos_loc = gl.get_attrib_location(&self.program, "a_position") as u32;
+        let color_loc = gl.get_attrib_location(&self.program, "a_color") as u32;
+        gl.enable_vertex_attrib_array(pos_loc);
+        gl.vertex_attrib_pointer_with_i32(pos_loc, 2, GL::FLOAT, false, stride, 0);
+        gl.enable_vertex_attrib_array(color_loc);
+        gl.vertex_attrib_pointer_with_i32(color_loc, 4, GL::FLOAT, false, stride, 2 * std::mem::size_of::<f32>() as i32);
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_resolution") {
+            gl.uniform2f(Some(&loc), self.resolution.0, self.resolution.1);
+        }
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_pointSize") {
+            gl.uniform1f(Some(&loc), point_size);
+        }
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_round") {
+            gl.uniform1i(Some(&loc), (mode == GL::POINTS) as i32);
+        }
+        gl.draw_arrays(mode, 0, (data.len() / 6) as i32);
+    }
+
+    fn set_hovered(&mut self, hovered: Option<usize>) -> Option<HoverEvent> {
+        if hovered == self.hovered {
+            return None;
+        }
+        self.hovered = hovered;
+        self.draw();
+        let callback = self.on_hover.clone()?;
+        let (index, rating) = match hovered {
+            Some(index) => (JsValue::from(index as u32), JsValue::from(self.ratings[index])),
+            None => (JsValue::NULL, JsValue::NULL),
+        };
+        Some(HoverEvent { callback, index, rating })
+    }
+}
+
+fn difficulty_rgb(rating: f32) -> [f32; 3] {
+    let mut color = DIFFICULTY_SCALE[0].1;
+    for (threshold, tier) in DIFFICULTY_SCALE.iter() {
+        if rating >= *threshold as f32 {
+            color = *tier;
+        }
+    }
+    color
+}
+
+// Dispatched after the chart borrow is released so the callback may call
+// back into `render_rating_chart`.
+struct HoverEvent {
+    callback: js_sys::Function,
+    index: JsValue,
+    rating: JsValue,
+}
+
+impl HoverEvent {
+    fn dispatch(self) {
+        let _ = self.callback.call2(&JsValue::NULL, &self.index, &self.rating);
+    }
+}
+
+fn attach_listeners(canvas: &HtmlCanvasElement, chart: &Rc<RefCell<RatingChart>>) {
+    {
+        let chart = chart.clone();
+        let move_closure = Closure::wrap(Box::new(move |event: MouseEvent| {
+            let dpr = window().unwrap().device_pixel_ratio() as f32;
+            let hover = {
+                let mut rc = chart.borrow_mut();
+                let hit = rc.hit_test(event.offset_x() as f32 * dpr, event.offset_y() as f32 * dpr);
+                rc.set_hovered(hit)
+            };
+            if let Some(hover) = hover {
+                hover.dispatch();
+            }
+        }) as Box<dyn FnMut(MouseEvent)>);
+        canvas
+            .add_event_listener_with_callback("mousemove", move_closure.as_ref().unchecked_ref())
+            .unwrap();
+        move_closure.forget();
+    }
+    {
+        let chart = chart.clone();
+        let leave_closure = Closure::wrap(Box::new(move || {
+            let hover = chart.borrow_mut().set_hovered(None);
+            if let Some(hover) = hover {
+                hover.dispatch();
+            }
+        }) as Box<dyn FnMut()>);
+        canvas
+            .add_event_listener_with_callback("mouseleave", leave_closure.as_ref().unchecked_ref())
+            .unwrap();
+        leave_closure.forget();
+    }
+    {
+        let chart = chart.clone();
+        let resize_closure = Closure::wrap(Box::new(move || {
+            let mut rc = chart.borrow_mut();
+            rc.resize();
+            rc.draw();
+        }) as Box<dyn FnMut()>);
+        window().unwrap()
+            .add_event_listener_with_callback("resize", resize_closure.as_ref().unchecked_ref())
+            .unwrap();
+        resize_closure.forget();
+    }
+}
+
+/// Renders a rating timeline into `canvas_id`: tier bands in the difficulty
+/// colors, a smoothed line and one point per contest. `times` (ms since the
+/// epoch, ascending) and `ratings` must have the same length. `on_hover` is
+/// called as `(contestIndex, rating)` when the pointer nears a point and
+/// `(null, null)` when it leaves. Calling this again replaces the data.
+#[wasm_bindgen]
+pub fn render_rating_chart(
+    canvas_id: &str,
+    times: Vec<f64>,
+    ratings: Vec<f32>,
+    on_hover: Option<js_sys::Function>,
+) -> Result<(), JsValue> {
+    if times.len() != ratings.len() {
+        return Err(JsValue::from_str(&format!(
+            "Rating chart needs one time per rating ({} times, {} ratings)",
+            times.len(),
+            ratings.len()
+        )));
+    }
+    if times.windows(2).any(|pair| pair[1] < pair[0]) {
+        return Err(JsValue::from_str("Rating chart times must be ascending"));
+    }
+    let existing = RATING_CHARTS.with(|charts| charts.borrow().get(canvas_id).cloned());
+    let chart = existing.unwrap_or_else(|| {
+        let chart = Rc::new(RefCell::new(RatingChart::new(canvas_id)));
+        let canvas = chart.borrow().canvas.clone();
+        attach_listeners(&canvas, &chart);
+        RATING_CHARTS.with(|charts| charts.borrow_mut().insert(canvas_id.to_string(), chart.clone()));
+        chart
+    });
+    let mut rc = chart.borrow_mut();
+    rc.set_data(times, ratings);
+    rc.on_hover = on_hover;
+    rc.draw();
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use std::cell::RefCell;
+
+use js_sys::{Array, Function, Promise};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    window, Blob, BlobEvent, BlobPropertyBag, MediaRecorder, MediaRecorderOptions, MediaStream, MediaStreamTrack,
+    RecordingState,
+};
+
+use crate::diagnostics::{log, Level};
+use crate::options::{get_option_string, invalid_option, number_option};
+use crate::stars::star_field_canvas;
+
+const DEFAULT_FRAME_RATE: f32 = 30.0;
+const MAX_FRAME_RATE: f32 = 60.0;
+const DEFAULT_MAX_SECONDS: f32 = 30.0;
+const MAX_SECONDS: f32 = 300.0;
+const DEFAULT_MIME_TYPE: &str = "video/webm";
+// Chunks are flushed periodically so a long recording is not held in one
+// encoder buffer until `stop`.
+const CHUNK_MS: i32 = 1000;
+
+struct Recording {
+    recorder: MediaRecorder,
+    // Settles with the finished video once the recorder stops, whether
+    // `stop_recording` or the duration limit stopped it.
+    finished: Promise,
+    timeout: i32,
+}
+
+thread_local! {
+    static RECORDING: RefCell<Option<Recording>> = const { RefCell::new(None) };
+}
+
+fn stop_tracks(stream: &MediaStream) {
+    for track in stream.get_tracks().iter() {
+        track.unchecked_into::<MediaStreamTrack>().stop();
+    }
+}
+
+// Wires the recorder's events into a promise for the final blob.
+fn collect_video(recorder: &MediaRecorder, mime_type: &str) -> Promise {
+    let chunks = Array::new();
+    let on_data = Closure::wrap(Box::new({
+        let chunks = chunks.clone();
+        move |event: BlobEvent| {
+            if let Some(data) = event.data().filter(|data| data.size() > 0.0) {
+                chunks.push(&data);
+            }
+        }
+    }) as Box<dyn FnMut(BlobEvent)>);
+    recorder.set_ondataavailable(Some(on_data.as_ref().unchecked_ref()));
+    on_data.forget();
+
+    let recorder = recorder.clone();
+    let mime_type = mime_type.to_string();
+    Promise::new(&mut |resolve: Function, reject: Function| {
+        let stream = recorder.stream();
+        let chunks = chunks.clone();
+        let mime_type = mime_type.clone();
+        let reject_stop = reject.clone();
+        let on_stop = Closure::once_into_js(move || {
+            stop_tracks(&stream);
+            let options = BlobPropertyBag::new();
+            options.set_type(&mime_type);
+            match Blob::new_with_blob_sequence_and_options(&chunks, &options) {
+                Ok(blob) => resolve.call1(&JsValue::NULL, &blob),
+                Err(e) => reject_stop.call1(&JsValue::NULL, &e),
+            }
+            .ok();
+        });
+        recorder.set_onstop(Some(on_stop.unchecked_ref()));
+        let on_error = Closure::once_into_js(move |event: JsValue| {
+            reject.call1(&JsValue::NULL, &event).ok();
+        });
+        recorder.set_onerror(Some(on_error.unchecked_ref()));
+    })
+}
+
+/// Starts recording the running starfield canvas. `options` may set
+/// `frameRate` (1 to 60, default 30), `maxDuration` (seconds, up to 300,
+/// default 30; recording stops by itself after that) and `mimeType`
+/// (default `"video/webm"`). Call `stop_recording` to get the video.
+#[wasm_bindgen]
+pub fn start_recording(options: JsValue) -> Result<(), JsValue> {
+    let frame_rate = number_option(&options, "frameRate", 1.0, MAX_FRAME_RATE)?.unwrap_or(DEFAULT_FRAME_RATE);
+    let max_seconds = number_option(&options, "maxDuration", 1.0, MAX_SECONDS)?.unwrap_or(DEFAULT_MAX_SECONDS);
+    let mime_type = get_option_string(&options, "mimeType").unwrap_or_else(|| DEFAULT_MIME_TYPE.to_string());
+    if !MediaRecorder::is_type_supported(&mime_type) {
+        return Err(invalid_option("mimeType", "a video type this browser can record", &mime_type.into()));
+    }
+    if RECORDING.with(|recording| recording.borrow().is_some()) {
+        return Err(JsValue::from_str("A recording is already in progress"));
+    }
+    let canvas = star_field_canvas().ok_or_else(|| JsValue::from_str("The starfield is not running"))?;
+
+    let stream = canvas.capture_stream_with_frame_request_rate(frame_rate as f64)?;
+    let recorder_options = MediaRecorderOptions::new();
+    recorder_options.set_mime_type(&mime_type);
+    let recorder = match MediaRecorder::new_with_media_stream_and_media_recorder_options(&stream, &recorder_options) {
+        Ok(recorder) => recorder,
+        Err(e) => {
+            stop_tracks(&stream);
+            return Err(e);
+        }
+    };
+    let finished = collect_video(&recorder, &mime_type);
+    recorder.start_with_time_slice(CHUNK_MS)?;
+
+    let limit = Closure::once_into_js({
+        let recorder = recorder.clone();
+        move || {
+            if recorder.state() != RecordingState::Inactive {
+                log(Level::Info, || "Recording reached its duration limit".to_string());
+                recorder.stop().ok();
+            }
+        }
+    });
+    let timeout = window()
+        .unwrap()
+        .set_timeout_with_callback_and_timeout_and_arguments_0(limit.unchecked_ref(), (max_seconds * 1000.0) as i32)?;
+    log(Level::Info, || format!("Recording {} at {} fps for up to {}s", mime_type, frame_rate, max_seconds));
+    RECORDING.with(|recording| *recording.borrow_mut() = Some(Recording { recorder, finished, timeout }));
+    Ok(())
+}
+
+/// Stops the recording (if the duration limit has not already) and returns
+/// a promise for the video `Blob`.
+#[wasm_bindgen]
+pub fn stop_recording() -> Result<Promise, JsValue> {
+    let recording = RECORDING
+        .with(|recording| recording.borrow_mut().take())
+        .ok_or_else(|| JsValue::from_str("No recording is in progress"))?;
+    window().unwrap().clear_timeout_with_handle(recording.timeout);
+    if recording.recorder.state() != RecordingState::Inactive {
+        recording.recorder.stop()?;
+    }
+    Ok(recording.finished)
+}
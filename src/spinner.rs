@@ -0,0 +1,251 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{window, HtmlCanvasElement, WebGlBuffer, WebGlProgram, WebGlRenderingContext as GL};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::stars::{compile_shader, link_program, FrameCallback};
+
+struct LoadingSpinner {
+    gl: GL,
+    canvas: HtmlCanvasElement,
+    resolution: (f32, f32),
+    program: WebGlProgram,
+    point_buffer: WebGlBuffer,
+    line_buffer: WebGlBuffer,
+    angle: f32,
+    head: f32,
+}
+
+const SPINNER_STARS: usize = 12;
+const SPINNER_RADII: [f32; SPINNER_STARS] = [1.0, 0.72, 0.9, 0.65, 0.95, 0.78, 1.0, 0.7, 0.88, 0.62, 0.97, 0.8];
+const SPINNER_ROTATION_SPEED: f32 = 0.01;
+const SPINNER_HEAD_SPEED: f32 = 0.12;
+const SPINNER_COLOR: [f32; 3] = [0.85, 0.92, 1.0];
+
+thread_local! {
+    static SPINNERS: RefCell<HashMap<String, Rc<Cell<bool>>>> = RefCell::new(HashMap::new());
+}
+
+impl LoadingSpinner {
+    fn new(canvas_id: &str) -> LoadingSpinner {
+        let document = window().unwrap().document().unwrap();
+        let canvas = document
+            .get_element_by_id(canvas_id)
+            .expect("Canvas element not found")
+            .dyn_into::<HtmlCanvasElement>()
+            .unwrap();
+
+        let dpr = window().unwrap().device_pixel_ratio() as f32;
+        let width = canvas.client_width() as f32 * dpr;
+        let height = canvas.client_height() as f32 * dpr;
+        canvas.set_width(width as u32);
+        canvas.set_height(height as u32);
+
+        let gl: GL = canvas
+            .get_context("webgl")
+            .unwrap()
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+
+        let vertex_shader_source = r#"
+            attribute vec2 a_position;
+            attribute float a_pointSize;
+            attribute float a_alpha;
+            uniform vec2 u_resolution;
+            varying float v_alpha;
+            void main() {
+                vec2 zeroToOne = a_position / u_resolution;
+                vec2 zeroToTwo = zeroToOne * 2.0;
+                vec2 clipSpace = zeroToTwo - 1.0;
+                clipSpace.y = -clipSpace.y;
+                gl_Position = vec4(clipSpace, 0.0, 1.0);
+                gl_PointSize = a_pointSize;
+                v_alpha = a_alpha;
+            }
+        "#;
+        let fragment_shader_source = r#"
+            precision mediump float;
+            uniform vec3 u_color;
+            uniform float u_round;
+            varying float v_alpha;
+            void main() {
+                float dist = length(gl_PointCoord - vec2(0.5));
+                float factor = mix(1.0, smoothstep(0.5, 0.2, dist), u_round);
+                gl_FragColor = vec4(u_color, v_alpha * factor);
+            }
+        "#;
+        let vertex_shader = compile_shader(&gl, GL::VERTEX_SHADER, vertex_shader_source)
+            .expect("Spinner vertex shader compile error");
+        let fragment_shader = compile_shader(&gl, GL::FRAGMENT_SHADER, fragment_shader_source)
+            .expect("Spinner fragment shader compile error");
+        let program = link_program(&gl, &vertex_shader, &fragment_shader)
+            .expect("Spinner program link error");
+
+        let point_buffer = gl.create_buffer().expect("Failed to create spinner point buffer");
+        let line_buffer = gl.create_buffer().expect("Failed to create spinner line buffer");
+
+        LoadingSpinner {
+            gl,
+            canvas,
+            resolution: (width, height),
+            program,
+            point_buffer,
+            line_buffer,
+            angle: 0.0,
+            head: 0.0,
+        }
+    }
+
+    fn star_positions(&self) -> [(f32, f32); SPINNER_STARS] {
+        let (width, height) = self.resolution;
+        let radius = width.min(height) * 0.4;
+        let mut positions = [(0.0, 0.0); SPINNER_STARS];
+        for (i, position) in positions.iter_mut().enumerate() {
+            let a = self.angle + i as f32 / SPINNER_STARS as f32 * std::f32::consts::TAU;
+            let r = radius * SPINNER_RADII[i];
+            *position = (width / 2.0 + r * a.cos(), height / 2.0 + r * a.sin());
+        }
+        positions
+    }
+
+    fn star_alpha(&self, i: usize) -> f32 {
+        let behind = (self.head - i as f32).rem_euclid(SPINNER_STARS as f32);
+        0.25 + 0.75 * (1.0 - behind / SPINNER_STARS as f32).powi(2)
+    }
+
+    fn sync_size(&mut self) {
+        let dpr = window().unwrap().device_pixel_ratio() as f32;
+        let width = self.canvas.client_width() as f32 * dpr;
+        let height = self.canvas.client_height() as f32 * dpr;
+        if (width, height) != self.resolution {
+            self.canvas.set_width(width as u32);
+            self.canvas.set_height(height as u32);
+            self.resolution = (width, height);
+        }
+    }
+
+    fn update(&mut self) {
+        let dt: f32 = 1.0;
+        self.sync_size();
+        self.angle = (self.angle + SPINNER_ROTATION_SPEED * dt) % std::f32::consts::TAU;
+        self.head = (self.head + SPINNER_HEAD_SPEED * dt) % SPINNER_STARS as f32;
+
+        let positions = self.star_positions();
+        let point_scale = self.resolution.0.min(self.resolution.1) * 0.06;
+        let mut point_data = Vec::with_capacity(SPINNER_STARS * 4);
+        for (i, (x, y)) in positions.iter().enumerate() {
+            let alpha = self.star_alpha(i);
+            point_data.push(*x);
+            point_data.push(*y);
+            point_data.push((point_scale * (0.5 + 0.5 * alpha)).max(2.0));
+            point_data.push(alpha);
+        }
+
+        let mut line_data = Vec::with_capacity(SPINNER_STARS * 2 * 4);
+        for i in 0..SPINNER_STARS {
+            let j = (i + 1) % SPINNER_STARS;
+            let alpha = self.star_alpha(i).min(self.star_alpha(j)) * 0.5;
+            for k in [i, j] {
+                line_data.push(positions[k].0);
+                line_data.push(positions[k].1);
+                line_data.push(1.0);
+                line_data.push(alpha);
+            }
+        }
+
+        self.gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.point_buffer));
+        unsafe {
+            let array = js_sys::Float32Array::view(&point_data);
+            self.gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &array, GL::DYNAMIC_DRAW);
+        }
+        self.gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.line_buffer));
+        unsafe {
+            let array = js_sys::Float32Array::view(&line_data);
+            self.gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &array, GL::DYNAMIC_DRAW);
+        }
+    }
+
+    fn draw(&self) {
+        let gl = &self.gl;
+        gl.viewport(0, 0, self.resolution.0 as i32, self.resolution.1 as i32);
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(GL::COLOR_BUFFER_BIT);
+        gl.use_program(Some(&self.program));
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_resolution") {
+            gl.uniform2f(Some(&loc), self.resolution.0, self.resolution.1);
+        }
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_color") {
+            gl.uniform3f(Some(&loc), SPINNER_COLOR[0], SPINNER_COLOR[1], SPINNER_COLOR[2]);
+        }
+        gl.enable(GL::BLEND);
+        gl.blend_func(GL::SRC_ALPHA, GL::ONE_MINUS_SRC_ALPHA);
+        self.draw_buffer(&self.line_buffer, GL::LINES, SPINNER_STARS * 2, 0.0);
+        self.draw_buffer(&self.point_buffer, GL::POINTS, SPINNER_STARS, 1.0);
+        gl.disable(GL::BLEND);
+    }
+
+    fn draw_buffer(&self, buffer: &WebGlBuffer, mode: u32, count: usize, round: f32) {
+        let gl = &self.gl;
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(buffer));
+        let stride = 4 * std::mem::size_of::<f32>() as i32;
+        let pos_loc = gl.get_attrib_location(&self.program, "a_position") as u32;
+        let size_loc = gl.get_attrib_location(&self.program, "a_pointSize") as u32;
+        let alpha_loc = gl.get_attrib_location(&self.program, "a_alpha") as u32;
+        gl.enable_vertex_attrib_array(pos_loc);
+        gl.vertex_attrib_pointer_with_i32(pos_loc, 2, GL::FLOAT, false, stride, 0);
+        gl.enable_vertex_attrib_array(size_loc);
+        gl.vertex_attrib_pointer_with_i32(size_loc, 1, GL::FLOAT, false, stride, 2 * std::mem::size_of::<f32>() as i32);
+        gl.enable_vertex_attrib_array(alpha_loc);
+        gl.vertex_attrib_pointer_with_i32(alpha_loc, 1, GL::FLOAT, false, stride, 3 * std::mem::size_of::<f32>() as i32);
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_round") {
+            gl.uniform1f(Some(&loc), round);
+        }
+        gl.draw_arrays(mode, 0, count as i32);
+    }
+}
+
+/// Renders a small rotating constellation into `canvas_id` as a loading
+/// indicator until `stop_loading_spinner` is called for the same canvas.
+#[wasm_bindgen]
+pub fn start_loading_spinner(canvas_id: &str) {
+    let running = Rc::new(Cell::new(true));
+    SPINNERS.with(|spinners| {
+        if let Some(previous) = spinners.borrow_mut().insert(canvas_id.to_string(), running.clone()) {
+            previous.set(false);
+        }
+    });
+
+    let mut spinner = LoadingSpinner::new(canvas_id);
+    let f: FrameCallback = Rc::new(RefCell::new(None));
+    let g = f.clone();
+
+    *g.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+        if !running.get() {
+            let gl = &spinner.gl;
+            gl.clear_color(0.0, 0.0, 0.0, 0.0);
+            gl.clear(GL::COLOR_BUFFER_BIT);
+            return;
+        }
+        spinner.update();
+        spinner.draw();
+        window().unwrap()
+            .request_animation_frame(f.borrow().as_ref().unwrap().as_ref().unchecked_ref())
+            .unwrap();
+    }) as Box<dyn FnMut()>));
+
+    window().unwrap()
+        .request_animation_frame(g.borrow().as_ref().unwrap().as_ref().unchecked_ref())
+        .unwrap();
+}
+
+#[wasm_bindgen]
+pub fn stop_loading_spinner(canvas_id: &str) {
+    SPINNERS.with(|spinners| {
+        if let Some(running) = spinners.borrow_mut().remove(canvas_id) {
+            running.set(false);
+        }
+    });
+}
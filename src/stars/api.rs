@@ -0,0 +1,1976 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use js_sys::Promise;
+use soj_sim::contest::ContestSchedule;
+use soj_sim::meteors::{MeteorColors, MeteorSchedule, MeteorTrajectory};
+use soj_sim::sky::Observer;
+use soj_sim::Rng;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    window, Blob, BlobPropertyBag, HtmlCanvasElement, IntersectionObserver, IntersectionObserverEntry, MediaQueryList,
+    MediaQueryListEvent, MouseEvent, ResizeObserver,
+};
+
+#[cfg(feature = "audio")]
+use crate::audio::{self, Sound};
+use crate::diagnostics::{log, now_ms, Level};
+use crate::errors::report;
+use crate::i18n::args_from_js;
+use crate::options::{bool_option, color_option, get_option, invalid_option, number_option, parse_hex_color, HEX_COLOR};
+
+use super::baked::{BakedDescriptor, BakedStarscape};
+use super::bokeh::{Bokeh, BokehDescriptor};
+use super::debris::{Debris, DebrisDescriptor};
+use super::effect::Effect;
+use super::emitter::{Emitter, EmitterDescriptor};
+use super::gl::{paint_fallback, BlendMode};
+use super::layer::Layer;
+use super::sim::{difficulty_color, sky_rng, Limits, ThemeTint};
+use super::vision::ColorVision;
+use super::{
+    ColorScheme, CornerGradient, DensityMap, Easing, ExclusionZone, FlowField, HueShift, PlexusStyle, StarBand,
+    StarCluster, StarField, StarFieldOptions, StarMask, StarPalette, StarSizes, ToneMapping, Transition, TwinkleConfig,
+    TwinkleMode, COUNTDOWN_COLOR, FRAME_MS, MAX_EXPOSURE, MAX_FLOW_SCALE, MAX_FLOW_SPEED, MAX_METEORS, MAX_MOTION_BLUR,
+    MAX_PARTICLES, MAX_PLEXUS_DISTANCE, MAX_POSTER_SIZE, MAX_STARS, MAX_GUIDED_METEOR_MS, MAX_TIME_SCALE,
+    MAX_TRANSITION_MS, MAX_WIND_SPEED, METEOR_MAX_SPEED, POINT_SCALE, RESIZE_DEBOUNCE_MS, TICKER_DEFAULT_FONT_SCALE,
+    TICKER_DEFAULT_SPEED, TICKER_MAX_SPEED,
+};
+
+struct Fallback {
+    renderer: &'static str,
+    reason: String,
+}
+
+thread_local! {
+    static STAR_FIELD: RefCell<Option<Rc<RefCell<StarField>>>> = const { RefCell::new(None) };
+    static FALLBACK: RefCell<Option<Fallback>> = const { RefCell::new(None) };
+    static FULLSCREEN_LISTENER: Cell<bool> = const { Cell::new(false) };
+    static POINTER_LISTENER: Cell<bool> = const { Cell::new(false) };
+    // Bumped by `stop_starfield`; loops and listeners of an older run quit.
+    static RUN: Cell<u32> = const { Cell::new(0) };
+    static COLOR_SCHEME_LISTENER: Cell<bool> = const { Cell::new(false) };
+    // Pauses the built-in loop while the canvas is out of view; follows the
+    // sky to a new canvas.
+    static VIEWPORT_OBSERVER: RefCell<Option<IntersectionObserver>> = const { RefCell::new(None) };
+    static RESIZE_LISTENER: RefCell<Option<ResizeListener>> = const { RefCell::new(None) };
+}
+
+// The window resize listener of the current run or fallback; dropping it
+// removes the listener and cancels a debounced resize still pending.
+struct ResizeListener {
+    callback: Closure<dyn FnMut()>,
+    pending: Rc<Cell<Option<i32>>>,
+}
+
+impl ResizeListener {
+    // Replaces the listener of any earlier run.
+    fn install(callback: Closure<dyn FnMut()>, pending: Rc<Cell<Option<i32>>>) {
+        window()
+            .unwrap()
+            .add_event_listener_with_callback("resize", callback.as_ref().unchecked_ref())
+            .unwrap();
+        RESIZE_LISTENER.with(|cell| *cell.borrow_mut() = Some(ResizeListener { callback, pending }));
+    }
+}
+
+impl Drop for ResizeListener {
+    fn drop(&mut self) {
+        let window = window().unwrap();
+        let _ = window.remove_event_listener_with_callback("resize", self.callback.as_ref().unchecked_ref());
+        if let Some(handle) = self.pending.take() {
+            window.clear_timeout_with_handle(handle);
+        }
+    }
+}
+
+pub(super) fn with_star_field<F: FnOnce(&mut StarField)>(f: F) {
+    STAR_FIELD.with(|cell| {
+        if let Some(star_field) = cell.borrow().as_ref() {
+            f(&mut star_field.borrow_mut());
+        }
+    });
+}
+
+const MAX_STAR_PALETTE_COLORS: usize = 64;
+
+// `[[r, g, b, weight], ...]` with 0-255 channels and positive weights.
+pub(super) fn star_palette_option(options: &JsValue, key: &str) -> Result<Option<StarPalette>, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(None);
+    };
+    let expected = format!(
+        "an array of up to {} [r, g, b, weight] entries with channels from 0 to 255 and positive weights",
+        MAX_STAR_PALETTE_COLORS
+    );
+    let entry = |item: JsValue| -> Option<([f32; 3], f32)> {
+        if !js_sys::Array::is_array(&item) {
+            return None;
+        }
+        let numbers: Option<Vec<f32>> =
+            js_sys::Array::from(&item).iter().map(|v| v.as_f64().map(|v| v as f32)).collect();
+        let [r, g, b, weight] = numbers?[..] else {
+            return None;
+        };
+        [r, g, b].iter().all(|c| (0.0..=255.0).contains(c)).then_some(([r / 255.0, g / 255.0, b / 255.0], weight))
+    };
+    if !js_sys::Array::is_array(&value) {
+        return Err(invalid_option(key, &expected, &value));
+    }
+    let items = js_sys::Array::from(&value);
+    if items.length() as usize > MAX_STAR_PALETTE_COLORS {
+        return Err(invalid_option(key, &expected, &value));
+    }
+    items
+        .iter()
+        .map(entry)
+        .collect::<Option<Vec<_>>>()
+        .and_then(|colors| StarPalette::new(&colors))
+        .map(Some)
+        .ok_or_else(|| invalid_option(key, &expected, &value))
+}
+
+const MAX_STAR_SIZE: f32 = 32.0;
+const MAX_STAR_SIZE_BINS: usize = 64;
+
+// `{ min, max, exponent }` for a power curve, or `[[size, weight], ...]` for
+// a histogram; sizes are point diameters in CSS pixels.
+pub(super) fn star_sizes_option(options: &JsValue, key: &str) -> Result<StarSizes, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(StarSizes::DEFAULT);
+    };
+    let expected = format!(
+        "an object with min, max and exponent, or an array of up to {} [size, weight] entries with sizes from 0 to {} \
+         and positive weights",
+        MAX_STAR_SIZE_BINS, MAX_STAR_SIZE
+    );
+    if js_sys::Array::is_array(&value) {
+        let items = js_sys::Array::from(&value);
+        if items.length() as usize > MAX_STAR_SIZE_BINS {
+            return Err(invalid_option(key, &expected, &value));
+        }
+        let entry = |item: JsValue| -> Option<(f32, f32)> {
+            let numbers: Option<Vec<f32>> = js_sys::Array::is_array(&item)
+                .then(|| js_sys::Array::from(&item).iter().map(|v| v.as_f64().map(|v| v as f32)).collect())?;
+            let [size, weight] = numbers?[..] else {
+                return None;
+            };
+            (0.0..=MAX_STAR_SIZE).contains(&size).then_some((size / POINT_SCALE, weight))
+        };
+        return items
+            .iter()
+            .map(entry)
+            .collect::<Option<Vec<_>>>()
+            .and_then(|radii| StarSizes::histogram(&radii))
+            .ok_or_else(|| invalid_option(key, &expected, &value));
+    }
+    if !value.is_object() {
+        return Err(invalid_option(key, &expected, &value));
+    }
+    let min = number_option(&value, "min", 0.0, MAX_STAR_SIZE)?.unwrap_or(0.5);
+    let max = number_option(&value, "max", 0.0, MAX_STAR_SIZE)?.unwrap_or(4.0_f32.max(min));
+    let exponent = number_option(&value, "exponent", 0.01, 100.0)?.unwrap_or(2.0);
+    StarSizes::power(min / POINT_SCALE, max / POINT_SCALE, exponent)
+        .ok_or_else(|| invalid_option(key, "an object with min no greater than max", &value))
+}
+
+const TWINKLE_MODES: &str = "\"sine\" or \"noise\"";
+
+fn twinkle_mode_from_name(name: &str) -> Option<TwinkleMode> {
+    match name.to_ascii_lowercase().as_str() {
+        "sine" => Some(TwinkleMode::Sine),
+        "noise" | "perlin" => Some(TwinkleMode::Noise),
+        _ => None,
+    }
+}
+
+// `{ amplitude, speedMin, speedMax, alphas }` with speeds in radians per
+// second and `alphas` as `[[alpha, weight], ...]`; omitted fields keep the
+// defaults. `None` when the option is missing.
+pub(super) fn twinkle_option(options: &JsValue, key: &str) -> Result<Option<TwinkleConfig>, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(None);
+    };
+    if !value.is_object() || js_sys::Array::is_array(&value) {
+        return Err(invalid_option(key, "an object with amplitude, speedMin, speedMax, alphas and mode", &value));
+    }
+    let default = TwinkleConfig::default();
+    let amplitude = number_option(&value, "amplitude", 0.0, 1.0)?.unwrap_or(default.amplitude);
+    // Radians per second in, per frame kept.
+    let per_frame = |key| Ok::<_, JsValue>(number_option(&value, key, 0.0, 60.0)?.map(|speed| speed / 60.0));
+    let speed_min = per_frame("speedMin")?.unwrap_or(default.speed.0);
+    let speed_max = per_frame("speedMax")?.unwrap_or(default.speed.1.max(speed_min));
+    if speed_max < speed_min {
+        let value = get_option(&value, "speedMax").unwrap_or(JsValue::UNDEFINED);
+        return Err(invalid_option("speedMax", "a number from speedMin up", &value));
+    }
+    let Some(alphas) = get_option(&value, "alphas") else {
+        return Ok(TwinkleConfig::new(amplitude, (speed_min, speed_max), &TwinkleConfig::DEFAULT_ALPHAS));
+    };
+    let expected = format!(
+        "an array of up to {} [alpha, weight] entries with alphas from 0 to 1 and positive weights",
+        MAX_TWINKLE_ALPHAS
+    );
+    let entry = |item: JsValue| -> Option<(f32, f32)> {
+        if !js_sys::Array::is_array(&item) {
+            return None;
+        }
+        let numbers: Option<Vec<f32>> =
+            js_sys::Array::from(&item).iter().map(|v| v.as_f64().map(|v| v as f32)).collect();
+        let [alpha, weight] = numbers?[..] else {
+            return None;
+        };
+        Some((alpha, weight))
+    };
+    if !js_sys::Array::is_array(&alphas) {
+        return Err(invalid_option("alphas", &expected, &alphas));
+    }
+    let items = js_sys::Array::from(&alphas);
+    if items.length() as usize > MAX_TWINKLE_ALPHAS {
+        return Err(invalid_option("alphas", &expected, &alphas));
+    }
+    items
+        .iter()
+        .map(entry)
+        .collect::<Option<Vec<_>>>()
+        .and_then(|tiers| TwinkleConfig::new(amplitude, (speed_min, speed_max), &tiers))
+        .map(Some)
+        .ok_or_else(|| invalid_option("alphas", &expected, &alphas))
+}
+
+// The `mode` field of the `twinkle` option.
+pub(super) fn twinkle_mode_option(options: &JsValue, key: &str) -> Result<TwinkleMode, JsValue> {
+    let Some(mode) = get_option(options, key).and_then(|value| get_option(&value, "mode")) else {
+        return Ok(TwinkleMode::Sine);
+    };
+    mode.as_string()
+        .and_then(|name| twinkle_mode_from_name(&name))
+        .ok_or_else(|| invalid_option("mode", TWINKLE_MODES, &mode))
+}
+
+const MAX_TWINKLE_ALPHAS: usize = 16;
+
+// `{ center, angle, sigma, probability }`, each defaulting to `StarBand::DEFAULT`;
+// `angle` is in degrees.
+pub(super) fn star_band_option(options: &JsValue, key: &str) -> Result<StarBand, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(StarBand::DEFAULT);
+    };
+    if !value.is_object() {
+        return Err(invalid_option(key, "an object with center, angle, sigma and probability", &value));
+    }
+    let default = StarBand::DEFAULT;
+    Ok(StarBand {
+        center: number_option(&value, "center", -1.0, 2.0)?.unwrap_or(default.center),
+        angle: number_option(&value, "angle", -180.0, 180.0)?.map_or(default.angle, f32::to_radians),
+        sigma: number_option(&value, "sigma", 0.0, 1.0)?.unwrap_or(default.sigma),
+        probability: number_option(&value, "probability", 0.0, 1.0)?.unwrap_or(default.probability),
+    })
+}
+
+const MAX_STAR_CLUSTERS: usize = 16;
+
+// Integers above this lose precision as JavaScript numbers.
+const MAX_SEED: f64 = 9_007_199_254_740_991.0;
+
+pub(super) fn seed_option(options: &JsValue, key: &str) -> Result<Option<u64>, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(None);
+    };
+    match value.as_f64() {
+        Some(seed) if (0.0..=MAX_SEED).contains(&seed) && seed.fract() == 0.0 => Ok(Some(seed as u64)),
+        _ => Err(invalid_option(key, "a non-negative integer up to 2^53 - 1", &value)),
+    }
+}
+
+// `[{ x, y, radius, density, sizeBias, tightness }, ...]`, missing fields
+// defaulting to `StarCluster::CENTER`'s, or a count of such clusters at
+// random places, drawn from `seed` when there is one.
+pub(super) fn star_clusters_option(
+    options: &JsValue,
+    key: &str,
+    seed: Option<u64>,
+) -> Result<Option<Vec<StarCluster>>, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(None);
+    };
+    let expected = format!(
+        "a count or an array of up to {} {{ x, y, radius, density, sizeBias, tightness }} objects",
+        MAX_STAR_CLUSTERS
+    );
+    if let Some(count) = value.as_f64() {
+        if !(0.0..=MAX_STAR_CLUSTERS as f64).contains(&count) || count.fract() != 0.0 {
+            return Err(invalid_option(key, &expected, &value));
+        }
+        // A stream apart from the sky's, which starts from the same seed.
+        let mut rng = sky_rng(seed.map(|seed| !seed));
+        // Kept off the edges, where most of a cluster would be cut off.
+        let clusters = (0..count as usize)
+            .map(|_| StarCluster { x: rng.range((0.1, 0.9)), y: rng.range((0.1, 0.9)), ..StarCluster::CENTER })
+            .collect();
+        return Ok(Some(clusters));
+    }
+    if !js_sys::Array::is_array(&value) || js_sys::Array::from(&value).length() as usize > MAX_STAR_CLUSTERS {
+        return Err(invalid_option(key, &expected, &value));
+    }
+    let default = StarCluster::CENTER;
+    js_sys::Array::from(&value)
+        .iter()
+        .map(|item| {
+            if !item.is_object() {
+                return Err(invalid_option(key, &expected, &item));
+            }
+            Ok(StarCluster {
+                x: number_option(&item, "x", -1.0, 2.0)?.unwrap_or(default.x),
+                y: number_option(&item, "y", -1.0, 2.0)?.unwrap_or(default.y),
+                radius: number_option(&item, "radius", 0.0, 2.0)?.unwrap_or(default.radius),
+                density: number_option(&item, "density", 0.0, 1.0)?.unwrap_or(default.density),
+                size_bias: number_option(&item, "sizeBias", 0.0, 1.0)?.unwrap_or(default.size_bias),
+                tightness: number_option(&item, "tightness", 0.0, 1.0)?.unwrap_or(default.tightness),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+// `{ head, tail }` hex colors; one omitted keeps the default.
+pub(super) fn meteor_colors_option(options: &JsValue, key: &str) -> Result<MeteorColors, JsValue> {
+    let fallback = MeteorColors::DEFAULT;
+    let Some(value) = get_option(options, key) else {
+        return Ok(fallback);
+    };
+    if !value.is_object() {
+        return Err(invalid_option(key, "an object with head and tail colors", &value));
+    }
+    Ok(MeteorColors {
+        head: color_option(&value, "head")?.unwrap_or(fallback.head),
+        tail: color_option(&value, "tail")?.unwrap_or(fallback.tail),
+    })
+}
+
+const MAX_DENSITY_MAP_CELLS: usize = 1 << 20;
+
+// Rows of non-negative weights (`[[w, ...], ...]`), or an `ImageData`-like
+// `{ width, height, data }` whose cells weigh their mean RGB times alpha.
+pub(super) fn density_map_option(options: &JsValue, key: &str) -> Result<Option<DensityMap>, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(None);
+    };
+    let expected = format!(
+        "an array of equal-length rows of non-negative weights, or ImageData, of up to {} cells with some weight",
+        MAX_DENSITY_MAP_CELLS
+    );
+    let invalid = || invalid_option(key, &expected, &value);
+    let (width, height, weights) = if js_sys::Array::is_array(&value) {
+        let rows = js_sys::Array::from(&value);
+        let height = rows.length() as usize;
+        let mut width = 0;
+        let mut weights = Vec::new();
+        for row in rows.iter() {
+            if !js_sys::Array::is_array(&row) {
+                return Err(invalid());
+            }
+            let row = js_sys::Array::from(&row);
+            width = row.length() as usize;
+            if weights.len() + width > MAX_DENSITY_MAP_CELLS {
+                return Err(invalid());
+            }
+            for weight in row.iter() {
+                weights.push(weight.as_f64().ok_or_else(invalid)? as f32);
+            }
+        }
+        (width, height, weights)
+    } else {
+        let (width, height, data) = image_data(&value).ok_or_else(invalid)?;
+        let weights = data
+            .chunks_exact(4)
+            .map(|rgba| (rgba[0] as f32 + rgba[1] as f32 + rgba[2] as f32) / 3.0 * rgba[3] as f32 / 255.0)
+            .collect();
+        (width, height, weights)
+    };
+    // A ragged array leaves a length that no longer matches the grid.
+    DensityMap::new(width, height, &weights).map(Some).ok_or_else(invalid)
+}
+
+// The size and RGBA bytes of an `ImageData`-like `{ width, height, data }`
+// of up to `MAX_DENSITY_MAP_CELLS` pixels.
+fn image_data(value: &JsValue) -> Option<(usize, usize, Vec<u8>)> {
+    let dimension = |name: &str| get_option(value, name).and_then(|v| v.as_f64()).map(|v| v as usize);
+    let (width, height) = (dimension("width")?, dimension("height")?);
+    let data = get_option(value, "data")?;
+    if width.saturating_mul(height) > MAX_DENSITY_MAP_CELLS || !data.is_instance_of::<js_sys::Uint8ClampedArray>() {
+        return None;
+    }
+    let data = js_sys::Uint8ClampedArray::from(data).to_vec();
+    (data.len() == width * height * 4).then_some((width, height, data))
+}
+
+// `ImageData` whose alpha confines stars, or `{ image, invert }` with
+// `invert` keeping them out of opaque areas instead.
+pub(super) fn star_mask_option(options: &JsValue, key: &str) -> Result<Option<StarMask>, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(None);
+    };
+    star_mask_from_js(&value, key).map(Some)
+}
+
+fn star_mask_from_js(value: &JsValue, key: &str) -> Result<StarMask, JsValue> {
+    let expected = format!(
+        "ImageData, or {{ image, invert }}, of up to {} pixels with some room for stars",
+        MAX_DENSITY_MAP_CELLS
+    );
+    let invalid = || invalid_option(key, &expected, value);
+    let (image, invert) = match get_option(value, "image") {
+        Some(image) => (image, bool_option(value, "invert")?.unwrap_or(false)),
+        None => (value.clone(), false),
+    };
+    let (width, height, data) = image_data(&image).ok_or_else(invalid)?;
+    let alphas: Vec<f32> = data.chunks_exact(4).map(|rgba| rgba[3] as f32 / 255.0).collect();
+    StarMask::new(width, height, &alphas, invert).ok_or_else(invalid)
+}
+
+const MAX_CORNER_COLORS: usize = 16;
+
+/// Paints the background with a color per corner, blended bilinearly,
+/// instead of the day or night sky's vertical gradient. `corners` is
+/// `{ topLeft, topRight, bottomLeft, bottomRight, period }`: each corner a
+/// hex color, or an array of up to 16 that it cycles through smoothly once
+/// per `period` seconds (default 30) of sky time. The theme tint still
+/// applies. `null` restores the sky gradient.
+#[wasm_bindgen]
+pub fn set_corner_gradient(corners: JsValue) -> Result<(), JsValue> {
+    let corners = if corners.is_undefined() || corners.is_null() {
+        None
+    } else {
+        Some(corner_gradient_from_js(&corners)?)
+    };
+    with_star_field(|sf| sf.set_corner_gradient(corners));
+    Ok(())
+}
+
+fn corner_gradient_from_js(value: &JsValue) -> Result<CornerGradient, JsValue> {
+    if !value.is_object() || js_sys::Array::is_array(value) {
+        return Err(invalid_option("corners", "an object with topLeft, topRight, bottomLeft and bottomRight", value));
+    }
+    let expected = format!("a hex color or an array of 1 to {} hex colors", MAX_CORNER_COLORS);
+    let corner = |key: &str| -> Result<Vec<[f32; 3]>, JsValue> {
+        let color = get_option(value, key).unwrap_or(JsValue::UNDEFINED);
+        let colors = if js_sys::Array::is_array(&color) {
+            js_sys::Array::from(&color).iter().collect()
+        } else {
+            vec![color.clone()]
+        };
+        if colors.is_empty() || colors.len() > MAX_CORNER_COLORS {
+            return Err(invalid_option(key, &expected, &color));
+        }
+        colors
+            .iter()
+            .map(|color| color.as_string().and_then(|hex| parse_hex_color(&hex)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| invalid_option(key, &expected, &color))
+    };
+    Ok(CornerGradient {
+        corners: [corner("topLeft")?, corner("topRight")?, corner("bottomLeft")?, corner("bottomRight")?],
+        period: number_option(value, "period", 1.0, 3600.0)?.unwrap_or(30.0),
+    })
+}
+
+pub(super) fn hue_shift_option(options: &JsValue, key: &str) -> Result<Option<HueShift>, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(None);
+    };
+    hue_shift_from_js(&value, key).map(Some)
+}
+
+fn hue_shift_from_js(value: &JsValue, key: &str) -> Result<HueShift, JsValue> {
+    if !value.is_object() {
+        return Err(invalid_option(key, "an object with range and period", value));
+    }
+    Ok(HueShift {
+        range: number_option(value, "range", 0.0, 360.0)?.unwrap_or(40.0),
+        period: number_option(value, "period", 10.0, 86_400.0)?.unwrap_or(600.0),
+    })
+}
+
+/// Lets the background's hue drift slowly so long-lived pages don't feel
+/// static. `shift` is `{ range, period }`: the swing in degrees from end to
+/// end, default 40, and the seconds of sky time to go there and back,
+/// default 600. The gradient's colors are rotated as a whole, corners
+/// included. `null` stops the drift.
+#[wasm_bindgen]
+pub fn set_hue_shift(shift: JsValue) -> Result<(), JsValue> {
+    let shift = if shift.is_undefined() || shift.is_null() {
+        None
+    } else {
+        Some(hue_shift_from_js(&shift, "shift")?)
+    };
+    with_star_field(|sf| sf.set_hue_shift(shift));
+    Ok(())
+}
+
+/// Confines star placement to the alpha of `mask`, stretched over the
+/// canvas: `ImageData`, or `{ image, invert }` with `invert: true` keeping
+/// stars out of the opaque areas instead. Partly transparent pixels thin
+/// stars out. Stars outside the new shape move into it; `null` lifts the
+/// mask. Stars still drift, so they slowly wander past its edges.
+#[wasm_bindgen]
+pub fn set_star_mask(mask: JsValue) -> Result<(), JsValue> {
+    let mask = if mask.is_undefined() || mask.is_null() {
+        None
+    } else {
+        Some(star_mask_from_js(&mask, "mask")?)
+    };
+    with_star_field(|sf| sf.set_star_mask(mask));
+    Ok(())
+}
+
+pub(crate) type FrameCallback = Rc<RefCell<Option<Closure<dyn FnMut()>>>>;
+
+pub(super) fn validate_start(canvas_id: &str, num_stars: usize) -> Result<(), JsValue> {
+    let element = window().unwrap().document().unwrap().get_element_by_id(canvas_id);
+    if !element.is_some_and(|element| element.has_type::<HtmlCanvasElement>()) {
+        return Err(invalid_option("canvasId", "the id of a <canvas> element", &canvas_id.into()));
+    }
+    if num_stars > MAX_STARS {
+        let expected = format!("a star count from 0 to {}", MAX_STARS);
+        return Err(invalid_option("numStars", &expected, &(num_stars as f64).into()));
+    }
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn start_starfield(canvas_id: &str, num_stars: usize) -> Result<(), JsValue> {
+    validate_start(canvas_id, num_stars)?;
+    run_starfield(canvas_id, num_stars, &StarFieldOptions::default());
+    Ok(())
+}
+
+/// Like `start_starfield`, with `options`: `transparent` (skip the sky
+/// gradient and let the page background show through the canvas), `manual`
+/// (no built-in animation loop; call `tick`, or `step` and `render`), `colorScheme`
+/// (see `set_color_scheme`), `starPalette` (weighted star colors as
+/// `[[r, g, b, weight], ...]`, channels 0-255), `starSizes` (star diameters
+/// in CSS pixels: `{ min, max, exponent }`, default 0.5, 4 and 2, higher
+/// exponents favoring small stars, or a histogram `[[size, weight], ...]`
+/// from subtle dust to chunky cartoon stars), `twinkle` (`{ amplitude,
+/// speedMin, speedMax, alphas, mode }`: the brightness swing from 0 to 1,
+/// default 0.3, the twinkle speed range in radians per second, default 0.12
+/// to 0.3, base alphas as `[[alpha, weight], ...]`, default 0.5, 0.7 and
+/// 0.9 equally, and `mode` as in `set_twinkle_mode`), `band` (the dense
+/// band of stars: `center` as a fraction of the height, `angle` in degrees
+/// clockwise, `sigma` as a fraction of the height and the `probability` a
+/// star falls in it), `clusters` (hotspots of big stars, by default one at
+/// the center: `[{ x, y, radius, density, sizeBias, tightness }, ...]` with
+/// positions and radius as fractions of the canvas, `density` the chance a
+/// big star lands there, `sizeBias` the smallest star that joins, as a
+/// fraction of the biggest, and `tightness` from 0 (even) to 1 (gathered at
+/// the center); or a count of clusters at random places, 0 for none, which
+/// with a band `probability` of 0 gives a perfectly uniform sky) and
+/// `densityMap` (where the initial stars gather: rows of weights, or
+/// `ImageData` whose brighter pixels draw more stars; it replaces the band),
+/// `starMask` (confines stars to a shape; see `set_star_mask`),
+/// `hueShift` (a slow drift of the background's hue; see `set_hue_shift`),
+/// `meteorColors` (`{ head, tail }` hex colors the trail fades between) and
+/// `colorVision` (`"deuteranopia"`, `"protanopia"` or `"tritanopia"` for
+/// color-blind-safe stars, status tints and rating bursts; see
+/// `set_color_vision`), `seed` (an integer that makes every random draw
+/// repeat from start to start, for tests and screenshots) and `dataSaver`
+/// (render at one pixel per CSS pixel on high-DPI screens, for metered or
+/// low-end devices; defaults to the browser's Save-Data hint). Either
+/// way, a canvas that is hidden or 0×0 delays startup until it is laid out,
+/// and the built-in loop pauses while the canvas is scrolled out of view.
+#[wasm_bindgen]
+pub fn start_starfield_with_options(canvas_id: &str, num_stars: usize, options: JsValue) -> Result<(), JsValue> {
+    validate_start(canvas_id, num_stars)?;
+    run_starfield(canvas_id, num_stars, &StarFieldOptions::from_js(&options)?);
+    Ok(())
+}
+
+/// Stops the running starfield, or its pending startup or static fallback:
+/// the animation loop and resize handling end and the sky is released. The
+/// canvas keeps its last frame; start again with `start_starfield`.
+#[wasm_bindgen]
+pub fn stop_starfield() {
+    RUN.with(|run| run.set(run.get().wrapping_add(1)));
+    RESIZE_LISTENER.with(|cell| *cell.borrow_mut() = None);
+    STAR_FIELD.with(|cell| *cell.borrow_mut() = None);
+    FALLBACK.with(|fallback| *fallback.borrow_mut() = None);
+}
+
+/// Moves the running sky to `canvas`, keeping its stars, meteors and
+/// settings, for single-page apps whose route changes replace the element
+/// it was drawn on. Recreates the GL resources there; the old canvas is left
+/// blank. Custom shaders return to the default.
+#[wasm_bindgen]
+pub fn transfer_to_canvas(canvas: HtmlCanvasElement) -> Result<(), JsValue> {
+    let mut result = Err(JsValue::from_str("The starfield is not running"));
+    with_star_field(|sf| result = sf.transfer_to_canvas(canvas.clone()).map_err(|e| JsValue::from_str(&e)));
+    if result.is_ok() {
+        VIEWPORT_OBSERVER.with(|observer| {
+            if let Some(observer) = observer.borrow().as_ref() {
+                observer.disconnect();
+                observer.observe(&canvas);
+            }
+        });
+    }
+    result
+}
+
+fn report_gl_error(code: Option<u32>) {
+    if let Some(code) = code {
+        let message = format!("WebGL error 0x{:04x}", code);
+        log(Level::Error, || message.clone());
+        report("gl", &message);
+    }
+}
+
+pub(super) fn find_canvas(canvas_id: &str) -> HtmlCanvasElement {
+    window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .get_element_by_id(canvas_id)
+        .expect("Canvas element not found")
+        .dyn_into::<HtmlCanvasElement>()
+        .unwrap()
+}
+
+// A canvas that is display: none or not laid out yet has no size to build
+// the sky for, so startup waits until it gets one.
+fn defer_until_laid_out(
+    canvas: &HtmlCanvasElement,
+    canvas_id: &str,
+    num_stars: usize,
+    options: &StarFieldOptions,
+) -> bool {
+    if canvas.client_width() > 0 && canvas.client_height() > 0 {
+        return false;
+    }
+    let canvas_clone = canvas.clone();
+    let canvas_id = canvas_id.to_string();
+    let options = options.clone();
+    let run = RUN.with(Cell::get);
+    let on_resize = Closure::wrap(Box::new(move |_entries: JsValue, observer: ResizeObserver| {
+        if RUN.with(Cell::get) != run {
+            observer.disconnect();
+        } else if canvas_clone.client_width() > 0 && canvas_clone.client_height() > 0 {
+            observer.disconnect();
+            run_starfield(&canvas_id, num_stars, &options);
+        }
+    }) as Box<dyn FnMut(JsValue, ResizeObserver)>);
+    let Ok(observer) = ResizeObserver::new(on_resize.as_ref().unchecked_ref()) else {
+        return false;
+    };
+    observer.observe(canvas);
+    on_resize.forget();
+    true
+}
+
+pub(super) fn run_starfield(canvas_id: &str, num_stars: usize, options: &StarFieldOptions) {
+    if defer_until_laid_out(&find_canvas(canvas_id), canvas_id, num_stars, options) {
+        log(Level::Info, || format!("Canvas #{} has no size yet; deferring startup", canvas_id));
+        return;
+    }
+    let started = now_ms();
+    let star_field = match StarField::new(canvas_id, num_stars, options) {
+        Ok(star_field) => Rc::new(RefCell::new(star_field)),
+        Err(reason) => {
+            start_fallback(canvas_id, num_stars, options, reason);
+            return;
+        }
+    };
+    log(Level::Info, || format!("Starfield initialized in {:.1}ms", now_ms() - started));
+    star_field.borrow().log_buffer_sizes(Level::Info);
+    FALLBACK.with(|fallback| *fallback.borrow_mut() = None);
+    STAR_FIELD.with(|cell| *cell.borrow_mut() = Some(star_field.clone()));
+    install_color_scheme_listener();
+    let run = RUN.with(Cell::get);
+
+    // Manual hosts drive the frames but still get resize handling.
+    {
+        // Window resizes fire continuously while dragging; only rebuild once
+        // the size has settled.
+        let star_field_clone = star_field.clone();
+        let settled = Closure::wrap(Box::new(move || {
+            if RUN.with(Cell::get) == run {
+                star_field_clone.borrow_mut().resize();
+            }
+        }) as Box<dyn FnMut()>);
+        let pending: Rc<Cell<Option<i32>>> = Rc::new(Cell::new(None));
+        let timeout = pending.clone();
+        let resize_closure = Closure::wrap(Box::new(move || {
+            let window = window().unwrap();
+            if let Some(handle) = timeout.take() {
+                window.clear_timeout_with_handle(handle);
+            }
+            let handle = window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    settled.as_ref().unchecked_ref(),
+                    RESIZE_DEBOUNCE_MS,
+                )
+                .unwrap();
+            timeout.set(Some(handle));
+        }) as Box<dyn FnMut()>);
+        ResizeListener::install(resize_closure, pending);
+    }
+    if options.manual {
+        return;
+    }
+
+    let f: FrameCallback = Rc::new(RefCell::new(None));
+    let g = f.clone();
+    // The loop stops rescheduling itself while the canvas is scrolled out of
+    // view; the viewport observer restarts it.
+    let in_view = Rc::new(Cell::new(true));
+    let scheduled = Rc::new(Cell::new(true));
+
+    let mut first_frame = true;
+    let frame_in_view = in_view.clone();
+    let frame_scheduled = scheduled.clone();
+    *g.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+        if RUN.with(Cell::get) != run {
+            log(Level::Debug, || "Starfield stopped; animation loop ended".to_string());
+            return;
+        }
+        if !frame_in_view.get() {
+            frame_scheduled.set(false);
+            log(Level::Debug, || "Canvas left the viewport; animation paused".to_string());
+            return;
+        }
+        if first_frame {
+            first_frame = false;
+            log(Level::Info, || format!("First frame {:.1}ms after startup", now_ms() - started));
+        }
+        let gl_error = {
+            let mut sf = star_field.borrow_mut();
+            if sf.check_visibility() {
+                sf.update(1.0);
+                sf.draw();
+            }
+            sf.take_gl_error()
+        };
+        report_gl_error(gl_error);
+        window().unwrap()
+            .request_animation_frame(f.borrow().as_ref().unwrap().as_ref().unchecked_ref())
+            .unwrap();
+    }) as Box<dyn FnMut()>));
+
+    window().unwrap()
+        .request_animation_frame(g.borrow().as_ref().unwrap().as_ref().unchecked_ref())
+        .unwrap();
+
+    let canvas = find_canvas(canvas_id);
+    observe_viewport(&canvas, move |visible| {
+        in_view.set(visible);
+        if visible && !scheduled.get() {
+            scheduled.set(true);
+            log(Level::Debug, || "Canvas entered the viewport; animation resumed".to_string());
+            window().unwrap()
+                .request_animation_frame(g.borrow().as_ref().unwrap().as_ref().unchecked_ref())
+                .unwrap();
+        }
+    });
+}
+
+// Calls `on_change` whenever the canvas enters or leaves the viewport. Where
+// IntersectionObserver is missing the canvas is treated as always in view.
+fn observe_viewport(canvas: &HtmlCanvasElement, mut on_change: impl FnMut(bool) + 'static) {
+    let on_intersect = Closure::wrap(Box::new(move |entries: js_sys::Array| {
+        // Entries arrive oldest first; only the latest state matters.
+        if let Some(entry) = entries.iter().last() {
+            on_change(entry.unchecked_into::<IntersectionObserverEntry>().is_intersecting());
+        }
+    }) as Box<dyn FnMut(js_sys::Array)>);
+    let Ok(observer) = IntersectionObserver::new(on_intersect.as_ref().unchecked_ref()) else {
+        return;
+    };
+    observer.observe(canvas);
+    on_intersect.forget();
+    VIEWPORT_OBSERVER.with(|cell| *cell.borrow_mut() = Some(observer));
+}
+
+/// Caps how many objects the sky may hold so buffers stay bounded however
+/// long the page runs: `options` may set `maxStars` (up to 100000),
+/// `maxMeteors` and `maxParticles` (effect particles); omitted caps keep
+/// their current value. Spawns beyond a cap are skipped; lowering a cap
+/// drops the excess immediately.
+#[wasm_bindgen]
+pub fn set_particle_limits(options: JsValue) -> Result<(), JsValue> {
+    let limit = |key: &str, max: usize| -> Result<Option<usize>, JsValue> {
+        Ok(number_option(&options, key, 0.0, max as f32)?.map(|n| n as usize))
+    };
+    let stars = limit("maxStars", MAX_STARS)?;
+    let meteors = limit("maxMeteors", MAX_METEORS)?;
+    let particles = limit("maxParticles", MAX_PARTICLES)?;
+    with_star_field(|sf| {
+        sf.set_limits(Limits {
+            stars: stars.unwrap_or(sf.limits.stars),
+            meteors: meteors.unwrap_or(sf.limits.meteors),
+            particles: particles.unwrap_or(sf.limits.particles),
+        })
+    });
+    Ok(())
+}
+
+/// Changes how many stars the sky holds, up to 100000 and the
+/// `maxStars` limit, scaled to the canvas's area as on resize. Stars fade
+/// in or out over the transition set with `set_transition`.
+#[wasm_bindgen]
+pub fn set_star_count(count: usize) -> Result<(), JsValue> {
+    if count > MAX_STARS {
+        let expected = format!("a star count from 0 to {}", MAX_STARS);
+        return Err(invalid_option("count", &expected, &(count as f64).into()));
+    }
+    with_star_field(|sf| sf.set_star_count(count));
+    Ok(())
+}
+
+/// Advances the simulation by `dt_ms` milliseconds without drawing, for
+/// hosts running their own loop (`manual` mode; see also `tick`) or
+/// stepping the sky deterministically in tests, which also needs the
+/// `seed` start option. Meteor schedules, shower calendars, contests and
+/// observer charts still follow the wall clock.
+#[wasm_bindgen]
+pub fn step(dt_ms: f64) -> Result<(), JsValue> {
+    if !(dt_ms.is_finite() && dt_ms >= 0.0) {
+        return Err(invalid_option("dtMs", "a non-negative number of milliseconds", &dt_ms.into()));
+    }
+    with_star_field(|sf| sf.update((dt_ms / FRAME_MS) as f32));
+    Ok(())
+}
+
+/// Steps and draws the sky in one call from a host's own animation loop:
+/// pass the `requestAnimationFrame` timestamp, or `performance.now()`, and
+/// the sky advances by the time since the last tick. Start with `manual`
+/// so the built-in loop does not run as well; the sky then stays frame-locked
+/// with whatever else the host draws.
+#[wasm_bindgen]
+pub fn tick(timestamp: f64) -> Result<(), JsValue> {
+    if !timestamp.is_finite() {
+        return Err(invalid_option("timestamp", "a number of milliseconds", &timestamp.into()));
+    }
+    let mut gl_error = None;
+    with_star_field(|sf| {
+        sf.tick(timestamp);
+        gl_error = sf.take_gl_error();
+    });
+    report_gl_error(gl_error);
+    Ok(())
+}
+
+/// Draws the current state of the sky once.
+#[wasm_bindgen]
+pub fn render() {
+    let mut gl_error = None;
+    with_star_field(|sf| {
+        if sf.check_visibility() {
+            sf.draw();
+        }
+        gl_error = sf.take_gl_error();
+    });
+    report_gl_error(gl_error);
+}
+
+/// Renders the current sky once and returns it as a PNG `Blob` at the
+/// canvas's pixel size, e.g. for a "save image" button. A transparent
+/// starfield keeps its transparency. Fails if the starfield is not running.
+#[wasm_bindgen]
+pub fn capture_png() -> Result<Blob, JsValue> {
+    let mut frame = None;
+    let mut gl_error = None;
+    with_star_field(|sf| {
+        frame = Some(sf.capture());
+        gl_error = sf.take_gl_error();
+    });
+    report_gl_error(gl_error);
+    let (width, height, pixels) = frame.ok_or_else(|| JsValue::from_str("The starfield is not running"))?;
+    let encoded = encode_png(width, height, pixels)?;
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(encoded.as_slice()));
+    let options = BlobPropertyBag::new();
+    options.set_type("image/png");
+    Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+}
+
+/// Renders the current sky, paused, at `width`×`height` pixels (e.g.
+/// 3840×2160) regardless of the canvas's size, and returns PNG bytes.
+/// Sizes are limited by the GPU's maximum texture size.
+#[wasm_bindgen]
+pub fn render_poster(width: u32, height: u32) -> Result<Vec<u8>, JsValue> {
+    for (field, value) in [("width", width), ("height", height)] {
+        if value == 0 || value > MAX_POSTER_SIZE {
+            let expected = format!("a pixel size from 1 to {}", MAX_POSTER_SIZE);
+            return Err(invalid_option(field, &expected, &value.into()));
+        }
+    }
+    let mut frame = None;
+    let mut gl_error = None;
+    with_star_field(|sf| {
+        frame = Some(sf.render_poster(width, height));
+        gl_error = sf.take_gl_error();
+    });
+    report_gl_error(gl_error);
+    let pixels = frame
+        .ok_or_else(|| JsValue::from_str("The starfield is not running"))?
+        .map_err(|e| JsValue::from_str(&e))?;
+    encode_png(width, height, pixels)
+}
+
+fn encode_png(width: u32, height: u32, pixels: Vec<u8>) -> Result<Vec<u8>, JsValue> {
+    let image = image::RgbaImage::from_raw(width, height, pixels)
+        .ok_or_else(|| JsValue::from_str("Captured frame has the wrong size"))?;
+    let mut encoded = std::io::Cursor::new(Vec::new());
+    image
+        .write_to(&mut encoded, image::ImageFormat::Png)
+        .map_err(|e| JsValue::from_str(&format!("Failed to encode PNG: {}", e)))?;
+    Ok(encoded.into_inner())
+}
+
+/// Returns the current sky as an SVG document: the sky gradient (unless
+/// transparent) and every star with its position, size, color and
+/// brightness, for print or marketing assets. Fails if the starfield is not
+/// running.
+#[wasm_bindgen]
+pub fn export_svg() -> Result<String, JsValue> {
+    let mut svg = None;
+    with_star_field(|sf| svg = Some(sf.to_svg()));
+    svg.ok_or_else(|| JsValue::from_str("The starfield is not running"))
+}
+
+/// Configures where meteors come from and how they fly. `options` may set
+/// `angleMin` / `angleMax` (degrees clockwise from rightward, between 0 and
+/// 180 so meteors fall), `speedMin` / `speedMax` (CSS pixels per frame) and
+/// `edges`, an array of `"top"` and `"side"` to enter from. Meteors spawn
+/// just off-screen, so they never pop into existence mid-canvas.
+#[wasm_bindgen]
+pub fn set_meteor_trajectory(options: JsValue) -> Result<(), JsValue> {
+    let default = MeteorTrajectory::DEFAULT;
+    let degrees = |key: &str, fallback: f32| -> Result<f32, JsValue> {
+        Ok(number_option(&options, key, 1.0, 179.0)?.map_or(fallback, f32::to_radians))
+    };
+    let angle_range = (degrees("angleMin", default.angle_range.0)?, degrees("angleMax", default.angle_range.1)?);
+    let speed_range = (
+        number_option(&options, "speedMin", 0.1, METEOR_MAX_SPEED)?.unwrap_or(default.speed_range.0),
+        number_option(&options, "speedMax", 0.1, METEOR_MAX_SPEED)?.unwrap_or(default.speed_range.1),
+    );
+    if angle_range.0 > angle_range.1 {
+        let value = get_option(&options, "angleMax").unwrap_or(JsValue::UNDEFINED);
+        return Err(invalid_option("angleMax", "a number no smaller than angleMin", &value));
+    }
+    if speed_range.0 > speed_range.1 {
+        let value = get_option(&options, "speedMax").unwrap_or(JsValue::UNDEFINED);
+        return Err(invalid_option("speedMax", "a number no smaller than speedMin", &value));
+    }
+    let (mut from_top, mut from_side) = (default.from_top, default.from_side);
+    if let Some(edges) = get_option(&options, "edges") {
+        const EDGES: &str = "an array of \"top\" and \"side\"";
+        if !js_sys::Array::is_array(&edges) {
+            return Err(invalid_option("edges", EDGES, &edges));
+        }
+        from_top = false;
+        from_side = false;
+        for edge in js_sys::Array::from(&edges).iter() {
+            match edge.as_string().as_deref() {
+                Some("top") => from_top = true,
+                Some("side") => from_side = true,
+                _ => return Err(invalid_option("edges", EDGES, &edge)),
+            }
+        }
+    }
+    let trajectory = MeteorTrajectory { angle_range, speed_range, from_top, from_side };
+    with_star_field(|sf| sf.meteors.trajectory = trajectory);
+    Ok(())
+}
+
+const MAX_METEOR_SCHEDULE_WEIGHT: f32 = 50.0;
+
+/// Scales how often meteors spawn by the local time of day, easing between
+/// hours. `schedule` is 24 hourly weights from midnight, or an array of
+/// `{ from, to, weight }` hour ranges (`to` exclusive, wrapping past
+/// midnight, e.g. `{ from: 22, to: 2 }`) with other hours at 1. A weight of
+/// 0 stops meteors, 2 doubles them. `null` restores the constant rate.
+#[wasm_bindgen]
+pub fn set_meteor_schedule(schedule: JsValue) -> Result<(), JsValue> {
+    let schedule = if schedule.is_undefined() || schedule.is_null() {
+        None
+    } else {
+        Some(meteor_schedule_from_js(&schedule)?)
+    };
+    with_star_field(|sf| sf.meteors.schedule = schedule);
+    Ok(())
+}
+
+fn meteor_schedule_from_js(schedule: &JsValue) -> Result<MeteorSchedule, JsValue> {
+    const EXPECTED: &str = "an array of 24 hourly weights or of { from, to, weight } ranges";
+    if !js_sys::Array::is_array(schedule) {
+        return Err(invalid_option("schedule", EXPECTED, schedule));
+    }
+    let entries = js_sys::Array::from(schedule);
+    let mut hourly = [1.0; 24];
+    if entries.iter().all(|entry| entry.as_f64().is_some()) {
+        if entries.length() != 24 {
+            return Err(invalid_option("schedule", EXPECTED, schedule));
+        }
+        for (hour, entry) in entries.iter().enumerate() {
+            let weight = entry.as_f64().unwrap() as f32;
+            if !(0.0..=MAX_METEOR_SCHEDULE_WEIGHT).contains(&weight) {
+                let expected = format!("weights from 0 to {}", MAX_METEOR_SCHEDULE_WEIGHT);
+                return Err(invalid_option("schedule", &expected, &entry));
+            }
+            hourly[hour] = weight;
+        }
+    } else {
+        for entry in entries.iter() {
+            if !entry.is_object() || js_sys::Array::is_array(&entry) {
+                return Err(invalid_option("schedule", EXPECTED, &entry));
+            }
+            let required = |key: &str, max: f32| -> Result<f32, JsValue> {
+                number_option(&entry, key, 0.0, max)?
+                    .ok_or_else(|| invalid_option(key, &format!("a number from 0 to {}", max), &JsValue::UNDEFINED))
+            };
+            let from = required("from", 24.0)? as usize % 24;
+            let to = required("to", 24.0)? as usize % 24;
+            let weight = required("weight", MAX_METEOR_SCHEDULE_WEIGHT)?;
+            let hours = if from < to { to - from } else { to + 24 - from };
+            for hour in (from..from + hours).map(|hour| hour % 24) {
+                hourly[hour] = weight;
+            }
+        }
+    }
+    MeteorSchedule::new(hourly).ok_or_else(|| invalid_option("schedule", EXPECTED, schedule))
+}
+
+/// Follows the real meteor-shower calendar: around the peaks of the major
+/// yearly showers (Quadrantids, Lyrids, Eta Aquariids, Delta Aquariids,
+/// Perseids, Draconids, Orionids, Leonids, Geminids and Ursids), by the
+/// user's local date, meteors spawn up to 16 times as often and fly from
+/// the shower's radiant instead of `set_meteor_trajectory`'s directions.
+/// Stacks with `set_meteor_schedule`. Off by default.
+#[wasm_bindgen]
+pub fn set_meteor_shower_calendar(enabled: bool) {
+    with_star_field(|sf| sf.meteors.calendar = enabled);
+}
+
+/// The name of the shower meteors are flying from, like `"Perseids"`, or
+/// `undefined` with the calendar off, between showers or no starfield
+/// running.
+#[wasm_bindgen]
+pub fn active_meteor_shower() -> Option<String> {
+    let mut name = None;
+    with_star_field(|sf| name = sf.meteors.active_shower().map(str::to_string));
+    name
+}
+
+/// Colors new meteors with `{ head, tail }` hex colors, their trail fading
+/// from one to the other; a color left out is unchanged. Meteors already in
+/// flight keep theirs.
+#[wasm_bindgen]
+pub fn set_meteor_colors(colors: JsValue) -> Result<(), JsValue> {
+    if !colors.is_object() {
+        return Err(invalid_option("colors", "an object with head and tail colors", &colors));
+    }
+    let head = color_option(&colors, "head")?;
+    let tail = color_option(&colors, "tail")?;
+    with_star_field(|sf| {
+        let colors = &mut sf.meteors.colors;
+        colors.head = head.unwrap_or(colors.head);
+        colors.tail = tail.unwrap_or(colors.tail);
+    });
+    Ok(())
+}
+
+fn layer_from_js(name: &str) -> Result<Layer, JsValue> {
+    Layer::from_name(name).ok_or_else(|| invalid_option("layer", Layer::NAMES, &name.into()))
+}
+
+/// Sets how a layer blends over what is below it: `"alpha"` for standard
+/// transparency or `"additive"` for glow. Nebula, meteors and foreground
+/// (`"particles"` still works) default to additive, the rest to alpha.
+#[wasm_bindgen]
+pub fn set_blend_mode(layer: &str, mode: &str) -> Result<(), JsValue> {
+    let layer = layer_from_js(layer)?;
+    let mode = BlendMode::from_name(mode)
+        .ok_or_else(|| invalid_option("mode", "\"alpha\" or \"additive\"", &mode.into()))?;
+    with_star_field(|sf| sf.layers.get_mut(layer).blend = mode);
+    Ok(())
+}
+
+/// Shows or hides a layer (`"background"`, `"nebula"`, `"stars"`,
+/// `"meteors"`, `"foreground"` or `"overlay"`). Hidden layers keep
+/// animating, so showing one again does not jump.
+#[wasm_bindgen]
+pub fn set_layer_enabled(layer: &str, enabled: bool) -> Result<(), JsValue> {
+    let layer = layer_from_js(layer)?;
+    with_star_field(|sf| sf.layers.get_mut(layer).enabled = enabled);
+    Ok(())
+}
+
+/// Sets a layer's opacity, from 0 (hidden) to 1 (the default), easing
+/// there over the transition set with `set_transition`.
+#[wasm_bindgen]
+pub fn set_layer_opacity(layer: &str, opacity: f32) -> Result<(), JsValue> {
+    let layer = layer_from_js(layer)?;
+    if !(0.0..=1.0).contains(&opacity) {
+        return Err(invalid_option("opacity", "a number from 0 to 1", &opacity.into()));
+    }
+    with_star_field(|sf| {
+        let transition = sf.transition;
+        sf.layers.get_mut(layer).opacity.retarget(opacity, transition);
+    });
+    Ok(())
+}
+
+/// Sets the draw order from an array naming every layer exactly once,
+/// bottom first, e.g. `["background", "stars", "nebula", "meteors",
+/// "foreground", "overlay"]`.
+#[wasm_bindgen]
+pub fn set_layer_order(order: JsValue) -> Result<(), JsValue> {
+    let expected = "an array naming every layer once";
+    if !js_sys::Array::is_array(&order) {
+        return Err(invalid_option("order", expected, &order));
+    }
+    let names = js_sys::Array::from(&order);
+    let mut layers = Vec::new();
+    for name in names.iter() {
+        let name = name.as_string().ok_or_else(|| invalid_option("order", expected, &order))?;
+        let layer = layer_from_js(&name)?;
+        if layers.contains(&layer) {
+            return Err(invalid_option("order", expected, &order));
+        }
+        layers.push(layer);
+    }
+    let order: [Layer; 6] = layers.try_into().map_err(|_| invalid_option("order", expected, &order))?;
+    with_star_field(|sf| sf.layers.order = order);
+    Ok(())
+}
+
+/// Splices a GLSL snippet into the `"background"` or `"stars"` fragment
+/// shader. The snippet must define `vec4 custom_color(vec4 color)`, which
+/// receives and returns premultiplied color and may read `gl_FragCoord`
+/// (and `gl_PointCoord` for stars), e.g. for a tint or vignette. Pass
+/// `null` to restore the default. Throws with the compiler's log, line
+/// numbers counted from the snippet, if it does not compile; the previous
+/// shader keeps drawing.
+#[wasm_bindgen]
+pub fn set_custom_shader(layer: &str, snippet: Option<String>) -> Result<(), JsValue> {
+    let target = layer_from_js(layer)?;
+    if !matches!(target, Layer::Background | Layer::Stars) {
+        return Err(invalid_option("layer", "\"background\" or \"stars\"", &layer.into()));
+    }
+    let mut result = None;
+    with_star_field(|sf| result = Some(sf.set_custom_shader(target, snippet.as_deref())));
+    result
+        .ok_or_else(|| JsValue::from_str("The starfield is not running"))?
+        .map_err(|e| JsError::new(&e).into())
+}
+
+fn dark_scheme_query() -> Option<MediaQueryList> {
+    window().unwrap().match_media("(prefers-color-scheme: dark)").ok().flatten()
+}
+
+// Browsers without the media query get the night sky.
+pub(super) fn prefers_dark_scheme() -> bool {
+    dark_scheme_query().is_none_or(|query| query.matches())
+}
+
+// The device pixel ratio to render at; data saver keeps to one pixel per CSS
+// pixel, sparing fill rate and buffer memory on dense screens.
+pub(super) fn pixel_ratio(data_saver: bool) -> f32 {
+    let dpr = window().unwrap().device_pixel_ratio() as f32;
+    if data_saver {
+        dpr.min(1.0)
+    } else {
+        dpr
+    }
+}
+
+// The Save-Data hint, where the browser has the Network Information API.
+pub(super) fn save_data_requested() -> bool {
+    js_sys::Reflect::get(&window().unwrap(), &"navigator".into())
+        .and_then(|navigator| js_sys::Reflect::get(&navigator, &"connection".into()))
+        .ok()
+        .filter(JsValue::is_object)
+        .and_then(|connection| js_sys::Reflect::get(&connection, &"saveData".into()).ok())
+        .and_then(|save_data| save_data.as_bool())
+        .unwrap_or(false)
+}
+
+fn install_color_scheme_listener() {
+    if COLOR_SCHEME_LISTENER.with(|installed| installed.replace(true)) {
+        return;
+    }
+    let Some(query) = dark_scheme_query() else {
+        return;
+    };
+    let on_change = Closure::wrap(Box::new(move |event: MediaQueryListEvent| {
+        with_star_field(|sf| sf.set_color_scheme(sf.color_scheme, event.matches()));
+    }) as Box<dyn FnMut(MediaQueryListEvent)>);
+    query
+        .add_event_listener_with_callback("change", on_change.as_ref().unchecked_ref())
+        .unwrap();
+    on_change.forget();
+}
+
+/// Switches the sky between the night preset (`"dark"`) and a pale daytime
+/// haze with faint stars (`"light"`), crossfading like theme tints.
+/// `"auto"`, the default, follows the OS `prefers-color-scheme` setting and
+/// switches when it changes; the other two force a scheme for sites with
+/// their own theme toggle.
+#[wasm_bindgen]
+pub fn set_color_scheme(scheme: &str) -> Result<(), JsValue> {
+    let scheme = ColorScheme::from_js(Some(scheme.into()))?;
+    with_star_field(|sf| sf.set_color_scheme(scheme, sf.prefers_dark));
+    Ok(())
+}
+
+// Leaving fullscreen by any route (Esc, browser UI, `exit_wallpaper_mode`)
+// ends wallpaper mode. Entering resizes right away rather than after the
+// window resize listener's debounce.
+fn install_fullscreen_listener() {
+    if FULLSCREEN_LISTENER.with(|installed| installed.replace(true)) {
+        return;
+    }
+    let document = window().unwrap().document().unwrap();
+    let on_change = Closure::wrap(Box::new(|| {
+        let document = window().unwrap().document().unwrap();
+        if document.fullscreen_element().is_none() {
+            with_star_field(|sf| sf.exit_wallpaper());
+        } else {
+            with_star_field(|sf| sf.resize());
+        }
+    }) as Box<dyn FnMut()>);
+    document
+        .add_event_listener_with_callback("fullscreenchange", on_change.as_ref().unchecked_ref())
+        .unwrap();
+    on_change.forget();
+}
+
+// The canvas usually sits behind the page with `pointer-events: none`, so
+// the pointer is tracked on the whole document.
+fn install_pointer_listener() {
+    if POINTER_LISTENER.with(|installed| installed.replace(true)) {
+        return;
+    }
+    let document = window().unwrap().document().unwrap().document_element().unwrap();
+    let on_move = Closure::wrap(Box::new(|event: MouseEvent| {
+        with_star_field(|sf| {
+            let rect = sf.canvas.get_bounding_client_rect();
+            let x = event.client_x() as f64 - rect.left();
+            let y = event.client_y() as f64 - rect.top();
+            sf.set_pointer(Some((x as f32, y as f32)));
+        });
+    }) as Box<dyn FnMut(MouseEvent)>);
+    let on_leave = Closure::wrap(Box::new(|| with_star_field(|sf| sf.set_pointer(None))) as Box<dyn FnMut()>);
+    document
+        .add_event_listener_with_callback("pointermove", on_move.as_ref().unchecked_ref())
+        .unwrap();
+    document
+        .add_event_listener_with_callback("pointerleave", on_leave.as_ref().unchecked_ref())
+        .unwrap();
+    on_move.forget();
+    on_leave.forget();
+}
+
+/// Draws faint lines between stars near the pointer (a "plexus"). `options`
+/// is `false` to turn it off, `true` for the defaults, or an object with
+/// `radius` (CSS pixels around the pointer), `distance` (how close two stars
+/// must be, in CSS pixels), `color` (hex) and `alpha` (of the strongest
+/// line).
+#[wasm_bindgen]
+pub fn set_plexus(options: JsValue) -> Result<(), JsValue> {
+    let style = match options.as_bool() {
+        Some(false) => None,
+        Some(true) => Some(PlexusStyle::DEFAULT),
+        None if options.is_object() => {
+            let default = PlexusStyle::DEFAULT;
+            Some(PlexusStyle {
+                radius: number_option(&options, "radius", 0.0, MAX_PLEXUS_DISTANCE)?.unwrap_or(default.radius),
+                link_distance: number_option(&options, "distance", 0.0, MAX_PLEXUS_DISTANCE)?
+                    .unwrap_or(default.link_distance),
+                color: color_option(&options, "color")?.unwrap_or(default.color),
+                alpha: number_option(&options, "alpha", 0.0, 1.0)?.unwrap_or(default.alpha),
+            })
+        }
+        None => return Err(invalid_option("options", "true, false or an object", &options)),
+    };
+    let mut result = Err(JsValue::from_str("The starfield is not running"));
+    with_star_field(|sf| result = sf.set_plexus(style).map_err(|error| JsValue::from_str(&error)));
+    if result.is_ok() && style.is_some() {
+        install_pointer_listener();
+    }
+    result
+}
+
+/// Makes the stars drift along slowly evolving, swirling currents (a curl
+/// noise flow field) instead of each on its own random course. `options` is
+/// `false` to go back, `true` for the defaults, or an object with `scale`
+/// (rough width of a swirl in CSS pixels, default 400), `speed` (CSS pixels
+/// per frame, default 0.1) and `evolution` (how fast the currents change
+/// shape, default 0.02 per second).
+#[wasm_bindgen]
+pub fn set_flow_field(options: JsValue) -> Result<(), JsValue> {
+    let flow = match options.as_bool() {
+        Some(false) => None,
+        Some(true) => Some(FlowField::DEFAULT),
+        None if options.is_object() => {
+            let default = FlowField::DEFAULT;
+            Some(FlowField {
+                scale: number_option(&options, "scale", 1.0, MAX_FLOW_SCALE)?.unwrap_or(default.scale),
+                speed: number_option(&options, "speed", 0.0, MAX_FLOW_SPEED)?.unwrap_or(default.speed),
+                evolution: number_option(&options, "evolution", 0.0, 10.0)?.unwrap_or(default.evolution),
+            })
+        }
+        None => return Err(invalid_option("options", "true, false or an object", &options)),
+    };
+    with_star_field(|sf| sf.set_flow_field(flow));
+    Ok(())
+}
+
+/// Pushes the whole sky gently in one direction, e.g. to match a hero
+/// image's motion: `vx` and `vy` in CSS pixels per second, added to each
+/// star's own drift, up to 600 either way. The wind eases into new
+/// settings over about a second; `set_wind(0, 0)` lets it die down.
+#[wasm_bindgen]
+pub fn set_wind(vx: f32, vy: f32) -> Result<(), JsValue> {
+    for (key, value) in [("vx", vx), ("vy", vy)] {
+        if !(-MAX_WIND_SPEED..=MAX_WIND_SPEED).contains(&value) {
+            return Err(invalid_option(key, "a number from -600 to 600", &value.into()));
+        }
+    }
+    with_star_field(|sf| sf.set_wind(vx / 60.0, vy / 60.0));
+    Ok(())
+}
+
+/// Dials the sky down behind dense content, or back up on a landing hero:
+/// `intensity` from 0 to 1 scales the brightness of the stars, meteors and
+/// effects and how often meteors and emitters spawn, fading over a second
+/// or two. The background and overlays are left alone.
+#[wasm_bindgen]
+pub fn set_intensity(intensity: f32) -> Result<(), JsValue> {
+    if !(0.0..=1.0).contains(&intensity) {
+        return Err(invalid_option("intensity", "a number from 0 to 1", &intensity.into()));
+    }
+    with_star_field(|sf| sf.set_intensity(intensity));
+    Ok(())
+}
+
+/// Sets how later changes to the sky palette, corner gradient, star count
+/// and layer opacities animate: over `duration_ms` milliseconds (up to
+/// 60000; 0 snaps) along `easing`, one of `"linear"`, `"ease-in"`,
+/// `"ease-out"` or `"ease-in-out"` (the default). Starts at 1500ms.
+#[wasm_bindgen]
+pub fn set_transition(duration_ms: f32, easing: Option<String>) -> Result<(), JsValue> {
+    if !(0.0..=MAX_TRANSITION_MS).contains(&duration_ms) {
+        return Err(invalid_option("durationMs", "a number of milliseconds from 0 to 60000", &duration_ms.into()));
+    }
+    let easing = match easing {
+        Some(name) => Easing::from_name(&name).ok_or_else(|| invalid_option("easing", Easing::NAMES, &name.into()))?,
+        None => Easing::default(),
+    };
+    with_star_field(|sf| sf.set_transition(Transition { seconds: duration_ms / 1000.0, easing }));
+    Ok(())
+}
+
+/// Twinkles stars along sine waves (`"sine"`, the default) or smooth
+/// per-star noise (`"noise"`), which avoids the synchronized "breathing"
+/// of many stars sharing a phase.
+#[wasm_bindgen]
+pub fn set_twinkle_mode(mode: &str) -> Result<(), JsValue> {
+    let mode = twinkle_mode_from_name(mode).ok_or_else(|| invalid_option("mode", TWINKLE_MODES, &mode.into()))?;
+    with_star_field(|sf| sf.set_twinkle_mode(mode));
+    Ok(())
+}
+
+/// Adds `effect` to `layer` of the running starfield, above the layer's
+/// built-in content, and returns an id for `remove_effect`. For Rust
+/// callers; see `Effect`.
+pub fn register_effect(layer: Layer, effect: impl Effect + 'static) -> Result<u32, JsValue> {
+    let mut id = None;
+    with_star_field(|sf| id = Some(sf.register_effect(layer, Box::new(effect))));
+    id.ok_or_else(|| JsValue::from_str("The starfield is not running"))?.map_err(|e| JsValue::from_str(&e))
+}
+
+/// Removes an effect added with `register_effect`. Returns whether it was
+/// still registered.
+pub fn remove_effect(id: u32) -> bool {
+    let mut removed = false;
+    with_star_field(|sf| removed = sf.remove_effect(id));
+    removed
+}
+
+/// Starts an ambient particle effect described by `descriptor`, an object
+/// or a JSON string of one, and returns an id for `remove_emitter`. Fields,
+/// all optional:
+/// - `shape`: `"point"` (default), `"rect"` or `"circle"`, centered at `x`,
+///   `y` (fractions of the canvas, default 0.5), sized by `width`/`height`
+///   or `radius` (fractions of the canvas and of its shorter side).
+/// - `rate` per second (default 10), capped at `maxParticles` alive (1000).
+/// - `lifetime` in seconds, `speed` in CSS pixels per second and
+///   `direction` in degrees clockwise from rightward: a number or a
+///   `[min, max]` range picked from per particle.
+/// - `gravity` in CSS pixels per second squared, downward.
+/// - `size` (CSS pixels), `alpha`, `color` (hex) and `velocity` (a
+///   multiplier on the launch speed): a value or an array of keyframes
+///   spread evenly over each particle's life.
+/// - `layer` to draw in, default `"foreground"`.
+#[wasm_bindgen]
+pub fn create_emitter(descriptor: JsValue) -> Result<u32, JsValue> {
+    let descriptor = EmitterDescriptor::from_js(&descriptor)?;
+    let layer = descriptor.layer;
+    let mut id = None;
+    with_star_field(|sf| {
+        let emitter = Emitter::new(&sf.gl, sf.star_program.clone(), descriptor, Rng::new(sf.rng.next_u64()));
+        id = Some(emitter.and_then(|emitter| sf.register_effect(layer, Box::new(emitter))));
+    });
+    id.ok_or_else(|| JsValue::from_str("The starfield is not running"))?.map_err(|e| JsValue::from_str(&e))
+}
+
+/// Stops an emitter and drops its particles. Returns whether it was still
+/// running.
+#[wasm_bindgen]
+pub fn remove_emitter(id: u32) -> bool {
+    remove_effect(id)
+}
+
+/// Adds a ring of tiny drifting specks, like orbital debris or an asteroid
+/// belt, drawn dimly behind the stars, and returns an id for
+/// `remove_debris_ring`. `options`, all optional: `x` and `y` (center, as
+/// fractions of the canvas), `radius` (a fraction of its width), `flatten`
+/// (height over width; 1 is a circle), `rotation`, `arcStart` and `arcEnd`
+/// (degrees clockwise; specks wrap around a partial arc), `thickness` (a
+/// fraction of the radius), `density` (speck count), `speed` (degrees per
+/// second), `size` (CSS pixels), `alpha`, `color` (hex) and `layer`
+/// (default `"nebula"`).
+#[wasm_bindgen]
+pub fn create_debris_ring(options: JsValue) -> Result<u32, JsValue> {
+    let descriptor = DebrisDescriptor::from_js(&options)?;
+    let layer = descriptor.layer;
+    let mut id = None;
+    with_star_field(|sf| {
+        let debris = Debris::new(&sf.gl, sf.star_program.clone(), descriptor, &mut sf.rng);
+        id = Some(debris.and_then(|debris| sf.register_effect(layer, Box::new(debris))));
+    });
+    id.ok_or_else(|| JsValue::from_str("The starfield is not running"))?.map_err(|e| JsValue::from_str(&e))
+}
+
+/// Removes a debris ring. Returns whether it was still shown.
+#[wasm_bindgen]
+pub fn remove_debris_ring(id: u32) -> bool {
+    remove_effect(id)
+}
+
+/// Adds a sparse layer of big, very soft dust motes drifting in front of
+/// the stars, as if out of focus, and returns an id for
+/// `remove_bokeh_dust`. `options`, all optional: `density` (mote count,
+/// default 24), `sizeMin` and `sizeMax` (diameters in CSS pixels, default 24
+/// and 72; bigger motes are nearer and drift faster), `driftX` and `driftY`
+/// (CSS pixels per second of the nearest motes), `alpha` (default 0.08),
+/// `color` (hex) and `layer` (default `"foreground"`).
+#[wasm_bindgen]
+pub fn create_bokeh_dust(options: JsValue) -> Result<u32, JsValue> {
+    let descriptor = BokehDescriptor::from_js(&options)?;
+    let layer = descriptor.layer;
+    let mut id = None;
+    with_star_field(|sf| {
+        let bokeh = Bokeh::new(&sf.gl, descriptor, &mut sf.rng);
+        id = Some(bokeh.and_then(|bokeh| sf.register_effect(layer, Box::new(bokeh))));
+    });
+    id.ok_or_else(|| JsValue::from_str("The starfield is not running"))?.map_err(|e| JsValue::from_str(&e))
+}
+
+/// Removes bokeh dust. Returns whether it was still shown.
+#[wasm_bindgen]
+pub fn remove_bokeh_dust(id: u32) -> bool {
+    remove_effect(id)
+}
+
+/// Draws thousands of faint micro-stars once into a texture and shows it as
+/// a single quad each frame, for visual density at almost no per-frame
+/// cost; pair it with a few hundred animated stars (`start_starfield(id, 300)`
+/// or `set_particle_limits({ maxStars: 300 })`) for an ultra-cheap sky.
+/// Returns an id for `remove_baked_starscape`. `options`, all optional:
+/// `count` (default 4000), `minSize` and `maxSize` (CSS pixels), `minAlpha`
+/// and `maxAlpha`, and `layer` (default `"nebula"`, behind the stars). The
+/// texture is redrawn, with the same stars, when the canvas resizes.
+#[wasm_bindgen]
+pub fn create_baked_starscape(options: JsValue) -> Result<u32, JsValue> {
+    let descriptor = BakedDescriptor::from_js(&options)?;
+    let layer = descriptor.layer;
+    let mut id = None;
+    with_star_field(|sf| {
+        let palette = sf.star_config.palette.clone();
+        let baked = BakedStarscape::new(&sf.gl, sf.star_program.clone(), palette, descriptor, sf.rng.next_u64());
+        id = Some(baked.and_then(|baked| sf.register_effect(layer, Box::new(baked))));
+    });
+    id.ok_or_else(|| JsValue::from_str("The starfield is not running"))?.map_err(|e| JsValue::from_str(&e))
+}
+
+/// Removes a baked starscape. Returns whether it was still shown.
+#[wasm_bindgen]
+pub fn remove_baked_starscape(id: u32) -> bool {
+    remove_effect(id)
+}
+
+/// Turns the starfield into a fullscreen ambient screensaver: the canvas
+/// goes fullscreen with a denser sky, the countdown and ticker overlays and
+/// the cursor are hidden, and the animation keeps running. Must be called
+/// from a user gesture. Leaving fullscreen restores the previous settings.
+#[wasm_bindgen]
+pub fn enter_wallpaper_mode() -> Result<(), JsValue> {
+    let canvas = star_field_canvas().ok_or_else(|| JsValue::from_str("The starfield is not running"))?;
+    install_fullscreen_listener();
+    canvas.request_fullscreen()?;
+    with_star_field(|sf| sf.enter_wallpaper());
+    Ok(())
+}
+
+/// Leaves fullscreen wallpaper mode, restoring the previous settings.
+#[wasm_bindgen]
+pub fn exit_wallpaper_mode() {
+    let document = window().unwrap().document().unwrap();
+    if document.fullscreen_element().is_some() {
+        document.exit_fullscreen();
+    }
+    with_star_field(|sf| sf.exit_wallpaper());
+}
+
+/// Sets how fast the sky's clock runs: 1 is normal, 0 pauses every
+/// animation (frames keep drawing), values between slow it down and up to
+/// 10 fast-forward. The countdown follows the wall clock regardless.
+#[wasm_bindgen]
+pub fn set_time_scale(scale: f32) -> Result<(), JsValue> {
+    if !(0.0..=MAX_TIME_SCALE).contains(&scale) {
+        return Err(invalid_option("scale", "a number from 0 to 10", &scale.into()));
+    }
+    with_star_field(|sf| sf.clock.scale = scale);
+    Ok(())
+}
+
+/// Star, meteor and particle sizes are in CSS pixels by default so they look
+/// the same on high-DPI screens; `raw` switches back to device pixels, which
+/// renders everything thinner on 2x displays.
+#[wasm_bindgen]
+pub fn set_raw_pixel_sizing(raw: bool) {
+    with_star_field(|sf| sf.raw_pixels = raw);
+}
+
+fn start_fallback(canvas_id: &str, num_stars: usize, options: &StarFieldOptions, reason: String) {
+    web_sys::console::warn_1(&JsValue::from_str(&format!("Starfield falling back to a static sky: {}", reason)));
+    let canvas = find_canvas(canvas_id);
+    let renderer = paint_fallback(&canvas, num_stars, options);
+    log(Level::Warn, || format!("Fallback renderer: {}", renderer));
+    let message = format!("WebGL unavailable, using {} fallback: {}", renderer, reason);
+    FALLBACK.with(|fallback| *fallback.borrow_mut() = Some(Fallback { renderer, reason }));
+    STAR_FIELD.with(|cell| *cell.borrow_mut() = None);
+    report("fallback", &message);
+
+    let options = options.clone();
+    let run = RUN.with(Cell::get);
+    let resize_closure = Closure::wrap(Box::new(move || {
+        if RUN.with(Cell::get) == run {
+            paint_fallback(&canvas, num_stars, &options);
+        }
+    }) as Box<dyn FnMut()>);
+    ResizeListener::install(resize_closure, Rc::new(Cell::new(None)));
+}
+
+/// Reports how the starfield is rendering: `{ renderer, fallbackReason,
+/// stars, meteors, particles, width, height }`. `renderer` is `"webgl"`,
+/// or `"canvas2d"` / `"css"` / `"none"` when WebGL failed and a static sky
+/// was drawn instead (with the reason in `fallbackReason`); `"none"` also
+/// means the starfield has not started (yet, if its canvas is hidden).
+#[wasm_bindgen]
+pub fn starfield_stats() -> JsValue {
+    let result = js_sys::Object::new();
+    let set = |key: &str, value: JsValue| {
+        js_sys::Reflect::set(&result, &JsValue::from_str(key), &value).unwrap();
+    };
+    let (renderer, reason) = FALLBACK.with(|fallback| match fallback.borrow().as_ref() {
+        Some(fallback) => (fallback.renderer, JsValue::from_str(&fallback.reason)),
+        None => ("none", JsValue::NULL),
+    });
+    set("renderer", JsValue::from_str(renderer));
+    set("fallbackReason", reason);
+    let (mut stars, mut meteors, mut particles, mut width, mut height) = (0, 0, 0, 0.0, 0.0);
+    with_star_field(|sf| {
+        set("renderer", JsValue::from_str("webgl"));
+        stars = sf.stars.stars.len();
+        meteors = sf.meteors.meteors.len();
+        particles = sf.particles.len();
+        (width, height) = sf.resolution;
+    });
+    set("stars", JsValue::from(stars as u32));
+    set("meteors", JsValue::from(meteors as u32));
+    set("particles", JsValue::from(particles as u32));
+    set("width", JsValue::from(width));
+    set("height", JsValue::from(height));
+    result.into()
+}
+
+/// Crossfades the sky and star palette toward the tint for a judge status
+/// (`pending`, `judging`, `ac`, `wa`, `tle`, `mle`, `re`, `ce`, or `none` to
+/// clear it).
+#[wasm_bindgen]
+pub fn set_status_theme(status: &str) -> Result<(), JsValue> {
+    if ThemeTint::for_status(status, ColorVision::Typical).is_none() {
+        return Err(JsValue::from_str(&format!("Unknown judge status: {}", status)));
+    }
+    with_star_field(|sf| {
+        if let Some(theme) = ThemeTint::for_status(status, sf.color_vision) {
+            sf.set_theme(theme);
+        }
+    });
+    #[cfg(feature = "audio")]
+    play_status_sound(status);
+    Ok(())
+}
+
+/// Blends the sky in linear light and encodes it to sRGB at output, so faint
+/// stars and gradients are not muddied by blending in gamma space; `false`
+/// goes back, for comparison. Overlay text, custom effects' own shaders and
+/// posters stay in gamma space. Fails without `EXT_sRGB` or a half-float
+/// render target.
+#[wasm_bindgen]
+pub fn set_linear_rendering(enabled: bool) -> Result<(), JsValue> {
+    let mut result = Err(JsValue::from_str("The starfield is not running"));
+    with_star_field(|sf| result = sf.set_linear_rendering(enabled).map_err(|error| JsValue::from_str(&error)));
+    result
+}
+
+/// Smears meteor heads and sparks along their motion so fast ones read as
+/// streaks rather than hopping at low frame rates: `strength` 1 covers the
+/// ground moved since the last frame, up to 4; 0 (the default) turns it
+/// off.
+#[wasm_bindgen]
+pub fn set_motion_blur(strength: f32) -> Result<(), JsValue> {
+    if !(0.0..=MAX_MOTION_BLUR).contains(&strength) {
+        return Err(invalid_option("strength", "a number from 0 to 4", &strength.into()));
+    }
+    let mut result = Err(JsValue::from_str("The starfield is not running"));
+    with_star_field(|sf| result = sf.set_motion_blur(strength).map_err(|error| JsValue::from_str(&error)));
+    result
+}
+
+/// Compresses bright accumulations (bloom, supernovae, dense showers)
+/// instead of clipping them: `operator` is `"none"`, `"reinhard"` or
+/// `"aces"`, applied after multiplying by `exposure` (default 1). Works in
+/// linear light whether or not `set_linear_rendering` is on; posters are
+/// left unmapped. Fails without a half-float or sRGB render target.
+#[wasm_bindgen]
+pub fn set_tone_mapping(operator: &str, exposure: Option<f32>) -> Result<(), JsValue> {
+    let tone_mapping = ToneMapping::from_name(operator)
+        .ok_or_else(|| invalid_option("operator", ToneMapping::NAMES, &operator.into()))?;
+    let exposure = exposure.unwrap_or(1.0);
+    if !(exposure > 0.0 && exposure <= MAX_EXPOSURE) {
+        return Err(invalid_option("exposure", "a number above 0, at most 16", &exposure.into()));
+    }
+    let mut result = Err(JsValue::from_str("The starfield is not running"));
+    with_star_field(|sf| {
+        result = sf.set_tone_mapping(tone_mapping, exposure).map_err(|error| JsValue::from_str(&error));
+    });
+    result
+}
+
+/// Picks judge-status tints and rating burst colors that stay
+/// distinguishable for `vision`: `"typical"`, `"deuteranopia"`,
+/// `"protanopia"` or `"tritanopia"`. Star colors follow only the
+/// `colorVision` start option, as stars already drawn keep theirs.
+#[wasm_bindgen]
+pub fn set_color_vision(vision: &str) -> Result<(), JsValue> {
+    let vision =
+        ColorVision::from_name(vision).ok_or_else(|| invalid_option("vision", ColorVision::NAMES, &vision.into()))?;
+    with_star_field(|sf| sf.color_vision = vision);
+    Ok(())
+}
+
+pub(super) fn color_vision_option(options: &JsValue, key: &str) -> Result<ColorVision, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(ColorVision::Typical);
+    };
+    value
+        .as_string()
+        .and_then(|name| ColorVision::from_name(&name))
+        .ok_or_else(|| invalid_option(key, ColorVision::NAMES, &value))
+}
+
+pub(crate) fn star_field_canvas() -> Option<HtmlCanvasElement> {
+    let mut canvas = None;
+    with_star_field(|sf| canvas = Some(sf.canvas.clone()));
+    canvas
+}
+
+pub(crate) fn set_theme_tint(sky: [f32; 3], sky_mix: f32, star: [f32; 3], star_mix: f32) {
+    with_star_field(|sf| sf.set_theme(ThemeTint { sky, sky_mix, star, star_mix }));
+}
+
+pub(crate) fn drive_status_sky(status: &str, finished: bool) {
+    let judging = !finished && status.eq_ignore_ascii_case("judging");
+    with_star_field(|sf| {
+        if let Some(theme) = ThemeTint::for_status(status, sf.color_vision) {
+            sf.set_theme(theme);
+        }
+        sf.set_pulsing(judging);
+    });
+    #[cfg(feature = "audio")]
+    if finished {
+        play_status_sound(status);
+    }
+}
+
+#[cfg(feature = "audio")]
+fn play_status_sound(status: &str) {
+    if matches!(status.to_ascii_lowercase().as_str(), "ac" | "accepted") {
+        audio::play(Sound::Accepted);
+    }
+}
+
+/// Returns the difficulty-scale color for a problem rating as `#rrggbb`.
+#[wasm_bindgen]
+pub fn theme_for_difficulty(rating: u32) -> String {
+    let color = difficulty_color(rating);
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        (color[0] * 255.0).round() as u8,
+        (color[1] * 255.0).round() as u8,
+        (color[2] * 255.0).round() as u8,
+    )
+}
+
+/// Crossfades the sky and star palette toward the difficulty color of a
+/// problem rating.
+#[wasm_bindgen]
+pub fn apply_difficulty_theme(rating: u32) {
+    with_star_field(|sf| sf.set_theme(ThemeTint::for_difficulty(rating)));
+}
+
+/// Starts or stops the slow "breathing" of the whole sky shown while a
+/// submission is being judged.
+#[wasm_bindgen]
+pub fn set_pulsing(pulsing: bool) {
+    with_star_field(|sf| sf.set_pulsing(pulsing));
+}
+
+/// Emits an upward green fountain for a rating gain or a red drizzle for a
+/// loss; the particle count scales with `|delta|`.
+#[wasm_bindgen]
+pub fn rating_burst(delta: i32) {
+    with_star_field(|sf| sf.rating_burst(delta));
+}
+
+/// Plays the rank-up effect: a ring of stars converges on the center and
+/// explodes outward in the new tier's color (`#rgb` or `#rrggbb`).
+#[wasm_bindgen]
+pub fn rank_up(color_hex: &str) -> Result<(), JsValue> {
+    let color = parse_hex_color(color_hex).ok_or_else(|| invalid_option("color", HEX_COLOR, &color_hex.into()))?;
+    with_star_field(|sf| sf.rank_up(color));
+    Ok(())
+}
+
+/// Celebrates a streak of `n` consecutive accepted days or solves: `n`
+/// small golden stars (up to 30) arc up into a row near the top of the sky
+/// and stay about eight seconds before fading. Replaces a streak still
+/// showing; 0 clears it.
+#[wasm_bindgen]
+pub fn streak_effect(n: u32) -> Result<(), JsValue> {
+    let mut result = Err(JsValue::from_str("The starfield is not running"));
+    with_star_field(|sf| result = sf.streak_effect(n as usize).map_err(|error| JsValue::from_str(&error)));
+    result
+}
+
+/// Scrolls `text` horizontally across the sky. `options` may set `speed`
+/// (pixels per frame), `position` (`"top"` or `"bottom"`), `fontScale`
+/// (fraction of the canvas height), `color` (hex string) and `translate`:
+/// when true, `text` is an i18n message key formatted with `args` and
+/// re-translated when the locale changes.
+#[wasm_bindgen]
+pub fn set_ticker(text: &str, options: JsValue) -> Result<(), JsValue> {
+    let speed = number_option(&options, "speed", 0.0, TICKER_MAX_SPEED)?.unwrap_or(TICKER_DEFAULT_SPEED);
+    let at_top = match get_option(&options, "position") {
+        None => true,
+        Some(value) => match value.as_string().as_deref() {
+            Some("top") => true,
+            Some("bottom") => false,
+            _ => return Err(invalid_option("position", "\"top\" or \"bottom\"", &value)),
+        },
+    };
+    let font_scale = number_option(&options, "fontScale", 0.005, 0.5)?.unwrap_or(TICKER_DEFAULT_FONT_SCALE);
+    let color = color_option(&options, "color")?.unwrap_or(COUNTDOWN_COLOR);
+    let args = match bool_option(&options, "translate")? {
+        Some(true) => Some(args_from_js(&get_option(&options, "args").unwrap_or(JsValue::UNDEFINED))),
+        _ => None,
+    };
+    let mut result = Ok(());
+    with_star_field(|sf| {
+        result = sf.set_ticker(text, args, speed, at_top, font_scale, color).map_err(|error| JsValue::from_str(&error));
+    });
+    result
+}
+
+#[wasm_bindgen]
+pub fn clear_ticker() {
+    with_star_field(|sf| sf.clear_ticker());
+}
+
+const MAX_EXCLUSION_ZONES: usize = 32;
+
+/// Keeps meteors, bursts and effect particles from distracting behind
+/// critical UI. `zones` is an array of `{ x, y, width, height, feather,
+/// strength }` in CSS pixels from the canvas's top-left (for a full-page
+/// canvas, an element's `getBoundingClientRect()` works as is), where
+/// `feather` is the soft edge (default 32) and `strength` how much effects
+/// dim inside, 0 to 1 (default 1). Meteors only launch on paths clear of
+/// every zone. Replaces earlier zones; call again when the layout changes,
+/// or with `null` or `[]` to clear them.
+#[wasm_bindgen]
+pub fn set_exclusion_zones(zones: JsValue) -> Result<(), JsValue> {
+    let zones = if zones.is_undefined() || zones.is_null() {
+        Vec::new()
+    } else {
+        exclusion_zones_from_js(&zones)?
+    };
+    with_star_field(|sf| sf.set_exclusion_zones(&zones));
+    Ok(())
+}
+
+fn exclusion_zones_from_js(zones: &JsValue) -> Result<Vec<ExclusionZone>, JsValue> {
+    let expected = format!("an array of up to {} {{ x, y, width, height }} rectangles", MAX_EXCLUSION_ZONES);
+    if !js_sys::Array::is_array(zones) || js_sys::Array::from(zones).length() as usize > MAX_EXCLUSION_ZONES {
+        return Err(invalid_option("zones", &expected, zones));
+    }
+    js_sys::Array::from(zones)
+        .iter()
+        .map(|zone| {
+            if !zone.is_object() {
+                return Err(invalid_option("zones", &expected, &zone));
+            }
+            let required = |key: &str, min: f32, expected: &str| -> Result<f32, JsValue> {
+                let value = get_option(&zone, key).unwrap_or(JsValue::UNDEFINED);
+                let number = value.as_f64().map(|number| number as f32);
+                number
+                    .filter(|number| number.is_finite() && *number >= min)
+                    .ok_or_else(|| invalid_option(key, expected, &value))
+            };
+            Ok(ExclusionZone {
+                x: required("x", f32::MIN, "a number")?,
+                y: required("y", f32::MIN, "a number")?,
+                width: required("width", 0.0, "a number from 0 up")?,
+                height: required("height", 0.0, "a number from 0 up")?,
+                feather: number_option(&zone, "feather", 0.0, f32::MAX)?.unwrap_or(32.0),
+                strength: number_option(&zone, "strength", 0.0, 1.0)?.unwrap_or(1.0),
+            })
+        })
+        .collect()
+}
+
+/// Flies a meteor from (`x0`, `y0`) to (`x1`, `y1`), in CSS pixels from the
+/// canvas's top-left (for a full-page canvas, element positions from
+/// `getBoundingClientRect()` work as is), e.g. from the Submit button to the
+/// verdict panel. It arcs upward and takes `duration` milliseconds of sky
+/// time, so it waits while the sky is paused, and is not dimmed by
+/// exclusion zones. Returns a promise that resolves with `true` when the
+/// meteor arrives, or `false` if the starfield stops first.
+#[wasm_bindgen]
+pub fn fly_meteor_between(x0: f32, y0: f32, x1: f32, y1: f32, duration: f32) -> Result<Promise, JsValue> {
+    for (key, value) in [("x0", x0), ("y0", y0), ("x1", x1), ("y1", y1)] {
+        if !value.is_finite() {
+            return Err(invalid_option(key, "a number", &value.into()));
+        }
+    }
+    if !(1.0..=MAX_GUIDED_METEOR_MS).contains(&duration) {
+        return Err(invalid_option("duration", "a number of milliseconds from 1 to 60000", &duration.into()));
+    }
+    let mut result = Err(JsValue::from_str("The starfield is not running"));
+    with_star_field(|sf| {
+        let mut arrived = None;
+        let promise = Promise::new(&mut |resolve, _reject| arrived = Some(resolve));
+        let Some(arrived) = arrived else {
+            return;
+        };
+        result = sf
+            .fly_meteor_between((x0, y0), (x1, y1), duration / 1000.0, arrived)
+            .map(|()| promise)
+            .map_err(|error| JsValue::from_str(&error));
+    });
+    result
+}
+
+/// Toggles a readout of frame rate, frame time, star, meteor and particle
+/// counts and estimated GPU memory in the canvas's top-left corner, for
+/// debugging where devtools are awkward to open. It is drawn on the overlay
+/// layer, so hiding that layer or a screensaver hides it too.
+#[wasm_bindgen]
+pub fn set_debug_overlay(enabled: bool) -> Result<(), JsValue> {
+    let mut result = Ok(());
+    with_star_field(|sf| result = sf.set_debug_overlay(enabled).map_err(|error| JsValue::from_str(&error)));
+    result
+}
+
+/// Shows a large `days:hours:minutes:seconds` countdown to `target_ms`
+/// (a Unix timestamp in milliseconds) in the middle of the sky. A
+/// `countdown` i18n message, if loaded, overrides the format with the
+/// zero-padded `{days}`, `{hours}`, `{minutes}` and `{seconds}`.
+#[wasm_bindgen]
+pub fn set_countdown(target_ms: f64) -> Result<(), JsValue> {
+    if !target_ms.is_finite() {
+        return Err(invalid_option("targetMs", "a finite timestamp in milliseconds", &target_ms.into()));
+    }
+    let mut result = Ok(());
+    with_star_field(|sf| result = sf.set_countdown(target_ms).map_err(|error| JsValue::from_str(&error)));
+    result
+}
+
+#[wasm_bindgen]
+pub fn clear_countdown() {
+    with_star_field(|sf| sf.clear_countdown());
+}
+
+/// Themes the sky by a contest's phase, from `start_ms` to `end_ms` (Unix
+/// timestamps in milliseconds): a calm blue with few meteors beforehand, an
+/// energetic violet while it runs, a tense red with a meteor storm in the
+/// final minute and a gold-tinted afterglow once it ends. Phases follow the
+/// wall clock; a status theme set meanwhile holds until the next phase.
+#[wasm_bindgen]
+pub fn set_contest_schedule(start_ms: f64, end_ms: f64) -> Result<(), JsValue> {
+    let schedule = ContestSchedule::new(start_ms, end_ms).ok_or_else(|| {
+        invalid_option("endMs", "a finite timestamp in milliseconds after startMs", &end_ms.into())
+    })?;
+    with_star_field(|sf| sf.set_contest_schedule(Some(schedule)));
+    Ok(())
+}
+
+/// Stops following a contest's phases and clears its theme.
+#[wasm_bindgen]
+pub fn clear_contest_schedule() {
+    with_star_field(|sf| sf.set_contest_schedule(None));
+}
+
+// The range of a JavaScript `Date`.
+const MAX_TIMESTAMP_MS: f64 = 8.64e15;
+
+/// Shows the real sky over `latitude` (-90 to 90, north positive) and
+/// `longitude` (-180 to 180, east positive) in degrees at `timestamp`
+/// (milliseconds since the Unix epoch, by default now): the brightest stars
+/// as a star chart with the zenith at the center, north up and east left,
+/// turning slowly with the Earth from there. Stars rise and set as they
+/// cross the horizon. `clear_observer` returns to a random sky.
+#[wasm_bindgen]
+pub fn set_observer(latitude: f64, longitude: f64, timestamp: Option<f64>) -> Result<(), JsValue> {
+    if !(-90.0..=90.0).contains(&latitude) {
+        return Err(invalid_option("latitude", "a number of degrees from -90 to 90", &latitude.into()));
+    }
+    if !(-180.0..=180.0).contains(&longitude) {
+        return Err(invalid_option("longitude", "a number of degrees from -180 to 180", &longitude.into()));
+    }
+    let timestamp = timestamp.unwrap_or_else(js_sys::Date::now);
+    if !(-MAX_TIMESTAMP_MS..=MAX_TIMESTAMP_MS).contains(&timestamp) {
+        return Err(invalid_option("timestamp", "a time in milliseconds since the Unix epoch", &timestamp.into()));
+    }
+    with_star_field(|sf| sf.set_observer_at(Observer { latitude, longitude }, timestamp));
+    Ok(())
+}
+
+/// Returns from the observer's sky to random stars.
+#[wasm_bindgen]
+pub fn clear_observer() {
+    with_star_field(|sf| sf.set_observer(None));
+}
@@ -0,0 +1,166 @@
+use soj_sim::rng::Rng;
+use soj_sim::stars::StarPalette;
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGlBuffer, WebGlFramebuffer, WebGlProgram, WebGlRenderingContext as GL, WebGlTexture};
+
+use crate::options::{get_option, get_option_string, invalid_option, number_option};
+
+use super::effect::{Effect, EffectContext};
+use super::gl::{create_texture_program, draw_points, draw_texture, upload_vertices, BlendMode};
+use super::layer::Layer;
+use super::sim::ThemeTint;
+
+const MAX_BAKED_STARS: f32 = 200_000.0;
+
+// A baked starscape described from JavaScript.
+pub(super) struct BakedDescriptor {
+    count: usize,
+    // Point diameter range in CSS pixels.
+    size: (f32, f32),
+    alpha: (f32, f32),
+    pub(super) layer: Layer,
+}
+
+impl BakedDescriptor {
+    pub(super) fn from_js(options: &JsValue) -> Result<BakedDescriptor, JsValue> {
+        let missing = options.is_undefined() || options.is_null();
+        if !missing && (!options.is_object() || js_sys::Array::is_array(options)) {
+            return Err(invalid_option("options", "an object", options));
+        }
+        let layer = match get_option_string(options, "layer") {
+            Some(name) => Layer::from_name(&name).ok_or_else(|| invalid_option("layer", Layer::NAMES, &name.into()))?,
+            None => Layer::Nebula,
+        };
+        let range = |min_key: &str, max_key: &str, limit: f32, default: (f32, f32)| -> Result<(f32, f32), JsValue> {
+            let min = number_option(options, min_key, 0.0, limit)?.unwrap_or(default.0);
+            let max = number_option(options, max_key, 0.0, limit)?.unwrap_or(default.1.max(min));
+            if max < min {
+                let value = get_option(options, max_key).unwrap_or(JsValue::UNDEFINED);
+                return Err(invalid_option(max_key, &format!("a number from {} up", min_key), &value));
+            }
+            Ok((min, max))
+        };
+        Ok(BakedDescriptor {
+            count: number_option(options, "count", 0.0, MAX_BAKED_STARS)?.unwrap_or(4_000.0) as usize,
+            size: range("minSize", "maxSize", 8.0, (0.5, 1.5))?,
+            alpha: range("minAlpha", "maxAlpha", 1.0, (0.15, 0.6))?,
+            layer,
+        })
+    }
+}
+
+// Thousands of faint stars drawn once into a texture, then shown as a
+// single quad each frame. Rebaked, in the same places, on resize.
+pub(super) struct BakedStarscape {
+    descriptor: BakedDescriptor,
+    palette: StarPalette,
+    seed: u64,
+    star_program: WebGlProgram,
+    program: WebGlProgram,
+    quad: WebGlBuffer,
+    texture: WebGlTexture,
+    framebuffer: WebGlFramebuffer,
+}
+
+impl BakedStarscape {
+    pub(super) fn new(
+        gl: &GL,
+        star_program: WebGlProgram,
+        palette: StarPalette,
+        descriptor: BakedDescriptor,
+        seed: u64,
+    ) -> Result<BakedStarscape, String> {
+        let (program, quad) = create_texture_program(gl)?;
+        let texture = gl.create_texture().ok_or("Failed to create starscape texture")?;
+        let framebuffer = gl.create_framebuffer().ok_or("Failed to create starscape framebuffer")?;
+        Ok(BakedStarscape { descriptor, palette, seed, star_program, program, quad, texture, framebuffer })
+    }
+
+    fn bake(&self, context: &EffectContext) -> Result<(), String> {
+        let gl = &context.gl;
+        let (width, height) = context.resolution;
+        gl.bind_texture(GL::TEXTURE_2D, Some(&self.texture));
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, GL::NEAREST as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MAG_FILTER, GL::NEAREST as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE as i32);
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            GL::TEXTURE_2D, 0, GL::RGBA as i32, width as i32, height as i32, 0, GL::RGBA, GL::UNSIGNED_BYTE, None,
+        )
+        .map_err(|_| "Failed to allocate starscape texture")?;
+        gl.bind_framebuffer(GL::FRAMEBUFFER, Some(&self.framebuffer));
+        gl.framebuffer_texture_2d(GL::FRAMEBUFFER, GL::COLOR_ATTACHMENT0, GL::TEXTURE_2D, Some(&self.texture), 0);
+        let result = if gl.check_framebuffer_status(GL::FRAMEBUFFER) == GL::FRAMEBUFFER_COMPLETE {
+            self.draw_stars(context);
+            Ok(())
+        } else {
+            Err("Starscape framebuffer is incomplete".to_string())
+        };
+        gl.bind_framebuffer(GL::FRAMEBUFFER, None);
+        result
+    }
+
+    // Positions are drawn as fractions, so each bake puts the stars in the
+    // same places on any canvas size.
+    fn draw_stars(&self, context: &EffectContext) {
+        let gl = &context.gl;
+        let d = &self.descriptor;
+        let (width, height) = context.resolution;
+        let mut rng = Rng::new(self.seed);
+        let mut data = Vec::with_capacity(d.count * 7);
+        for _ in 0..d.count {
+            let (x, y) = (rng.next_f32() * width, rng.next_f32() * height);
+            let size = (rng.range(d.size) * context.size_scale).max(1.0);
+            let alpha = rng.range(d.alpha);
+            let color = self.palette.pick(&mut rng);
+            data.extend_from_slice(&[x, y, size, alpha, color[0], color[1], color[2]]);
+        }
+        let buffer = gl.create_buffer();
+        if let Some(buffer) = &buffer {
+            upload_vertices(gl, buffer, &data, GL::STATIC_DRAW);
+            gl.viewport(0, 0, width as i32, height as i32);
+            gl.clear_color(0.0, 0.0, 0.0, 0.0);
+            gl.clear(GL::COLOR_BUFFER_BIT);
+            BlendMode::Alpha.apply(gl);
+            let context = EffectContext { point_scale: 1.0, opacity: 1.0, linear: false, ..context.clone() };
+            draw_points(&context, &self.star_program, buffer, d.count, ThemeTint::NONE.star, 0.0);
+            gl.disable(GL::BLEND);
+        }
+        gl.delete_buffer(buffer.as_ref());
+    }
+}
+
+impl Effect for BakedStarscape {
+    fn init(&mut self, context: &EffectContext) -> Result<(), String> {
+        self.bake(context)
+    }
+
+    fn update(&mut self, _context: &EffectContext, _dt: f32) {}
+
+    fn draw(&self, context: &EffectContext) {
+        // Daylight washes the baked stars out like the live ones.
+        let context = EffectContext { opacity: context.opacity * context.sky.star_alpha, ..context.clone() };
+        draw_texture(&context, &self.program, &self.quad, &self.texture);
+    }
+
+    fn resize(&mut self, context: &EffectContext, _old_resolution: (f32, f32)) {
+        // A failed rebake leaves the texture empty; the live stars still show.
+        let _ = self.bake(context);
+    }
+
+    fn transfer(&mut self, context: &EffectContext) -> Result<(), String> {
+        let gl = &context.gl;
+        (self.program, self.quad) = create_texture_program(gl)?;
+        self.texture = gl.create_texture().ok_or("Failed to create starscape texture")?;
+        self.framebuffer = gl.create_framebuffer().ok_or("Failed to create starscape framebuffer")?;
+        self.star_program = context.star_program.clone();
+        self.bake(context)
+    }
+
+    fn destroy(&mut self, gl: &GL) {
+        gl.delete_framebuffer(Some(&self.framebuffer));
+        gl.delete_texture(Some(&self.texture));
+        gl.delete_program(Some(&self.program));
+        gl.delete_buffer(Some(&self.quad));
+    }
+}
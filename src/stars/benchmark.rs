@@ -0,0 +1,156 @@
+use soj_sim::meteors::{launch_meteor, MeteorColors, MeteorTrajectory};
+use soj_sim::stars::{scatter_stars, step_stars};
+use soj_sim::ParticleStore;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{window, HtmlCanvasElement, WebGlRenderingContext as GL};
+
+use crate::options::{bool_option, invalid_option, number_option};
+
+use super::gl::lose_context;
+use super::sim::seeded_rng;
+use super::{StarField, StarFieldOptions, MAX_BENCHMARK_FRAMES, MAX_STARS, METEOR_CHANCE_PER_FRAME, POINT_SCALE};
+
+const BENCHMARK_CANVAS_ID: &str = "soj-benchmark";
+const DEFAULT_WIDTH: f32 = 1280.0;
+const DEFAULT_HEIGHT: f32 = 720.0;
+
+fn performance_now() -> f64 {
+    window().and_then(|window| window.performance()).map_or(0.0, |performance| performance.now())
+}
+
+// `{ mean, p50, p90, p99, max }` of `times`, in milliseconds.
+fn summarize(mut times: Vec<f64>) -> JsValue {
+    let result = js_sys::Object::new();
+    let set = |key: &str, value: f64| {
+        js_sys::Reflect::set(&result, &JsValue::from_str(key), &JsValue::from(value)).unwrap();
+    };
+    times.sort_by(f64::total_cmp);
+    // Nearest rank.
+    let percentile = |p: f64| {
+        let rank = ((p / 100.0 * times.len() as f64).ceil() as usize).clamp(1, times.len());
+        times[rank - 1]
+    };
+    set("mean", times.iter().sum::<f64>() / times.len() as f64);
+    set("p50", percentile(50.0));
+    set("p90", percentile(90.0));
+    set("p99", percentile(99.0));
+    set("max", times[times.len() - 1]);
+    result.into()
+}
+
+// Steps stars and meteors alone, without a canvas or WebGL.
+fn simulate(frames: usize, stars: usize, (width, height): (f32, f32)) -> Vec<f64> {
+    let mut rng = seeded_rng();
+    let config = StarFieldOptions::default().star_config();
+    let mut field = ParticleStore::with_capacity(stars);
+    scatter_stars(&mut rng, &config, &mut field, stars, width, height);
+    let mut meteors = ParticleStore::with_capacity(8);
+    let twinkle = StarFieldOptions::default().twinkle_mode;
+    (0..frames)
+        .map(|_| {
+            let start = performance_now();
+            step_stars(&mut field, 1.0, (width, height), POINT_SCALE / 2.0, twinkle);
+            if rng.next_f32() < METEOR_CHANCE_PER_FRAME {
+                let trajectory = &MeteorTrajectory::DEFAULT;
+                if let Some(meteor) = launch_meteor(&mut rng, trajectory, MeteorColors::DEFAULT, (width, height), 1.0) {
+                    meteors.push(meteor);
+                }
+            }
+            meteors.integrate(1.0);
+            meteors.remove_expired();
+            performance_now() - start
+        })
+        .collect()
+}
+
+// Runs a whole starfield on an off-screen canvas, timing updates and draws
+// separately. Draws wait for the GPU to finish so they count its work too.
+// The context is released afterwards: browsers keep only a few live and
+// drop the oldest, which may be the page's own sky.
+fn render(frames: usize, stars: usize, (width, height): (f32, f32)) -> Result<(Vec<f64>, Vec<f64>), JsValue> {
+    let document = window().unwrap().document().unwrap();
+    let canvas = document.create_element("canvas")?.dyn_into::<HtmlCanvasElement>()?;
+    canvas.set_id(BENCHMARK_CANVAS_ID);
+    canvas.set_attribute("aria-hidden", "true")?;
+    let style = canvas.style();
+    style.set_property("position", "fixed")?;
+    style.set_property("left", "-100000px")?;
+    style.set_property("top", "0")?;
+    style.set_property("width", &format!("{}px", width))?;
+    style.set_property("height", &format!("{}px", height))?;
+    document.body().ok_or("The page has no body")?.append_child(&canvas)?;
+    let options = StarFieldOptions { manual: true, ..StarFieldOptions::default() };
+    let star_field = StarField::new(BENCHMARK_CANVAS_ID, stars, &options);
+    canvas.remove();
+    let mut star_field = match star_field {
+        Ok(star_field) => star_field,
+        Err(error) => {
+            // Returns the context made before the failure, if any.
+            let context = canvas.get_context("webgl").ok().flatten();
+            if let Some(gl) = context.and_then(|context| context.dyn_into::<GL>().ok()) {
+                lose_context(&gl);
+            }
+            return Err(JsValue::from_str(&error));
+        }
+    };
+    let (mut updates, mut draws) = (Vec::with_capacity(frames), Vec::with_capacity(frames));
+    for _ in 0..frames {
+        let start = performance_now();
+        star_field.update(1.0);
+        let updated = performance_now();
+        star_field.draw();
+        star_field.gl.finish();
+        updates.push(updated - start);
+        draws.push(performance_now() - updated);
+    }
+    lose_context(&star_field.gl);
+    Ok((updates, draws))
+}
+
+/// Runs the sky for `frames` frames with `stars` stars as fast as it can
+/// and reports frame-time percentiles, for performance reports from real
+/// devices. `options`, all optional: `render` (also build a full starfield
+/// on an off-screen canvas and time its updates and draws, including the
+/// GPU's work; default false), `width` and `height` (the sky's size in CSS
+/// pixels, default 1280×720). Returns `{ frames, stars, width, height,
+/// devicePixelRatio, simulation, update, draw }`, each timing `{ mean, p50,
+/// p90, p99, max }` in milliseconds; `update` and `draw` are `null` unless
+/// rendering. Blocks the page while it runs, so keep `frames` modest.
+#[wasm_bindgen]
+pub fn benchmark(frames: u32, stars: u32, options: JsValue) -> Result<JsValue, JsValue> {
+    if !(1..=MAX_BENCHMARK_FRAMES).contains(&(frames as usize)) {
+        let expected = format!("a number from 1 to {}", MAX_BENCHMARK_FRAMES);
+        return Err(invalid_option("frames", &expected, &frames.into()));
+    }
+    if stars as usize > MAX_STARS {
+        let expected = format!("a number up to {}", MAX_STARS);
+        return Err(invalid_option("stars", &expected, &stars.into()));
+    }
+    let (frames, stars) = (frames as usize, stars as usize);
+    let width = number_option(&options, "width", 1.0, 8192.0)?.unwrap_or(DEFAULT_WIDTH);
+    let height = number_option(&options, "height", 1.0, 8192.0)?.unwrap_or(DEFAULT_HEIGHT);
+    let dpr = window().unwrap().device_pixel_ratio();
+
+    let simulation = summarize(simulate(frames, stars, (width * dpr as f32, height * dpr as f32)));
+    let (update, draw) = if bool_option(&options, "render")?.unwrap_or(false) {
+        let (updates, draws) = render(frames, stars, (width, height))?;
+        (summarize(updates), summarize(draws))
+    } else {
+        (JsValue::NULL, JsValue::NULL)
+    };
+
+    let result = js_sys::Object::new();
+    let set = |key: &str, value: JsValue| {
+        js_sys::Reflect::set(&result, &JsValue::from_str(key), &value).unwrap();
+    };
+    set("frames", JsValue::from(frames as u32));
+    set("stars", JsValue::from(stars as u32));
+    set("width", JsValue::from(width));
+    set("height", JsValue::from(height));
+    set("devicePixelRatio", JsValue::from(dpr));
+    set("simulation", simulation);
+    set("update", update);
+    set("draw", draw);
+    Ok(result.into())
+}
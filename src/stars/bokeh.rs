@@ -0,0 +1,110 @@
+use soj_sim::bokeh::{BokehDust, Mote};
+use soj_sim::Rng;
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGlBuffer, WebGlProgram, WebGlRenderingContext as GL};
+
+use crate::options::{color_option, get_option, get_option_string, invalid_option, number_option};
+
+use super::effect::{Effect, EffectContext};
+use super::gl::{create_bokeh_program, draw_points, max_point_size, upload_vertices};
+use super::layer::Layer;
+use super::sim::ThemeTint;
+
+// Bokeh is meant to be sparse; hundreds of big discs would wash the sky out.
+const MAX_BOKEH_MOTES: usize = 512;
+
+// Bokeh dust described from JavaScript.
+pub(super) struct BokehDescriptor {
+    dust: BokehDust,
+    count: usize,
+    color: [f32; 3],
+    pub(super) layer: Layer,
+}
+
+impl BokehDescriptor {
+    pub(super) fn from_js(options: &JsValue) -> Result<BokehDescriptor, JsValue> {
+        let missing = options.is_undefined() || options.is_null();
+        if !missing && (!options.is_object() || js_sys::Array::is_array(options)) {
+            return Err(invalid_option("options", "an object", options));
+        }
+        let layer = match get_option_string(options, "layer") {
+            Some(name) => Layer::from_name(&name).ok_or_else(|| invalid_option("layer", Layer::NAMES, &name.into()))?,
+            None => Layer::Foreground,
+        };
+        let default = BokehDust::DEFAULT;
+        let size = (
+            number_option(options, "sizeMin", 1.0, 256.0)?.unwrap_or(default.size.0),
+            number_option(options, "sizeMax", 1.0, 256.0)?.unwrap_or(default.size.1),
+        );
+        if size.1 < size.0 {
+            let value = get_option(options, "sizeMax").unwrap_or(JsValue::UNDEFINED);
+            return Err(invalid_option("sizeMax", "a number no less than sizeMin", &value));
+        }
+        // CSS pixels per second in, per frame kept.
+        let drift = |key: &str, fallback: f32| -> Result<f32, JsValue> {
+            Ok(number_option(options, key, -600.0, 600.0)?.map_or(fallback, |speed| speed / 60.0))
+        };
+        let dust = BokehDust {
+            size,
+            drift: (drift("driftX", default.drift.0)?, drift("driftY", default.drift.1)?),
+            alpha: number_option(options, "alpha", 0.0, 1.0)?.unwrap_or(default.alpha),
+        };
+        Ok(BokehDescriptor {
+            dust,
+            count: number_option(options, "density", 0.0, MAX_BOKEH_MOTES as f32)?.unwrap_or(24.0) as usize,
+            color: color_option(options, "color")?.unwrap_or([0.85, 0.88, 1.0]),
+            layer,
+        })
+    }
+}
+
+pub(super) struct Bokeh {
+    descriptor: BokehDescriptor,
+    motes: Vec<Mote>,
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+    // Bigger motes are clamped rather than drawn as quads; at this blur
+    // nobody can tell.
+    max_point_size: f32,
+}
+
+impl Bokeh {
+    pub(super) fn new(gl: &GL, descriptor: BokehDescriptor, rng: &mut Rng) -> Result<Bokeh, String> {
+        let program = create_bokeh_program(gl)?;
+        let buffer = gl.create_buffer().ok_or("Failed to create bokeh buffer")?;
+        let motes = descriptor.dust.scatter(rng, descriptor.count);
+        Ok(Bokeh { descriptor, motes, program, buffer, max_point_size: max_point_size(gl) })
+    }
+}
+
+impl Effect for Bokeh {
+    fn update(&mut self, context: &EffectContext, dt: f32) {
+        let d = &self.descriptor;
+        d.dust.step(&mut self.motes, context.resolution, context.size_scale, dt);
+        let mut data = Vec::with_capacity(self.motes.len() * 7);
+        for mote in &self.motes {
+            let (x, y, alpha) = d.dust.place(mote, context.resolution, context.size_scale);
+            let size = (d.dust.diameter(mote) * context.size_scale).min(self.max_point_size);
+            let alpha = alpha * context.exclusion(x, y);
+            data.extend_from_slice(&[x, y, size, alpha, d.color[0], d.color[1], d.color[2]]);
+        }
+        upload_vertices(&context.gl, &self.buffer, &data, GL::DYNAMIC_DRAW);
+    }
+
+    fn draw(&self, context: &EffectContext) {
+        draw_points(context, &self.program, &self.buffer, self.motes.len(), ThemeTint::NONE.star, 0.0);
+    }
+
+    fn transfer(&mut self, context: &EffectContext) -> Result<(), String> {
+        let gl = &context.gl;
+        self.program = create_bokeh_program(gl)?;
+        self.buffer = gl.create_buffer().ok_or("Failed to create bokeh buffer")?;
+        self.max_point_size = max_point_size(gl);
+        Ok(())
+    }
+
+    fn destroy(&mut self, gl: &GL) {
+        gl.delete_buffer(Some(&self.buffer));
+        gl.delete_program(Some(&self.program));
+    }
+}
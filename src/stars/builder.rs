@@ -0,0 +1,171 @@
+use wasm_bindgen::prelude::*;
+
+use super::api::{run_starfield, validate_start};
+use super::{
+    ColorScheme, ColorVision, DensityMap, HueShift, MeteorColors, SkyPalette, StarBand, StarCluster, StarField,
+    StarFieldOptions, StarMask, StarPalette, StarSizes, TwinkleConfig, TwinkleMode, MAX_STARS,
+};
+
+pub(super) const DEFAULT_STARS: usize = 1_000;
+const _: () = assert!(DEFAULT_STARS <= MAX_STARS);
+
+/// Typed start-up settings for Rust frontends embedding this crate, in
+/// place of `start_starfield_with_options`' options object. The defaults
+/// match that function's and are `const`, so a builder can be a constant:
+/// `StarFieldBuilder::new().stars(2000).palette(palette).start("sky")`.
+#[derive(Clone)]
+pub struct StarFieldBuilder {
+    stars: usize,
+    options: StarFieldOptions,
+}
+
+impl StarFieldBuilder {
+    pub const fn new() -> StarFieldBuilder {
+        StarFieldBuilder {
+            stars: DEFAULT_STARS,
+            options: StarFieldOptions {
+                transparent: false,
+                manual: false,
+                color_scheme: ColorScheme::Auto,
+                palette: None,
+                raw_pixels: false,
+                star_palette: None,
+                star_sizes: StarSizes::DEFAULT,
+                twinkle: None,
+                twinkle_mode: TwinkleMode::Sine,
+                band: StarBand::DEFAULT,
+                clusters: None,
+                density_map: None,
+                star_mask: None,
+                hue_shift: None,
+                meteor_colors: MeteorColors::DEFAULT,
+                color_vision: ColorVision::Typical,
+                seed: None,
+                data_saver: false,
+            },
+        }
+    }
+
+    /// Stars at the canvas' starting size; density is kept on resize.
+    pub const fn stars(mut self, stars: usize) -> StarFieldBuilder {
+        self.stars = stars;
+        self
+    }
+
+    pub const fn transparent(mut self, transparent: bool) -> StarFieldBuilder {
+        self.options.transparent = transparent;
+        self
+    }
+
+    /// No built-in animation loop; the host calls `step` and `render`.
+    pub const fn manual(mut self, manual: bool) -> StarFieldBuilder {
+        self.options.manual = manual;
+        self
+    }
+
+    pub const fn color_scheme(mut self, color_scheme: ColorScheme) -> StarFieldBuilder {
+        self.options.color_scheme = color_scheme;
+        self
+    }
+
+    /// A fixed sky instead of the night and daytime presets.
+    pub const fn palette(mut self, palette: SkyPalette) -> StarFieldBuilder {
+        self.options.palette = Some(palette);
+        self
+    }
+
+    /// Weighted star colors instead of the default warm, blue and white.
+    pub fn star_palette(mut self, palette: StarPalette) -> StarFieldBuilder {
+        self.options.star_palette = Some(palette);
+        self
+    }
+
+    pub fn star_sizes(mut self, sizes: StarSizes) -> StarFieldBuilder {
+        self.options.star_sizes = sizes;
+        self
+    }
+
+    pub fn twinkle(mut self, twinkle: TwinkleConfig) -> StarFieldBuilder {
+        self.options.twinkle = Some(twinkle);
+        self
+    }
+
+    pub const fn twinkle_mode(mut self, mode: TwinkleMode) -> StarFieldBuilder {
+        self.options.twinkle_mode = mode;
+        self
+    }
+
+    pub const fn band(mut self, band: StarBand) -> StarFieldBuilder {
+        self.options.band = band;
+        self
+    }
+
+    pub fn clusters(mut self, clusters: Vec<StarCluster>) -> StarFieldBuilder {
+        self.options.clusters = Some(clusters);
+        self
+    }
+
+    pub fn density_map(mut self, map: DensityMap) -> StarFieldBuilder {
+        self.options.density_map = Some(map);
+        self
+    }
+
+    pub fn star_mask(mut self, mask: StarMask) -> StarFieldBuilder {
+        self.options.star_mask = Some(mask);
+        self
+    }
+
+    pub const fn hue_shift(mut self, shift: HueShift) -> StarFieldBuilder {
+        self.options.hue_shift = Some(shift);
+        self
+    }
+
+    pub const fn color_vision(mut self, vision: ColorVision) -> StarFieldBuilder {
+        self.options.color_vision = vision;
+        self
+    }
+
+    pub const fn meteor_colors(mut self, colors: MeteorColors) -> StarFieldBuilder {
+        self.options.meteor_colors = colors;
+        self
+    }
+
+    /// Renders at one pixel per CSS pixel; unlike the options object, the
+    /// builder does not read the Save-Data hint.
+    pub const fn data_saver(mut self, data_saver: bool) -> StarFieldBuilder {
+        self.options.data_saver = data_saver;
+        self
+    }
+
+    /// Makes every start with this seed scatter, twinkle and launch alike.
+    pub const fn seed(mut self, seed: u64) -> StarFieldBuilder {
+        self.options.seed = Some(seed);
+        self
+    }
+
+    pub const fn raw_pixels(mut self, raw_pixels: bool) -> StarFieldBuilder {
+        self.options.raw_pixels = raw_pixels;
+        self
+    }
+
+    /// Starts the page's starfield on `canvas_id`, as `start_starfield`
+    /// does; the JS exports (`step`, `set_layer_enabled`, ...) then drive it.
+    pub fn start(&self, canvas_id: &str) -> Result<(), JsValue> {
+        validate_start(canvas_id, self.stars)?;
+        run_starfield(canvas_id, self.stars, &self.options);
+        Ok(())
+    }
+
+    /// Creates a standalone starfield on `canvas_id` that the caller owns
+    /// and drives with `tick`, or `update` and `draw`, outside the JS exports.
+    pub fn build(&self, canvas_id: &str) -> Result<StarField, JsValue> {
+        validate_start(canvas_id, self.stars)?;
+        StarField::new(canvas_id, self.stars, &self.options).map_err(|e| JsValue::from_str(&e))
+    }
+}
+
+impl Default for StarFieldBuilder {
+    fn default() -> StarFieldBuilder {
+        StarFieldBuilder::new()
+    }
+}
@@ -0,0 +1,245 @@
+use web_sys::{WebGlBuffer, WebGlFramebuffer, WebGlProgram, WebGlRenderingContext as GL, WebGlTexture};
+
+use crate::diagnostics::{log, Level};
+
+use super::gl::{build_program, upload_vertices};
+use super::StarField;
+
+// From EXT_sRGB and OES_texture_half_float; web-sys only has them on the
+// extension objects.
+const SRGB_ALPHA_EXT: u32 = 0x8C42;
+const HALF_FLOAT_OES: u32 = 0x8D61;
+
+const COMPOSITE_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_position;
+    varying vec2 v_uv;
+    void main() {
+        gl_Position = vec4(a_position, 0.0, 1.0);
+        v_uv = a_position * 0.5 + 0.5;
+    }
+"#;
+
+// The frame holds premultiplied color (an sRGB texture decodes on sampling),
+// in linear light when `u_linear` is set. Mapping and encoding straight
+// color keeps soft edges from darkening.
+const COMPOSITE_FRAGMENT_SHADER: &str = r#"
+    precision mediump float;
+    uniform sampler2D u_frame;
+    uniform float u_linear;
+    uniform float u_exposure;
+    uniform float u_toneMapping;
+    varying vec2 v_uv;
+    void main() {
+        vec4 color = texture2D(u_frame, v_uv);
+        if (color.a <= 0.0) {
+            gl_FragColor = vec4(0.0);
+            return;
+        }
+        vec3 c = max(color.rgb / color.a, 0.0);
+        if (u_linear < 0.5) {
+            c = mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+        }
+        c *= u_exposure;
+        if (u_toneMapping > 1.5) {
+            c = c * (2.51 * c + 0.03) / (c * (2.43 * c + 0.59) + 0.14);
+        } else if (u_toneMapping > 0.5) {
+            c = c / (1.0 + c);
+        }
+        c = clamp(c, 0.0, 1.0);
+        vec3 encoded = mix(c * 12.92, 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, c));
+        gl_FragColor = vec4(encoded * color.a, color.a);
+    }
+"#;
+
+/// How bright areas are compressed before output, instead of clipping.
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum ToneMapping {
+    #[default]
+    None,
+    /// `c / (1 + c)`: gentle, dims midtones too.
+    Reinhard,
+    /// Narkowicz's fit of the ACES filmic curve: keeps contrast, rolls off
+    /// highlights.
+    Aces,
+}
+
+impl ToneMapping {
+    pub(super) const NAMES: &str = "\"none\", \"reinhard\" or \"aces\"";
+
+    pub(super) fn from_name(name: &str) -> Option<ToneMapping> {
+        match name.to_ascii_lowercase().as_str() {
+            "none" | "off" => Some(ToneMapping::None),
+            "reinhard" => Some(ToneMapping::Reinhard),
+            "aces" | "filmic" => Some(ToneMapping::Aces),
+            _ => None,
+        }
+    }
+}
+
+// How the drawn frame becomes canvas pixels.
+#[derive(Clone, Copy, PartialEq)]
+pub(super) struct Output {
+    pub(super) linear: bool,
+    pub(super) tone_mapping: ToneMapping,
+    pub(super) exposure: f32,
+}
+
+impl Output {
+    pub(super) const GAMMA: Output = Output { linear: false, tone_mapping: ToneMapping::None, exposure: 1.0 };
+
+    // Anything but plain gamma output draws offscreen and composites.
+    fn needs_composite(self) -> bool {
+        self != Output::GAMMA
+    }
+}
+
+// An offscreen frame the sky is blended into, then tone mapped and encoded
+// onto the canvas.
+pub(super) struct Composite {
+    texture: WebGlTexture,
+    framebuffer: WebGlFramebuffer,
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+    // Texture format and type: half floats, which keep brights above 1 for
+    // tone mapping, or sRGB bytes without them.
+    format: (u32, u32),
+}
+
+impl Composite {
+    pub(super) fn new(gl: &GL, size: (f32, f32)) -> Result<Composite, String> {
+        let program = build_program(gl, "Composite", COMPOSITE_VERTEX_SHADER, COMPOSITE_FRAGMENT_SHADER)?;
+        let buffer = gl.create_buffer().ok_or("Failed to create composite buffer")?;
+        upload_vertices(gl, &buffer, &[-1.0, -1.0, 3.0, -1.0, -1.0, 3.0], GL::STATIC_DRAW);
+        let texture = gl.create_texture().ok_or("Failed to create composite texture")?;
+        let framebuffer = gl.create_framebuffer().ok_or("Failed to create composite framebuffer")?;
+        let mut composite = Composite { texture, framebuffer, program, buffer, format: (0, 0) };
+        let extension = |name| gl.get_extension(name).ok().flatten().is_some();
+        let mut formats = Vec::new();
+        if extension("OES_texture_half_float") && extension("EXT_color_buffer_half_float") {
+            formats.push((GL::RGBA, HALF_FLOAT_OES));
+        }
+        if extension("EXT_sRGB") {
+            formats.push((SRGB_ALPHA_EXT, GL::UNSIGNED_BYTE));
+        }
+        for format in formats {
+            composite.format = format;
+            if composite.allocate(gl, size).is_ok() {
+                return Ok(composite);
+            }
+        }
+        composite.destroy(gl);
+        Err("This GPU has no half-float or sRGB render target".to_string())
+    }
+
+    fn allocate(&self, gl: &GL, (width, height): (f32, f32)) -> Result<(), String> {
+        let (format, kind) = self.format;
+        gl.bind_texture(GL::TEXTURE_2D, Some(&self.texture));
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, GL::NEAREST as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MAG_FILTER, GL::NEAREST as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE as i32);
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            GL::TEXTURE_2D, 0, format as i32, width as i32, height as i32, 0, format, kind, None,
+        )
+        .map_err(|_| "Failed to allocate composite texture")?;
+        gl.bind_framebuffer(GL::FRAMEBUFFER, Some(&self.framebuffer));
+        gl.framebuffer_texture_2d(GL::FRAMEBUFFER, GL::COLOR_ATTACHMENT0, GL::TEXTURE_2D, Some(&self.texture), 0);
+        let complete = gl.check_framebuffer_status(GL::FRAMEBUFFER) == GL::FRAMEBUFFER_COMPLETE;
+        gl.bind_framebuffer(GL::FRAMEBUFFER, None);
+        if complete {
+            Ok(())
+        } else {
+            Err("Composite framebuffer is incomplete".to_string())
+        }
+    }
+
+    // Texture memory per pixel of the offscreen frame.
+    pub(super) fn bytes_per_pixel(&self) -> f32 {
+        if self.format.1 == HALF_FLOAT_OES {
+            8.0
+        } else {
+            4.0
+        }
+    }
+
+    pub(super) fn bind(&self, gl: &GL) {
+        gl.bind_framebuffer(GL::FRAMEBUFFER, Some(&self.framebuffer));
+    }
+
+    // Maps and encodes the frame onto the canvas, leaving the canvas bound.
+    pub(super) fn present(&self, gl: &GL, (width, height): (f32, f32), output: Output) {
+        gl.bind_framebuffer(GL::FRAMEBUFFER, None);
+        gl.viewport(0, 0, width as i32, height as i32);
+        gl.disable(GL::BLEND);
+        gl.use_program(Some(&self.program));
+        gl.active_texture(GL::TEXTURE0);
+        gl.bind_texture(GL::TEXTURE_2D, Some(&self.texture));
+        let uniform = |name, value| {
+            if let Some(loc) = gl.get_uniform_location(&self.program, name) {
+                gl.uniform1f(Some(&loc), value);
+            }
+        };
+        uniform("u_linear", if output.linear { 1.0 } else { 0.0 });
+        uniform("u_exposure", output.exposure);
+        uniform("u_toneMapping", output.tone_mapping as u8 as f32);
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_frame") {
+            gl.uniform1i(Some(&loc), 0);
+        }
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.buffer));
+        let pos_loc = gl.get_attrib_location(&self.program, "a_position") as u32;
+        gl.enable_vertex_attrib_array(pos_loc);
+        gl.vertex_attrib_pointer_with_i32(pos_loc, 2, GL::FLOAT, false, 0, 0);
+        gl.draw_arrays(GL::TRIANGLES, 0, 3);
+    }
+
+    fn destroy(&self, gl: &GL) {
+        gl.delete_framebuffer(Some(&self.framebuffer));
+        gl.delete_texture(Some(&self.texture));
+        gl.delete_program(Some(&self.program));
+        gl.delete_buffer(Some(&self.buffer));
+    }
+}
+
+impl StarField {
+    /// Blends the sky in linear light and encodes it to sRGB at output, so
+    /// faint stars and gradients stay clean; `false` returns to blending in
+    /// gamma space. Fails, changing nothing, without a half-float or sRGB
+    /// render target.
+    pub fn set_linear_rendering(&mut self, enabled: bool) -> Result<(), String> {
+        self.set_output(Output { linear: enabled, ..self.output })
+    }
+
+    /// Compresses bright accumulations (bloom, bursts, dense showers) with
+    /// `tone_mapping` after multiplying by `exposure`, instead of clipping.
+    /// Brights only exceed 1 before mapping on half-float targets. Fails,
+    /// changing nothing, without a half-float or sRGB render target.
+    pub fn set_tone_mapping(&mut self, tone_mapping: ToneMapping, exposure: f32) -> Result<(), String> {
+        self.set_output(Output { tone_mapping, exposure, ..self.output })
+    }
+
+    fn set_output(&mut self, output: Output) -> Result<(), String> {
+        match (output.needs_composite(), &self.composite) {
+            (true, None) => self.composite = Some(Composite::new(&self.gl, self.resolution)?),
+            (false, Some(_)) => {
+                if let Some(composite) = self.composite.take() {
+                    composite.destroy(&self.gl);
+                }
+            }
+            _ => {}
+        }
+        self.output = output;
+        Ok(())
+    }
+
+    // Reallocates the offscreen frame at the canvas's new size, falling back
+    // to plain gamma output if that fails.
+    pub(super) fn resize_composite(&mut self) {
+        let Some(composite) = &self.composite else {
+            return;
+        };
+        if let Err(error) = composite.allocate(&self.gl, self.resolution) {
+            log(Level::Warn, || format!("Linear rendering and tone mapping turned off: {}", error));
+            let _ = self.set_output(Output::GAMMA);
+        }
+    }
+}
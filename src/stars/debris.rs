@@ -0,0 +1,116 @@
+use soj_sim::debris::{DebrisRing, Speck};
+use soj_sim::Rng;
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGlBuffer, WebGlProgram, WebGlRenderingContext as GL};
+
+use crate::options::{color_option, get_option, get_option_string, invalid_option, number_option};
+
+use super::effect::{Effect, EffectContext};
+use super::gl::{draw_points, upload_vertices};
+use super::layer::Layer;
+use super::sim::ThemeTint;
+use super::MAX_PARTICLES;
+
+// A debris ring described from JavaScript.
+pub(super) struct DebrisDescriptor {
+    ring: DebrisRing,
+    count: usize,
+    // Speck diameter in CSS pixels.
+    size: f32,
+    alpha: f32,
+    color: [f32; 3],
+    pub(super) layer: Layer,
+}
+
+impl DebrisDescriptor {
+    pub(super) fn from_js(options: &JsValue) -> Result<DebrisDescriptor, JsValue> {
+        let missing = options.is_undefined() || options.is_null();
+        if !missing && (!options.is_object() || js_sys::Array::is_array(options)) {
+            return Err(invalid_option("options", "an object", options));
+        }
+        let layer = match get_option_string(options, "layer") {
+            Some(name) => Layer::from_name(&name).ok_or_else(|| invalid_option("layer", Layer::NAMES, &name.into()))?,
+            None => Layer::Nebula,
+        };
+        let default = DebrisRing::DEFAULT;
+        let degrees = |key: &str, fallback: f32| -> Result<f32, JsValue> {
+            Ok(number_option(options, key, -720.0, 720.0)?.map_or(fallback, f32::to_radians))
+        };
+        let arc = (degrees("arcStart", default.arc.0)?, degrees("arcEnd", default.arc.1)?);
+        if arc.1 <= arc.0 || arc.1 - arc.0 > std::f32::consts::TAU + 0.001 {
+            let value = get_option(options, "arcEnd").unwrap_or(JsValue::UNDEFINED);
+            return Err(invalid_option("arcEnd", "a number of degrees after arcStart, at most 360 later", &value));
+        }
+        let ring = DebrisRing {
+            center: (
+                number_option(options, "x", -1.0, 2.0)?.unwrap_or(default.center.0),
+                number_option(options, "y", -1.0, 2.0)?.unwrap_or(default.center.1),
+            ),
+            radius: number_option(options, "radius", 0.0, 3.0)?.unwrap_or(default.radius),
+            flatten: number_option(options, "flatten", 0.0, 1.0)?.unwrap_or(default.flatten),
+            rotation: degrees("rotation", default.rotation)?,
+            arc,
+            thickness: number_option(options, "thickness", 0.0, 2.0)?.unwrap_or(default.thickness),
+            // Degrees per second in, radians per frame kept.
+            speed: number_option(options, "speed", -360.0, 360.0)?
+                .map_or(default.speed, |speed| speed.to_radians() / 60.0),
+        };
+        Ok(DebrisDescriptor {
+            ring,
+            count: number_option(options, "density", 0.0, MAX_PARTICLES as f32)?.unwrap_or(800.0) as usize,
+            size: number_option(options, "size", 0.0, 16.0)?.unwrap_or(1.5),
+            alpha: number_option(options, "alpha", 0.0, 1.0)?.unwrap_or(0.3),
+            color: color_option(options, "color")?.unwrap_or([0.75, 0.8, 0.9]),
+            layer,
+        })
+    }
+}
+
+pub(super) struct Debris {
+    descriptor: DebrisDescriptor,
+    specks: Vec<Speck>,
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+}
+
+impl Debris {
+    pub(super) fn new(
+        gl: &GL,
+        program: WebGlProgram,
+        descriptor: DebrisDescriptor,
+        rng: &mut Rng,
+    ) -> Result<Debris, String> {
+        let buffer = gl.create_buffer().ok_or("Failed to create debris buffer")?;
+        let specks = descriptor.ring.scatter(rng, descriptor.count);
+        Ok(Debris { descriptor, specks, program, buffer })
+    }
+}
+
+impl Effect for Debris {
+    fn update(&mut self, context: &EffectContext, dt: f32) {
+        let d = &self.descriptor;
+        d.ring.step(&mut self.specks, dt);
+        let size = d.size * context.size_scale;
+        let mut data = Vec::with_capacity(self.specks.len() * 7);
+        for speck in &self.specks {
+            let (x, y, fade) = d.ring.place(speck, context.resolution);
+            let alpha = d.alpha * fade * context.exclusion(x, y);
+            data.extend_from_slice(&[x, y, size, alpha, d.color[0], d.color[1], d.color[2]]);
+        }
+        upload_vertices(&context.gl, &self.buffer, &data, GL::DYNAMIC_DRAW);
+    }
+
+    fn draw(&self, context: &EffectContext) {
+        draw_points(context, &self.program, &self.buffer, self.specks.len(), ThemeTint::NONE.star, 0.0);
+    }
+
+    fn transfer(&mut self, context: &EffectContext) -> Result<(), String> {
+        self.program = context.star_program.clone();
+        self.buffer = context.gl.create_buffer().ok_or("Failed to create debris buffer")?;
+        Ok(())
+    }
+
+    fn destroy(&mut self, gl: &GL) {
+        gl.delete_buffer(Some(&self.buffer));
+    }
+}
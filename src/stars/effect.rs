@@ -0,0 +1,197 @@
+use std::rc::Rc;
+
+use soj_sim::zones::{attenuation, ExclusionZone};
+use web_sys::{WebGlProgram, WebGlRenderingContext as GL};
+
+use super::gl::Background;
+use super::layer::Layer;
+use super::sim::{Meteors, SkyPalette, Stars, ThemeTint};
+use super::StarField;
+
+/// What every effect sees of the sky on a given frame.
+#[derive(Clone)]
+pub struct EffectContext {
+    pub gl: GL,
+    /// Canvas size in device pixels; positions are in this space.
+    pub resolution: (f32, f32),
+    /// Device pixels per CSS pixel, or 1 with raw pixel sizing. Sizes
+    /// authored in CSS pixels are multiplied by this.
+    pub size_scale: f32,
+    /// Extra factor for `gl_PointSize`, above 1 when rendering a poster
+    /// larger than the canvas.
+    pub point_scale: f32,
+    /// The day or night sky, as currently faded.
+    pub sky: SkyPalette,
+    /// The judge-status or difficulty tint, as currently faded.
+    pub theme: ThemeTint,
+    /// Alpha and size multipliers of the judging "breath"; 1 when still.
+    pub pulse_alpha: f32,
+    pub pulse_size: f32,
+    /// Sky time in seconds since start. Follows the time scale, so it
+    /// stands still while the sky is paused.
+    pub time: f32,
+    /// The drawing layer's opacity, which `draw` should multiply into its
+    /// (premultiplied) output. 1 outside `draw`.
+    pub opacity: f32,
+    /// Whether the frame blends in linear light (see
+    /// `set_linear_rendering`). Built-in shaders then decode their sRGB
+    /// output; effects with their own shaders should do the same.
+    pub linear: bool,
+    /// Frames of motion to smear fast things over (see `set_motion_blur`);
+    /// 0 when off or paused.
+    pub motion_blur: f32,
+    /// Screen rectangles bright effects dim inside (see
+    /// `set_exclusion_zones`), in device pixels.
+    pub exclusion_zones: Rc<[ExclusionZone]>,
+    /// The sky's intensity (see `set_intensity`), 0 to 1, already applied
+    /// to `opacity`; effects that spawn things should scale their rate by it.
+    pub intensity: f32,
+    // The shared point-sprite program built-in effects draw with.
+    pub(super) star_program: WebGlProgram,
+}
+
+impl EffectContext {
+    /// What to multiply a bright effect's alpha by at (`x`, `y`) to keep it
+    /// out of the exclusion zones.
+    pub fn exclusion(&self, x: f32, y: f32) -> f32 {
+        attenuation(&self.exclusion_zones, x, y)
+    }
+}
+
+/// Something drawn in the sky. The background, stars and meteors are
+/// effects themselves; `register_effect` adds more (aurora, snow, ...) to
+/// any `Layer` without touching `StarField`.
+pub trait Effect {
+    /// Creates GL resources, once, when the effect is registered.
+    fn init(&mut self, _context: &EffectContext) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Advances the effect by `dt` frames at 60 fps, already scaled by the
+    /// time scale.
+    fn update(&mut self, context: &EffectContext, dt: f32);
+
+    /// Draws the effect with premultiplied-alpha blending enabled. May run
+    /// more than once per update, e.g. for captures.
+    fn draw(&self, context: &EffectContext);
+
+    /// Called after the canvas changes size from `old_resolution`.
+    fn resize(&mut self, _context: &EffectContext, _old_resolution: (f32, f32)) {}
+
+    /// Recreates GL resources on `context.gl`, keeping the effect's state,
+    /// after `transfer_to_canvas` moves the sky to another canvas. The old
+    /// context's resources are gone with it. Effects that fail, or leave
+    /// this unimplemented, are removed.
+    fn transfer(&mut self, _context: &EffectContext) -> Result<(), String> {
+        Err("The effect cannot move to another canvas".to_string())
+    }
+
+    /// Frees GL resources when the effect is removed.
+    fn destroy(&mut self, _gl: &GL) {}
+}
+
+pub(super) struct EffectSlot {
+    pub(super) id: u32,
+    pub(super) layer: Layer,
+    pub(super) effect: Box<dyn Effect>,
+}
+
+impl StarField {
+    /// Adds `effect` to `layer`, drawn after the layer's built-in content,
+    /// and returns an id for `remove_effect`. Fails if the effect's `init`
+    /// does.
+    pub fn register_effect(&mut self, layer: Layer, mut effect: Box<dyn Effect>) -> Result<u32, String> {
+        effect.init(&self.effect_context(1.0))?;
+        self.next_effect_id += 1;
+        self.effects.push(EffectSlot { id: self.next_effect_id, layer, effect });
+        Ok(self.next_effect_id)
+    }
+
+    /// Removes a registered effect and frees its GL resources. Returns
+    /// whether `id` was registered.
+    pub fn remove_effect(&mut self, id: u32) -> bool {
+        let Some(index) = self.effects.iter().position(|slot| slot.id == id) else {
+            return false;
+        };
+        let mut slot = self.effects.remove(index);
+        slot.effect.destroy(&self.gl);
+        true
+    }
+
+    pub(super) fn effect_context(&self, point_scale: f32) -> EffectContext {
+        let (pulse_alpha, pulse_size) = self.pulse_factors();
+        EffectContext {
+            gl: self.gl.clone(),
+            resolution: self.resolution,
+            size_scale: self.size_scale(),
+            point_scale,
+            sky: self.sky,
+            theme: self.theme,
+            pulse_alpha,
+            pulse_size,
+            time: self.clock.seconds(),
+            opacity: 1.0,
+            linear: false,
+            motion_blur: self.motion_blur * self.last_dt,
+            exclusion_zones: self.exclusion_zones.clone(),
+            intensity: self.intensity,
+            star_program: self.star_program.clone(),
+        }
+    }
+
+    // Built-in effects, then registered ones.
+    pub(super) fn layers_mut(&mut self) -> impl Iterator<Item = &mut (dyn Effect + 'static)> {
+        let builtin: [&mut (dyn Effect + 'static); 3] = [&mut self.background, &mut self.stars, &mut self.meteors];
+        let streak = self.solve_streak.as_mut().map(|streak| streak as &mut (dyn Effect + 'static));
+        builtin.into_iter().chain(streak).chain(self.effects.iter_mut().map(|slot| slot.effect.as_mut()))
+    }
+}
+
+impl Effect for Background {
+    // The gradient is drawn from the current sky each frame.
+    fn update(&mut self, _context: &EffectContext, _dt: f32) {}
+
+    fn draw(&self, context: &EffectContext) {
+        self.render(context);
+    }
+
+    fn destroy(&mut self, gl: &GL) {
+        gl.delete_buffer(Some(&self.buffer));
+        gl.delete_program(Some(&self.program));
+    }
+}
+
+impl Effect for Stars {
+    fn update(&mut self, context: &EffectContext, dt: f32) {
+        self.step(context, dt);
+        self.upload(context);
+    }
+
+    fn draw(&self, context: &EffectContext) {
+        self.render(context);
+    }
+
+    fn destroy(&mut self, gl: &GL) {
+        gl.delete_buffer(Some(&self.buffer));
+        gl.delete_buffer(Some(&self.quad_buffer));
+        gl.delete_program(self.quad_program.as_ref());
+    }
+}
+
+impl Effect for Meteors {
+    fn update(&mut self, context: &EffectContext, dt: f32) {
+        self.step(context, dt);
+        self.upload(context);
+    }
+
+    fn draw(&self, context: &EffectContext) {
+        self.render(context);
+    }
+
+    fn destroy(&mut self, gl: &GL) {
+        gl.delete_buffer(Some(&self.buffer));
+        gl.delete_program(Some(&self.program));
+        gl.delete_buffer(Some(&self.glow_buffer));
+        gl.delete_program(Some(&self.glow_program));
+    }
+}
@@ -0,0 +1,83 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{window, HtmlElement};
+
+use crate::options::invalid_option;
+use crate::diagnostics::{log, Level};
+
+use super::api::set_plexus;
+use super::builder::DEFAULT_STARS;
+use super::mount::{is_mounted_in, mount_in, unmount};
+use super::{ColorScheme, StarFieldOptions, MAX_STARS};
+
+// Custom elements must be classes extending `HTMLElement`, which Rust
+// cannot declare; this shim forwards the lifecycle callbacks to closures.
+const ELEMENT_CLASS: &str = r#"
+    return class extends HTMLElement {
+        static get observedAttributes() { return ["stars", "preset", "interactive"]; }
+        connectedCallback() { connected(this); }
+        disconnectedCallback() { disconnected(this); }
+        attributeChangedCallback() { if (this.isConnected) connected(this); }
+    };
+"#;
+
+/// Defines the `<star-field>` custom element (or `name`, which must contain
+/// a hyphen). Each element, while in the document, shows the starfield
+/// behind its content, restarting when an attribute changes: `stars` (star
+/// count, default 1000), `preset` (`"auto"`, `"dark"` or `"light"`, as for
+/// `set_color_scheme`) and `interactive` (present to link stars near the
+/// pointer, see `set_plexus`). There is one starfield per page, so the
+/// element connected last shows it. Defining a name twice does nothing.
+#[wasm_bindgen]
+pub fn define_star_field_element(name: Option<String>) -> Result<(), JsValue> {
+    let name = name.unwrap_or_else(|| "star-field".to_string());
+    if !name.contains('-') {
+        return Err(invalid_option("name", "a custom element name containing a hyphen", &name.into()));
+    }
+    let registry = window().unwrap().custom_elements();
+    if !registry.get(&name).is_undefined() {
+        return Ok(());
+    }
+    let connected = Closure::wrap(Box::new(|element: HtmlElement| {
+        if let Err(error) = connect(&element) {
+            log(Level::Warn, || format!("<{}> failed to start: {:?}", element.local_name(), error));
+        }
+    }) as Box<dyn FnMut(HtmlElement)>);
+    let disconnected = Closure::wrap(Box::new(|element: HtmlElement| {
+        if is_mounted_in(&element) {
+            unmount();
+        }
+    }) as Box<dyn FnMut(HtmlElement)>);
+    let class = js_sys::Function::new_with_args("connected, disconnected", ELEMENT_CLASS)
+        .call2(&JsValue::NULL, connected.as_ref(), disconnected.as_ref())?;
+    connected.forget();
+    disconnected.forget();
+    registry.define(&name, class.unchecked_ref())
+}
+
+fn connect(element: &HtmlElement) -> Result<(), JsValue> {
+    let num_stars = match element.get_attribute("stars") {
+        Some(stars) => stars
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|&stars| stars <= MAX_STARS)
+            .ok_or_else(|| invalid_option("stars", &format!("a star count from 0 to {}", MAX_STARS), &stars.into()))?,
+        None => DEFAULT_STARS,
+    };
+    let color_scheme = ColorScheme::from_js(element.get_attribute("preset").map(JsValue::from))?;
+    // Custom elements are inline by default, which leaves no box to fill.
+    let display = window().unwrap().get_computed_style(element)?.map(|style| style.get_property_value("display"));
+    if let Some(Ok(display)) = display {
+        if display == "inline" {
+            element.style().set_property("display", "block")?;
+        }
+    }
+    let options = StarFieldOptions { color_scheme, ..StarFieldOptions::default() };
+    mount_in(element.clone(), num_stars, &options, -1)?;
+    if element.has_attribute("interactive") {
+        // Skipped while startup waits for layout or after a fallback.
+        let _ = set_plexus(JsValue::TRUE);
+    }
+    Ok(())
+}
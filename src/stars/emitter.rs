@@ -0,0 +1,311 @@
+use soj_sim::{ParticleStore, Rng, Spawn};
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGlBuffer, WebGlProgram, WebGlRenderingContext as GL};
+
+use crate::options::{color_option, get_option, get_option_string, invalid_option, number_option, parse_hex_color};
+
+use super::effect::{Effect, EffectContext};
+use super::gl::{draw_points, upload_vertices};
+use super::layer::Layer;
+use super::sim::ThemeTint;
+use super::MAX_PARTICLES;
+
+const MAX_CURVE_POINTS: usize = 16;
+const DEFAULT_EMITTER_PARTICLES: f32 = 1_000.0;
+
+// Where particles appear. Positions are fractions of the canvas; a circle's
+// radius is a fraction of its shorter side.
+enum EmitterShape {
+    Point { x: f32, y: f32 },
+    Rect { x: f32, y: f32, width: f32, height: f32 },
+    Circle { x: f32, y: f32, radius: f32 },
+}
+
+impl EmitterShape {
+    fn from_js(descriptor: &JsValue) -> Result<EmitterShape, JsValue> {
+        let x = number_option(descriptor, "x", -1.0, 2.0)?.unwrap_or(0.5);
+        let y = number_option(descriptor, "y", -1.0, 2.0)?.unwrap_or(0.5);
+        match get_option_string(descriptor, "shape").as_deref() {
+            None | Some("point") => Ok(EmitterShape::Point { x, y }),
+            Some("rect") => {
+                let width = number_option(descriptor, "width", 0.0, 3.0)?.unwrap_or(1.0);
+                let height = number_option(descriptor, "height", 0.0, 3.0)?.unwrap_or(1.0);
+                Ok(EmitterShape::Rect { x, y, width, height })
+            }
+            Some("circle") => {
+                let radius = number_option(descriptor, "radius", 0.0, 3.0)?.unwrap_or(0.1);
+                Ok(EmitterShape::Circle { x, y, radius })
+            }
+            Some(_) => Err(invalid_option(
+                "shape",
+                "\"point\", \"rect\" or \"circle\"",
+                &get_option(descriptor, "shape").unwrap(),
+            )),
+        }
+    }
+
+    fn sample(&self, (width, height): (f32, f32), rng: &mut Rng) -> (f32, f32) {
+        let mut random = || rng.next_f32();
+        match *self {
+            EmitterShape::Point { x, y } => (x * width, y * height),
+            EmitterShape::Rect { x, y, width: w, height: h } => {
+                ((x + (random() - 0.5) * w) * width, (y + (random() - 0.5) * h) * height)
+            }
+            EmitterShape::Circle { x, y, radius } => {
+                // sqrt spreads points evenly over the disc instead of bunching them at the center.
+                let r = radius * width.min(height) * random().sqrt();
+                let angle = random() * std::f32::consts::TAU;
+                (x * width + r * angle.cos(), y * height + r * angle.sin())
+            }
+        }
+    }
+}
+
+// An ambient effect described from JavaScript. Rates and speeds are kept
+// per frame at 60 fps, like the rest of the simulation.
+pub(super) struct EmitterDescriptor {
+    shape: EmitterShape,
+    rate: f32,
+    max_particles: usize,
+    lifetime: (f32, f32),
+    // CSS pixels per frame.
+    speed: (f32, f32),
+    // Radians clockwise from rightward.
+    direction: (f32, f32),
+    // CSS pixels per frame squared.
+    gravity: f32,
+    // Curves over each particle's life, evenly spaced keyframes.
+    size: Vec<f32>,
+    alpha: Vec<f32>,
+    color: Vec<[f32; 3]>,
+    velocity: Vec<f32>,
+    pub(super) layer: Layer,
+}
+
+impl EmitterDescriptor {
+    pub(super) fn from_js(descriptor: &JsValue) -> Result<EmitterDescriptor, JsValue> {
+        let descriptor = match descriptor.as_string() {
+            Some(json) => js_sys::JSON::parse(&json)
+                .map_err(|_| invalid_option("descriptor", "an object or a JSON object string", descriptor))?,
+            None => descriptor.clone(),
+        };
+        if !descriptor.is_object() || js_sys::Array::is_array(&descriptor) {
+            return Err(invalid_option("descriptor", "an object or a JSON object string", &descriptor));
+        }
+        let descriptor = &descriptor;
+        let layer = match get_option_string(descriptor, "layer") {
+            Some(name) => Layer::from_name(&name).ok_or_else(|| invalid_option("layer", Layer::NAMES, &name.into()))?,
+            None => Layer::Foreground,
+        };
+        let per_second = |(min, max): (f32, f32)| (min / 60.0, max / 60.0);
+        Ok(EmitterDescriptor {
+            shape: EmitterShape::from_js(descriptor)?,
+            rate: number_option(descriptor, "rate", 0.0, 1_000.0)?.unwrap_or(10.0) / 60.0,
+            max_particles: number_option(descriptor, "maxParticles", 1.0, MAX_PARTICLES as f32)?
+                .unwrap_or(DEFAULT_EMITTER_PARTICLES) as usize,
+            lifetime: range_option(descriptor, "lifetime", 0.05, 60.0)?
+                .map_or((120.0, 120.0), |(min, max)| (min * 60.0, max * 60.0)),
+            speed: per_second(range_option(descriptor, "speed", 0.0, 2_000.0)?.unwrap_or((30.0, 30.0))),
+            direction: range_option(descriptor, "direction", -360.0, 720.0)?
+                .map_or((0.0, std::f32::consts::TAU), |(min, max)| (min.to_radians(), max.to_radians())),
+            gravity: number_option(descriptor, "gravity", -2_000.0, 2_000.0)?.unwrap_or(0.0) / 3_600.0,
+            size: curve_option(descriptor, "size", 0.0, 256.0)?.unwrap_or_else(|| vec![3.0]),
+            alpha: curve_option(descriptor, "alpha", 0.0, 1.0)?.unwrap_or_else(|| vec![1.0, 0.0]),
+            color: color_curve_option(descriptor, "color")?.unwrap_or_else(|| vec![[1.0, 1.0, 1.0]]),
+            velocity: curve_option(descriptor, "velocity", -10.0, 10.0)?.unwrap_or_else(|| vec![1.0]),
+            layer,
+        })
+    }
+}
+
+// A number, or a `[min, max]` pair picked from uniformly per particle.
+fn range_option(options: &JsValue, key: &str, min: f32, max: f32) -> Result<Option<(f32, f32)>, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(None);
+    };
+    let expected = || format!("a number or [min, max] from {} to {}", min, max);
+    let in_range = |v: JsValue| v.as_f64().map(|v| v as f32).filter(|v| (min..=max).contains(v));
+    if let Some(number) = in_range(value.clone()) {
+        return Ok(Some((number, number)));
+    }
+    if js_sys::Array::is_array(&value) {
+        let pair = js_sys::Array::from(&value);
+        if pair.length() == 2 {
+            if let (Some(lo), Some(hi)) = (in_range(pair.get(0)), in_range(pair.get(1))) {
+                if lo <= hi {
+                    return Ok(Some((lo, hi)));
+                }
+            }
+        }
+    }
+    Err(invalid_option(key, &expected(), &value))
+}
+
+// A number, or up to `MAX_CURVE_POINTS` numbers spread evenly over a
+// particle's life.
+fn curve_option(options: &JsValue, key: &str, min: f32, max: f32) -> Result<Option<Vec<f32>>, JsValue> {
+    let Some(value) = get_option(options, key) else {
+        return Ok(None);
+    };
+    let expected = || format!("a number or an array of up to {} numbers from {} to {}", MAX_CURVE_POINTS, min, max);
+    let in_range = |v: JsValue| v.as_f64().map(|v| v as f32).filter(|v| (min..=max).contains(v));
+    if let Some(number) = in_range(value.clone()) {
+        return Ok(Some(vec![number]));
+    }
+    let points = curve_points(&value).ok_or_else(|| invalid_option(key, &expected(), &value))?;
+    points
+        .into_iter()
+        .map(in_range)
+        .collect::<Option<Vec<f32>>>()
+        .map(Some)
+        .ok_or_else(|| invalid_option(key, &expected(), &value))
+}
+
+fn color_curve_option(options: &JsValue, key: &str) -> Result<Option<Vec<[f32; 3]>>, JsValue> {
+    if let Ok(Some(color)) = color_option(options, key) {
+        return Ok(Some(vec![color]));
+    }
+    let Some(value) = get_option(options, key) else {
+        return Ok(None);
+    };
+    let expected = || format!("a hex color or an array of up to {} hex colors", MAX_CURVE_POINTS);
+    let points = curve_points(&value).ok_or_else(|| invalid_option(key, &expected(), &value))?;
+    points
+        .into_iter()
+        .map(|v| v.as_string().and_then(|hex| parse_hex_color(&hex)))
+        .collect::<Option<Vec<[f32; 3]>>>()
+        .map(Some)
+        .ok_or_else(|| invalid_option(key, &expected(), &value))
+}
+
+fn curve_points(value: &JsValue) -> Option<Vec<JsValue>> {
+    if !js_sys::Array::is_array(value) {
+        return None;
+    }
+    let points: Vec<JsValue> = js_sys::Array::from(value).iter().collect();
+    (1..=MAX_CURVE_POINTS).contains(&points.len()).then_some(points)
+}
+
+// The keyframes around `t` (0 to 1 over a life) and how far between them.
+fn curve_position(len: usize, t: f32) -> (usize, usize, f32) {
+    let position = t.clamp(0.0, 1.0) * (len - 1) as f32;
+    let lo = position.floor() as usize;
+    (lo, (lo + 1).min(len - 1), position - lo as f32)
+}
+
+fn sample_curve(points: &[f32], t: f32) -> f32 {
+    let (lo, hi, f) = curve_position(points.len(), t);
+    points[lo] + (points[hi] - points[lo]) * f
+}
+
+fn sample_color_curve(points: &[[f32; 3]], t: f32) -> [f32; 3] {
+    let (lo, hi, f) = curve_position(points.len(), t);
+    [0, 1, 2].map(|c| points[lo][c] + (points[hi][c] - points[lo][c]) * f)
+}
+
+// Emitter-only components: the launch velocity, which the velocity curve
+// scales, and the speed gained from gravity so far.
+struct Motion {
+    vx: f32,
+    vy: f32,
+    fall: f32,
+}
+
+pub(super) struct Emitter {
+    descriptor: EmitterDescriptor,
+    particles: ParticleStore<Motion>,
+    // Fractional particles carried over to the next frame.
+    pending: f32,
+    rng: Rng,
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+}
+
+impl Emitter {
+    pub(super) fn new(
+        gl: &GL,
+        program: WebGlProgram,
+        descriptor: EmitterDescriptor,
+        rng: Rng,
+    ) -> Result<Emitter, String> {
+        let buffer = gl.create_buffer().ok_or("Failed to create emitter buffer")?;
+        let particles = ParticleStore::with_capacity(0);
+        Ok(Emitter { descriptor, particles, pending: 0.0, rng, program, buffer })
+    }
+
+    fn spawn(&mut self, context: &EffectContext) {
+        let d = &self.descriptor;
+        let rng = &mut self.rng;
+        let (x, y) = d.shape.sample(context.resolution, rng);
+        let mut random_in = |(min, max): (f32, f32)| rng.range((min, max));
+        let angle = random_in(d.direction);
+        let speed = random_in(d.speed) * context.size_scale;
+        let (vx, vy) = (speed * angle.cos(), speed * angle.sin());
+        self.particles.push(Spawn {
+            x,
+            y,
+            vx,
+            vy,
+            color: d.color[0],
+            size: d.size[0],
+            max_lifetime: random_in(d.lifetime),
+            data: Motion { vx, vy, fall: 0.0 },
+        });
+    }
+}
+
+impl Effect for Emitter {
+    fn update(&mut self, context: &EffectContext, dt: f32) {
+        self.pending += self.descriptor.rate * context.intensity * dt;
+        let count = self.pending.floor();
+        self.pending -= count;
+        let room = self.descriptor.max_particles.saturating_sub(self.particles.len());
+        for _ in 0..(count as usize).min(room) {
+            self.spawn(context);
+        }
+
+        let gravity = self.descriptor.gravity * context.size_scale;
+        let particles = &mut self.particles;
+        for i in 0..particles.len() {
+            let t = particles.lifetime[i] / particles.max_lifetime[i];
+            let scale = sample_curve(&self.descriptor.velocity, t);
+            let motion = &mut particles.data[i];
+            motion.fall += gravity * dt;
+            particles.vx[i] = motion.vx * scale;
+            particles.vy[i] = motion.vy * scale + motion.fall;
+        }
+        particles.integrate(dt);
+        particles.remove_expired();
+
+        let d = &self.descriptor;
+        let mut data = Vec::with_capacity(particles.len() * 7);
+        for i in 0..particles.len() {
+            let t = particles.lifetime[i] / particles.max_lifetime[i];
+            let color = sample_color_curve(&d.color, t);
+            data.extend_from_slice(&[
+                particles.x[i],
+                particles.y[i],
+                sample_curve(&d.size, t) * context.size_scale,
+                sample_curve(&d.alpha, t) * context.exclusion(particles.x[i], particles.y[i]),
+                color[0],
+                color[1],
+                color[2],
+            ]);
+        }
+        upload_vertices(&context.gl, &self.buffer, &data, GL::DYNAMIC_DRAW);
+    }
+
+    fn draw(&self, context: &EffectContext) {
+        draw_points(context, &self.program, &self.buffer, self.particles.len(), ThemeTint::NONE.star, 0.0);
+    }
+
+    fn transfer(&mut self, context: &EffectContext) -> Result<(), String> {
+        self.program = context.star_program.clone();
+        self.buffer = context.gl.create_buffer().ok_or("Failed to create emitter buffer")?;
+        Ok(())
+    }
+
+    fn destroy(&mut self, gl: &GL) {
+        gl.delete_buffer(Some(&self.buffer));
+    }
+}
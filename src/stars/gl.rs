@@ -0,0 +1,1308 @@
+use std::ops::Range;
+
+use soj_sim::meteors::{TRAIL_LENGTH, WIDTH as METEOR_WIDTH};
+use soj_sim::stars::scatter_stars;
+use soj_sim::zones::attenuation;
+use soj_sim::ParticleStore;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, WebGlBuffer, WebGlProgram, WebGlRenderingContext as GL, WebGlShader,
+    WebGlTexture,
+};
+
+use crate::diagnostics::{log, Level};
+
+use super::api::pixel_ratio;
+use super::effect::{Effect, EffectContext};
+use super::layer::Layer;
+use super::sim::{rotate_hue, sky_rng, CornerGradient, HueShift, Meteors, SkyPalette, StarRun, Stars, ThemeTint};
+use super::tween::{Lerp, Tween};
+use super::{
+    StarField, StarFieldOptions, DEBUG_OVERLAY_MARGIN, GUIDED_METEOR_SETTLE_FRAMES, GUIDED_TRAIL_SEGMENTS,
+    HEAD_GLOW_FLICKER, HEAD_GLOW_PULSES, HEAD_GLOW_SCALE, MAX_STREAK_LENGTH, POINT_SCALE, SKY_BOTTOM_COLOR,
+    SKY_TOP_COLOR, TICKER_MARGIN_SCALE,
+};
+
+#[derive(Clone, Copy)]
+pub(super) enum BlendMode {
+    Alpha,
+    // Overlapping glows add up toward white instead of covering each other.
+    Additive,
+}
+
+impl BlendMode {
+    pub(super) fn from_name(name: &str) -> Option<BlendMode> {
+        match name.to_ascii_lowercase().as_str() {
+            "alpha" | "normal" => Some(BlendMode::Alpha),
+            "additive" | "add" => Some(BlendMode::Additive),
+            _ => None,
+        }
+    }
+
+    // Shaders output premultiplied color, so the source factor is always ONE.
+    // Blending straight color with SRC_ALPHA left the framebuffer's color and
+    // alpha out of step, which the page compositor (expecting premultiplied
+    // pixels) showed as dark fringes around soft meteor trails.
+    pub(super) fn apply(self, gl: &GL) {
+        gl.enable(GL::BLEND);
+        let destination = match self {
+            BlendMode::Alpha => GL::ONE_MINUS_SRC_ALPHA,
+            BlendMode::Additive => GL::ONE,
+        };
+        gl.blend_func_separate(GL::ONE, destination, GL::ONE, GL::ONE_MINUS_SRC_ALPHA);
+    }
+}
+
+pub(super) fn create_context(canvas: &HtmlCanvasElement, transparent: bool) -> Result<GL, String> {
+    let context_options = js_sys::Object::new();
+    js_sys::Reflect::set(&context_options, &"alpha".into(), &transparent.into()).unwrap();
+    js_sys::Reflect::set(&context_options, &"premultipliedAlpha".into(), &true.into()).unwrap();
+    let gl: GL = canvas
+        .get_context_with_context_options("webgl", &context_options)
+        .ok()
+        .flatten()
+        .and_then(|context| context.dyn_into().ok())
+        .ok_or("WebGL is unavailable")?;
+    log(Level::Info, || {
+        let attributes = gl
+            .get_context_attributes()
+            .and_then(|attributes| js_sys::JSON::stringify(&attributes).ok())
+            .and_then(|json| json.as_string())
+            .unwrap_or_default();
+        let parameter = |name| gl.get_parameter(name).ok().and_then(|v| v.as_string()).unwrap_or_default();
+        format!(
+            "WebGL context: {} ({}), attributes {}",
+            parameter(GL::VERSION),
+            parameter(GL::RENDERER),
+            attributes
+        )
+    });
+    Ok(gl)
+}
+
+// Releases a context's GPU resources now rather than whenever its canvas is
+// collected; browsers cap how many live contexts a page may hold.
+pub(super) fn lose_context(gl: &GL) {
+    let Some(extension) = gl.get_extension("WEBGL_lose_context").ok().flatten() else {
+        return;
+    };
+    let lose = js_sys::Reflect::get(&extension, &"loseContext".into());
+    if let Ok(lose) = lose.and_then(|lose| lose.dyn_into::<js_sys::Function>()) {
+        lose.call0(&extension).ok();
+    }
+}
+
+const BACKGROUND_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_position;
+    varying vec2 v_uv;
+    void main() {
+        gl_Position = vec4(a_position, 0.0, 1.0);
+        v_uv = a_position * 0.5 + 0.5;
+    }
+"#;
+
+// The gradient is computed per fragment, at high precision where available,
+// and dithered by under one 8-bit step: interpolated vertex colors showed
+// stepped bands across large (4K) canvases. It blends four corner colors
+// bilinearly; a vertical sky gives both top corners the same color.
+const BACKGROUND_FRAGMENT_SHADER: &str = r#"
+    #ifdef GL_FRAGMENT_PRECISION_HIGH
+    precision highp float;
+    #else
+    precision mediump float;
+    #endif
+    uniform vec3 u_topLeft;
+    uniform vec3 u_topRight;
+    uniform vec3 u_bottomLeft;
+    uniform vec3 u_bottomRight;
+    uniform vec3 u_tint;
+    uniform float u_tintMix;
+    uniform float u_opacity;
+    varying vec2 v_uv;
+    #pragma to_linear
+    #pragma custom_color
+    void main() {
+        vec3 top = mix(u_topLeft, u_topRight, v_uv.x);
+        vec3 bottom = mix(u_bottomLeft, u_bottomRight, v_uv.x);
+        vec3 color = mix(mix(bottom, top, v_uv.y), u_tint, u_tintMix);
+        // Interleaved gradient noise, even enough not to read as grain.
+        float noise = fract(52.9829189 * fract(dot(gl_FragCoord.xy, vec2(0.06711056, 0.00583715))));
+        color += (noise - 0.5) / 255.0;
+        gl_FragColor = to_linear(custom_color(vec4(color * u_opacity, u_opacity)));
+    }
+"#;
+
+const STAR_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_position;
+    attribute float a_pointSize;
+    attribute float a_alpha;
+    attribute vec3 a_color;
+    uniform vec2 u_resolution;
+    uniform float u_pointScale;
+    varying float v_alpha;
+    varying vec3 v_color;
+    void main() {
+        vec2 zeroToOne = a_position / u_resolution;
+        vec2 zeroToTwo = zeroToOne * 2.0;
+        vec2 clipSpace = zeroToTwo - 1.0;
+        clipSpace.y = -clipSpace.y;
+        gl_Position = vec4(clipSpace, 0.0, 1.0);
+        gl_PointSize = a_pointSize * u_pointScale;
+        v_alpha = a_alpha;
+        v_color = a_color;
+    }
+"#;
+
+const STAR_FRAGMENT_SHADER: &str = r#"
+    precision mediump float;
+    uniform vec3 u_tint;
+    uniform float u_tintMix;
+    uniform float u_opacity;
+    varying float v_alpha;
+    varying vec3 v_color;
+    #pragma to_linear
+    #pragma custom_color
+    void main() {
+        vec3 color = mix(v_color, u_tint, u_tintMix);
+        float alpha = v_alpha * u_opacity;
+        gl_FragColor = to_linear(custom_color(vec4(color * alpha, alpha)));
+    }
+"#;
+
+// Stars too big for the GPU's points, as two triangles each. The star
+// fragment shader reads `v_pointCoord` in place of `gl_PointCoord`.
+const STAR_QUAD_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_position;
+    attribute float a_alpha;
+    attribute vec3 a_color;
+    attribute vec2 a_pointCoord;
+    uniform vec2 u_resolution;
+    varying float v_alpha;
+    varying vec3 v_color;
+    varying vec2 v_pointCoord;
+    void main() {
+        vec2 zeroToOne = a_position / u_resolution;
+        vec2 zeroToTwo = zeroToOne * 2.0;
+        vec2 clipSpace = zeroToTwo - 1.0;
+        clipSpace.y = -clipSpace.y;
+        gl_Position = vec4(clipSpace, 0.0, 1.0);
+        v_alpha = a_alpha;
+        v_color = a_color;
+        v_pointCoord = a_pointCoord;
+    }
+"#;
+
+const METEOR_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_position;
+    attribute float a_alpha;
+    attribute vec3 a_color;
+    uniform vec2 u_resolution;
+    varying float v_alpha;
+    varying vec3 v_color;
+    void main() {
+        vec2 zeroToOne = a_position / u_resolution;
+        vec2 zeroToTwo = zeroToOne * 2.0;
+        vec2 clipSpace = zeroToTwo - 1.0;
+        clipSpace.y = -clipSpace.y;
+        gl_Position = vec4(clipSpace, 0.0, 1.0);
+        v_alpha = a_alpha;
+        v_color = a_color;
+    }
+"#;
+
+const METEOR_FRAGMENT_SHADER: &str = r#"
+    precision mediump float;
+    uniform float u_opacity;
+    varying float v_alpha;
+    varying vec3 v_color;
+    #pragma to_linear
+    void main() {
+        float dist = length(gl_PointCoord - vec2(0.5));
+        float factor = smoothstep(0.5, 0.0, dist);
+        float alpha = v_alpha * factor * u_opacity;
+        gl_FragColor = to_linear(vec4(v_color * alpha, alpha));
+    }
+"#;
+
+// Sparks smeared along their velocity by `u_blur` frames of motion. The
+// sprite grows to hold the streak, centered between where the spark is and
+// where it was, and the fragment shader keeps a capsule around that path.
+const STREAK_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_position;
+    attribute float a_pointSize;
+    attribute float a_alpha;
+    attribute vec3 a_color;
+    attribute vec2 a_velocity;
+    uniform vec2 u_resolution;
+    uniform float u_pointScale;
+    uniform float u_blur;
+    uniform float u_maxStreak;
+    varying float v_alpha;
+    varying vec3 v_color;
+    varying vec2 v_halfStreak;
+    varying float v_size;
+    void main() {
+        vec2 streak = a_velocity * u_blur;
+        float streakLength = length(streak);
+        if (streakLength > u_maxStreak) {
+            streak *= u_maxStreak / streakLength;
+        }
+        vec2 zeroToOne = (a_position - streak * 0.5) / u_resolution;
+        vec2 clipSpace = zeroToOne * 2.0 - 1.0;
+        clipSpace.y = -clipSpace.y;
+        gl_Position = vec4(clipSpace, 0.0, 1.0);
+        v_size = a_pointSize * u_pointScale;
+        v_halfStreak = streak * 0.5 * u_pointScale;
+        gl_PointSize = v_size + 2.0 * length(v_halfStreak);
+        v_alpha = a_alpha;
+        v_color = a_color;
+    }
+"#;
+
+const STREAK_FRAGMENT_SHADER: &str = r#"
+    precision mediump float;
+    uniform float u_opacity;
+    varying float v_alpha;
+    varying vec3 v_color;
+    varying vec2 v_halfStreak;
+    varying float v_size;
+    #pragma to_linear
+    void main() {
+        // Both y down, like canvas pixels.
+        vec2 p = (gl_PointCoord - 0.5) * (v_size + 2.0 * length(v_halfStreak));
+        vec2 path = 2.0 * v_halfStreak;
+        float along = clamp(dot(p + v_halfStreak, path) / max(dot(path, path), 0.0001), 0.0, 1.0);
+        float dist = length(p + v_halfStreak - path * along);
+        float coverage = clamp(v_size * 0.5 - dist + 0.5, 0.0, 1.0);
+        float alpha = v_alpha * coverage * u_opacity;
+        gl_FragColor = to_linear(vec4(v_color * alpha, alpha));
+    }
+"#;
+
+// Meteor vertices drawn as plain lines, which have no point coordinate.
+const LINE_FRAGMENT_SHADER: &str = r#"
+    precision mediump float;
+    uniform float u_opacity;
+    varying float v_alpha;
+    varying vec3 v_color;
+    #pragma to_linear
+    void main() {
+        float alpha = v_alpha * u_opacity;
+        gl_FragColor = to_linear(vec4(v_color * alpha, alpha));
+    }
+"#;
+
+// Where `set_custom_shader` splices its snippet into a fragment shader.
+const CUSTOM_COLOR_MARKER: &str = "#pragma custom_color";
+pub(super) const DEFAULT_CUSTOM_COLOR: &str = "vec4 custom_color(vec4 color) { return color; }";
+
+// A premultiplied texture stretched over the viewport.
+const TEXTURE_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_position;
+    varying vec2 v_uv;
+    void main() {
+        gl_Position = vec4(a_position, 0.0, 1.0);
+        v_uv = a_position * 0.5 + 0.5;
+    }
+"#;
+
+const TEXTURE_FRAGMENT_SHADER: &str = r#"
+    precision mediump float;
+    uniform sampler2D u_texture;
+    uniform float u_opacity;
+    varying vec2 v_uv;
+    #pragma to_linear
+    void main() {
+        gl_FragColor = to_linear(texture2D(u_texture, v_uv) * u_opacity);
+    }
+"#;
+
+// Decodes premultiplied sRGB output to linear light while `u_linear` is set,
+// for blending into the linear frame of `set_linear_rendering`.
+const TO_LINEAR_MARKER: &str = "#pragma to_linear";
+const TO_LINEAR: &str = r#"
+    uniform float u_linear;
+    vec4 to_linear(vec4 color) {
+        if (u_linear < 0.5 || color.a <= 0.0) {
+            return color;
+        }
+        vec3 c = clamp(color.rgb / color.a, 0.0, 1.0);
+        vec3 decoded = mix(c / 12.92, pow((c + 0.055) / 1.055, vec3(2.4)), step(0.04045, c));
+        return vec4(decoded * color.a, color.a);
+    }
+"#;
+
+// `#line 1` makes compile errors in the snippet point at its own lines.
+fn with_custom_color(source: &str, snippet: &str) -> String {
+    source.replace(CUSTOM_COLOR_MARKER, &format!("#line 1\n{}\n", snippet))
+}
+
+pub(super) fn build_program(
+    gl: &GL,
+    name: &str,
+    vertex_source: &str,
+    fragment_source: &str,
+) -> Result<WebGlProgram, String> {
+    let fragment_source = &fragment_source.replace(TO_LINEAR_MARKER, TO_LINEAR);
+    let vertex_shader = compile_shader(gl, GL::VERTEX_SHADER, vertex_source)
+        .map_err(|e| format!("{} vertex shader compile error: {}", name, e))?;
+    let fragment_shader = compile_shader(gl, GL::FRAGMENT_SHADER, fragment_source)
+        .map_err(|e| format!("{} fragment shader compile error: {}", name, e))?;
+    link_program(gl, &vertex_shader, &fragment_shader).map_err(|e| format!("{} program link error: {}", name, e))
+}
+
+// Background gradient, point sprites (stars and particles) and meteor trails.
+pub(super) fn create_programs(gl: &GL) -> Result<(WebGlProgram, WebGlProgram, WebGlProgram), String> {
+    let background_fragment_shader = with_custom_color(BACKGROUND_FRAGMENT_SHADER, DEFAULT_CUSTOM_COLOR);
+    let background_program = build_program(gl, "Background", BACKGROUND_VERTEX_SHADER, &background_fragment_shader)?;
+    let star_fragment_shader = with_custom_color(STAR_FRAGMENT_SHADER, DEFAULT_CUSTOM_COLOR);
+    let star_program = build_program(gl, "Star", STAR_VERTEX_SHADER, &star_fragment_shader)?;
+    let meteor_program = build_program(gl, "Meteor", METEOR_VERTEX_SHADER, METEOR_FRAGMENT_SHADER)?;
+    Ok((background_program, star_program, meteor_program))
+}
+
+// Star quads laid out as (x, y, alpha, r, g, b, u, v) per vertex, with the
+// star fragment shader around `snippet`.
+pub(super) fn create_star_quad_program(gl: &GL, snippet: &str) -> Result<WebGlProgram, String> {
+    let fragment_shader = with_custom_color(STAR_FRAGMENT_SHADER, snippet)
+        .replace("gl_PointCoord", "v_pointCoord")
+        .replacen("varying vec3 v_color;", "varying vec3 v_color;\n    varying vec2 v_pointCoord;", 1);
+    build_program(gl, "Star quad", STAR_QUAD_VERTEX_SHADER, &fragment_shader)
+}
+
+// The largest point size, in pixels, the GPU draws without clamping.
+pub(super) fn max_point_size(gl: &GL) -> f32 {
+    gl.get_parameter(GL::ALIASED_POINT_SIZE_RANGE)
+        .ok()
+        .and_then(|range| range.dyn_into::<js_sys::Float32Array>().ok())
+        .map_or(f32::MAX, |range| range.get_index(1))
+}
+
+// Bokeh motes: a flat disc with a soft rim, like an out-of-focus highlight.
+const BOKEH_CUSTOM_COLOR: &str = r#"
+    vec4 custom_color(vec4 color) {
+        float distance = length(gl_PointCoord - 0.5) * 2.0;
+        return color * (1.0 - smoothstep(0.55, 1.0, distance));
+    }
+"#;
+
+// Points laid out like stars, drawn as soft discs.
+pub(super) fn create_bokeh_program(gl: &GL) -> Result<WebGlProgram, String> {
+    let fragment_shader = with_custom_color(STAR_FRAGMENT_SHADER, BOKEH_CUSTOM_COLOR);
+    build_program(gl, "Bokeh", STAR_VERTEX_SHADER, &fragment_shader)
+}
+
+// Meteor heads: a bright core falling off into a wide halo.
+const GLOW_CUSTOM_COLOR: &str = r#"
+    vec4 custom_color(vec4 color) {
+        float distance = length(gl_PointCoord - 0.5) * 2.0;
+        float glow = exp(-distance * distance * 6.0) * (1.0 - smoothstep(0.8, 1.0, distance));
+        return color * glow;
+    }
+"#;
+
+// Points laid out like stars, drawn as radial glows.
+pub(super) fn create_glow_program(gl: &GL) -> Result<WebGlProgram, String> {
+    let fragment_shader = with_custom_color(STAR_FRAGMENT_SHADER, GLOW_CUSTOM_COLOR);
+    build_program(gl, "Glow", STAR_VERTEX_SHADER, &fragment_shader)
+}
+
+// Five-pointed stars with a faint halo, for the solve streak.
+const GOLD_STAR_CUSTOM_COLOR: &str = r#"
+    vec4 custom_color(vec4 color) {
+        vec2 p = gl_PointCoord - 0.5;
+        float angle = atan(p.x, -p.y);
+        float spike = pow(cos(5.0 * angle) * 0.5 + 0.5, 2.0);
+        float radius = mix(0.2, 0.46, spike);
+        float star = 1.0 - smoothstep(radius - 0.04, radius, length(p));
+        float halo = 0.3 * exp(-dot(p, p) * 24.0);
+        return color * max(star, halo);
+    }
+"#;
+
+// Points laid out like stars, drawn as five-pointed stars.
+pub(super) fn create_gold_star_program(gl: &GL) -> Result<WebGlProgram, String> {
+    let fragment_shader = with_custom_color(STAR_FRAGMENT_SHADER, GOLD_STAR_CUSTOM_COLOR);
+    build_program(gl, "Gold star", STAR_VERTEX_SHADER, &fragment_shader)
+}
+
+// Lines laid out as (x, y, alpha, r, g, b) per vertex, for the plexus.
+pub(super) fn create_line_program(gl: &GL) -> Result<WebGlProgram, String> {
+    build_program(gl, "Line", METEOR_VERTEX_SHADER, LINE_FRAGMENT_SHADER)
+}
+
+// Textures laid over the whole viewport, with a buffer holding the one
+// triangle covering it.
+pub(super) fn create_texture_program(gl: &GL) -> Result<(WebGlProgram, WebGlBuffer), String> {
+    let program = build_program(gl, "Texture", TEXTURE_VERTEX_SHADER, TEXTURE_FRAGMENT_SHADER)?;
+    let buffer = gl.create_buffer().ok_or("Failed to create texture quad buffer")?;
+    upload_vertices(gl, &buffer, &[-1.0, -1.0, 3.0, -1.0, -1.0, 3.0], GL::STATIC_DRAW);
+    Ok((program, buffer))
+}
+
+// Sparks laid out as (x, y, size, alpha, r, g, b, vx, vy), for motion blur.
+pub(super) fn create_streak_program(gl: &GL) -> Result<WebGlProgram, String> {
+    build_program(gl, "Streak", STREAK_VERTEX_SHADER, STREAK_FRAGMENT_SHADER)
+}
+
+// The sky gradient behind everything else.
+pub(super) struct Background {
+    pub(super) program: WebGlProgram,
+    pub(super) buffer: WebGlBuffer,
+    // Replaces the sky's vertical gradient while set, fading in from
+    // `previous_corners` (or the sky's gradient) as `corners_fade` nears 1.
+    pub(super) corners: Option<CornerGradient>,
+    pub(super) previous_corners: Option<CornerGradient>,
+    pub(super) corners_fade: Tween<f32>,
+    pub(super) hue_shift: Option<HueShift>,
+}
+
+impl Background {
+    pub(super) fn new(gl: &GL, program: WebGlProgram) -> Result<Background, String> {
+        let buffer = gl.create_buffer().ok_or("Failed to create background buffer")?;
+        let background_vertices: [f32; 6 * 2] = [
+            -1.0, -1.0,
+             1.0, -1.0,
+            -1.0,  1.0,
+             1.0, -1.0,
+             1.0,  1.0,
+            -1.0,  1.0,
+        ];
+        upload_vertices(gl, &buffer, &background_vertices, GL::STATIC_DRAW);
+        Ok(Background {
+            program,
+            buffer,
+            corners: None,
+            previous_corners: None,
+            corners_fade: Tween::settled(1.0),
+            hue_shift: None,
+        })
+    }
+
+    // Top-left, top-right, bottom-left and bottom-right colors at sky time
+    // `time`, before the theme tint.
+    pub(super) fn corner_colors(&self, sky: &SkyPalette, time: f32) -> [[f32; 3]; 4] {
+        let colors_of = |corners: &Option<CornerGradient>| match corners {
+            Some(corners) => corners.colors_at(time),
+            None => [sky.top, sky.top, sky.bottom, sky.bottom],
+        };
+        let mut colors = colors_of(&self.corners);
+        if !self.corners_fade.is_settled() {
+            let (from, t) = (colors_of(&self.previous_corners), self.corners_fade.value());
+            colors = [0, 1, 2, 3].map(|i| from[i].lerp(colors[i], t));
+        }
+        match self.hue_shift {
+            Some(shift) => colors.map(|color| rotate_hue(color, shift.angle_at(time))),
+            None => colors,
+        }
+    }
+
+    pub(super) fn render(&self, context: &EffectContext) {
+        let gl = &context.gl;
+        gl.use_program(Some(&self.program));
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.buffer));
+        let pos_attrib_location = gl.get_attrib_location(&self.program, "a_position") as u32;
+        gl.enable_vertex_attrib_array(pos_attrib_location);
+        gl.vertex_attrib_pointer_with_i32(pos_attrib_location, 2, GL::FLOAT, false, 0, 0);
+        let corners = self.corner_colors(&context.sky, context.time);
+        for (name, color) in ["u_topLeft", "u_topRight", "u_bottomLeft", "u_bottomRight"].into_iter().zip(corners) {
+            if let Some(loc) = gl.get_uniform_location(&self.program, name) {
+                gl.uniform3f(Some(&loc), color[0], color[1], color[2]);
+            }
+        }
+        apply_tint(gl, &self.program, context.theme.sky, context.theme.sky_mix);
+        apply_opacity(gl, &self.program, context.opacity);
+        apply_linear(gl, &self.program, context.linear);
+        gl.draw_arrays(GL::TRIANGLES, 0, 6);
+    }
+}
+
+impl Stars {
+    // Stars the GPU would clamp go to the quad buffer when there is a quad
+    // program. The split is made at a `point_scale` of 1, so posters may
+    // still clamp a few.
+    pub(super) fn upload(&mut self, context: &EffectContext) {
+        let stars = &self.stars;
+        let mut star_data = Vec::with_capacity(stars.len() * 7);
+        let mut quad_data = Vec::new();
+        let mut runs = Vec::new();
+        for i in 0..stars.len() {
+            let point_size = (stars.size[i] * POINT_SCALE * context.pulse_size * context.size_scale).max(1.0);
+            let alpha = (stars.data[i].alpha * context.pulse_alpha * context.sky.star_alpha).clamp(0.0, 1.0);
+            let [r, g, b] = stars.color[i];
+            if point_size > self.max_point_size && self.quad_program.is_some() {
+                let (x, y, half) = (stars.x[i], stars.y[i], point_size / 2.0);
+                for (u, v) in [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+                    let (corner_x, corner_y) = (x + (u * 2.0 - 1.0) * half, y + (v * 2.0 - 1.0) * half);
+                    quad_data.extend_from_slice(&[corner_x, corner_y, alpha, r, g, b, u, v]);
+                }
+                let end = quad_data.len() / 8;
+                extend_runs(&mut runs, true, end - 6..end);
+            } else {
+                star_data.extend_from_slice(&[stars.x[i], stars.y[i], point_size, alpha, r, g, b]);
+                let end = star_data.len() / 7;
+                extend_runs(&mut runs, false, end - 1..end);
+            }
+        }
+        self.runs = runs;
+        upload_vertices(&context.gl, &self.buffer, &star_data, GL::DYNAMIC_DRAW);
+        if self.quad_vertices > 0 || !quad_data.is_empty() {
+            upload_vertices(&context.gl, &self.quad_buffer, &quad_data, GL::DYNAMIC_DRAW);
+        }
+        self.quad_vertices = quad_data.len() / 8;
+    }
+
+    pub(super) fn render(&self, context: &EffectContext) {
+        let (tint, mix) = (context.theme.star, context.theme.star_mix);
+        for run in &self.runs {
+            match &self.quad_program {
+                Some(program) if run.quads => {
+                    draw_star_quads(context, program, &self.quad_buffer, run.range.clone(), tint, mix)
+                }
+                _ => draw_point_range(context, &self.program, &self.buffer, run.range.clone(), tint, mix),
+            }
+        }
+    }
+}
+
+// Adds `range` to the last run when it is drawn the same way, or starts a
+// new one.
+fn extend_runs(runs: &mut Vec<StarRun>, quads: bool, range: Range<usize>) {
+    match runs.last_mut() {
+        Some(last) if last.quads == quads => last.range.end = range.end,
+        _ => runs.push(StarRun { quads, range }),
+    }
+}
+
+impl Meteors {
+    pub(super) fn upload(&mut self, context: &EffectContext) {
+        let meteors = &self.meteors;
+        let mut meteor_data = Vec::with_capacity(meteors.len() * 12 * 6);
+        let mut glow_data = Vec::with_capacity(meteors.len() * 7);
+        for i in 0..meteors.len() {
+            let (head_x, head_y) = (meteors.x[i], meteors.y[i]);
+            let (vx, vy) = (meteors.vx[i], meteors.vy[i]);
+            let (head_color, tail_color) = (meteors.color[i], meteors.data[i]);
+            let speed = (vx * vx + vy * vy).sqrt();
+            let (norm_vx, norm_vy) = if speed > 0.0001 {
+                (vx / speed, vy / speed)
+            } else {
+                (1.0, 0.0)
+            };
+            let half_width = meteors.size[i] * context.size_scale / 2.0;
+            let (perp_x, perp_y) = (-norm_vy * half_width, norm_vx * half_width);
+            // Two triangles from a front edge at `front` to a back edge at
+            // `back`, each as wide as the meteor.
+            let mut quad = |front: (f32, f32, f32, [f32; 3]), back: (f32, f32, f32, [f32; 3])| {
+                let vertex = |(x, y, alpha, color): (f32, f32, f32, [f32; 3]), side: f32| {
+                    let (x, y) = (x + perp_x * side, y + perp_y * side);
+                    [x, y, alpha * context.exclusion(x, y), color[0], color[1], color[2]]
+                };
+                let ends = [(front, 1.0), (front, -1.0), (back, 1.0), (front, -1.0), (back, 1.0), (back, -1.0)];
+                for (end, side) in ends {
+                    meteor_data.extend_from_slice(&vertex(end, side));
+                }
+            };
+            let head_alpha = meteors.fade(i);
+            // With motion blur the head stays bright over the ground it
+            // covered since the last frame, then the trail fades.
+            let streak = (speed * context.motion_blur).min(MAX_STREAK_LENGTH * context.size_scale);
+            let (trail_x, trail_y) = (head_x - norm_vx * streak, head_y - norm_vy * streak);
+            if streak > 0.0 {
+                quad((head_x, head_y, head_alpha, head_color), (trail_x, trail_y, head_alpha, head_color));
+            }
+            let tail_x = trail_x - norm_vx * TRAIL_LENGTH * context.size_scale;
+            let tail_y = trail_y - norm_vy * TRAIL_LENGTH * context.size_scale;
+            quad((trail_x, trail_y, head_alpha, head_color), (tail_x, tail_y, 0.0, tail_color));
+            // The head glow shrinks as the meteor burns out, flickering on
+            // the way.
+            let flicker = 1.0 + HEAD_GLOW_FLICKER * (head_alpha * HEAD_GLOW_PULSES * std::f32::consts::TAU).sin();
+            let glow_size = meteors.size[i] * HEAD_GLOW_SCALE * context.size_scale * (0.4 + 0.6 * head_alpha) * flicker;
+            let glow_alpha = head_alpha * context.exclusion(head_x, head_y);
+            // Whiter than the head, like a hot core.
+            let glow_color = head_color.map(|channel| (channel + 1.0) / 2.0);
+            glow_data.extend_from_slice(&[head_x, head_y, glow_size, glow_alpha]);
+            glow_data.extend_from_slice(&glow_color);
+        }
+        for guided in &self.guided {
+            let half_width = METEOR_WIDTH * context.size_scale / 2.0;
+            let settle = ((guided.elapsed - guided.duration) / GUIDED_METEOR_SETTLE_FRAMES).clamp(0.0, 1.0);
+            let head = guided.progress();
+            let span = (TRAIL_LENGTH * context.size_scale / guided.length.max(1.0)).min(1.0) * (1.0 - settle);
+            let alpha = 1.0 - settle;
+            let colors = guided.colors;
+            // The trail follows the curve back from the head, fading out.
+            let points: Vec<_> = (0..=GUIDED_TRAIL_SEGMENTS)
+                .map(|k| {
+                    let f = k as f32 / GUIDED_TRAIL_SEGMENTS as f32;
+                    let (x, y) = guided.path.point((head - span * f).max(0.0));
+                    let color = [0, 1, 2].map(|c| colors.head[c] + (colors.tail[c] - colors.head[c]) * f);
+                    (x, y, alpha * (1.0 - f), color)
+                })
+                .collect();
+            for pair in points.windows(2) {
+                let ((x0, y0, alpha0, color0), (x1, y1, alpha1, color1)) = (pair[0], pair[1]);
+                let length = ((x0 - x1).powi(2) + (y0 - y1).powi(2)).sqrt();
+                if length < 0.0001 {
+                    continue;
+                }
+                let (perp_x, perp_y) = (-(y0 - y1) / length * half_width, (x0 - x1) / length * half_width);
+                let vertex = |x: f32, y: f32, alpha: f32, color: [f32; 3], side: f32| {
+                    [x + perp_x * side, y + perp_y * side, alpha, color[0], color[1], color[2]]
+                };
+                for (x, y, alpha, color, side) in [
+                    (x0, y0, alpha0, color0, 1.0),
+                    (x0, y0, alpha0, color0, -1.0),
+                    (x1, y1, alpha1, color1, 1.0),
+                    (x0, y0, alpha0, color0, -1.0),
+                    (x1, y1, alpha1, color1, 1.0),
+                    (x1, y1, alpha1, color1, -1.0),
+                ] {
+                    meteor_data.extend_from_slice(&vertex(x, y, alpha, color, side));
+                }
+            }
+            let (x, y, _, _) = points[0];
+            let glow_size = METEOR_WIDTH * HEAD_GLOW_SCALE * context.size_scale * (0.4 + 0.6 * alpha);
+            glow_data.extend_from_slice(&[x, y, glow_size, alpha]);
+            glow_data.extend_from_slice(&colors.head.map(|channel| (channel + 1.0) / 2.0));
+        }
+        upload_vertices(&context.gl, &self.buffer, &meteor_data, GL::DYNAMIC_DRAW);
+        self.vertices = meteor_data.len() / 6;
+        upload_vertices(&context.gl, &self.glow_buffer, &glow_data, GL::DYNAMIC_DRAW);
+        self.glows = glow_data.len() / 7;
+    }
+
+    pub(super) fn render(&self, context: &EffectContext) {
+        let gl = &context.gl;
+        gl.use_program(Some(&self.program));
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.buffer));
+        let meteor_stride = 6 * std::mem::size_of::<f32>() as i32; // (x,y,alpha,r,g,b)
+        let meteor_pos_loc = gl.get_attrib_location(&self.program, "a_position") as u32;
+        let meteor_alpha_loc = gl.get_attrib_location(&self.program, "a_alpha") as u32;
+        let meteor_color_loc = gl.get_attrib_location(&self.program, "a_color") as u32;
+        gl.enable_vertex_attrib_array(meteor_pos_loc);
+        gl.vertex_attrib_pointer_with_i32(meteor_pos_loc, 2, GL::FLOAT, false, meteor_stride, 0);
+        gl.enable_vertex_attrib_array(meteor_alpha_loc);
+        gl.vertex_attrib_pointer_with_i32(meteor_alpha_loc, 1, GL::FLOAT, false, meteor_stride, 2 * std::mem::size_of::<f32>() as i32);
+        gl.enable_vertex_attrib_array(meteor_color_loc);
+        gl.vertex_attrib_pointer_with_i32(meteor_color_loc, 3, GL::FLOAT, false, meteor_stride, 3 * std::mem::size_of::<f32>() as i32);
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_resolution") {
+            gl.uniform2f(Some(&loc), context.resolution.0, context.resolution.1);
+        }
+        apply_opacity(gl, &self.program, context.opacity);
+        apply_linear(gl, &self.program, context.linear);
+        gl.draw_arrays(GL::TRIANGLES, 0, self.vertices as i32);
+        draw_points(context, &self.glow_program, &self.glow_buffer, self.glows, ThemeTint::NONE.star, 0.0);
+    }
+}
+
+impl StarField {
+    /// Recompiles the background or stars fragment shader with `snippet`,
+    /// which must define `vec4 custom_color(vec4 color)` over premultiplied
+    /// color; `None` restores the default. On a compile error the current
+    /// shader stays and the error is returned with snippet line numbers.
+    pub fn set_custom_shader(&mut self, layer: Layer, snippet: Option<&str>) -> Result<(), String> {
+        let snippet = snippet.unwrap_or(DEFAULT_CUSTOM_COLOR);
+        let gl = &self.gl;
+        match layer {
+            Layer::Background => {
+                let fragment_shader = with_custom_color(BACKGROUND_FRAGMENT_SHADER, snippet);
+                let program = build_program(gl, "Background", BACKGROUND_VERTEX_SHADER, &fragment_shader)?;
+                gl.delete_program(Some(&std::mem::replace(&mut self.background.program, program)));
+            }
+            Layer::Stars => {
+                let fragment_shader = with_custom_color(STAR_FRAGMENT_SHADER, snippet);
+                let program = build_program(gl, "Star", STAR_VERTEX_SHADER, &fragment_shader)?;
+                if self.stars.quad_program.is_some() {
+                    let quad_program = match create_star_quad_program(gl, snippet) {
+                        Ok(quad_program) => quad_program,
+                        Err(error) => {
+                            gl.delete_program(Some(&program));
+                            return Err(error);
+                        }
+                    };
+                    gl.delete_program(self.stars.quad_program.replace(quad_program).as_ref());
+                }
+                let old = std::mem::replace(&mut self.stars.program, program);
+                // Particles keep drawing with the default program.
+                if old != self.star_program {
+                    gl.delete_program(Some(&old));
+                }
+            }
+            _ => return Err("Only the background and stars layers take a custom shader".to_string()),
+        }
+        Ok(())
+    }
+
+    // Returns a new GL error code, once; a persistent error would otherwise
+    // be reported every frame.
+    pub(super) fn take_gl_error(&mut self) -> Option<u32> {
+        let code = self.gl.get_error();
+        let changed = code != self.last_gl_error;
+        self.last_gl_error = code;
+        (changed && code != GL::NO_ERROR).then_some(code)
+    }
+
+    pub(super) fn log_buffer_sizes(&self, level: Level) {
+        const VERTEX_BYTES: usize = 7 * std::mem::size_of::<f32>();
+        log(level, || {
+            format!(
+                "Buffers: {} stars ({} B), {} meteors ({} B), {} particles ({} B)",
+                self.stars.stars.len(),
+                self.stars.stars.len() * VERTEX_BYTES,
+                self.meteors.meteors.len(),
+                self.meteors.meteors.len() * 6 * 6 * std::mem::size_of::<f32>(),
+                self.particles.len(),
+                self.particles.len() * VERTEX_BYTES,
+            )
+        });
+    }
+
+    pub(super) fn upload_particles(&mut self) {
+        let size_scale = self.size_scale();
+        let sparks = &self.particles;
+        // Streaks also take each spark's velocity.
+        self.particles_streaked = self.streak_program.is_some();
+        let mut particle_data = Vec::with_capacity(sparks.len() * 9);
+        for i in 0..sparks.len() {
+            particle_data.push(sparks.x[i]);
+            particle_data.push(sparks.y[i]);
+            particle_data.push(sparks.size[i] * size_scale);
+            particle_data.push(sparks.fade(i) * attenuation(&self.exclusion_zones, sparks.x[i], sparks.y[i]));
+            particle_data.push(sparks.color[i][0]);
+            particle_data.push(sparks.color[i][1]);
+            particle_data.push(sparks.color[i][2]);
+            if self.particles_streaked {
+                particle_data.push(sparks.vx[i]);
+                particle_data.push(sparks.vy[i]);
+            }
+        }
+        upload_vertices(&self.gl, &self.particle_buffer, &particle_data, GL::DYNAMIC_DRAW);
+    }
+
+    /// Smears meteor heads and sparks along their motion over `strength`
+    /// frames' worth of the last step, so fast ones read as streaks at low
+    /// frame rates; 0 turns it off.
+    pub fn set_motion_blur(&mut self, strength: f32) -> Result<(), String> {
+        match (strength > 0.0, &self.streak_program) {
+            (true, None) => self.streak_program = Some(create_streak_program(&self.gl)?),
+            (false, Some(_)) => {
+                if let Some(program) = self.streak_program.take() {
+                    self.gl.delete_program(Some(&program));
+                }
+            }
+            _ => {}
+        }
+        self.motion_blur = strength;
+        Ok(())
+    }
+
+    /// Draws the sky as last `update`d onto the canvas.
+    pub fn draw(&self) {
+        match &self.composite {
+            Some(composite) => {
+                composite.bind(&self.gl);
+                self.draw_to(self.resolution, 1.0, self.output.linear);
+                composite.present(&self.gl, self.resolution, self.output);
+            }
+            None => self.draw_to(self.resolution, 1.0, false),
+        }
+    }
+
+    // Draws into a viewport of `size` pixels. Positions stay in canvas
+    // pixels and stretch to fill it; point sizes, which the rasterizer takes
+    // in viewport pixels, are multiplied by `point_scale` to keep up.
+    // `linear` has the built-in shaders output linear light.
+    fn draw_to(&self, size: (f32, f32), point_scale: f32, linear: bool) {
+        let gl = &self.gl;
+        gl.viewport(0, 0, size.0 as i32, size.1 as i32);
+        let clear_alpha = if self.transparent { 0.0 } else { 1.0 };
+        gl.clear_color(0.0, 0.0, 0.0, clear_alpha);
+        gl.clear(GL::COLOR_BUFFER_BIT);
+        let mut context = self.effect_context(point_scale);
+        context.linear = linear;
+        for layer in self.layers.order {
+            let state = self.layers.get(layer);
+            // The sky and the page's own text keep their brightness.
+            let intensity = match layer {
+                Layer::Background | Layer::Overlay => 1.0,
+                _ => self.intensity,
+            };
+            let opacity = state.opacity.value() * intensity;
+            if !state.enabled || opacity <= 0.0 {
+                continue;
+            }
+            context.opacity = opacity;
+            state.blend.apply(gl);
+            self.draw_builtin(layer, &context);
+            // Overlay text sets its own blending.
+            state.blend.apply(gl);
+            for slot in self.effects.iter().filter(|slot| slot.layer == layer) {
+                slot.effect.draw(&context);
+            }
+        }
+        gl.disable(GL::BLEND);
+    }
+
+    fn draw_builtin(&self, layer: Layer, context: &EffectContext) {
+        match layer {
+            Layer::Background if !self.transparent => self.background.draw(context),
+            Layer::Stars => {
+                // Under the stars, so the lines seem to run between them.
+                if let Some(plexus) = &self.plexus {
+                    draw_lines(context, &plexus.program, &plexus.buffer, plexus.vertices);
+                }
+                self.stars.draw(context);
+            }
+            Layer::Meteors => self.meteors.draw(context),
+            Layer::Foreground => {
+                if let Some(streak) = &self.solve_streak {
+                    streak.draw(context);
+                }
+                let count = self.particles.len();
+                match &self.streak_program {
+                    Some(program) if self.particles_streaked => {
+                        draw_streaks(context, program, &self.particle_buffer, count);
+                    }
+                    // Uploaded for motion blur since turned off; the next
+                    // update rewrites the buffer.
+                    _ if self.particles_streaked => {}
+                    _ => {
+                        let tint = ThemeTint::NONE.star;
+                        draw_points(context, &self.star_program, &self.particle_buffer, count, tint, 0.0);
+                    }
+                }
+            }
+            // Overlays are page furniture; a screensaver shows only the sky.
+            Layer::Overlay if self.wallpaper.is_none() => self.draw_overlays(context),
+            _ => {}
+        }
+    }
+
+    fn draw_overlays(&self, context: &EffectContext) {
+        let gl = &self.gl;
+        if let Some(countdown) = &self.countdown {
+            let center = (self.resolution.0 / 2.0, self.resolution.1 / 2.0);
+            self.text_renderer.draw(gl, &countdown.layer, self.resolution, center, context.opacity);
+        }
+        if let Some(ticker) = &self.ticker {
+            let (w, h) = ticker.layer.size();
+            let margin = self.resolution.1 * TICKER_MARGIN_SCALE;
+            let x = self.resolution.0 + w / 2.0 - ticker.offset;
+            let y = if ticker.at_top { margin + h / 2.0 } else { self.resolution.1 - margin - h / 2.0 };
+            self.text_renderer.draw(gl, &ticker.layer, self.resolution, (x, y), context.opacity);
+        }
+        if let Some(overlay) = &self.debug_overlay {
+            let (w, h) = overlay.layer.size();
+            let margin = DEBUG_OVERLAY_MARGIN * self.size_scale();
+            self.text_renderer.draw(gl, &overlay.layer, self.resolution, (margin + w / 2.0, margin + h / 2.0), 1.0);
+        }
+    }
+
+    // Draws a frame and reads it back before the browser composites (and,
+    // without preserveDrawingBuffer, clears) it. Returns straight-alpha RGBA
+    // rows from the top down, as image encoders expect.
+    pub(super) fn capture(&self) -> (u32, u32, Vec<u8>) {
+        self.draw();
+        let (width, height) = (self.resolution.0 as u32, self.resolution.1 as u32);
+        (width, height, self.read_pixels(width, height))
+    }
+
+    // Renders the sky at `width`×`height` into an offscreen framebuffer,
+    // independent of the canvas size, and reads it back like `capture`.
+    pub(super) fn render_poster(&self, width: u32, height: u32) -> Result<Vec<u8>, String> {
+        let gl = &self.gl;
+        let max_size = [GL::MAX_TEXTURE_SIZE, GL::MAX_RENDERBUFFER_SIZE]
+            .into_iter()
+            .filter_map(|name| gl.get_parameter(name).ok().and_then(|v| v.as_f64()))
+            .fold(f64::INFINITY, f64::min) as u32;
+        if width > max_size || height > max_size {
+            return Err(format!("This GPU renders at most {}×{} pixels", max_size, max_size));
+        }
+        let texture = gl.create_texture().ok_or("Failed to create poster texture")?;
+        let framebuffer = gl.create_framebuffer().ok_or("Failed to create poster framebuffer")?;
+        gl.bind_texture(GL::TEXTURE_2D, Some(&texture));
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, GL::LINEAR as i32);
+        gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            GL::TEXTURE_2D, 0, GL::RGBA as i32, width as i32, height as i32, 0, GL::RGBA, GL::UNSIGNED_BYTE, None,
+        )
+        .map_err(|_| "Failed to allocate poster texture")?;
+        gl.bind_framebuffer(GL::FRAMEBUFFER, Some(&framebuffer));
+        gl.framebuffer_texture_2d(GL::FRAMEBUFFER, GL::COLOR_ATTACHMENT0, GL::TEXTURE_2D, Some(&texture), 0);
+
+        let result = if gl.check_framebuffer_status(GL::FRAMEBUFFER) == GL::FRAMEBUFFER_COMPLETE {
+            // Scaling by the geometric mean keeps star areas proportional
+            // when the poster's aspect ratio differs from the canvas's.
+            let point_scale = ((width as f32 / self.resolution.0) * (height as f32 / self.resolution.1)).sqrt();
+            // Posters skip linear output and tone mapping, needing no second target.
+            self.draw_to((width as f32, height as f32), point_scale, false);
+            Ok(self.read_pixels(width, height))
+        } else {
+            Err("Poster framebuffer is incomplete".to_string())
+        };
+        gl.bind_framebuffer(GL::FRAMEBUFFER, None);
+        gl.delete_framebuffer(Some(&framebuffer));
+        gl.delete_texture(Some(&texture));
+        result
+    }
+
+    // Reads the bound framebuffer as straight-alpha RGBA rows, top down.
+    fn read_pixels(&self, width: u32, height: u32) -> Vec<u8> {
+        let mut pixels = vec![0u8; (width * height * 4) as usize];
+        self.gl
+            .read_pixels_with_opt_u8_array(
+                0, 0, width as i32, height as i32, GL::RGBA, GL::UNSIGNED_BYTE, Some(&mut pixels),
+            )
+            .unwrap();
+        let row = (width * 4) as usize;
+        let mut flipped = Vec::with_capacity(pixels.len());
+        for y in (0..height as usize).rev() {
+            flipped.extend_from_slice(&pixels[y * row..(y + 1) * row]);
+        }
+        for pixel in flipped.chunks_exact_mut(4) {
+            let alpha = pixel[3];
+            if alpha > 0 && alpha < 255 {
+                for channel in &mut pixel[..3] {
+                    *channel = (*channel as u32 * 255 / alpha as u32).min(255) as u8;
+                }
+            }
+        }
+        flipped
+    }
+
+    // Stars become circles (as in the 2D fallback) in canvas pixels, sized
+    // for the canvas's CSS box. Transient effects are left out.
+    pub(super) fn to_svg(&self) -> String {
+        let (width, height) = self.resolution;
+        let tint = |color: [f32; 3], target: [f32; 3], amount: f32| {
+            css_color([0, 1, 2].map(|i| color[i] + (target[i] - color[i]) * amount))
+        };
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+            (width / self.dpr).round(),
+            (height / self.dpr).round(),
+            width,
+            height
+        );
+        if !self.transparent {
+            // SVG has no bilinear gradient; corners blend into a vertical one.
+            let [top_left, top_right, bottom_left, bottom_right] =
+                self.background.corner_colors(&self.sky, self.clock.seconds());
+            let average = |a: [f32; 3], b: [f32; 3]| [0, 1, 2].map(|i| (a[i] + b[i]) / 2.0);
+            svg.push_str(&format!(
+                concat!(
+                    "<defs><linearGradient id=\"sky\" x1=\"0\" y1=\"0\" x2=\"0\" y2=\"1\">",
+                    "<stop offset=\"0\" stop-color=\"{}\"/><stop offset=\"1\" stop-color=\"{}\"/>",
+                    "</linearGradient></defs>\n<rect width=\"100%\" height=\"100%\" fill=\"url(#sky)\"/>\n"
+                ),
+                tint(average(top_left, top_right), self.theme.sky, self.theme.sky_mix),
+                tint(average(bottom_left, bottom_right), self.theme.sky, self.theme.sky_mix)
+            ));
+        }
+        let size_scale = self.size_scale();
+        svg.push_str("<g>\n");
+        let stars = &self.stars.stars;
+        for i in 0..stars.len() {
+            let radius = (stars.size[i] * POINT_SCALE * size_scale).max(1.0) / 2.0;
+            svg.push_str(&format!(
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{:.2}\" fill=\"{}\" fill-opacity=\"{:.2}\"/>\n",
+                stars.x[i],
+                stars.y[i],
+                radius,
+                tint(stars.color[i], self.theme.star, self.theme.star_mix),
+                stars.data[i].alpha * self.sky.star_alpha
+            ));
+        }
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+}
+
+// Draws `count` points from `buffer`, laid out as (x, y, size, alpha, r, g,
+// b), with the star program or one sharing its attributes.
+pub(super) fn draw_points(
+    context: &EffectContext,
+    program: &WebGlProgram,
+    buffer: &WebGlBuffer,
+    count: usize,
+    tint: [f32; 3],
+    tint_mix: f32,
+) {
+    draw_point_range(context, program, buffer, 0..count, tint, tint_mix);
+}
+
+// Like `draw_points`, for the points in `range` only.
+fn draw_point_range(
+    context: &EffectContext,
+    program: &WebGlProgram,
+    buffer: &WebGlBuffer,
+    range: Range<usize>,
+    tint: [f32; 3],
+    tint_mix: f32,
+) {
+    if range.is_empty() {
+        return;
+    }
+    let gl = &context.gl;
+    gl.use_program(Some(program));
+    gl.bind_buffer(GL::ARRAY_BUFFER, Some(buffer));
+    let star_stride = 7 * std::mem::size_of::<f32>() as i32;
+    let star_pos_loc = gl.get_attrib_location(program, "a_position") as u32;
+    let point_size_loc = gl.get_attrib_location(program, "a_pointSize") as u32;
+    let alpha_loc = gl.get_attrib_location(program, "a_alpha") as u32;
+    let color_loc = gl.get_attrib_location(program, "a_color") as u32;
+    gl.enable_vertex_attrib_array(star_pos_loc);
+    gl.vertex_attrib_pointer_with_i32(star_pos_loc, 2, GL::FLOAT, false, star_stride, 0);
+    gl.enable_vertex_attrib_array(point_size_loc);
+    gl.vertex_attrib_pointer_with_i32(point_size_loc, 1, GL::FLOAT, false, star_stride, 2 * std::mem::size_of::<f32>() as i32);
+    gl.enable_vertex_attrib_array(alpha_loc);
+    gl.vertex_attrib_pointer_with_i32(alpha_loc, 1, GL::FLOAT, false, star_stride, 3 * std::mem::size_of::<f32>() as i32);
+    gl.enable_vertex_attrib_array(color_loc);
+    gl.vertex_attrib_pointer_with_i32(color_loc, 3, GL::FLOAT, false, star_stride, 4 * std::mem::size_of::<f32>() as i32);
+    if let Some(loc) = gl.get_uniform_location(program, "u_resolution") {
+        gl.uniform2f(Some(&loc), context.resolution.0, context.resolution.1);
+    }
+    if let Some(loc) = gl.get_uniform_location(program, "u_pointScale") {
+        gl.uniform1f(Some(&loc), context.point_scale);
+    }
+    apply_tint(gl, program, tint, tint_mix);
+    apply_opacity(gl, program, context.opacity);
+    apply_linear(gl, program, context.linear);
+    gl.draw_arrays(GL::POINTS, range.start as i32, range.len() as i32);
+}
+
+// Draws the `vertices` star quad corners from `buffer`, laid out as (x, y,
+// alpha, r, g, b, u, v), with the star quad program.
+fn draw_star_quads(
+    context: &EffectContext,
+    program: &WebGlProgram,
+    buffer: &WebGlBuffer,
+    vertices: Range<usize>,
+    tint: [f32; 3],
+    tint_mix: f32,
+) {
+    let gl = &context.gl;
+    gl.use_program(Some(program));
+    gl.bind_buffer(GL::ARRAY_BUFFER, Some(buffer));
+    let float = std::mem::size_of::<f32>() as i32;
+    let stride = 8 * float;
+    for (name, size, offset) in [("a_position", 2, 0), ("a_alpha", 1, 2), ("a_color", 3, 3), ("a_pointCoord", 2, 6)] {
+        let loc = gl.get_attrib_location(program, name) as u32;
+        gl.enable_vertex_attrib_array(loc);
+        gl.vertex_attrib_pointer_with_i32(loc, size, GL::FLOAT, false, stride, offset * float);
+    }
+    if let Some(loc) = gl.get_uniform_location(program, "u_resolution") {
+        gl.uniform2f(Some(&loc), context.resolution.0, context.resolution.1);
+    }
+    apply_tint(gl, program, tint, tint_mix);
+    apply_opacity(gl, program, context.opacity);
+    apply_linear(gl, program, context.linear);
+    gl.draw_arrays(GL::TRIANGLES, vertices.start as i32, vertices.len() as i32);
+}
+
+// Draws `count` sparks from `buffer`, laid out as (x, y, size, alpha, r, g,
+// b, vx, vy), with the streak program.
+fn draw_streaks(context: &EffectContext, program: &WebGlProgram, buffer: &WebGlBuffer, count: usize) {
+    if count == 0 {
+        return;
+    }
+    let gl = &context.gl;
+    gl.use_program(Some(program));
+    gl.bind_buffer(GL::ARRAY_BUFFER, Some(buffer));
+    let stride = 9 * std::mem::size_of::<f32>() as i32;
+    let float = std::mem::size_of::<f32>() as i32;
+    for (name, size, offset) in
+        [("a_position", 2, 0), ("a_pointSize", 1, 2), ("a_alpha", 1, 3), ("a_color", 3, 4), ("a_velocity", 2, 7)]
+    {
+        let loc = gl.get_attrib_location(program, name) as u32;
+        gl.enable_vertex_attrib_array(loc);
+        gl.vertex_attrib_pointer_with_i32(loc, size, GL::FLOAT, false, stride, offset * float);
+    }
+    if let Some(loc) = gl.get_uniform_location(program, "u_resolution") {
+        gl.uniform2f(Some(&loc), context.resolution.0, context.resolution.1);
+    }
+    for (name, value) in [
+        ("u_pointScale", context.point_scale),
+        ("u_blur", context.motion_blur),
+        ("u_maxStreak", MAX_STREAK_LENGTH * context.size_scale),
+    ] {
+        if let Some(loc) = gl.get_uniform_location(program, name) {
+            gl.uniform1f(Some(&loc), value);
+        }
+    }
+    apply_opacity(gl, program, context.opacity);
+    apply_linear(gl, program, context.linear);
+    gl.draw_arrays(GL::POINTS, 0, count as i32);
+}
+
+// Draws `count` vertices from `buffer` as `GL::LINES`, laid out as (x, y,
+// alpha, r, g, b), with the line program.
+pub(super) fn draw_lines(context: &EffectContext, program: &WebGlProgram, buffer: &WebGlBuffer, count: usize) {
+    if count == 0 {
+        return;
+    }
+    let gl = &context.gl;
+    gl.use_program(Some(program));
+    gl.bind_buffer(GL::ARRAY_BUFFER, Some(buffer));
+    let stride = 6 * std::mem::size_of::<f32>() as i32;
+    let pos_loc = gl.get_attrib_location(program, "a_position") as u32;
+    let alpha_loc = gl.get_attrib_location(program, "a_alpha") as u32;
+    let color_loc = gl.get_attrib_location(program, "a_color") as u32;
+    gl.enable_vertex_attrib_array(pos_loc);
+    gl.vertex_attrib_pointer_with_i32(pos_loc, 2, GL::FLOAT, false, stride, 0);
+    gl.enable_vertex_attrib_array(alpha_loc);
+    gl.vertex_attrib_pointer_with_i32(alpha_loc, 1, GL::FLOAT, false, stride, 2 * std::mem::size_of::<f32>() as i32);
+    gl.enable_vertex_attrib_array(color_loc);
+    gl.vertex_attrib_pointer_with_i32(color_loc, 3, GL::FLOAT, false, stride, 3 * std::mem::size_of::<f32>() as i32);
+    if let Some(loc) = gl.get_uniform_location(program, "u_resolution") {
+        gl.uniform2f(Some(&loc), context.resolution.0, context.resolution.1);
+    }
+    apply_opacity(gl, program, context.opacity);
+    apply_linear(gl, program, context.linear);
+    gl.draw_arrays(GL::LINES, 0, count as i32);
+}
+
+// Draws `texture` over the viewport with the texture program.
+pub(super) fn draw_texture(
+    context: &EffectContext,
+    program: &WebGlProgram,
+    buffer: &WebGlBuffer,
+    texture: &WebGlTexture,
+) {
+    let gl = &context.gl;
+    gl.use_program(Some(program));
+    gl.active_texture(GL::TEXTURE0);
+    gl.bind_texture(GL::TEXTURE_2D, Some(texture));
+    if let Some(loc) = gl.get_uniform_location(program, "u_texture") {
+        gl.uniform1i(Some(&loc), 0);
+    }
+    gl.bind_buffer(GL::ARRAY_BUFFER, Some(buffer));
+    let pos_loc = gl.get_attrib_location(program, "a_position") as u32;
+    gl.enable_vertex_attrib_array(pos_loc);
+    gl.vertex_attrib_pointer_with_i32(pos_loc, 2, GL::FLOAT, false, 0, 0);
+    apply_opacity(gl, program, context.opacity);
+    apply_linear(gl, program, context.linear);
+    gl.draw_arrays(GL::TRIANGLES, 0, 3);
+}
+
+fn apply_opacity(gl: &GL, program: &WebGlProgram, opacity: f32) {
+    if let Some(loc) = gl.get_uniform_location(program, "u_opacity") {
+        gl.uniform1f(Some(&loc), opacity);
+    }
+}
+
+fn apply_linear(gl: &GL, program: &WebGlProgram, linear: bool) {
+    if let Some(loc) = gl.get_uniform_location(program, "u_linear") {
+        gl.uniform1f(Some(&loc), if linear { 1.0 } else { 0.0 });
+    }
+}
+
+fn apply_tint(gl: &GL, program: &WebGlProgram, tint: [f32; 3], mix: f32) {
+    if let Some(loc) = gl.get_uniform_location(program, "u_tint") {
+        gl.uniform3f(Some(&loc), tint[0], tint[1], tint[2]);
+    }
+    if let Some(loc) = gl.get_uniform_location(program, "u_tintMix") {
+        gl.uniform1f(Some(&loc), mix);
+    }
+}
+
+pub(super) fn upload_vertices(gl: &GL, buffer: &WebGlBuffer, data: &[f32], usage: u32) {
+    gl.bind_buffer(GL::ARRAY_BUFFER, Some(buffer));
+    unsafe {
+        let array = js_sys::Float32Array::view(data);
+        gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &array, usage);
+    }
+}
+
+fn css_color(color: [f32; 3]) -> String {
+    let [r, g, b] = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("rgb({}, {}, {})", r, g, b)
+}
+
+// Paints one static frame of sky and stars for browsers without WebGL. Falls
+// back further to a CSS gradient when even a 2D context is unavailable.
+pub(super) fn paint_fallback(canvas: &HtmlCanvasElement, num_stars: usize, options: &StarFieldOptions) -> &'static str {
+    let transparent = options.transparent;
+    let dpr = pixel_ratio(options.data_saver);
+    let width = canvas.client_width() as f32 * dpr;
+    let height = canvas.client_height() as f32 * dpr;
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+
+    let context = canvas
+        .get_context("2d")
+        .ok()
+        .flatten()
+        .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok());
+    let Some(ctx) = context else {
+        if transparent {
+            return "none";
+        }
+        let gradient = format!(
+            "linear-gradient(to bottom, {}, {})",
+            css_color(SKY_TOP_COLOR),
+            css_color(SKY_BOTTOM_COLOR)
+        );
+        let _ = canvas.style().set_property("background", &gradient);
+        return "css";
+    };
+
+    ctx.clear_rect(0.0, 0.0, width as f64, height as f64);
+    if !transparent {
+        let gradient = ctx.create_linear_gradient(0.0, 0.0, 0.0, height as f64);
+        let _ = gradient.add_color_stop(0.0, &css_color(SKY_TOP_COLOR));
+        let _ = gradient.add_color_stop(1.0, &css_color(SKY_BOTTOM_COLOR));
+        ctx.set_fill_style_canvas_gradient(&gradient);
+        ctx.fill_rect(0.0, 0.0, width as f64, height as f64);
+    }
+    let mut stars = ParticleStore::with_capacity(num_stars);
+    scatter_stars(&mut sky_rng(options.seed), &options.star_config(), &mut stars, num_stars, width, height);
+    for i in 0..stars.len() {
+        ctx.set_global_alpha(stars.data[i].base_alpha as f64);
+        ctx.set_fill_style_str(&css_color(stars.color[i]));
+        ctx.begin_path();
+        let radius = (stars.size[i] * POINT_SCALE * dpr / 2.0).max(0.5);
+        let _ = ctx.arc(stars.x[i] as f64, stars.y[i] as f64, radius as f64, 0.0, std::f64::consts::TAU);
+        ctx.fill();
+    }
+    ctx.set_global_alpha(1.0);
+    "canvas2d"
+}
+
+pub(crate) fn compile_shader(gl: &GL, shader_type: u32, source: &str) -> Result<WebGlShader, String> {
+    let shader = gl.create_shader(shader_type).ok_or("Unable to create shader object")?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+    if gl.get_shader_parameter(&shader, GL::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        // Drivers may still report warnings for shaders that compiled.
+        log(Level::Debug, || {
+            let info = gl.get_shader_info_log(&shader).unwrap_or_default();
+            format!("Compiled shader ({} bytes) {}", source.len(), info.trim())
+        });
+        Ok(shader)
+    } else {
+        let info = gl.get_shader_info_log(&shader).unwrap_or_else(|| "Unknown error creating shader".into());
+        log(Level::Error, || format!("Shader compile failed: {}\n{}", info.trim(), source));
+        Err(info)
+    }
+}
+
+pub(crate) fn link_program(gl: &GL, vertex_shader: &WebGlShader, fragment_shader: &WebGlShader) -> Result<WebGlProgram, String> {
+    let program = gl.create_program().ok_or("Unable to create shader program")?;
+    gl.attach_shader(&program, vertex_shader);
+    gl.attach_shader(&program, fragment_shader);
+    gl.link_program(&program);
+    if gl.get_program_parameter(&program, GL::LINK_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(program)
+    } else {
+        let info = gl.get_program_info_log(&program).unwrap_or_else(|| "Unknown error linking program".into());
+        log(Level::Error, || format!("Program link failed: {}", info.trim()));
+        Err(info)
+    }
+}
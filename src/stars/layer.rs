@@ -0,0 +1,75 @@
+use super::gl::BlendMode;
+use super::tween::Tween;
+
+/// The sky's draw slots, bottom to top by default. Built-in content lives
+/// in `Background`, `Stars`, `Meteors`, `Foreground` (effect particles) and
+/// `Overlay` (countdown and ticker); `Nebula` is left for effects.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Layer {
+    Background,
+    Nebula,
+    Stars,
+    Meteors,
+    Foreground,
+    Overlay,
+}
+
+impl Layer {
+    const ALL: [Layer; 6] =
+        [Layer::Background, Layer::Nebula, Layer::Stars, Layer::Meteors, Layer::Foreground, Layer::Overlay];
+    pub(super) const NAMES: &str = "\"background\", \"nebula\", \"stars\", \"meteors\", \"foreground\" or \"overlay\"";
+
+    pub(super) fn from_name(name: &str) -> Option<Layer> {
+        match name.to_ascii_lowercase().as_str() {
+            "background" | "sky" => Some(Layer::Background),
+            "nebula" => Some(Layer::Nebula),
+            "stars" => Some(Layer::Stars),
+            "meteors" => Some(Layer::Meteors),
+            "foreground" | "particles" => Some(Layer::Foreground),
+            "overlay" | "text" => Some(Layer::Overlay),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(super) struct LayerState {
+    pub(super) enabled: bool,
+    // Eases to a new opacity rather than jumping.
+    pub(super) opacity: Tween<f32>,
+    pub(super) blend: BlendMode,
+}
+
+pub(super) struct Layers {
+    // Bottom to top.
+    pub(super) order: [Layer; 6],
+    states: [LayerState; 6],
+}
+
+impl Layers {
+    pub(super) const DEFAULT: Layers = Layers {
+        order: Layer::ALL,
+        states: [
+            LayerState { enabled: true, opacity: Tween::settled(1.0), blend: BlendMode::Alpha },
+            LayerState { enabled: true, opacity: Tween::settled(1.0), blend: BlendMode::Additive },
+            LayerState { enabled: true, opacity: Tween::settled(1.0), blend: BlendMode::Alpha },
+            LayerState { enabled: true, opacity: Tween::settled(1.0), blend: BlendMode::Additive },
+            LayerState { enabled: true, opacity: Tween::settled(1.0), blend: BlendMode::Additive },
+            LayerState { enabled: true, opacity: Tween::settled(1.0), blend: BlendMode::Alpha },
+        ],
+    };
+
+    pub(super) fn get(&self, layer: Layer) -> LayerState {
+        self.states[layer as usize]
+    }
+
+    pub(super) fn get_mut(&mut self, layer: Layer) -> &mut LayerState {
+        &mut self.states[layer as usize]
+    }
+
+    pub(super) fn step(&mut self, dt: f32) {
+        for state in &mut self.states {
+            state.opacity.step(dt);
+        }
+    }
+}
@@ -0,0 +1,499 @@
+//! The animated sky behind the site. `sim` owns the simulation state and
+//! how it evolves (the browser-free motion itself lives in the `soj-sim`
+//! crate), `gl` turns that state into pixels, and `api` is the
+//! JavaScript surface: the running starfield, its options and exports.
+//! Everything drawn implements `effect::Effect` and is drawn in a
+//! `layer::Layer`, in a configurable order. Other canvases (charts,
+//! confetti, spinners) reuse the GL helpers re-exported here.
+
+use std::rc::Rc;
+
+use soj_sim::particles::Sparks;
+use soj_sim::stars::{scatter_stars, StarConfig};
+use soj_sim::{ParticleStore, Rng};
+use wasm_bindgen::prelude::*;
+use web_sys::{HtmlCanvasElement, WebGlBuffer, WebGlProgram, WebGlRenderingContext as GL};
+
+use crate::diagnostics::{log, Level};
+use crate::options::{bool_option, get_option, invalid_option};
+use crate::text::TextRenderer;
+
+mod api;
+mod baked;
+mod benchmark;
+mod bokeh;
+mod builder;
+mod composite;
+mod debris;
+mod effect;
+mod element;
+mod emitter;
+mod gl;
+mod layer;
+mod mount;
+mod observer;
+mod plexus;
+mod sim;
+mod solve_streak;
+mod transfer;
+mod tween;
+mod vision;
+
+pub use api::*;
+pub use builder::StarFieldBuilder;
+pub use composite::ToneMapping;
+pub use effect::{Effect, EffectContext};
+pub use element::define_star_field_element;
+pub use layer::Layer;
+pub use mount::{mount, unmount};
+pub use plexus::PlexusStyle;
+pub use sim::{CornerGradient, HueShift, SkyPalette, ThemeTint};
+pub use tween::{Easing, Transition};
+pub use vision::ColorVision;
+pub use soj_sim::flow::FlowField;
+pub use soj_sim::meteors::MeteorColors;
+pub use soj_sim::stars::{
+    DensityMap, StarBand, StarCluster, StarMask, StarPalette, StarSizes, TwinkleConfig, TwinkleMode,
+};
+pub use soj_sim::zones::ExclusionZone;
+pub(crate) use gl::{compile_shader, link_program};
+pub(crate) use sim::DIFFICULTY_SCALE;
+
+use api::{
+    color_vision_option, density_map_option, find_canvas, hue_shift_option, meteor_colors_option, pixel_ratio,
+    prefers_dark_scheme, save_data_requested, seed_option, star_band_option, star_clusters_option, star_mask_option,
+    star_palette_option, star_sizes_option, twinkle_mode_option, twinkle_option,
+};
+use composite::{Composite, Output};
+use effect::EffectSlot;
+use gl::{create_context, create_programs, Background};
+use layer::Layers;
+use observer::ObserverSky;
+use plexus::PlexusLines;
+use sim::{
+    sky_rng, Clock, ContestThemes, Countdown, DebugOverlay, Limits, Meteors, PendingBurst, Stars, Ticker, Wallpaper,
+};
+use solve_streak::SolveStreak;
+use tween::Tween;
+
+#[wasm_bindgen]
+pub struct StarField {
+    gl: GL,
+    canvas: HtmlCanvasElement,
+    background: Background,
+    stars: Stars,
+    meteors: Meteors,
+    // Registered with `register_effect`, drawn above the built-in layers.
+    effects: Vec<EffectSlot>,
+    next_effect_id: u32,
+    resolution: (f32, f32),
+    dpr: f32,
+    raw_pixels: bool,
+    data_saver: bool,
+    // Star count requested for `reference_area` (CSS pixels squared), and
+    // the count the sky is easing through toward it.
+    num_stars: usize,
+    star_count: Tween<f32>,
+    reference_area: f32,
+    // Shared by the stars and particles.
+    star_program: WebGlProgram,
+    theme: ThemeTint,
+    theme_target: ThemeTint,
+    clock: Clock,
+    // Draws new stars on resize.
+    rng: Rng,
+    star_config: StarConfig,
+    pulsing: bool,
+    pulse_phase: f32,
+    pulse_strength: f32,
+    text_renderer: TextRenderer,
+    countdown: Option<Countdown>,
+    ticker: Option<Ticker>,
+    contest: Option<ContestThemes>,
+    solve_streak: Option<SolveStreak>,
+    debug_overlay: Option<DebugOverlay>,
+    particles: Sparks,
+    particle_buffer: WebGlBuffer,
+    pending_bursts: Vec<PendingBurst>,
+    layers: Layers,
+    transparent: bool,
+    hidden: bool,
+    last_gl_error: u32,
+    limits: Limits,
+    wallpaper: Option<Wallpaper>,
+    color_scheme: ColorScheme,
+    prefers_dark: bool,
+    sky: SkyPalette,
+    // Eases `sky` to a new palette.
+    sky_tween: Tween<SkyPalette>,
+    palette: Option<SkyPalette>,
+    plexus: Option<PlexusLines>,
+    // In device pixels; `None` while off the page.
+    pointer: Option<(f32, f32)>,
+    color_vision: ColorVision,
+    // Motion blur strength, the streak program while it is above 0, and
+    // whether the particle buffer was uploaded for it.
+    motion_blur: f32,
+    streak_program: Option<WebGlProgram>,
+    particles_streaked: bool,
+    // Scaled frames advanced by the last `update`.
+    last_dt: f32,
+    // Scales the brightness of everything above the background and how
+    // often meteors and emitters spawn; eases toward `intensity_target`.
+    intensity: f32,
+    intensity_target: f32,
+    // How palettes, gradients, star counts and layer opacities change.
+    transition: Transition,
+    // The host's timestamp at the last `tick`, in milliseconds.
+    last_tick: Option<f64>,
+    output: Output,
+    // The offscreen frame while `output` needs compositing.
+    composite: Option<Composite>,
+    // In device pixels.
+    exclusion_zones: Rc<[ExclusionZone]>,
+    // Shows the catalog stars as seen from a place on Earth instead of
+    // random ones.
+    observer: Option<ObserverSky>,
+}
+
+/// Start-up options for `start_starfield_with_options`.
+#[derive(Default, Clone)]
+pub struct StarFieldOptions {
+    /// Skips the sky gradient and clears to transparent so the page
+    /// background shows through.
+    pub transparent: bool,
+    /// Skips the built-in `requestAnimationFrame` loop; the host drives the
+    /// sky with `tick`, or `step` and `render`.
+    pub manual: bool,
+    /// Night or daytime sky; `Auto` follows `prefers-color-scheme`.
+    pub color_scheme: ColorScheme,
+    /// A fixed sky used instead of the night and daytime presets, whatever
+    /// the color scheme.
+    pub palette: Option<SkyPalette>,
+    /// Sizes in device rather than CSS pixels; see `set_raw_pixel_sizing`.
+    pub raw_pixels: bool,
+    /// Star colors; the default mixes warm, blue and white.
+    pub star_palette: Option<StarPalette>,
+    /// How big stars are; the default is mostly small stars, a few up to
+    /// eight times their size.
+    pub star_sizes: StarSizes,
+    /// Twinkle swing, speed and base alphas; see `TwinkleConfig`'s default.
+    pub twinkle: Option<TwinkleConfig>,
+    /// How stars twinkle at startup; see `set_twinkle_mode`.
+    pub twinkle_mode: TwinkleMode,
+    /// The dense band most initial stars fall in.
+    pub band: StarBand,
+    /// Hotspots of big initial stars; the default is one at the center.
+    pub clusters: Option<Vec<StarCluster>>,
+    /// Biases where the initial stars are placed; see `DensityMap`.
+    pub density_map: Option<DensityMap>,
+    /// Confines star placement to a shape; see `set_star_mask`.
+    pub star_mask: Option<StarMask>,
+    /// A slow drift of the background's hue; see `set_hue_shift`.
+    pub hue_shift: Option<HueShift>,
+    /// Meteor head and tail colors; the default is a flat pale yellow.
+    pub meteor_colors: MeteorColors,
+    /// Color-blind-safe stars, status tints and rating bursts; an explicit
+    /// `star_palette` still wins for the stars.
+    pub color_vision: ColorVision,
+    /// Seeds every random draw in the sky, so the same seed and steps give
+    /// the same frames; by default each start differs.
+    pub seed: Option<u64>,
+    /// Renders at one pixel per CSS pixel whatever the device pixel ratio,
+    /// trading sharpness on high-DPI screens for less fill rate and memory.
+    pub data_saver: bool,
+}
+
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum ColorScheme {
+    #[default]
+    Auto,
+    Dark,
+    Light,
+}
+
+impl ColorScheme {
+    fn from_name(name: &str) -> Option<ColorScheme> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" | "system" => Some(ColorScheme::Auto),
+            "dark" | "night" => Some(ColorScheme::Dark),
+            "light" | "day" => Some(ColorScheme::Light),
+            _ => None,
+        }
+    }
+
+    fn from_js(value: Option<JsValue>) -> Result<ColorScheme, JsValue> {
+        let Some(value) = value else {
+            return Ok(ColorScheme::Auto);
+        };
+        value
+            .as_string()
+            .and_then(|name| ColorScheme::from_name(&name))
+            .ok_or_else(|| invalid_option("colorScheme", "\"auto\", \"dark\" or \"light\"", &value))
+    }
+}
+
+impl StarFieldOptions {
+    pub fn from_js(options: &JsValue) -> Result<StarFieldOptions, JsValue> {
+        let seed = seed_option(options, "seed")?;
+        Ok(StarFieldOptions {
+            transparent: bool_option(options, "transparent")?.unwrap_or(false),
+            manual: bool_option(options, "manual")?.unwrap_or(false),
+            color_scheme: ColorScheme::from_js(get_option(options, "colorScheme"))?,
+            star_palette: star_palette_option(options, "starPalette")?,
+            star_sizes: star_sizes_option(options, "starSizes")?,
+            twinkle: twinkle_option(options, "twinkle")?,
+            twinkle_mode: twinkle_mode_option(options, "twinkle")?,
+            band: star_band_option(options, "band")?,
+            clusters: star_clusters_option(options, "clusters", seed)?,
+            density_map: density_map_option(options, "densityMap")?,
+            star_mask: star_mask_option(options, "starMask")?,
+            hue_shift: hue_shift_option(options, "hueShift")?,
+            meteor_colors: meteor_colors_option(options, "meteorColors")?,
+            color_vision: color_vision_option(options, "colorVision")?,
+            seed,
+            data_saver: bool_option(options, "dataSaver")?.unwrap_or_else(save_data_requested),
+            ..StarFieldOptions::default()
+        })
+    }
+
+    fn star_config(&self) -> StarConfig {
+        StarConfig {
+            palette: self.star_palette.clone().or_else(|| self.color_vision.star_palette()).unwrap_or_default(),
+            sizes: self.star_sizes.clone(),
+            twinkle: self.twinkle.clone().unwrap_or_default(),
+            band: self.band,
+            clusters: self.clusters.clone().unwrap_or_else(|| vec![StarCluster::CENTER]),
+            density: self.density_map.clone(),
+            mask: self.star_mask.clone(),
+        }
+    }
+}
+
+const SKY_BOTTOM_COLOR: [f32; 3] = [54.0 / 255.0, 69.0 / 255.0, 125.0 / 255.0];
+const SKY_TOP_COLOR: [f32; 3] = [25.0 / 255.0, 45.0 / 255.0, 105.0 / 255.0];
+// Star point diameter in pixels per unit of star radius.
+const POINT_SCALE: f32 = 100.0;
+// No star reaches this many pixels across, so GPUs with bigger points
+// need no quad fallback.
+const QUAD_FALLBACK_BELOW: f32 = 512.0;
+const RESIZE_DEBOUNCE_MS: i32 = 150;
+const METEOR_CHANCE_PER_FRAME: f32 = 0.001;
+const METEOR_LAUNCH_ATTEMPTS: usize = 4;
+const MAX_GUIDED_METEORS: usize = 32;
+// Meteor rate multipliers through a contest's phases.
+const CONTEST_CALM_METEOR_RATE: f32 = 0.5;
+const CONTEST_RUNNING_METEOR_RATE: f32 = 1.5;
+const CONTEST_FINAL_MINUTE_METEOR_RATE: f32 = 8.0;
+// The solve streak: golden stars, their size and spacing in CSS pixels and
+// the row's distance from the top, and frames between launches, in flight,
+// shown once all have landed and fading out.
+const MAX_SOLVE_STREAK_STARS: usize = 30;
+const SOLVE_STREAK_COLOR: [f32; 3] = [1.0, 0.82, 0.3];
+const SOLVE_STREAK_STAR_SIZE: f32 = 14.0;
+const SOLVE_STREAK_SPACING: f32 = 22.0;
+const SOLVE_STREAK_TOP: f32 = 36.0;
+const SOLVE_STREAK_STAGGER_FRAMES: f32 = 6.0;
+const SOLVE_STREAK_FLIGHT_FRAMES: f32 = 50.0;
+const SOLVE_STREAK_HOLD_FRAMES: f32 = 480.0;
+const SOLVE_STREAK_FADE_FRAMES: f32 = 60.0;
+const MAX_GUIDED_METEOR_MS: f32 = 60_000.0;
+// Frames a guided meteor's trail takes to catch up with its head.
+const GUIDED_METEOR_SETTLE_FRAMES: f32 = 20.0;
+// Straight pieces of a guided meteor's curved trail.
+const GUIDED_TRAIL_SEGMENTS: usize = 16;
+const FRAME_MS: f64 = 1000.0 / 60.0;
+const MAX_BENCHMARK_FRAMES: usize = 10_000;
+// Longer gaps between ticks count as one frame.
+const MAX_TICK_GAP_MS: f64 = 250.0;
+const MAX_STARS: usize = 100_000;
+const MAX_METEORS: usize = 1_000;
+const MAX_PARTICLES: usize = 100_000;
+const MAX_POSTER_SIZE: u32 = 16_384;
+const WALLPAPER_STAR_BOOST: f32 = 1.5;
+const METEOR_MAX_SPEED: f32 = 200.0;
+const TICKER_MAX_SPEED: f32 = 100.0;
+const MAX_TIME_SCALE: f32 = 10.0;
+const MAX_PLEXUS_DISTANCE: f32 = 2_000.0;
+const MAX_FLOW_SCALE: f32 = 10_000.0;
+const MAX_FLOW_SPEED: f32 = 10.0;
+// In CSS pixels per second.
+const MAX_WIND_SPEED: f32 = 600.0;
+// Frames for the wind to close about two thirds of the way to a new setting.
+const WIND_RAMP_FRAMES: f32 = 45.0;
+// Time constant of `set_intensity`'s fade, in frames; it settles in about
+// two seconds.
+const INTENSITY_FADE_FRAMES: f32 = 30.0;
+const MAX_TRANSITION_MS: f32 = 60_000.0;
+const MAX_EXPOSURE: f32 = 16.0;
+const MAX_MOTION_BLUR: f32 = 4.0;
+// Longest motion-blur streak, in CSS pixels.
+const MAX_STREAK_LENGTH: f32 = 120.0;
+// Meteor head glow diameter as a multiple of the meteor's width, and its
+// flicker: a swing of 15% in size, four times over a meteor's life.
+const HEAD_GLOW_SCALE: f32 = 16.0;
+const HEAD_GLOW_FLICKER: f32 = 0.15;
+const HEAD_GLOW_PULSES: f32 = 4.0;
+const THEME_FADE_RATE: f32 = 0.02;
+const PULSE_SPEED: f32 = 0.03;
+const PULSE_ALPHA_AMPLITUDE: f32 = 0.25;
+const PULSE_SIZE_AMPLITUDE: f32 = 0.15;
+const PULSE_FADE_RATE: f32 = 0.02;
+const RATING_BURST_MIN_PARTICLES: f32 = 20.0;
+const RATING_BURST_MAX_PARTICLES: f32 = 400.0;
+const RATING_UP_COLOR: [f32; 3] = [0.4, 1.0, 0.5];
+const RATING_DOWN_COLOR: [f32; 3] = [1.0, 0.35, 0.35];
+const RANK_UP_RING_STARS: usize = 24;
+const RANK_UP_CONVERGE_FRAMES: f32 = 45.0;
+const RANK_UP_EXPLOSION_PARTICLES: usize = 120;
+const TICKER_DEFAULT_SPEED: f32 = 1.5;
+const TICKER_DEFAULT_FONT_SCALE: f32 = 0.035;
+const TICKER_MARGIN_SCALE: f32 = 0.04;
+const COUNTDOWN_FONT_SCALE: f32 = 0.12;
+const COUNTDOWN_COLOR: [f32; 3] = [0.95, 0.97, 1.0];
+const COUNTDOWN_GLOW: [f32; 3] = [0.5, 0.8, 1.0];
+// In CSS pixels.
+const DEBUG_OVERLAY_FONT_PX: f32 = 12.0;
+const DEBUG_OVERLAY_MARGIN: f32 = 8.0;
+const DEBUG_OVERLAY_COLOR: [f32; 3] = [0.6, 1.0, 0.6];
+const DEBUG_OVERLAY_REFRESH_MS: f64 = 500.0;
+// Wall-clock time between observer chart updates; the sky turns about
+// 0.04 degrees in it.
+const OBSERVER_REPROJECT_MS: f64 = 10_000.0;
+#[cfg(feature = "audio")]
+const COUNTDOWN_TICK_SECONDS: u64 = 10;
+
+impl StarField {
+    /// Builds the sky on the canvas with id `canvas_id`, sized to its
+    /// current layout. Fails with a reason when WebGL or a shader is
+    /// unavailable; callers usually fall back to a static sky then.
+    pub fn new(canvas_id: &str, num_stars: usize, options: &StarFieldOptions) -> Result<StarField, String> {
+        let canvas = find_canvas(canvas_id);
+
+        let dpr = pixel_ratio(options.data_saver);
+        let css_width = canvas.client_width() as f32;
+        let css_height = canvas.client_height() as f32;
+        let width = css_width * dpr;
+        let height = css_height * dpr;
+        canvas.set_width(width as u32);
+        canvas.set_height(height as u32);
+        let resolution = (width, height);
+
+        let gl = create_context(&canvas, options.transparent)?;
+        log(Level::Info, || format!("Canvas {}x{} at devicePixelRatio {}", width, height, dpr));
+
+        let particle_buffer = gl.create_buffer().ok_or("Failed to create particle buffer")?;
+        let (background_program, star_program, meteor_program) = create_programs(&gl)?;
+
+        let mut rng = sky_rng(options.seed);
+        let mut stars = ParticleStore::with_capacity(num_stars);
+        let star_config = options.star_config();
+        scatter_stars(&mut rng, &star_config, &mut stars, num_stars, width, height);
+        let mut background = Background::new(&gl, background_program)?;
+        background.hue_shift = options.hue_shift;
+        let mut stars = Stars::new(&gl, star_program.clone(), stars)?;
+        stars.twinkle = options.twinkle_mode;
+        let meteor_rng = Rng::new(rng.next_u64());
+        let meteors = Meteors::new(&gl, meteor_program, options.meteor_colors, Limits::DEFAULT.meteors, meteor_rng)?;
+
+        let text_renderer = TextRenderer::new(&gl)?;
+        let prefers_dark = prefers_dark_scheme();
+        let sky = options.palette.unwrap_or_else(|| SkyPalette::for_scheme(options.color_scheme, prefers_dark));
+
+        let mut star_field = StarField {
+            gl,
+            canvas,
+            background,
+            stars,
+            meteors,
+            effects: Vec::new(),
+            next_effect_id: 0,
+            resolution,
+            dpr,
+            raw_pixels: options.raw_pixels,
+            data_saver: options.data_saver,
+            num_stars,
+            star_count: Tween::settled(num_stars as f32),
+            reference_area: css_width * css_height,
+            star_program,
+            theme: ThemeTint::NONE,
+            theme_target: ThemeTint::NONE,
+            clock: Clock::DEFAULT,
+            rng,
+            star_config,
+            pulsing: false,
+            pulse_phase: 0.0,
+            pulse_strength: 0.0,
+            text_renderer,
+            countdown: None,
+            ticker: None,
+            contest: None,
+            solve_streak: None,
+            debug_overlay: None,
+            particles: Sparks::with_capacity(0),
+            particle_buffer,
+            pending_bursts: Vec::new(),
+            layers: Layers::DEFAULT,
+            transparent: options.transparent,
+            hidden: false,
+            last_gl_error: GL::NO_ERROR,
+            limits: Limits::DEFAULT,
+            wallpaper: None,
+            color_scheme: options.color_scheme,
+            prefers_dark,
+            sky,
+            sky_tween: Tween::settled(sky),
+            palette: options.palette,
+            plexus: None,
+            pointer: None,
+            color_vision: options.color_vision,
+            motion_blur: 0.0,
+            streak_program: None,
+            particles_streaked: false,
+            last_dt: 0.0,
+            intensity: 1.0,
+            intensity_target: 1.0,
+            transition: Transition::DEFAULT,
+            last_tick: None,
+            output: Output::GAMMA,
+            composite: None,
+            exclusion_zones: Rc::from(Vec::new()),
+            observer: None,
+        };
+        // Fill the buffers so a `manual` host can render before its first step.
+        star_field.update(0.0);
+        Ok(star_field)
+    }
+
+    /// Advances the sky by `dt` frames at 60 fps, times the time scale, and
+    /// uploads the result for the next `draw`.
+    pub fn update(&mut self, dt: f32) {
+        let dt = self.clock.tick(dt);
+        self.last_dt = dt;
+        self.simulate(dt);
+        let context = self.effect_context(1.0);
+        for layer in self.layers_mut() {
+            layer.update(&context, dt);
+        }
+        self.expire_solve_streak();
+        self.update_plexus();
+        self.upload_particles();
+        self.update_debug_overlay();
+    }
+
+    /// Advances the sky to `timestamp_ms` (e.g. the `requestAnimationFrame`
+    /// timestamp) and draws it, for hosts running their own loop. The first
+    /// tick, and any after a long gap such as a hidden tab, advance a
+    /// single frame so the sky does not leap.
+    pub fn tick(&mut self, timestamp_ms: f64) {
+        let elapsed = match self.last_tick {
+            Some(last) if (0.0..=MAX_TICK_GAP_MS).contains(&(timestamp_ms - last)) => timestamp_ms - last,
+            _ => FRAME_MS,
+        };
+        self.last_tick = Some(timestamp_ms);
+        self.update((elapsed / FRAME_MS) as f32);
+        if self.check_visibility() {
+            self.draw();
+        }
+    }
+}
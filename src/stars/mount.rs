@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{window, HtmlCanvasElement, HtmlElement, ResizeObserver};
+
+use crate::options::{invalid_option, number_option};
+
+use super::api::{run_starfield, stop_starfield, with_star_field};
+use super::builder::DEFAULT_STARS;
+use super::{StarFieldOptions, MAX_STARS};
+
+const MOUNT_CANVAS_ID: &str = "soj-starfield";
+
+// A canvas injected by `mount`, with what it changed on its container.
+struct Mounted {
+    container: HtmlElement,
+    canvas: HtmlCanvasElement,
+    observer: Option<ResizeObserver>,
+    // The container's inline styles before mounting.
+    position: String,
+    isolation: String,
+}
+
+thread_local! {
+    static MOUNTED: RefCell<Option<Mounted>> = const { RefCell::new(None) };
+}
+
+/// Adds a canvas filling the first element matching `selector`, behind its
+/// content, and starts the starfield on it. `options` are those of
+/// `start_starfield_with_options` plus `numStars` (default 1000) and
+/// `zIndex` (default -1). A statically positioned container becomes
+/// `position: relative`, and gets `isolation: isolate` so the canvas stays
+/// above its background. Mounting again replaces the previous canvas;
+/// `unmount` removes it.
+#[wasm_bindgen]
+pub fn mount(selector: &str, options: JsValue) -> Result<(), JsValue> {
+    let document = window().unwrap().document().unwrap();
+    let container = document
+        .query_selector(selector)
+        .ok()
+        .flatten()
+        .and_then(|element| element.dyn_into::<HtmlElement>().ok())
+        .ok_or_else(|| invalid_option("selector", "a selector matching an element", &selector.into()))?;
+    let num_stars = number_option(&options, "numStars", 0.0, MAX_STARS as f32)?.map_or(DEFAULT_STARS, |n| n as usize);
+    let z_index = number_option(&options, "zIndex", -1e6, 1e6)?.unwrap_or(-1.0) as i32;
+    mount_in(container, num_stars, &StarFieldOptions::from_js(&options)?, z_index)
+}
+
+// `mount` on an element already found, with parsed options.
+pub(super) fn mount_in(
+    container: HtmlElement,
+    num_stars: usize,
+    options: &StarFieldOptions,
+    z_index: i32,
+) -> Result<(), JsValue> {
+    unmount();
+    let document = window().unwrap().document().unwrap();
+    let canvas = document.create_element("canvas")?.dyn_into::<HtmlCanvasElement>()?;
+    canvas.set_id(MOUNT_CANVAS_ID);
+    canvas.set_attribute("aria-hidden", "true")?;
+    let style = canvas.style();
+    style.set_property("position", "absolute")?;
+    style.set_property("inset", "0")?;
+    style.set_property("width", "100%")?;
+    style.set_property("height", "100%")?;
+    style.set_property("display", "block")?;
+    style.set_property("pointer-events", "none")?;
+    style.set_property("z-index", &z_index.to_string())?;
+
+    let container_style = container.style();
+    let position = container_style.get_property_value("position")?;
+    let isolation = container_style.get_property_value("isolation")?;
+    let computed = window().unwrap().get_computed_style(&container)?;
+    if computed.is_some_and(|computed| computed.get_property_value("position").as_deref() == Ok("static")) {
+        container_style.set_property("position", "relative")?;
+    }
+    container_style.set_property("isolation", "isolate")?;
+    container.prepend_with_node_1(&canvas)?;
+
+    // The window resize listener misses containers resized by the page.
+    let on_resize = Closure::wrap(Box::new(|| with_star_field(|sf| sf.resize())) as Box<dyn FnMut()>);
+    let observer = ResizeObserver::new(on_resize.as_ref().unchecked_ref()).ok();
+    if let Some(observer) = &observer {
+        observer.observe(&container);
+    }
+    on_resize.forget();
+
+    MOUNTED.with(|mounted| {
+        *mounted.borrow_mut() = Some(Mounted { container, canvas, observer, position, isolation });
+    });
+    run_starfield(MOUNT_CANVAS_ID, num_stars, options);
+    Ok(())
+}
+
+// Whether the mounted starfield, if any, lives in `element`.
+pub(super) fn is_mounted_in(element: &HtmlElement) -> bool {
+    MOUNTED.with(|mounted| mounted.borrow().as_ref().is_some_and(|mounted| &mounted.container == element))
+}
+
+/// Stops the starfield started by `mount`, removes its canvas and restores
+/// the container's styles. Does nothing if nothing is mounted.
+#[wasm_bindgen]
+pub fn unmount() {
+    let Some(mounted) = MOUNTED.with(|mounted| mounted.borrow_mut().take()) else {
+        return;
+    };
+    stop_starfield();
+    if let Some(observer) = &mounted.observer {
+        observer.disconnect();
+    }
+    mounted.canvas.remove();
+    let style = mounted.container.style();
+    let restore = |name: &str, value: &str| {
+        let _ = if value.is_empty() { style.remove_property(name).map(drop) } else { style.set_property(name, value) };
+    };
+    restore("position", &mounted.position);
+    restore("isolation", &mounted.isolation);
+}
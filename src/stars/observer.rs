@@ -0,0 +1,101 @@
+use soj_sim::sky::{chart_position, Observer, BRIGHT_STARS};
+use soj_sim::stars::new_star;
+use soj_sim::ParticleStore;
+
+use super::{StarField, OBSERVER_REPROJECT_MS};
+
+// Sirius, the brightest catalog star, is drawn at the largest star size.
+const BRIGHTEST_MAGNITUDE: f32 = -1.46;
+
+// The catalog stars as seen from `observer`, one per `BRIGHT_STARS` entry
+// in the star store while it is shown.
+pub(super) struct ObserverSky {
+    observer: Observer,
+    // Wall-clock time of the last projection, in milliseconds since the
+    // Unix epoch.
+    projected_at: f64,
+    // How far the shown sky runs ahead of the wall clock, in milliseconds.
+    offset: f64,
+}
+
+impl StarField {
+    /// Replaces the random stars with the brightest catalog stars as seen
+    /// from `observer` right now, charted with the zenith at the center,
+    /// north up and east left, and keeps them turning with the Earth. Stars
+    /// below the horizon are hidden and fade in as they rise. `None` returns
+    /// to a random sky of the current star count.
+    pub fn set_observer(&mut self, observer: Option<Observer>) {
+        match observer {
+            Some(observer) => self.set_observer_at(observer, js_sys::Date::now()),
+            None => self.clear_observer(),
+        }
+    }
+
+    /// Like `set_observer`, but shows the sky as it is at `timestamp`
+    /// (milliseconds since the Unix epoch) and keeps turning from there.
+    pub fn set_observer_at(&mut self, observer: Observer, timestamp: f64) {
+        let now = js_sys::Date::now();
+        self.observer = Some(ObserverSky { observer, projected_at: f64::NEG_INFINITY, offset: timestamp - now });
+        self.stars.pinned = true;
+        self.stars.stars = self.catalog_stars();
+        self.project_observer_sky(now);
+    }
+
+    fn clear_observer(&mut self) {
+        if self.observer.take().is_some() {
+            self.stars.pinned = false;
+            self.stars.stars = ParticleStore::with_capacity(self.num_stars);
+            self.match_star_count((0.0, 0.0));
+        }
+    }
+
+    // Hidden, motionless stars for the catalog, sized by magnitude.
+    fn catalog_stars(&mut self) -> soj_sim::stars::Stars {
+        let largest = self.star_config.sizes.max();
+        let mut stars = ParticleStore::with_capacity(BRIGHT_STARS.len());
+        for &(_, _, magnitude) in BRIGHT_STARS.iter().take(self.limits.stars) {
+            // Radius goes with the square root of brightness.
+            let radius = (largest * 10f32.powf(-0.2 * (magnitude - BRIGHTEST_MAGNITUDE))).max(largest / 8.0);
+            let mut star = new_star(&mut self.rng, &self.star_config, 0.0, 0.0, radius);
+            (star.vx, star.vy) = (0.0, 0.0);
+            star.data.visibility = 0.0;
+            stars.push(star);
+        }
+        stars
+    }
+
+    pub(super) fn update_observer_sky(&mut self) {
+        let now = js_sys::Date::now();
+        if self.observer.as_ref().is_some_and(|sky| now - sky.projected_at >= OBSERVER_REPROJECT_MS) {
+            self.project_observer_sky(now);
+        }
+    }
+
+    // Moves each catalog star to where the observer sees it at `now` on the
+    // wall clock, shifted to the sky's own time.
+    pub(super) fn project_observer_sky(&mut self, now: f64) {
+        let Some(sky) = &mut self.observer else {
+            return;
+        };
+        sky.projected_at = now;
+        let (observer, time) = (sky.observer, now + sky.offset);
+        let (width, height) = self.resolution;
+        let stars = &mut self.stars.stars;
+        // A lowered star limit keeps only the brightest.
+        for (i, &(ra, dec, _)) in BRIGHT_STARS.iter().enumerate().take(stars.len()) {
+            let (altitude, azimuth) = observer.horizontal(ra, dec, time);
+            let position = chart_position(altitude, azimuth, self.resolution)
+                .filter(|&(x, y)| (0.0..=width).contains(&x) && (0.0..=height).contains(&y));
+            let star = &mut stars.data[i];
+            match position {
+                Some((x, y)) => {
+                    (stars.x[i], stars.y[i]) = (x, y);
+                    if star.visibility <= 0.0 {
+                        star.fade_in();
+                    }
+                }
+                None => (star.visibility, star.fade) = (0.0, 0.0),
+            }
+        }
+    }
+}
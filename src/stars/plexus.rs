@@ -0,0 +1,98 @@
+use soj_sim::plexus::{Link, Plexus};
+use web_sys::{WebGlBuffer, WebGlProgram, WebGlRenderingContext as GL};
+
+use super::gl::{create_line_program, upload_vertices};
+use super::StarField;
+
+// Bounds the line buffer when many stars crowd the pointer.
+const MAX_PLEXUS_LINKS: usize = 4_096;
+
+/// Look of the plexus lines; distances are in CSS pixels.
+#[derive(Clone, Copy)]
+pub struct PlexusStyle {
+    /// Only stars this close to the pointer are linked.
+    pub radius: f32,
+    /// Stars this close to each other are linked.
+    pub link_distance: f32,
+    pub color: [f32; 3],
+    /// Alpha of the strongest line.
+    pub alpha: f32,
+}
+
+impl PlexusStyle {
+    pub const DEFAULT: PlexusStyle =
+        PlexusStyle { radius: 200.0, link_distance: 80.0, color: [0.7, 0.8, 1.0], alpha: 0.35 };
+}
+
+// Faint lines between the stars near the pointer, drawn under the stars.
+pub(super) struct PlexusLines {
+    pub(super) style: PlexusStyle,
+    plexus: Plexus,
+    links: Vec<Link>,
+    pub(super) program: WebGlProgram,
+    pub(super) buffer: WebGlBuffer,
+    pub(super) vertices: usize,
+}
+
+impl PlexusLines {
+    pub(super) fn new(gl: &GL, style: PlexusStyle) -> Result<PlexusLines, String> {
+        let program = create_line_program(gl)?;
+        let buffer = gl.create_buffer().ok_or("Failed to create plexus buffer")?;
+        let plexus = Plexus::new(style.radius, style.link_distance);
+        Ok(PlexusLines { style, plexus, links: Vec::new(), program, buffer, vertices: 0 })
+    }
+
+    fn destroy(&self, gl: &GL) {
+        gl.delete_program(Some(&self.program));
+        gl.delete_buffer(Some(&self.buffer));
+    }
+}
+
+impl StarField {
+    /// Links stars near the pointer with faint lines, or stops with `None`.
+    pub fn set_plexus(&mut self, style: Option<PlexusStyle>) -> Result<(), String> {
+        match (style, &mut self.plexus) {
+            (Some(style), Some(plexus)) => plexus.style = style,
+            (Some(style), None) => self.plexus = Some(PlexusLines::new(&self.gl, style)?),
+            (None, _) => {
+                if let Some(plexus) = self.plexus.take() {
+                    plexus.destroy(&self.gl);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves the pointer the plexus gathers around, in CSS pixels from the
+    /// canvas's top left; `None` when it leaves.
+    pub fn set_pointer(&mut self, pointer: Option<(f32, f32)>) {
+        self.pointer = pointer.map(|(x, y)| (x * self.dpr, y * self.dpr));
+    }
+
+    // Relinks after the stars have moved.
+    pub(super) fn update_plexus(&mut self) {
+        let size_scale = self.size_scale();
+        let Some(plexus) = &mut self.plexus else {
+            return;
+        };
+        let Some(pointer) = self.pointer else {
+            plexus.vertices = 0;
+            return;
+        };
+        let style = plexus.style;
+        plexus.plexus.radius = style.radius * size_scale;
+        plexus.plexus.link_distance = style.link_distance * size_scale;
+        let stars = &self.stars.stars;
+        plexus.plexus.links(&stars.x, &stars.y, pointer, MAX_PLEXUS_LINKS, &mut plexus.links);
+        let mut data = Vec::with_capacity(plexus.links.len() * 2 * 6);
+        for link in &plexus.links {
+            let alpha = style.alpha * link.strength;
+            for i in [link.a, link.b] {
+                data.extend_from_slice(&[stars.x[i], stars.y[i], alpha]);
+                data.extend_from_slice(&style.color);
+            }
+        }
+        upload_vertices(&self.gl, &plexus.buffer, &data, GL::DYNAMIC_DRAW);
+        plexus.vertices = plexus.links.len() * 2;
+    }
+}
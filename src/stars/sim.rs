@@ -0,0 +1,1151 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use js_sys::Function;
+use soj_sim::contest::{ContestPhase, ContestSchedule};
+use soj_sim::flow::FlowField;
+use soj_sim::meteors::{launch_meteor, GuidedPath, MeteorColors, MeteorSchedule, MeteorTrajectory};
+use soj_sim::showers::{active_shower, MeteorShower};
+use soj_sim::particles::step_sparks;
+use soj_sim::stars::{new_star, place_star, step_stars, StarMask, TwinkleMode};
+use soj_sim::zones::ExclusionZone;
+use soj_sim::{ParticleStore, Rng, Spawn};
+use wasm_bindgen::JsValue;
+use web_sys::{WebGlBuffer, WebGlProgram, WebGlRenderingContext as GL};
+
+#[cfg(feature = "audio")]
+use crate::audio::{self, Sound};
+use crate::diagnostics::{log, Level};
+use crate::i18n::translate;
+use crate::text::{TextLayer, TextStyle};
+
+#[cfg(feature = "audio")]
+use super::COUNTDOWN_TICK_SECONDS;
+use super::api::pixel_ratio;
+use super::effect::EffectContext;
+use super::gl::{create_glow_program, create_star_quad_program, max_point_size, DEFAULT_CUSTOM_COLOR};
+use super::tween::Tween;
+use super::vision::ColorVision;
+use super::{
+    ColorScheme, StarField, CONTEST_CALM_METEOR_RATE, CONTEST_FINAL_MINUTE_METEOR_RATE, CONTEST_RUNNING_METEOR_RATE,
+    COUNTDOWN_COLOR, COUNTDOWN_FONT_SCALE, COUNTDOWN_GLOW, DEBUG_OVERLAY_COLOR, DEBUG_OVERLAY_FONT_PX,
+    DEBUG_OVERLAY_REFRESH_MS, GUIDED_METEOR_SETTLE_FRAMES, INTENSITY_FADE_FRAMES, MAX_GUIDED_METEORS, MAX_STARS,
+    METEOR_CHANCE_PER_FRAME, METEOR_LAUNCH_ATTEMPTS, POINT_SCALE, PULSE_ALPHA_AMPLITUDE, PULSE_FADE_RATE,
+    PULSE_SIZE_AMPLITUDE, PULSE_SPEED, QUAD_FALLBACK_BELOW, RANK_UP_CONVERGE_FRAMES, RANK_UP_EXPLOSION_PARTICLES,
+    RANK_UP_RING_STARS, RATING_BURST_MAX_PARTICLES, RATING_BURST_MIN_PARTICLES, SKY_BOTTOM_COLOR, SKY_TOP_COLOR,
+    THEME_FADE_RATE, WALLPAPER_STAR_BOOST, WIND_RAMP_FRAMES,
+};
+
+// Settings saved on entering wallpaper mode, restored on exit.
+pub(super) struct Wallpaper {
+    num_stars: usize,
+    cursor: String,
+}
+
+pub(super) struct Limits {
+    pub(super) stars: usize,
+    pub(super) meteors: usize,
+    pub(super) particles: usize,
+}
+
+impl Limits {
+    pub(super) const DEFAULT: Limits = Limits { stars: MAX_STARS, meteors: 64, particles: 8_000 };
+}
+
+/// The sky gradient and how much of the stars shows through it.
+#[derive(Clone, Copy, PartialEq)]
+pub struct SkyPalette {
+    pub top: [f32; 3],
+    pub bottom: [f32; 3],
+    pub star_alpha: f32,
+}
+
+impl SkyPalette {
+    pub(super) fn for_scheme(scheme: ColorScheme, prefers_dark: bool) -> SkyPalette {
+        match scheme {
+            ColorScheme::Auto if prefers_dark => SkyPalette::NIGHT,
+            ColorScheme::Dark => SkyPalette::NIGHT,
+            _ => SkyPalette::DAY,
+        }
+    }
+
+    const NIGHT: SkyPalette = SkyPalette { top: SKY_TOP_COLOR, bottom: SKY_BOTTOM_COLOR, star_alpha: 1.0 };
+    // A pale daytime haze that only the brightest stars show through.
+    const DAY: SkyPalette = SkyPalette { top: [0.56, 0.7, 0.86], bottom: [0.8, 0.86, 0.92], star_alpha: 0.3 };
+}
+
+/// A color per canvas corner, blended bilinearly across the sky instead of
+/// its vertical gradient. A corner with several colors cycles through them
+/// smoothly, once per `period` seconds of sky time.
+#[derive(Clone, PartialEq)]
+pub struct CornerGradient {
+    /// Top-left, top-right, bottom-left and bottom-right.
+    pub corners: [Vec<[f32; 3]>; 4],
+    pub period: f32,
+}
+
+impl CornerGradient {
+    pub(super) fn colors_at(&self, time: f32) -> [[f32; 3]; 4] {
+        let cycle = if self.period > 0.0 { (time / self.period).fract() } else { 0.0 };
+        self.corners.each_ref().map(|colors| {
+            let Some(&first) = colors.first() else {
+                return [0.0; 3];
+            };
+            let position = cycle * colors.len() as f32;
+            let index = position as usize % colors.len();
+            let (from, to) = (colors[index], colors.get(index + 1).copied().unwrap_or(first));
+            let t = position.fract();
+            let t = t * t * (3.0 - 2.0 * t);
+            [0, 1, 2].map(|i| from[i] + (to[i] - from[i]) * t)
+        })
+    }
+}
+
+/// A slow swing of the background's hue, `range` degrees from end to end,
+/// there and back once per `period` seconds of sky time.
+#[derive(Clone, Copy, PartialEq)]
+pub struct HueShift {
+    pub range: f32,
+    pub period: f32,
+}
+
+impl HueShift {
+    // Radians to rotate the hue by at `time`.
+    pub(super) fn angle_at(&self, time: f32) -> f32 {
+        let phase = if self.period > 0.0 { time / self.period * std::f32::consts::TAU } else { 0.0 };
+        self.range.to_radians() / 2.0 * phase.sin()
+    }
+}
+
+// Rotates `color` about the gray axis, keeping its brightness roughly.
+pub(super) fn rotate_hue(color: [f32; 3], angle: f32) -> [f32; 3] {
+    let (sin, cos) = angle.sin_cos();
+    let k = 1.0 / 3.0_f32.sqrt();
+    let gray = k * (color[0] + color[1] + color[2]) * (1.0 - cos);
+    let cross = [k * (color[2] - color[1]), k * (color[0] - color[2]), k * (color[1] - color[0])];
+    [0, 1, 2].map(|i| (color[i] * cos + cross[i] * sin + k * gray).clamp(0.0, 1.0))
+}
+
+// Consecutive stars drawn the same way: `range` indexes quad vertices in
+// `quad_buffer` when `quads` is set, else points in `buffer`.
+pub(super) struct StarRun {
+    pub(super) quads: bool,
+    pub(super) range: Range<usize>,
+}
+
+// The star layer: a slowly drifting, twinkling field.
+pub(super) struct Stars {
+    pub(super) stars: soj_sim::stars::Stars,
+    // Currents the stars drift along instead of their own random drift;
+    // scale and speed in CSS pixels.
+    pub(super) flow: Option<FlowField>,
+    // Shared drift on top of each star's own, in CSS pixels per frame:
+    // `wind` eases toward `wind_target`.
+    pub(super) wind: (f32, f32),
+    pub(super) wind_target: (f32, f32),
+    pub(super) twinkle: TwinkleMode,
+    // Catalog stars stay where the observer's chart puts them, ignoring
+    // flow and wind.
+    pub(super) pinned: bool,
+    pub(super) program: WebGlProgram,
+    pub(super) buffer: WebGlBuffer,
+    // The uploaded stars in list order, so points and quads overlap as the
+    // stars do.
+    pub(super) runs: Vec<StarRun>,
+    // GPUs clamp points above `max_point_size`, so bigger stars are drawn
+    // as quads when it is below `QUAD_FALLBACK_BELOW`.
+    pub(super) max_point_size: f32,
+    pub(super) quad_program: Option<WebGlProgram>,
+    pub(super) quad_buffer: WebGlBuffer,
+    pub(super) quad_vertices: usize,
+}
+
+impl Stars {
+    pub(super) fn new(gl: &GL, program: WebGlProgram, stars: soj_sim::stars::Stars) -> Result<Stars, String> {
+        let buffer = gl.create_buffer().ok_or("Failed to create star buffer")?;
+        let quad_buffer = gl.create_buffer().ok_or("Failed to create star quad buffer")?;
+        let max_point_size = max_point_size(gl);
+        let quad_program = if max_point_size < QUAD_FALLBACK_BELOW {
+            log(Level::Info, || format!("Points clamp at {}px; drawing bigger stars as quads", max_point_size));
+            Some(create_star_quad_program(gl, DEFAULT_CUSTOM_COLOR)?)
+        } else {
+            None
+        };
+        Ok(Stars {
+            stars,
+            flow: None,
+            wind: (0.0, 0.0),
+            wind_target: (0.0, 0.0),
+            twinkle: TwinkleMode::Sine,
+            pinned: false,
+            program,
+            buffer,
+            runs: Vec::new(),
+            max_point_size,
+            quad_program,
+            quad_buffer,
+            quad_vertices: 0,
+        })
+    }
+
+    pub(super) fn step(&mut self, context: &EffectContext, dt: f32) {
+        let margin_per_radius = POINT_SCALE * context.pulse_size * context.size_scale / 2.0;
+        if let Some(flow) = self.flow.filter(|_| !self.pinned) {
+            let scale = context.size_scale;
+            let flow = FlowField { scale: flow.scale * scale, speed: flow.speed * scale, ..flow };
+            flow.steer(&mut self.stars, context.time, dt);
+        }
+        let ease = 1.0 - (-dt / WIND_RAMP_FRAMES).exp();
+        self.wind.0 += (self.wind_target.0 - self.wind.0) * ease;
+        self.wind.1 += (self.wind_target.1 - self.wind.1) * ease;
+        if self.wind != (0.0, 0.0) && !self.pinned {
+            let (dx, dy) = (self.wind.0 * context.size_scale * dt, self.wind.1 * context.size_scale * dt);
+            self.stars.x.iter_mut().for_each(|x| *x += dx);
+            self.stars.y.iter_mut().for_each(|y| *y += dy);
+        }
+        step_stars(&mut self.stars, dt, context.resolution, margin_per_radius, self.twinkle);
+    }
+}
+
+// A meteor flown between two points by `fly_meteor_between`. Its promise
+// resolves with true when the head arrives, or false if the meteor is
+// dropped first.
+pub(super) struct GuidedMeteor {
+    // In device pixels.
+    pub(super) path: GuidedPath,
+    pub(super) length: f32,
+    pub(super) colors: MeteorColors,
+    // In frames of sky time; the trail catches up for a few more after.
+    pub(super) duration: f32,
+    pub(super) elapsed: f32,
+    arrived: Option<Function>,
+}
+
+impl GuidedMeteor {
+    // How far along the path the head is, 0 to 1, easing in and out.
+    pub(super) fn progress(&self) -> f32 {
+        let t = (self.elapsed / self.duration).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    fn settle(&mut self, arrived: bool) {
+        if let Some(resolve) = self.arrived.take() {
+            resolve.call1(&JsValue::NULL, &arrived.into()).ok();
+        }
+    }
+}
+
+impl Drop for GuidedMeteor {
+    fn drop(&mut self) {
+        self.settle(false);
+    }
+}
+
+// Shooting stars, launched at random from just off-screen.
+pub(super) struct Meteors {
+    pub(super) meteors: soj_sim::meteors::Meteors,
+    pub(super) trajectory: MeteorTrajectory,
+    // For new meteors; those in flight keep theirs.
+    pub(super) colors: MeteorColors,
+    // Scales the spawn rate by the local time of day.
+    pub(super) schedule: Option<MeteorSchedule>,
+    // Follows the yearly showers by today's date.
+    pub(super) calendar: bool,
+    pub(super) limit: usize,
+    // Multiplies the spawn rate, e.g. for a contest's final minute.
+    pub(super) rate_boost: f32,
+    // Flown on request, apart from `meteors` and its limit.
+    pub(super) guided: Vec<GuidedMeteor>,
+    pub(super) rng: Rng,
+    pub(super) program: WebGlProgram,
+    pub(super) buffer: WebGlBuffer,
+    // Uploaded to `buffer`; more than six per meteor with motion blur.
+    pub(super) vertices: usize,
+    // A point sprite at each head, drawn over the trails.
+    pub(super) glow_program: WebGlProgram,
+    pub(super) glow_buffer: WebGlBuffer,
+    pub(super) glows: usize,
+}
+
+impl Meteors {
+    pub(super) fn new(
+        gl: &GL,
+        program: WebGlProgram,
+        colors: MeteorColors,
+        limit: usize,
+        rng: Rng,
+    ) -> Result<Meteors, String> {
+        let buffer = gl.create_buffer().ok_or("Failed to create meteor buffer")?;
+        let glow_program = create_glow_program(gl)?;
+        let glow_buffer = gl.create_buffer().ok_or("Failed to create meteor glow buffer")?;
+        let meteors = ParticleStore::with_capacity(limit);
+        let trajectory = MeteorTrajectory::DEFAULT;
+        let (schedule, calendar) = (None, false);
+        Ok(Meteors {
+            meteors,
+            trajectory,
+            colors,
+            schedule,
+            calendar,
+            limit,
+            rate_boost: 1.0,
+            guided: Vec::new(),
+            rng,
+            program,
+            buffer,
+            vertices: 0,
+            glow_program,
+            glow_buffer,
+            glows: 0,
+        })
+    }
+
+    pub(super) fn step(&mut self, context: &EffectContext, dt: f32) {
+        self.guided.retain_mut(|guided| {
+            guided.elapsed += dt;
+            if guided.elapsed >= guided.duration {
+                guided.settle(true);
+            }
+            guided.elapsed < guided.duration + GUIDED_METEOR_SETTLE_FRAMES
+        });
+        let (rate, shower) = self.real_time();
+        let rate = rate * self.rate_boost * context.intensity;
+        if self.rng.next_f32() < METEOR_CHANCE_PER_FRAME * rate * dt && self.meteors.len() < self.limit {
+            let trajectory = match shower {
+                Some(shower) => &shower.trajectory(&self.trajectory),
+                None => &self.trajectory,
+            };
+            // Retried a few times for a path clear of the exclusion zones.
+            let meteor = (0..METEOR_LAUNCH_ATTEMPTS).find_map(|_| {
+                let meteor =
+                    launch_meteor(&mut self.rng, trajectory, self.colors, context.resolution, context.size_scale)?;
+                let from = (meteor.x, meteor.y);
+                let to = (meteor.x + meteor.vx * meteor.max_lifetime, meteor.y + meteor.vy * meteor.max_lifetime);
+                let blocked = context.exclusion_zones.iter().any(|zone| zone.strength > 0.0 && zone.crosses(from, to));
+                (!blocked).then_some(meteor)
+            });
+            if let Some(meteor) = meteor {
+                self.meteors.push(meteor);
+                #[cfg(feature = "audio")]
+                audio::play(Sound::Meteor);
+            }
+        }
+        self.meteors.integrate(dt);
+        self.meteors.remove_expired();
+    }
+}
+
+impl Meteors {
+    // The spawn rate multiplier from the schedule and calendar at the
+    // user's local time, and the shower meteors fly from, if any.
+    fn real_time(&self) -> (f32, Option<&'static MeteorShower>) {
+        if self.schedule.is_none() && !self.calendar {
+            return (1.0, None);
+        }
+        let now = js_sys::Date::new_0();
+        let hour = now.get_hours() as f32 + now.get_minutes() as f32 / 60.0;
+        let mut rate = self.schedule.map_or(1.0, |schedule| schedule.weight(hour));
+        let shower = self.calendar.then(|| active_shower(now.get_month() + 1, now.get_date())).flatten();
+        if let Some((shower, activity)) = shower {
+            rate *= shower.rate(activity);
+        }
+        (rate, shower.map(|(shower, _)| shower))
+    }
+
+    pub(super) fn active_shower(&self) -> Option<&'static str> {
+        self.real_time().1.map(|shower| shower.name)
+    }
+}
+
+// A generator seeded from `Math.random`, so each page load gets its own sky.
+pub(super) fn seeded_rng() -> Rng {
+    Rng::new((js_sys::Math::random() * u64::MAX as f64) as u64)
+}
+
+// The sky's generator when started with `seed`, or a fresh one.
+pub(super) fn sky_rng(seed: Option<u64>) -> Rng {
+    seed.map_or_else(seeded_rng, Rng::new)
+}
+
+// Sky time. Every animation advances by `tick`'s scaled delta, so a
+// `scale` of 0 freezes the sky and 2 plays it at double speed.
+pub(super) struct Clock {
+    pub(super) scale: f32,
+    // Scaled frames since start.
+    elapsed: f32,
+}
+
+impl Clock {
+    pub(super) const DEFAULT: Clock = Clock { scale: 1.0, elapsed: 0.0 };
+
+    pub(super) fn tick(&mut self, dt: f32) -> f32 {
+        let dt = dt * self.scale;
+        self.elapsed += dt;
+        dt
+    }
+
+    pub(super) fn seconds(&self) -> f32 {
+        self.elapsed / 60.0
+    }
+}
+
+
+// The contest the theme follows, and the phase last applied.
+pub(super) struct ContestThemes {
+    schedule: ContestSchedule,
+    phase: Option<ContestPhase>,
+}
+
+pub(super) struct PendingBurst {
+    x: f32,
+    y: f32,
+    delay: f32,
+    color: [f32; 3],
+}
+
+pub(super) struct Ticker {
+    pub(super) layer: TextLayer,
+    pub(super) text: String,
+    // When set, `text` is a message key translated with these arguments.
+    pub(super) args: Option<Vec<(String, String)>>,
+    pub(super) offset: f32,
+    pub(super) speed: f32,
+    pub(super) at_top: bool,
+    pub(super) font_scale: f32,
+    pub(super) color: [f32; 3],
+}
+
+pub(super) struct Countdown {
+    pub(super) target_ms: f64,
+    pub(super) layer: TextLayer,
+    pub(super) last_remaining: Option<u64>,
+}
+
+// Frame rate, counts and a GPU memory estimate drawn in the corner,
+// refreshed every `DEBUG_OVERLAY_REFRESH_MS`.
+pub(super) struct DebugOverlay {
+    pub(super) layer: TextLayer,
+    // Wall-clock start of the current sample, the last frame in it, how
+    // many frames it has and the longest gap between them.
+    sample_start: f64,
+    last_frame: f64,
+    frames: u32,
+    longest: f64,
+}
+
+/// Colors the sky and stars are mixed toward, and by how much.
+#[derive(Clone, Copy)]
+pub struct ThemeTint {
+    pub sky: [f32; 3],
+    pub sky_mix: f32,
+    pub star: [f32; 3],
+    pub star_mix: f32,
+}
+
+impl ThemeTint {
+    pub(super) const NONE: ThemeTint =
+        ThemeTint { sky: [0.0, 0.0, 0.0], sky_mix: 0.0, star: [1.0, 1.0, 1.0], star_mix: 0.0 };
+
+    pub(super) fn for_status(status: &str, vision: ColorVision) -> Option<ThemeTint> {
+        let status = status.to_ascii_lowercase();
+        let (mut sky, sky_mix, mut star, star_mix) = match status.as_str() {
+            "" | "none" => return Some(ThemeTint::NONE),
+            "pending" => ([0.35, 0.38, 0.45], 0.15, [0.85, 0.85, 0.9], 0.1),
+            "judging" => ([0.2, 0.35, 0.75], 0.2, [0.6, 0.8, 1.0], 0.2),
+            "ac" | "accepted" => ([0.1, 0.55, 0.3], 0.25, [0.6, 1.0, 0.7], 0.3),
+            "wa" | "wrong_answer" => ([0.65, 0.12, 0.15], 0.25, [1.0, 0.6, 0.6], 0.3),
+            "tle" | "time_limit_exceeded" => ([0.7, 0.5, 0.1], 0.25, [1.0, 0.85, 0.5], 0.3),
+            "ce" | "compile_error" => ([0.45, 0.25, 0.6], 0.25, [0.85, 0.7, 1.0], 0.3),
+            "mle" | "memory_limit_exceeded" => ([0.7, 0.35, 0.1], 0.25, [1.0, 0.75, 0.5], 0.3),
+            "re" | "runtime_error" => ([0.55, 0.15, 0.45], 0.25, [1.0, 0.6, 0.9], 0.3),
+            _ => return None,
+        };
+        if let Some(hue) = vision.status_color(&status) {
+            sky = hue.map(|c| c * 0.7);
+            star = hue.map(|c| 0.5 + c * 0.5);
+        }
+        Some(ThemeTint { sky, sky_mix, star, star_mix })
+    }
+
+    pub(super) fn for_contest_phase(phase: ContestPhase) -> ThemeTint {
+        let (sky, sky_mix, star, star_mix) = match phase {
+            // Calm, cool blue while waiting.
+            ContestPhase::Before => ([0.15, 0.25, 0.5], 0.12, [0.8, 0.88, 1.0], 0.1),
+            ContestPhase::Running => ([0.4, 0.15, 0.6], 0.2, [1.0, 0.85, 0.95], 0.2),
+            ContestPhase::FinalMinute => ([0.65, 0.18, 0.22], 0.25, [1.0, 0.75, 0.7], 0.25),
+            // A golden afterglow.
+            ContestPhase::After => ([0.75, 0.55, 0.15], 0.25, [1.0, 0.9, 0.6], 0.3),
+        };
+        ThemeTint { sky, sky_mix, star, star_mix }
+    }
+
+    pub(super) fn for_difficulty(rating: u32) -> ThemeTint {
+        let color = difficulty_color(rating);
+        let star = [
+            0.7 + color[0] * 0.3,
+            0.7 + color[1] * 0.3,
+            0.7 + color[2] * 0.3,
+        ];
+        ThemeTint { sky: color, sky_mix: 0.18, star, star_mix: 0.2 }
+    }
+
+    fn approach(&mut self, target: &ThemeTint, rate: f32) {
+        for i in 0..3 {
+            self.sky[i] += (target.sky[i] - self.sky[i]) * rate;
+            self.star[i] += (target.star[i] - self.star[i]) * rate;
+        }
+        self.sky_mix += (target.sky_mix - self.sky_mix) * rate;
+        self.star_mix += (target.star_mix - self.star_mix) * rate;
+    }
+}
+
+pub(crate) const DIFFICULTY_SCALE: [(u32, [f32; 3]); 7] = [
+    (800, [0.5, 0.5, 0.5]),
+    (1200, [0.0, 0.5, 0.0]),
+    (1400, [0.01, 0.66, 0.62]),
+    (1600, [0.0, 0.0, 1.0]),
+    (1900, [0.67, 0.0, 0.67]),
+    (2100, [1.0, 0.55, 0.0]),
+    (2400, [1.0, 0.0, 0.0]),
+];
+
+impl StarField {
+    pub(super) fn set_color_scheme(&mut self, scheme: ColorScheme, prefers_dark: bool) {
+        self.color_scheme = scheme;
+        self.prefers_dark = prefers_dark;
+        let sky = self.palette.unwrap_or_else(|| SkyPalette::for_scheme(scheme, prefers_dark));
+        self.sky_tween.retarget(sky, self.transition);
+    }
+
+    /// Makes the stars drift along `flow`'s currents, with its scale and
+    /// speed in CSS pixels, or on their own random courses with `None`.
+    pub fn set_flow_field(&mut self, flow: Option<FlowField>) {
+        self.stars.flow = flow;
+    }
+
+    /// Pushes the whole sky along (`vx`, `vy`) in CSS pixels per frame, on
+    /// top of each star's own drift. The wind eases into a new setting
+    /// rather than jumping; (0, 0) lets it die down.
+    pub fn set_wind(&mut self, vx: f32, vy: f32) {
+        self.stars.wind_target = (vx, vy);
+    }
+
+    /// Fades the sky toward `intensity`, 0 to 1: stars, meteors and effects
+    /// dim and meteors and emitters spawn less often, e.g. behind dense
+    /// content, while the background and overlays stay as they are. 0 hides
+    /// all but those.
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity_target = intensity.clamp(0.0, 1.0);
+    }
+
+    /// Paints the background with `corners` instead of the day or night
+    /// sky's vertical gradient, or returns to it with `None`, crossfading
+    /// over the transition set with `set_transition`.
+    pub fn set_corner_gradient(&mut self, corners: Option<CornerGradient>) {
+        let background = &mut self.background;
+        background.previous_corners = std::mem::replace(&mut background.corners, corners);
+        background.corners_fade = Tween::settled(0.0);
+        background.corners_fade.retarget(1.0, self.transition);
+    }
+
+    /// Slowly swings the background's hue, or stops with `None`.
+    pub fn set_hue_shift(&mut self, shift: Option<HueShift>) {
+        self.background.hue_shift = shift;
+    }
+
+    /// Confines stars to `mask`'s shape, or lifts the confinement with
+    /// `None`. Stars the new mask rejects move to places it keeps.
+    pub fn set_star_mask(&mut self, mask: Option<StarMask>) {
+        self.star_config.mask = mask;
+        let Some(mask) = &self.star_config.mask else {
+            return;
+        };
+        if self.observer.is_some() {
+            return;
+        }
+        let (width, height) = self.resolution;
+        let stars = &mut self.stars.stars;
+        for i in 0..stars.len() {
+            if self.rng.next_f32() >= mask.acceptance(stars.x[i] / width, stars.y[i] / height) {
+                (stars.x[i], stars.y[i]) = place_star(&mut self.rng, &self.star_config, width, height, |rng| {
+                    (rng.next_f32() * width, rng.next_f32() * height)
+                });
+            }
+        }
+    }
+
+    /// Twinkles the stars along sine waves or smooth noise.
+    pub fn set_twinkle_mode(&mut self, mode: TwinkleMode) {
+        self.stars.twinkle = mode;
+    }
+
+    /// Matches the canvas to its current layout size, fading stars in or
+    /// out to keep the sky's density.
+    pub fn resize(&mut self) {
+        let dpr = pixel_ratio(self.data_saver);
+        let css_width = self.canvas.client_width() as f32;
+        let css_height = self.canvas.client_height() as f32;
+        let new_width = css_width * dpr;
+        let new_height = css_height * dpr;
+        // A hidden canvas keeps its last layout; `check_visibility` resizes
+        // again once it is shown.
+        if new_width <= 0.0 || new_height <= 0.0 {
+            return;
+        }
+        
+        let (old_width, old_height) = self.resolution;
+        self.dpr = dpr;
+        
+        self.canvas.set_width(new_width as u32);
+        self.canvas.set_height(new_height as u32);
+        self.resolution = (new_width, new_height);
+        self.resize_composite();
+
+        if self.observer.is_some() {
+            self.project_observer_sky(js_sys::Date::now());
+        } else {
+            // Trim stars left outside, then top up or thin out the rest.
+            let stars = &mut self.stars.stars;
+            stars.retain(|s, i| s.x[i] >= 0.0 && s.x[i] <= new_width && s.y[i] >= 0.0 && s.y[i] <= new_height);
+            self.match_star_count((old_width, old_height));
+        }
+        let context = self.effect_context(1.0);
+        for layer in self.layers_mut() {
+            layer.resize(&context, (old_width, old_height));
+        }
+        log(Level::Debug, || format!("Resized to {}x{} at devicePixelRatio {}", new_width, new_height, dpr));
+        self.log_buffer_sizes(Level::Debug);
+    }
+
+    /// Fades stars in or out until the sky holds `count` for the area it
+    /// started with, scaled to the canvas's area as on resize, over the
+    /// transition set with `set_transition`.
+    pub fn set_star_count(&mut self, count: usize) {
+        self.num_stars = count.min(self.limits.stars);
+        self.star_count.retarget(self.num_stars as f32, self.transition);
+        // Without a transition the tween is already settled and never steps.
+        self.match_star_count(self.resolution);
+    }
+
+    // Fades random stars in or out so the count tracks the target density,
+    // as far as the star count has eased, rather than drifting over many
+    // resizes. Stars still fading out count as gone. New stars go where
+    // the canvas grew beyond `old_size`, if it did. The observer's chart
+    // has its own stars.
+    pub(super) fn match_star_count(&mut self, old_size: (f32, f32)) {
+        if self.observer.is_some() {
+            return;
+        }
+        let (old_width, old_height) = old_size;
+        let (width, height) = self.resolution;
+        let css_area = width * height / (self.dpr * self.dpr);
+        if self.reference_area <= 0.0 {
+            self.reference_area = css_area;
+        }
+        let target = if self.reference_area > 0.0 {
+            (self.star_count.value() * css_area / self.reference_area).round() as usize
+        } else {
+            0
+        };
+        let target = target.min(self.limits.stars);
+        let stars = &mut self.stars.stars;
+        let mut shown = stars.data.iter().filter(|star| !star.is_fading_out()).count();
+        while shown > target {
+            let index = ((self.rng.next_f32() * stars.len() as f32) as usize).min(stars.len() - 1);
+            if !stars.data[index].is_fading_out() {
+                stars.data[index].fade_out();
+                shown -= 1;
+            }
+        }
+        // Stars on their way out come back before new ones are added.
+        for star in stars.data.iter_mut().filter(|star| star.is_fading_out()).take(target - shown) {
+            star.fade_in();
+            shown += 1;
+        }
+        while shown < target {
+            let (nx, ny) = place_star(&mut self.rng, &self.star_config, width, height, |rng| {
+                pick_random_in_diff_area(rng, old_width, old_height, width, height)
+            });
+            let radius = self.star_config.sizes.sample(&mut self.rng);
+            let mut star = new_star(&mut self.rng, &self.star_config, nx, ny, radius);
+            star.data.visibility = 0.0;
+            star.data.fade_in();
+            stars.push(star);
+            shown += 1;
+        }
+    }
+
+    // Returns whether the canvas is laid out, resizing when it reappears
+    // after being hidden (display: none, collapsed parent, ...).
+    pub(super) fn check_visibility(&mut self) -> bool {
+        let visible = self.canvas.client_width() > 0 && self.canvas.client_height() > 0;
+        if visible && self.hidden {
+            self.resize();
+        }
+        self.hidden = !visible;
+        visible
+    }
+
+    pub(super) fn enter_wallpaper(&mut self) {
+        if self.wallpaper.is_some() {
+            return;
+        }
+        let style = self.canvas.style();
+        self.wallpaper = Some(Wallpaper {
+            num_stars: self.num_stars,
+            cursor: style.get_property_value("cursor").unwrap_or_default(),
+        });
+        self.set_star_count((self.num_stars as f32 * WALLPAPER_STAR_BOOST) as usize);
+        let _ = style.set_property("cursor", "none");
+        self.resize();
+    }
+
+    pub(super) fn exit_wallpaper(&mut self) {
+        let Some(saved) = self.wallpaper.take() else {
+            return;
+        };
+        self.set_star_count(saved.num_stars);
+        let _ = self.canvas.style().set_property("cursor", &saved.cursor);
+        self.resize();
+    }
+
+    // Sizes are authored in CSS pixels; this converts them to canvas pixels.
+    pub(super) fn size_scale(&self) -> f32 {
+        if self.raw_pixels {
+            1.0
+        } else {
+            self.dpr
+        }
+    }
+
+    pub(super) fn set_theme(&mut self, theme: ThemeTint) {
+        self.theme_target = theme;
+    }
+
+    /// Themes the sky by the contest's phase at the wall-clock time: calm
+    /// before `schedule` starts, energetic while it runs, tense with many
+    /// more meteors in the final minute and a golden afterglow once it ends.
+    /// Each phase change sets the theme, so a status theme set in between
+    /// holds until the next one. `None` stops and clears the theme.
+    pub fn set_contest_schedule(&mut self, schedule: Option<ContestSchedule>) {
+        if schedule.is_none() && self.contest.is_some() {
+            self.set_theme(ThemeTint::NONE);
+            self.meteors.rate_boost = 1.0;
+        }
+        self.contest = schedule.map(|schedule| ContestThemes { schedule, phase: None });
+    }
+
+    fn update_contest(&mut self) {
+        let Some(contest) = &mut self.contest else {
+            return;
+        };
+        let phase = contest.schedule.phase(js_sys::Date::now());
+        if contest.phase == Some(phase) {
+            return;
+        }
+        contest.phase = Some(phase);
+        self.meteors.rate_boost = match phase {
+            ContestPhase::Before => CONTEST_CALM_METEOR_RATE,
+            ContestPhase::Running => CONTEST_RUNNING_METEOR_RATE,
+            ContestPhase::FinalMinute => CONTEST_FINAL_MINUTE_METEOR_RATE,
+            ContestPhase::After => 1.0,
+        };
+        self.set_theme(ThemeTint::for_contest_phase(phase));
+    }
+
+    pub(super) fn set_pulsing(&mut self, pulsing: bool) {
+        self.pulsing = pulsing;
+    }
+
+    pub(super) fn set_countdown(&mut self, target_ms: f64) -> Result<(), String> {
+        let layer = match self.countdown.take() {
+            Some(countdown) => countdown.layer,
+            None => TextLayer::new(&self.gl)?,
+        };
+        self.countdown = Some(Countdown { target_ms, layer, last_remaining: None });
+        Ok(())
+    }
+
+    pub(super) fn clear_countdown(&mut self) {
+        self.countdown = None;
+    }
+
+    pub(super) fn set_ticker(
+        &mut self,
+        text: &str,
+        args: Option<Vec<(String, String)>>,
+        speed: f32,
+        at_top: bool,
+        font_scale: f32,
+        color: [f32; 3],
+    ) -> Result<(), String> {
+        let layer = match self.ticker.take() {
+            Some(ticker) => ticker.layer,
+            None => TextLayer::new(&self.gl)?,
+        };
+        self.ticker = Some(Ticker {
+            layer,
+            text: text.to_string(),
+            args,
+            offset: 0.0,
+            speed,
+            at_top,
+            font_scale,
+            color,
+        });
+        Ok(())
+    }
+
+    pub(super) fn clear_ticker(&mut self) {
+        self.ticker = None;
+    }
+
+    /// Keeps bright effects out of `zones`, in CSS pixels from the canvas's
+    /// top-left: meteors launch only on paths that miss them, and meteors,
+    /// sparks and effect particles dim inside. Replaces any earlier zones;
+    /// call again when the layout changes.
+    pub fn set_exclusion_zones(&mut self, zones: &[ExclusionZone]) {
+        let scale = self.size_scale();
+        self.exclusion_zones = zones.iter().map(|zone| zone.scaled(scale)).collect();
+    }
+
+    /// Flies a meteor from `from` to `to`, in CSS pixels from the canvas's
+    /// top-left, along an upward arc over `seconds` of sky time, then calls
+    /// `arrived` with true; or with false if the sky stops first. Fails if
+    /// too many are already in flight.
+    pub fn fly_meteor_between(
+        &mut self,
+        from: (f32, f32),
+        to: (f32, f32),
+        seconds: f32,
+        arrived: Function,
+    ) -> Result<(), String> {
+        if self.meteors.guided.len() >= MAX_GUIDED_METEORS {
+            return Err(format!("At most {} guided meteors can be in flight", MAX_GUIDED_METEORS));
+        }
+        let scale = self.size_scale();
+        let path = GuidedPath::new((from.0 * scale, from.1 * scale), (to.0 * scale, to.1 * scale));
+        self.meteors.guided.push(GuidedMeteor {
+            path,
+            length: path.length(),
+            colors: self.meteors.colors,
+            duration: (seconds * 60.0).max(1.0),
+            elapsed: 0.0,
+            arrived: Some(arrived),
+        });
+        Ok(())
+    }
+
+    /// Shows frame rate, frame time, star, meteor and particle counts and
+    /// an estimate of GPU memory in the top-left corner of the canvas, for
+    /// devices without handy devtools. Fails if its text texture cannot be
+    /// created.
+    pub fn set_debug_overlay(&mut self, enabled: bool) -> Result<(), String> {
+        if !enabled {
+            self.debug_overlay = None;
+        } else if self.debug_overlay.is_none() {
+            let now = js_sys::Date::now();
+            let layer = TextLayer::new(&self.gl)?;
+            self.debug_overlay =
+                Some(DebugOverlay { layer, sample_start: now, last_frame: now, frames: 0, longest: 0.0 });
+        }
+        Ok(())
+    }
+
+    pub(super) fn update_debug_overlay(&mut self) {
+        let now = js_sys::Date::now();
+        let Some(overlay) = &mut self.debug_overlay else {
+            return;
+        };
+        overlay.frames += 1;
+        overlay.longest = overlay.longest.max(now - overlay.last_frame);
+        overlay.last_frame = now;
+        let elapsed = now - overlay.sample_start;
+        if elapsed < DEBUG_OVERLAY_REFRESH_MS {
+            return;
+        }
+        let frame_ms = elapsed / overlay.frames as f64;
+        let longest = overlay.longest;
+        (overlay.sample_start, overlay.frames, overlay.longest) = (now, 0, 0.0);
+        let text = format!(
+            "{:.0} fps · {:.1} ms (max {:.1}) · {} stars · {} meteors · {} particles · ~{:.1} MB GPU",
+            1000.0 / frame_ms,
+            frame_ms,
+            longest,
+            self.stars.stars.len(),
+            self.meteors.meteors.len(),
+            self.particles.len(),
+            self.gpu_bytes() / 1_000_000.0,
+        );
+        let font_px = DEBUG_OVERLAY_FONT_PX * self.size_scale();
+        let style = TextStyle { font_px, color: DEBUG_OVERLAY_COLOR, glow: [0.0; 3], glow_blur: font_px * 0.25 };
+        if let Some(overlay) = &mut self.debug_overlay {
+            overlay.layer.set_text(&self.gl, &text, style);
+        }
+    }
+
+    // Rough video memory for the canvas, offscreen frame, text and built-in
+    // vertex buffers; effects' own textures and buffers are not counted.
+    fn gpu_bytes(&self) -> f32 {
+        const FLOAT: f32 = 4.0;
+        let pixels = self.resolution.0 * self.resolution.1;
+        // Front and back buffers.
+        let mut bytes = pixels * 4.0 * 2.0;
+        if let Some(composite) = &self.composite {
+            bytes += pixels * composite.bytes_per_pixel();
+        }
+        let text_layers = [
+            self.countdown.as_ref().map(|countdown| &countdown.layer),
+            self.ticker.as_ref().map(|ticker| &ticker.layer),
+            self.debug_overlay.as_ref().map(|overlay| &overlay.layer),
+        ];
+        for (width, height) in text_layers.into_iter().flatten().map(TextLayer::size) {
+            bytes += width * height * 4.0;
+        }
+        bytes += self.stars.stars.len() as f32 * 7.0 * FLOAT;
+        bytes += self.meteors.vertices as f32 * 6.0 * FLOAT;
+        bytes += self.particles.len() as f32 * 9.0 * FLOAT;
+        if let Some(plexus) = &self.plexus {
+            bytes += plexus.vertices as f32 * 6.0 * FLOAT;
+        }
+        bytes
+    }
+
+    fn update_ticker(&mut self, dt: f32) {
+        if let Some(ticker) = &mut self.ticker {
+            let font_px = (self.resolution.1 * ticker.font_scale).max(10.0);
+            let style = TextStyle {
+                font_px,
+                color: ticker.color,
+                glow: ticker.color,
+                glow_blur: font_px * 0.2,
+            };
+            let text = match &ticker.args {
+                Some(args) => translate(&ticker.text, args).map_or(Cow::Borrowed(ticker.text.as_str()), Cow::Owned),
+                None => Cow::Borrowed(ticker.text.as_str()),
+            };
+            ticker.layer.set_text(&self.gl, &text, style);
+            ticker.offset += ticker.speed * dt;
+            let span = self.resolution.0 + ticker.layer.size().0;
+            if ticker.offset > span {
+                ticker.offset -= span;
+            }
+        }
+    }
+
+    fn update_countdown(&mut self) {
+        if let Some(countdown) = &mut self.countdown {
+            let remaining = ((countdown.target_ms - js_sys::Date::now()) / 1000.0).max(0.0) as u64;
+            #[cfg(feature = "audio")]
+            if countdown.last_remaining.is_some_and(|last| last != remaining) && remaining < COUNTDOWN_TICK_SECONDS {
+                audio::play(Sound::Tick);
+            }
+            countdown.last_remaining = Some(remaining);
+            let font_px = (self.resolution.1 * COUNTDOWN_FONT_SCALE).max(12.0);
+            let style = TextStyle {
+                font_px,
+                color: COUNTDOWN_COLOR,
+                glow: COUNTDOWN_GLOW,
+                glow_blur: font_px * 0.3,
+            };
+            countdown.layer.set_text(&self.gl, &format_countdown(remaining), style);
+        }
+    }
+
+    pub(super) fn rating_burst(&mut self, delta: i32) {
+        if delta == 0 {
+            return;
+        }
+        let (width, height) = self.resolution;
+        let count = (delta.unsigned_abs() as f32 * 2.0)
+            .clamp(RATING_BURST_MIN_PARTICLES, RATING_BURST_MAX_PARTICLES) as usize;
+        let count = count.min(self.particle_room());
+        let (up_color, down_color) = self.color_vision.rating_colors();
+        for _ in 0..count {
+            let particle = if delta > 0 {
+                Spawn {
+                    x: width / 2.0 + (self.rng.next_f32() - 0.5) * width * 0.05,
+                    y: height,
+                    vx: (self.rng.next_f32() - 0.5) * 4.0,
+                    vy: -(4.0 + self.rng.next_f32() * 6.0),
+                    color: up_color,
+                    size: 2.0 + self.rng.next_f32() * 3.0,
+                    max_lifetime: 120.0 + self.rng.next_f32() * 60.0,
+                    data: 0.06,
+                }
+            } else {
+                Spawn {
+                    x: self.rng.next_f32() * width,
+                    y: -self.rng.next_f32() * height * 0.2,
+                    vx: (self.rng.next_f32() - 0.5) * 0.3,
+                    vy: 1.0 + self.rng.next_f32() * 2.0,
+                    color: down_color,
+                    size: 1.5 + self.rng.next_f32() * 2.0,
+                    max_lifetime: 150.0 + self.rng.next_f32() * 60.0,
+                    data: 0.02,
+                }
+            };
+            self.particles.push(particle);
+        }
+    }
+
+    // Particles that can still be spawned under the cap. Every effect
+    // clamps its spawn count to this.
+    fn particle_room(&self) -> usize {
+        self.limits.particles.saturating_sub(self.particles.len())
+    }
+
+    pub(super) fn set_limits(&mut self, limits: Limits) {
+        self.stars.stars.truncate(limits.stars);
+        self.meteors.meteors.truncate(limits.meteors);
+        self.meteors.limit = limits.meteors;
+        self.particles.truncate(limits.particles);
+        self.limits = limits;
+    }
+
+    pub(super) fn rank_up(&mut self, color: [f32; 3]) {
+        if self.particle_room() < RANK_UP_RING_STARS {
+            return;
+        }
+        let (width, height) = self.resolution;
+        let cx = width / 2.0;
+        let cy = height / 2.0;
+        let ring_radius = width.min(height) * 0.35;
+        for i in 0..RANK_UP_RING_STARS {
+            let angle = i as f32 / RANK_UP_RING_STARS as f32 * std::f32::consts::TAU;
+            let (sin, cos) = angle.sin_cos();
+            self.particles.push(Spawn {
+                x: cx + cos * ring_radius,
+                y: cy + sin * ring_radius,
+                vx: -cos * ring_radius / RANK_UP_CONVERGE_FRAMES,
+                vy: -sin * ring_radius / RANK_UP_CONVERGE_FRAMES,
+                color,
+                size: 4.0,
+                max_lifetime: RANK_UP_CONVERGE_FRAMES,
+                data: 0.0,
+            });
+        }
+        self.pending_bursts.push(PendingBurst { x: cx, y: cy, delay: RANK_UP_CONVERGE_FRAMES, color });
+    }
+
+    fn update_bursts(&mut self, dt: f32) {
+        let mut ready = Vec::new();
+        self.pending_bursts.retain_mut(|burst| {
+            burst.delay -= dt;
+            if burst.delay <= 0.0 {
+                ready.push((burst.x, burst.y, burst.color));
+                false
+            } else {
+                true
+            }
+        });
+        for (x, y, color) in ready {
+            for _ in 0..RANK_UP_EXPLOSION_PARTICLES.min(self.particle_room()) {
+                let angle = self.rng.next_f32() * std::f32::consts::TAU;
+                let speed = 2.0 + self.rng.next_f32() * 6.0;
+                self.particles.push(Spawn {
+                    x,
+                    y,
+                    vx: speed * angle.cos(),
+                    vy: speed * angle.sin(),
+                    color,
+                    size: 2.0 + self.rng.next_f32() * 3.0,
+                    max_lifetime: 60.0 + self.rng.next_f32() * 60.0,
+                    data: 0.01,
+                });
+            }
+        }
+    }
+
+    fn update_particles(&mut self, dt: f32) {
+        self.update_bursts(dt);
+        step_sparks(&mut self.particles, dt);
+    }
+
+    // Alpha and size multipliers for the "breathing" sky.
+    pub(super) fn pulse_factors(&self) -> (f32, f32) {
+        let breath = self.pulse_strength * self.pulse_phase.sin();
+        (1.0 + PULSE_ALPHA_AMPLITUDE * breath, 1.0 + PULSE_SIZE_AMPLITUDE * breath)
+    }
+
+    // Moves everything on by `dt` frames at 60 fps without touching the
+    // vertex buffers; `update` uploads the result.
+    pub(super) fn simulate(&mut self, dt: f32) {
+        self.theme.approach(&self.theme_target, THEME_FADE_RATE * dt);
+        if !self.sky_tween.is_settled() {
+            self.sky_tween.step(dt);
+            self.sky = self.sky_tween.value();
+        }
+        self.background.corners_fade.step(dt);
+        self.layers.step(dt);
+        if !self.star_count.is_settled() {
+            self.star_count.step(dt);
+            self.match_star_count(self.resolution);
+        }
+        let pulse_target = if self.pulsing { 1.0 } else { 0.0 };
+        self.pulse_strength += (pulse_target - self.pulse_strength) * PULSE_FADE_RATE * dt;
+        if self.pulse_strength > 0.001 {
+            self.pulse_phase = (self.pulse_phase + PULSE_SPEED * dt) % std::f32::consts::TAU;
+        } else {
+            self.pulse_phase = 0.0;
+        }
+        let ease = 1.0 - (-dt / INTENSITY_FADE_FRAMES).exp();
+        self.intensity += (self.intensity_target - self.intensity) * ease;
+
+        self.update_particles(dt);
+        self.update_contest();
+        self.update_observer_sky();
+        self.update_countdown();
+        self.update_ticker(dt);
+    }
+}
+
+pub(super) fn difficulty_color(rating: u32) -> [f32; 3] {
+    let (first_rating, first_color) = DIFFICULTY_SCALE[0];
+    if rating <= first_rating {
+        return first_color;
+    }
+    for pair in DIFFICULTY_SCALE.windows(2) {
+        let (lo_rating, lo_color) = pair[0];
+        let (hi_rating, hi_color) = pair[1];
+        if rating < hi_rating {
+            let t = (rating - lo_rating) as f32 / (hi_rating - lo_rating) as f32;
+            return [
+                lo_color[0] + (hi_color[0] - lo_color[0]) * t,
+                lo_color[1] + (hi_color[1] - lo_color[1]) * t,
+                lo_color[2] + (hi_color[2] - lo_color[2]) * t,
+            ];
+        }
+    }
+    DIFFICULTY_SCALE[DIFFICULTY_SCALE.len() - 1].1
+}
+
+fn format_countdown(total_seconds: u64) -> String {
+    let days = total_seconds / 86400;
+    let hours = (total_seconds % 86400) / 3600;
+    let minutes = (total_seconds % 3600) / 60;
+    let seconds = total_seconds % 60;
+    let args = [("days", days), ("hours", hours), ("minutes", minutes), ("seconds", seconds)]
+        .map(|(name, value)| (name.to_string(), format!("{:02}", value)));
+    translate("countdown", &args)
+        .unwrap_or_else(|| format!("{:02}:{:02}:{:02}:{:02}", days, hours, minutes, seconds))
+}
+
+fn pick_random_in_diff_area(
+    rng: &mut Rng,
+    old_width: f32,
+    old_height: f32,
+    new_width: f32,
+    new_height: f32,
+) -> (f32, f32) {
+    if new_width <= old_width && new_height <= old_height {
+        return (rng.next_f32() * new_width, rng.next_f32() * new_height);
+    }
+    loop {
+        let x = rng.next_f32() * new_width;
+        let y = rng.next_f32() * new_height;
+        if x > old_width || y > old_height {
+            return (x, y);
+        }
+    }
+}
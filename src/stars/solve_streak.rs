@@ -0,0 +1,112 @@
+use web_sys::{WebGlBuffer, WebGlProgram, WebGlRenderingContext as GL};
+
+use super::effect::{Effect, EffectContext};
+use super::gl::{create_gold_star_program, draw_points, upload_vertices};
+use super::sim::ThemeTint;
+use super::{
+    StarField, MAX_SOLVE_STREAK_STARS, SOLVE_STREAK_COLOR, SOLVE_STREAK_FADE_FRAMES, SOLVE_STREAK_FLIGHT_FRAMES,
+    SOLVE_STREAK_HOLD_FRAMES, SOLVE_STREAK_SPACING, SOLVE_STREAK_STAGGER_FRAMES, SOLVE_STREAK_STAR_SIZE,
+    SOLVE_STREAK_TOP,
+};
+
+// A row of golden stars near the top of the sky, one per day or solve of a
+// streak, each arcing up from the bottom center into its place.
+pub(super) struct SolveStreak {
+    count: usize,
+    // Frames since the first star took off.
+    age: f32,
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+    points: usize,
+}
+
+impl SolveStreak {
+    fn new(gl: &GL, count: usize) -> Result<SolveStreak, String> {
+        let program = create_gold_star_program(gl)?;
+        let buffer = gl.create_buffer().ok_or("Failed to create streak buffer")?;
+        Ok(SolveStreak { count, age: 0.0, program, buffer, points: 0 })
+    }
+
+    // Frames until the last star has landed.
+    fn landed_at(&self) -> f32 {
+        (self.count - 1) as f32 * SOLVE_STREAK_STAGGER_FRAMES + SOLVE_STREAK_FLIGHT_FRAMES
+    }
+
+    fn finished(&self) -> bool {
+        self.age >= self.landed_at() + SOLVE_STREAK_HOLD_FRAMES + SOLVE_STREAK_FADE_FRAMES
+    }
+}
+
+impl Effect for SolveStreak {
+    fn update(&mut self, context: &EffectContext, dt: f32) {
+        self.age += dt;
+        let (width, height) = context.resolution;
+        let scale = context.size_scale;
+        let spacing = SOLVE_STREAK_SPACING * scale;
+        let row_left = width / 2.0 - spacing * (self.count - 1) as f32 / 2.0;
+        let fade_age = self.age - self.landed_at() - SOLVE_STREAK_HOLD_FRAMES;
+        let fade = 1.0 - (fade_age / SOLVE_STREAK_FADE_FRAMES).clamp(0.0, 1.0);
+        let mut data = Vec::with_capacity(self.count * 7);
+        for i in 0..self.count {
+            let flown = self.age - i as f32 * SOLVE_STREAK_STAGGER_FRAMES;
+            if flown <= 0.0 {
+                continue;
+            }
+            let t = (flown / SOLVE_STREAK_FLIGHT_FRAMES).min(1.0);
+            let eased = 1.0 - (1.0 - t).powi(3);
+            let target_x = row_left + i as f32 * spacing;
+            let target_y = SOLVE_STREAK_TOP * scale;
+            // Stars bound for either end of the row swing out wide on the way.
+            let swing = (std::f32::consts::PI * eased).sin() * (target_x - width / 2.0) * 0.5;
+            let x = width / 2.0 + (target_x - width / 2.0) * eased + swing;
+            let y = height + (target_y - height) * eased;
+            // A little pop on landing, then a gentle twinkle.
+            let landed = (flown - SOLVE_STREAK_FLIGHT_FRAMES).max(0.0);
+            let pop = if flown > SOLVE_STREAK_FLIGHT_FRAMES { 1.0 + 0.5 * (-landed / 8.0).exp() } else { 1.0 };
+            let twinkle = 1.0 + 0.08 * (self.age * 0.05 + i as f32).sin();
+            let size = SOLVE_STREAK_STAR_SIZE * scale * pop * twinkle;
+            let alpha = fade * (0.4 + 0.6 * t);
+            data.extend_from_slice(&[x, y, size, alpha]);
+            data.extend_from_slice(&SOLVE_STREAK_COLOR);
+        }
+        upload_vertices(&context.gl, &self.buffer, &data, GL::DYNAMIC_DRAW);
+        self.points = data.len() / 7;
+    }
+
+    fn draw(&self, context: &EffectContext) {
+        draw_points(context, &self.program, &self.buffer, self.points, ThemeTint::NONE.star, 0.0);
+    }
+
+    fn transfer(&mut self, context: &EffectContext) -> Result<(), String> {
+        self.program = create_gold_star_program(&context.gl)?;
+        self.buffer = context.gl.create_buffer().ok_or("Failed to create streak buffer")?;
+        Ok(())
+    }
+
+    fn destroy(&mut self, gl: &GL) {
+        gl.delete_buffer(Some(&self.buffer));
+        gl.delete_program(Some(&self.program));
+    }
+}
+
+impl StarField {
+    /// Flies `count` small golden stars, one per day or solve of a streak,
+    /// up into a row near the top of the sky, where they stay a while
+    /// before fading. Replaces a streak still showing; 0 clears it.
+    pub fn streak_effect(&mut self, count: usize) -> Result<(), String> {
+        if let Some(mut streak) = self.solve_streak.take() {
+            streak.destroy(&self.gl);
+        }
+        if count > 0 {
+            self.solve_streak = Some(SolveStreak::new(&self.gl, count.min(MAX_SOLVE_STREAK_STARS))?);
+        }
+        Ok(())
+    }
+
+    // Drops the streak once it has faded out.
+    pub(super) fn expire_solve_streak(&mut self) {
+        if self.solve_streak.as_ref().is_some_and(SolveStreak::finished) {
+            self.streak_effect(0).ok();
+        }
+    }
+}
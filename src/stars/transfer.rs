@@ -0,0 +1,104 @@
+use soj_sim::ParticleStore;
+use web_sys::{HtmlCanvasElement, WebGlRenderingContext as GL};
+
+use crate::diagnostics::{log, Level};
+use crate::text::{TextLayer, TextRenderer};
+
+use super::composite::Composite;
+use super::effect::Effect;
+use super::gl::{create_context, create_programs, create_streak_program, lose_context, Background};
+use super::plexus::PlexusLines;
+use super::sim::{Meteors, Stars};
+use super::StarField;
+
+impl StarField {
+    /// Moves the sky to `canvas`, e.g. when a route change in a single-page
+    /// app replaces the element it was drawn on. GL resources are recreated
+    /// there while the stars, meteors, sparks and settings carry over; the
+    /// old canvas's context is released, leaving it blank. Custom shaders
+    /// return to the default, and registered effects that cannot move are
+    /// removed. Fails, changing nothing, if the new canvas has no WebGL.
+    pub fn transfer_to_canvas(&mut self, canvas: HtmlCanvasElement) -> Result<(), String> {
+        if canvas == self.canvas {
+            return Ok(());
+        }
+        let gl = create_context(&canvas, self.transparent)?;
+        let (background_program, star_program, meteor_program) = create_programs(&gl)?;
+        let background = Background::new(&gl, background_program)?;
+        let stars = Stars::new(&gl, star_program.clone(), ParticleStore::with_capacity(0))?;
+        let meteor_rng = self.meteors.rng.clone();
+        let meteors = Meteors::new(&gl, meteor_program, self.meteors.colors, self.meteors.limit, meteor_rng)?;
+        let particle_buffer = gl.create_buffer().ok_or("Failed to create particle buffer")?;
+        let streak_program = self.streak_program.as_ref().map(|_| create_streak_program(&gl)).transpose()?;
+        let plexus = self.plexus.as_ref().map(|plexus| PlexusLines::new(&gl, plexus.style)).transpose()?;
+        let composite = self.composite.as_ref().map(|_| Composite::new(&gl, self.resolution)).transpose()?;
+        let text_renderer = TextRenderer::new(&gl)?;
+        let countdown_layer = self.countdown.as_ref().map(|_| TextLayer::new(&gl)).transpose()?;
+        let ticker_layer = self.ticker.as_ref().map(|_| TextLayer::new(&gl)).transpose()?;
+        let overlay_layer = self.debug_overlay.as_ref().map(|_| TextLayer::new(&gl)).transpose()?;
+
+        // Nothing below fails, so the sky never ends up split across canvases.
+        let old_gl = std::mem::replace(&mut self.gl, gl);
+        self.canvas = canvas;
+        self.star_program = star_program;
+        self.particle_buffer = particle_buffer;
+        self.streak_program = streak_program;
+        self.plexus = plexus;
+        self.composite = composite;
+        self.text_renderer = text_renderer;
+        self.last_gl_error = GL::NO_ERROR;
+
+        let old = std::mem::replace(&mut self.background, background);
+        self.background.corners = old.corners;
+        self.background.previous_corners = old.previous_corners;
+        self.background.corners_fade = old.corners_fade;
+        self.background.hue_shift = old.hue_shift;
+        let old = std::mem::replace(&mut self.stars, stars);
+        self.stars.stars = old.stars;
+        self.stars.flow = old.flow;
+        (self.stars.wind, self.stars.wind_target) = (old.wind, old.wind_target);
+        self.stars.twinkle = old.twinkle;
+        self.stars.pinned = old.pinned;
+        let old = std::mem::replace(&mut self.meteors, meteors);
+        self.meteors.meteors = old.meteors;
+        self.meteors.trajectory = old.trajectory;
+        self.meteors.schedule = old.schedule;
+        self.meteors.calendar = old.calendar;
+        self.meteors.rate_boost = old.rate_boost;
+        self.meteors.guided = old.guided;
+
+        // Text is redrawn into the new textures on the next update.
+        if let (Some(countdown), Some(layer)) = (&mut self.countdown, countdown_layer) {
+            countdown.layer = layer;
+            countdown.last_remaining = None;
+        }
+        if let (Some(ticker), Some(layer)) = (&mut self.ticker, ticker_layer) {
+            ticker.layer = layer;
+        }
+        if let (Some(overlay), Some(layer)) = (&mut self.debug_overlay, overlay_layer) {
+            overlay.layer = layer;
+        }
+        if self.wallpaper.is_some() {
+            let _ = self.canvas.style().set_property("cursor", "none");
+        }
+
+        let context = self.effect_context(1.0);
+        if let Some(mut streak) = self.solve_streak.take() {
+            self.solve_streak = streak.transfer(&context).is_ok().then_some(streak);
+        }
+        self.effects.retain_mut(|slot| match slot.effect.transfer(&context) {
+            Ok(()) => true,
+            Err(error) => {
+                log(Level::Warn, || format!("Effect {} removed on canvas transfer: {}", slot.id, error));
+                false
+            }
+        });
+        lose_context(&old_gl);
+
+        // Match the new canvas's size, or wait until it is laid out.
+        self.hidden = true;
+        self.check_visibility();
+        self.update(0.0);
+        Ok(())
+    }
+}
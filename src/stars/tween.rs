@@ -0,0 +1,138 @@
+use super::sim::SkyPalette;
+use super::StarField;
+
+/// The curve a transition follows from its old value to its new one.
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    pub(super) const NAMES: &str = "\"linear\", \"ease-in\", \"ease-out\" or \"ease-in-out\"";
+
+    pub(super) fn from_name(name: &str) -> Option<Easing> {
+        match name.to_ascii_lowercase().as_str() {
+            "linear" => Some(Easing::Linear),
+            "ease-in" => Some(Easing::EaseIn),
+            "ease-out" => Some(Easing::EaseOut),
+            "ease-in-out" | "ease" => Some(Easing::EaseInOut),
+            _ => None,
+        }
+    }
+
+    // Cubic curves, as in CSS's keywords but symmetric.
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4.0 * t * t * t,
+            Easing::EaseInOut => 1.0 - (2.0 - 2.0 * t).powi(3) / 2.0,
+        }
+    }
+}
+
+/// How sky palettes, corner gradients, star counts and layer opacities
+/// move to new settings: over `seconds` of sky time along `easing`, or at
+/// once with 0.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Transition {
+    pub seconds: f32,
+    pub easing: Easing,
+}
+
+impl Transition {
+    pub const DEFAULT: Transition = Transition { seconds: 1.5, easing: Easing::EaseInOut };
+}
+
+impl Default for Transition {
+    fn default() -> Transition {
+        Transition::DEFAULT
+    }
+}
+
+// Values a tween can blend between.
+pub(super) trait Lerp: Copy {
+    fn lerp(self, to: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, to: f32, t: f32) -> f32 {
+        self + (to - self) * t
+    }
+}
+
+impl Lerp for [f32; 3] {
+    fn lerp(self, to: [f32; 3], t: f32) -> [f32; 3] {
+        [0, 1, 2].map(|i| self[i].lerp(to[i], t))
+    }
+}
+
+impl Lerp for SkyPalette {
+    fn lerp(self, to: SkyPalette, t: f32) -> SkyPalette {
+        SkyPalette {
+            top: self.top.lerp(to.top, t),
+            bottom: self.bottom.lerp(to.bottom, t),
+            star_alpha: self.star_alpha.lerp(to.star_alpha, t),
+        }
+    }
+}
+
+// A value easing from `from` to `to` over `duration` frames.
+#[derive(Clone, Copy)]
+pub(super) struct Tween<T> {
+    from: T,
+    to: T,
+    elapsed: f32,
+    duration: f32,
+    easing: Easing,
+}
+
+impl<T: Lerp> Tween<T> {
+    pub(super) const fn settled(value: T) -> Tween<T> {
+        Tween { from: value, to: value, elapsed: 0.0, duration: 0.0, easing: Easing::Linear }
+    }
+
+    // Heads for `to` from wherever the tween is now.
+    pub(super) fn retarget(&mut self, to: T, transition: Transition) {
+        let duration = transition.seconds * 60.0;
+        *self = Tween { from: self.value(), to, elapsed: 0.0, duration, easing: transition.easing };
+    }
+
+    pub(super) fn step(&mut self, dt: f32) {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+    }
+
+    pub(super) fn is_settled(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    pub(super) fn progress(&self) -> f32 {
+        if self.is_settled() {
+            1.0
+        } else {
+            self.easing.apply(self.elapsed / self.duration)
+        }
+    }
+
+    pub(super) fn value(&self) -> T {
+        if self.is_settled() {
+            self.to
+        } else {
+            self.from.lerp(self.to, self.progress())
+        }
+    }
+}
+
+impl StarField {
+    /// Sets how later changes to the sky palette, corner gradient, star
+    /// count and layer opacities play out; transitions already running
+    /// keep theirs.
+    pub fn set_transition(&mut self, transition: Transition) {
+        self.transition = transition;
+    }
+}
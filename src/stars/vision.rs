@@ -0,0 +1,88 @@
+use super::{StarPalette, RATING_DOWN_COLOR, RATING_UP_COLOR};
+
+// The Okabe-Ito palette, chosen to stay distinguishable under every common
+// color-vision deficiency.
+const ORANGE: [f32; 3] = [0.902, 0.624, 0.0];
+const SKY_BLUE: [f32; 3] = [0.337, 0.706, 0.914];
+const BLUISH_GREEN: [f32; 3] = [0.0, 0.620, 0.451];
+const YELLOW: [f32; 3] = [0.941, 0.894, 0.259];
+const BLUE: [f32; 3] = [0.0, 0.447, 0.698];
+const VERMILLION: [f32; 3] = [0.835, 0.369, 0.0];
+const REDDISH_PURPLE: [f32; 3] = [0.8, 0.475, 0.655];
+const GREY: [f32; 3] = [0.6, 0.6, 0.6];
+
+/// Which color vision the sky's color-coded effects (judge-status tints,
+/// rating bursts and, unless `starPalette` is set, star colors) are chosen
+/// for. Deuteranopia and protanopia share the red-green-safe set, except
+/// that protanopia swaps wrong answer to the brighter orange, as reds look
+/// dark to protanopes.
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum ColorVision {
+    #[default]
+    Typical,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl ColorVision {
+    pub(super) const NAMES: &str = "\"typical\", \"deuteranopia\", \"protanopia\" or \"tritanopia\"";
+
+    pub(super) fn from_name(name: &str) -> Option<ColorVision> {
+        match name.to_ascii_lowercase().as_str() {
+            "typical" | "normal" | "none" => Some(ColorVision::Typical),
+            "deuteranopia" | "deutan" => Some(ColorVision::Deuteranopia),
+            "protanopia" | "protan" => Some(ColorVision::Protanopia),
+            "tritanopia" | "tritan" => Some(ColorVision::Tritanopia),
+            _ => None,
+        }
+    }
+
+    /// Star colors for this vision; `None` keeps the default warm, blue and
+    /// white mix.
+    pub fn star_palette(self) -> Option<StarPalette> {
+        let white = [1.0, 1.0, 1.0];
+        let colors = match self {
+            ColorVision::Typical => return None,
+            ColorVision::Deuteranopia | ColorVision::Protanopia => [pastel(ORANGE), pastel(SKY_BLUE), white],
+            ColorVision::Tritanopia => [pastel(VERMILLION), pastel(BLUISH_GREEN), white],
+        };
+        StarPalette::new(&colors.map(|color| (color, 1.0)))
+    }
+
+    // The hue a judge status tints the sky with; `None` for typical vision,
+    // which keeps the hand-tuned tints, or an unknown status.
+    pub(super) fn status_color(self, status: &str) -> Option<[f32; 3]> {
+        let hue = |red_green: [f32; 3], tritan: [f32; 3]| match self {
+            ColorVision::Typical => None,
+            ColorVision::Deuteranopia | ColorVision::Protanopia => Some(red_green),
+            ColorVision::Tritanopia => Some(tritan),
+        };
+        let protan = self == ColorVision::Protanopia;
+        match status {
+            "pending" => hue(GREY, GREY),
+            "judging" => hue(SKY_BLUE, SKY_BLUE),
+            "ac" | "accepted" => hue(BLUE, BLUISH_GREEN),
+            "wa" | "wrong_answer" => hue(if protan { ORANGE } else { VERMILLION }, VERMILLION),
+            "tle" | "time_limit_exceeded" => hue(YELLOW, ORANGE),
+            "mle" | "memory_limit_exceeded" => hue(if protan { VERMILLION } else { ORANGE }, YELLOW),
+            "ce" | "compile_error" => hue(REDDISH_PURPLE, BLUE),
+            "re" | "runtime_error" => hue(BLUISH_GREEN, REDDISH_PURPLE),
+            _ => None,
+        }
+    }
+
+    // Rising and falling rating burst colors.
+    pub(super) fn rating_colors(self) -> ([f32; 3], [f32; 3]) {
+        match self {
+            ColorVision::Typical => (RATING_UP_COLOR, RATING_DOWN_COLOR),
+            ColorVision::Deuteranopia | ColorVision::Protanopia => (pastel(SKY_BLUE), ORANGE),
+            ColorVision::Tritanopia => (pastel(BLUISH_GREEN), VERMILLION),
+        }
+    }
+}
+
+// Halfway to white, as star and particle colors are.
+fn pastel(color: [f32; 3]) -> [f32; 3] {
+    color.map(|c| 0.5 + c * 0.5)
+}
@@ -0,0 +1,378 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{window, HtmlCanvasElement, WebGlBuffer, WebGlProgram, WebGlRenderingContext as GL};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::f32::consts::{FRAC_PI_2, TAU};
+use std::rc::Rc;
+
+use crate::options::{get_option, get_option_string, parse_hex_color};
+use crate::stars::{compile_shader, link_program, FrameCallback};
+
+#[derive(Clone, Copy, PartialEq)]
+enum ChartKind {
+    Bars,
+    Donut,
+}
+
+struct StatsChart {
+    gl: GL,
+    canvas: HtmlCanvasElement,
+    resolution: (f32, f32),
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+    kind: ChartKind,
+    colors: Vec<[f32; 3]>,
+    from: Vec<f32>,
+    to: Vec<f32>,
+    progress: f32,
+    animating: bool,
+}
+
+const STATS_ANIMATION_SPEED: f32 = 0.03;
+const STATS_PADDING: f32 = 12.0;
+const STATS_BAR_GAP_RATIO: f32 = 0.3;
+const STATS_BAR_BASE_SHADE: f32 = 0.45;
+const STATS_GLOW_SIZE: f32 = 14.0;
+const STATS_DONUT_INNER_RATIO: f32 = 0.62;
+const STATS_DONUT_GAP: f32 = 0.015;
+const STATS_DONUT_SEGMENT_ANGLE: f32 = 0.035;
+const STATS_PALETTE: [[f32; 3]; 8] = [
+    [0.45, 0.7, 1.0],
+    [1.0, 0.75, 0.35],
+    [0.55, 0.9, 0.6],
+    [0.95, 0.5, 0.6],
+    [0.75, 0.6, 1.0],
+    [0.4, 0.9, 0.9],
+    [1.0, 0.9, 0.5],
+    [0.8, 0.8, 0.85],
+];
+
+// Each chart keeps its frame callback so re-renders restart the same loop.
+type ChartHandle = (Rc<RefCell<StatsChart>>, FrameCallback);
+
+thread_local! {
+    static STATS_CHARTS: RefCell<HashMap<String, ChartHandle>> = RefCell::new(HashMap::new());
+}
+
+// Verdict labels get the same hues as the status sky tints.
+fn verdict_color(label: &str) -> Option<[f32; 3]> {
+    match label.to_ascii_lowercase().as_str() {
+        "ac" | "accepted" => Some([0.3, 0.85, 0.45]),
+        "wa" | "wrong_answer" => Some([0.95, 0.35, 0.35]),
+        "tle" | "time_limit_exceeded" => Some([1.0, 0.75, 0.25]),
+        "mle" | "memory_limit_exceeded" => Some([1.0, 0.55, 0.25]),
+        "re" | "runtime_error" => Some([0.85, 0.4, 0.8]),
+        "ce" | "compile_error" => Some([0.65, 0.5, 0.95]),
+        "pending" | "judging" => Some([0.6, 0.65, 0.75]),
+        _ => None,
+    }
+}
+
+fn ease_out(t: f32) -> f32 {
+    1.0 - (1.0 - t).powi(3)
+}
+
+fn push_vertex(data: &mut Vec<f32>, x: f32, y: f32, color: [f32; 3], alpha: f32) {
+    data.extend_from_slice(&[x, y, color[0], color[1], color[2], alpha]);
+}
+
+impl StatsChart {
+    fn new(canvas_id: &str) -> StatsChart {
+        let document = window().unwrap().document().unwrap();
+        let canvas = document
+            .get_element_by_id(canvas_id)
+            .expect("Canvas element not found")
+            .dyn_into::<HtmlCanvasElement>()
+            .unwrap();
+
+        let gl: GL = canvas
+            .get_context("webgl")
+            .unwrap()
+            .unwrap()
+            .dyn_into()
+            .unwrap();
+
+        let vertex_shader_source = r#"
+            attribute vec2 a_position;
+            attribute vec4 a_color;
+            uniform vec2 u_resolution;
+            uniform float u_pointSize;
+            varying vec4 v_color;
+            void main() {
+                vec2 zeroToOne = a_position / u_resolution;
+                vec2 zeroToTwo = zeroToOne * 2.0;
+                vec2 clipSpace = zeroToTwo - 1.0;
+                clipSpace.y = -clipSpace.y;
+                gl_Position = vec4(clipSpace, 0.0, 1.0);
+                gl_PointSize = u_pointSize;
+                v_color = a_color;
+            }
+        "#;
+        let fragment_shader_source = r#"
+            precision mediump float;
+            uniform bool u_glow;
+            varying vec4 v_color;
+            void main() {
+                float alpha = v_color.a;
+                if (u_glow) {
+                    float dist = length(gl_PointCoord - 0.5) * 2.0;
+                    alpha *= pow(max(1.0 - dist, 0.0), 2.0);
+                }
+                gl_FragColor = vec4(v_color.rgb, alpha);
+            }
+        "#;
+        let vertex_shader = compile_shader(&gl, GL::VERTEX_SHADER, vertex_shader_source)
+            .expect("Stats chart vertex shader compile error");
+        let fragment_shader = compile_shader(&gl, GL::FRAGMENT_SHADER, fragment_shader_source)
+            .expect("Stats chart fragment shader compile error");
+        let program = link_program(&gl, &vertex_shader, &fragment_shader)
+            .expect("Stats chart program link error");
+        let buffer = gl.create_buffer().expect("Failed to create stats chart buffer");
+
+        let mut chart = StatsChart {
+            gl,
+            canvas,
+            resolution: (0.0, 0.0),
+            program,
+            buffer,
+            kind: ChartKind::Bars,
+            colors: Vec::new(),
+            from: Vec::new(),
+            to: Vec::new(),
+            progress: 1.0,
+            animating: false,
+        };
+        chart.resize();
+        chart
+    }
+
+    fn resize(&mut self) {
+        let dpr = window().unwrap().device_pixel_ratio() as f32;
+        let width = self.canvas.client_width() as f32 * dpr;
+        let height = self.canvas.client_height() as f32 * dpr;
+        self.canvas.set_width(width as u32);
+        self.canvas.set_height(height as u32);
+        self.resolution = (width, height);
+    }
+
+    fn set_data(&mut self, kind: ChartKind, values: Vec<f32>, colors: Vec<[f32; 3]>) {
+        // Animate from the values currently on screen; a different number of
+        // entries or chart kind grows from zero instead.
+        self.from = if kind == self.kind && values.len() == self.to.len() {
+            self.current_values()
+        } else {
+            vec![0.0; values.len()]
+        };
+        self.kind = kind;
+        self.to = values;
+        self.colors = colors;
+        self.progress = 0.0;
+    }
+
+    fn current_values(&self) -> Vec<f32> {
+        let t = ease_out(self.progress);
+        self.from.iter().zip(&self.to).map(|(from, to)| from + (to - from) * t).collect()
+    }
+
+    fn update(&mut self) {
+        self.progress = (self.progress + STATS_ANIMATION_SPEED).min(1.0);
+    }
+
+    fn draw(&self) {
+        let gl = &self.gl;
+        gl.viewport(0, 0, self.resolution.0 as i32, self.resolution.1 as i32);
+        gl.clear_color(0.0, 0.0, 0.0, 0.0);
+        gl.clear(GL::COLOR_BUFFER_BIT);
+        if self.to.is_empty() {
+            return;
+        }
+        let values = self.current_values();
+        let (triangles, glows) = match self.kind {
+            ChartKind::Bars => self.bar_geometry(&values),
+            ChartKind::Donut => (self.donut_geometry(&values), Vec::new()),
+        };
+        let dpr = window().unwrap().device_pixel_ratio() as f32;
+        gl.enable(GL::BLEND);
+        gl.blend_func(GL::SRC_ALPHA, GL::ONE_MINUS_SRC_ALPHA);
+        self.draw_vertices(&triangles, GL::TRIANGLES, 1.0);
+        self.draw_vertices(&glows, GL::POINTS, STATS_GLOW_SIZE * dpr);
+        gl.disable(GL::BLEND);
+    }
+
+    fn bar_geometry(&self, values: &[f32]) -> (Vec<f32>, Vec<f32>) {
+        let max = self.to.iter().chain(&self.from).copied().fold(0.0, f32::max);
+        let (width, height) = (self.resolution.0 - STATS_PADDING * 2.0, self.resolution.1 - STATS_PADDING * 2.0);
+        let slot = width / values.len() as f32;
+        let bar = slot * (1.0 - STATS_BAR_GAP_RATIO);
+        let bottom = STATS_PADDING + height;
+        let mut triangles = Vec::with_capacity(values.len() * 36);
+        let mut glows = Vec::with_capacity(values.len() * 6);
+        for (i, value) in values.iter().enumerate() {
+            let fraction = if max > 0.0 { value / max } else { 0.0 };
+            let x0 = STATS_PADDING + slot * i as f32 + (slot - bar) * 0.5;
+            let x1 = x0 + bar;
+            let top = bottom - height * fraction;
+            let color = self.colors[i];
+            let base = color.map(|c| c * STATS_BAR_BASE_SHADE);
+            for (x, y, c) in [
+                (x0, top, color),
+                (x1, top, color),
+                (x0, bottom, base),
+                (x1, top, color),
+                (x1, bottom, base),
+                (x0, bottom, base),
+            ] {
+                push_vertex(&mut triangles, x, y, c, 0.9);
+            }
+            if fraction > 0.0 {
+                push_vertex(&mut glows, (x0 + x1) * 0.5, top, [1.0, 1.0, 1.0], 0.9);
+            }
+        }
+        (triangles, glows)
+    }
+
+    fn donut_geometry(&self, values: &[f32]) -> Vec<f32> {
+        let total: f32 = values.iter().sum();
+        if total <= 0.0 {
+            return Vec::new();
+        }
+        let center = (self.resolution.0 * 0.5, self.resolution.1 * 0.5);
+        let outer = (self.resolution.0.min(self.resolution.1) * 0.5 - STATS_PADDING).max(0.0);
+        let inner = outer * STATS_DONUT_INNER_RATIO;
+        // The whole ring also sweeps in as it animates.
+        let sweep = TAU * ease_out(self.progress);
+        let gap = if values.iter().filter(|v| **v > 0.0).count() > 1 { STATS_DONUT_GAP } else { 0.0 };
+        let point = |angle: f32, radius: f32| (center.0 + angle.cos() * radius, center.1 + angle.sin() * radius);
+
+        let mut triangles = Vec::new();
+        let mut start = -FRAC_PI_2;
+        for (i, value) in values.iter().enumerate() {
+            let span = value / total * sweep;
+            let (a0, a1) = (start + gap * 0.5, start + span - gap * 0.5);
+            start += span;
+            if a1 <= a0 {
+                continue;
+            }
+            let color = self.colors[i];
+            let inner_color = color.map(|c| c * STATS_BAR_BASE_SHADE);
+            let segments = ((a1 - a0) / STATS_DONUT_SEGMENT_ANGLE).ceil().max(1.0) as usize;
+            for s in 0..segments {
+                let b0 = a0 + (a1 - a0) * s as f32 / segments as f32;
+                let b1 = a0 + (a1 - a0) * (s + 1) as f32 / segments as f32;
+                let (o0, o1, i0, i1) = (point(b0, outer), point(b1, outer), point(b0, inner), point(b1, inner));
+                for ((x, y), c) in [(o0, color), (o1, color), (i0, inner_color), (o1, color), (i1, inner_color), (i0, inner_color)] {
+                    push_vertex(&mut triangles, x, y, c, 0.9);
+                }
+            }
+        }
+        triangles
+    }
+
+    fn draw_vertices(&self, data: &[f32], mode: u32, point_size: f32) {
+        if data.is_empty() {
+            return;
+        }
+        let gl = &self.gl;
+        gl.use_program(Some(&self.program));
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.buffer));
+        unsafe {
+            let array = js_sys::Float32Array::view(data);
+            gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &array, GL::DYNAMIC_DRAW);
+        }
+        let stride = 6 * std::mem::size_of::<f32>() as i32;
+        let pos_loc = gl.get_attrib_location(&self.program, "a_position") as u32;
+        let color_loc = gl.get_attrib_location(&self.program, "a_color") as u32;
+        gl.enable_vertex_attrib_array(pos_loc);
+        gl.vertex_attrib_pointer_with_i32(pos_loc, 2, GL::FLOAT, false, stride, 0);
+        gl.enable_vertex_attrib_array(color_loc);
+        gl.vertex_attrib_pointer_with_i32(color_loc, 4, GL::FLOAT, false, stride, 2 * std::mem::size_of::<f32>() as i32);
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_resolution") {
+            gl.uniform2f(Some(&loc), self.resolution.0, self.resolution.1);
+        }
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_pointSize") {
+            gl.uniform1f(Some(&loc), point_size);
+        }
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_glow") {
+            gl.uniform1i(Some(&loc), (mode == GL::POINTS) as i32);
+        }
+        gl.draw_arrays(mode, 0, (data.len() / 6) as i32);
+    }
+}
+
+fn create_chart(canvas_id: &str) -> ChartHandle {
+    let chart = Rc::new(RefCell::new(StatsChart::new(canvas_id)));
+    let f: FrameCallback = Rc::new(RefCell::new(None));
+    let g = f.clone();
+    let frame_chart = chart.clone();
+    *g.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+        let mut sc = frame_chart.borrow_mut();
+        sc.update();
+        sc.draw();
+        if sc.progress < 1.0 {
+            window().unwrap()
+                .request_animation_frame(f.borrow().as_ref().unwrap().as_ref().unchecked_ref())
+                .unwrap();
+        } else {
+            sc.animating = false;
+        }
+    }) as Box<dyn FnMut()>));
+
+    let resize_chart = chart.clone();
+    let resize_closure = Closure::wrap(Box::new(move || {
+        let mut sc = resize_chart.borrow_mut();
+        sc.resize();
+        sc.draw();
+    }) as Box<dyn FnMut()>);
+    window().unwrap()
+        .add_event_listener_with_callback("resize", resize_closure.as_ref().unchecked_ref())
+        .unwrap();
+    resize_closure.forget();
+
+    (chart, g)
+}
+
+/// Renders submission statistics into `canvas_id` as animated bars or a
+/// donut. `data` is an array of `{ label, value, color? }`; verdict labels
+/// (`ac`, `wa`, `tle`, ...) get their status colors, others cycle through a
+/// palette unless `color` (hex) is given. `options.kind` is `"bars"`
+/// (default) or `"donut"`. Calling this again animates to the new values.
+#[wasm_bindgen]
+pub fn render_stats_chart(canvas_id: &str, data: js_sys::Array, options: JsValue) -> Result<(), JsValue> {
+    let kind = match get_option_string(&options, "kind").as_deref() {
+        None | Some("bars") => ChartKind::Bars,
+        Some("donut") => ChartKind::Donut,
+        Some(other) => return Err(JsValue::from_str(&format!("Unknown stats chart kind: {}", other))),
+    };
+    let mut values = Vec::with_capacity(data.length() as usize);
+    let mut colors = Vec::with_capacity(data.length() as usize);
+    for (i, entry) in data.iter().enumerate() {
+        let value = get_option(&entry, "value")
+            .and_then(|v| v.as_f64())
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .ok_or_else(|| JsValue::from_str(&format!("Stats entry {} needs a non-negative value", i)))?;
+        let label = get_option_string(&entry, "label").unwrap_or_default();
+        let color = match get_option_string(&entry, "color") {
+            Some(hex) => parse_hex_color(&hex).ok_or_else(|| JsValue::from_str(&format!("Invalid color: {}", hex)))?,
+            None => verdict_color(&label).unwrap_or(STATS_PALETTE[i % STATS_PALETTE.len()]),
+        };
+        values.push(value as f32);
+        colors.push(color);
+    }
+
+    let existing = STATS_CHARTS.with(|charts| charts.borrow().get(canvas_id).cloned());
+    let (chart, frame) = existing.unwrap_or_else(|| {
+        let created = create_chart(canvas_id);
+        STATS_CHARTS.with(|charts| charts.borrow_mut().insert(canvas_id.to_string(), created.clone()));
+        created
+    });
+    let mut sc = chart.borrow_mut();
+    sc.set_data(kind, values, colors);
+    if !sc.animating {
+        sc.animating = true;
+        window().unwrap()
+            .request_animation_frame(frame.borrow().as_ref().unwrap().as_ref().unchecked_ref())
+            .unwrap();
+    }
+    Ok(())
+}
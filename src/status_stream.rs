@@ -0,0 +1,195 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{EventSource, MessageEvent, WebSocket};
+
+use crate::stars::drive_status_sky;
+
+/// A decoded submission status message. The wire format is one JSON object
+/// per WebSocket message / EventSource event:
+///
+/// ```json
+/// { "type": "status", "status": "judging", "current": 3, "total": 10 }
+/// { "type": "verdict", "status": "wa", "time_ms": 120, "memory_kb": 2048, "score": 40, "message": "..." }
+/// { "type": "error", "message": "submission not found" }
+/// ```
+pub enum StatusMessage {
+    Status { status: String, current: Option<u32>, total: Option<u32> },
+    Verdict {
+        status: String,
+        time_ms: Option<f64>,
+        memory_kb: Option<f64>,
+        score: Option<f64>,
+        message: Option<String>,
+    },
+    Error { message: String },
+}
+
+impl StatusMessage {
+    pub fn decode(text: &str) -> Result<StatusMessage, String> {
+        let value = js_sys::JSON::parse(text).map_err(|_| "status message is not valid JSON".to_string())?;
+        if !value.is_object() {
+            return Err("status message is not an object".to_string());
+        }
+        let field = |key: &str| {
+            js_sys::Reflect::get(&value, &JsValue::from_str(key))
+                .ok()
+                .filter(|v| !v.is_undefined() && !v.is_null())
+        };
+        let string = |key: &str| field(key).and_then(|v| v.as_string());
+        let number = |key: &str| field(key).and_then(|v| v.as_f64());
+        let kind = string("type").ok_or("status message has no type")?;
+        match kind.as_str() {
+            "status" => Ok(StatusMessage::Status {
+                status: string("status").ok_or("status message has no status")?,
+                current: number("current").map(|v| v as u32),
+                total: number("total").map(|v| v as u32),
+            }),
+            "verdict" => Ok(StatusMessage::Verdict {
+                status: string("status").ok_or("verdict message has no status")?,
+                time_ms: number("time_ms"),
+                memory_kb: number("memory_kb"),
+                score: number("score"),
+                message: string("message"),
+            }),
+            "error" => Ok(StatusMessage::Error {
+                message: string("message").unwrap_or_else(|| "unknown error".to_string()),
+            }),
+            other => Err(format!("unknown status message type: {}", other)),
+        }
+    }
+}
+
+struct Callbacks {
+    on_status: Option<js_sys::Function>,
+    on_verdict: Option<js_sys::Function>,
+    on_error: Option<js_sys::Function>,
+    drive_sky: bool,
+}
+
+impl Callbacks {
+    fn from_js(options: &JsValue) -> Callbacks {
+        let get = |key: &str| {
+            if !options.is_object() {
+                return None;
+            }
+            js_sys::Reflect::get(options, &JsValue::from_str(key)).ok()
+        };
+        let function = |key: &str| get(key).and_then(|v| v.dyn_into::<js_sys::Function>().ok());
+        Callbacks {
+            on_status: function("onStatus"),
+            on_verdict: function("onVerdict"),
+            on_error: function("onError"),
+            drive_sky: get("driveSky").and_then(|v| v.as_bool()).unwrap_or(true),
+        }
+    }
+
+    fn error(&self, message: &str) {
+        if let Some(callback) = &self.on_error {
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from_str(message));
+        }
+    }
+
+    fn dispatch(&self, text: &str) {
+        let message = match StatusMessage::decode(text) {
+            Ok(message) => message,
+            Err(error) => return self.error(&error),
+        };
+        match message {
+            StatusMessage::Status { status, current, total } => {
+                if self.drive_sky {
+                    drive_status_sky(&status, false);
+                }
+                if let Some(callback) = &self.on_status {
+                    let optional = |v: Option<u32>| v.map(JsValue::from).unwrap_or(JsValue::NULL);
+                    let _ = callback.call3(&JsValue::NULL, &JsValue::from_str(&status), &optional(current), &optional(total));
+                }
+            }
+            StatusMessage::Verdict { status, time_ms, memory_kb, score, message } => {
+                if self.drive_sky {
+                    drive_status_sky(&status, true);
+                }
+                if let Some(callback) = &self.on_verdict {
+                    let verdict = js_sys::Object::new();
+                    let set = |key: &str, value: JsValue| {
+                        js_sys::Reflect::set(&verdict, &JsValue::from_str(key), &value).unwrap();
+                    };
+                    let optional = |v: Option<f64>| v.map(JsValue::from).unwrap_or(JsValue::NULL);
+                    set("status", JsValue::from_str(&status));
+                    set("timeMs", optional(time_ms));
+                    set("memoryKb", optional(memory_kb));
+                    set("score", optional(score));
+                    set("message", message.map(JsValue::from).unwrap_or(JsValue::NULL));
+                    let _ = callback.call1(&JsValue::NULL, &verdict);
+                }
+            }
+            StatusMessage::Error { message } => self.error(&message),
+        }
+    }
+}
+
+enum Transport {
+    WebSocket(WebSocket),
+    EventSource(EventSource),
+}
+
+/// Handle returned by `subscribe_submission_status`; call `close()` when the
+/// page no longer needs updates.
+#[wasm_bindgen]
+pub struct StatusSubscription {
+    transport: Transport,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_error: Closure<dyn FnMut()>,
+}
+
+#[wasm_bindgen]
+impl StatusSubscription {
+    pub fn close(&self) {
+        match &self.transport {
+            Transport::WebSocket(socket) => {
+                let _ = socket.close();
+            }
+            Transport::EventSource(source) => source.close(),
+        }
+    }
+}
+
+/// Subscribes to submission status updates at `url` (`ws://`/`wss://` use a
+/// WebSocket, anything else an EventSource). `options` may provide
+/// `onStatus(status, current, total)`, `onVerdict(verdict)`,
+/// `onError(message)` and `driveSky` (default `true`), which tints the
+/// starfield and toggles its judging pulse as updates arrive.
+#[wasm_bindgen]
+pub fn subscribe_submission_status(url: &str, options: JsValue) -> Result<StatusSubscription, JsValue> {
+    let callbacks = std::rc::Rc::new(Callbacks::from_js(&options));
+
+    let message_callbacks = callbacks.clone();
+    let on_message = Closure::wrap(Box::new(move |event: MessageEvent| {
+        match event.data().as_string() {
+            Some(text) => message_callbacks.dispatch(&text),
+            None => message_callbacks.error("status message is not text"),
+        }
+    }) as Box<dyn FnMut(MessageEvent)>);
+
+    let error_callbacks = callbacks.clone();
+    let on_error = Closure::wrap(Box::new(move || {
+        error_callbacks.error("status stream connection error");
+    }) as Box<dyn FnMut()>);
+
+    let transport = if url.starts_with("ws://") || url.starts_with("wss://") {
+        let socket = WebSocket::new(url)?;
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        Transport::WebSocket(socket)
+    } else {
+        let source = EventSource::new(url)?;
+        source.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        source.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        Transport::EventSource(source)
+    };
+
+    Ok(StatusSubscription {
+        transport,
+        _on_message: on_message,
+        _on_error: on_error,
+    })
+}
@@ -0,0 +1,193 @@
+use wasm_bindgen::JsCast;
+use web_sys::{
+    window, CanvasRenderingContext2d, HtmlCanvasElement, WebGlBuffer, WebGlProgram,
+    WebGlRenderingContext as GL, WebGlTexture,
+};
+
+use crate::stars::{compile_shader, link_program};
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct TextStyle {
+    pub font_px: f32,
+    pub color: [f32; 3],
+    pub glow: [f32; 3],
+    pub glow_blur: f32,
+}
+
+pub(crate) struct TextLayer {
+    canvas: HtmlCanvasElement,
+    ctx: CanvasRenderingContext2d,
+    texture: WebGlTexture,
+    text: String,
+    style: Option<TextStyle>,
+    size: (f32, f32),
+}
+
+pub(crate) struct TextRenderer {
+    program: WebGlProgram,
+    buffer: WebGlBuffer,
+}
+
+impl TextLayer {
+    pub fn new(gl: &GL) -> Result<TextLayer, String> {
+        let document = window().unwrap().document().unwrap();
+        let canvas = document
+            .create_element("canvas")
+            .ok()
+            .and_then(|element| element.dyn_into::<HtmlCanvasElement>().ok())
+            .ok_or("Failed to create text canvas")?;
+        let ctx = canvas
+            .get_context("2d")
+            .ok()
+            .flatten()
+            .and_then(|context| context.dyn_into::<CanvasRenderingContext2d>().ok())
+            .ok_or("2D context unavailable for text")?;
+        let texture = gl.create_texture().ok_or("Failed to create text texture")?;
+        gl.bind_texture(GL::TEXTURE_2D, Some(&texture));
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_S, GL::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_WRAP_T, GL::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MIN_FILTER, GL::LINEAR as i32);
+        gl.tex_parameteri(GL::TEXTURE_2D, GL::TEXTURE_MAG_FILTER, GL::LINEAR as i32);
+        Ok(TextLayer {
+            canvas,
+            ctx,
+            texture,
+            text: String::new(),
+            style: None,
+            size: (0.0, 0.0),
+        })
+    }
+
+    pub fn set_text(&mut self, gl: &GL, text: &str, style: TextStyle) {
+        if self.text == text && self.style == Some(style) {
+            return;
+        }
+        let font = format!("bold {}px sans-serif", style.font_px);
+        self.ctx.set_font(&font);
+        let text_width = self.ctx.measure_text(text).map(|m| m.width() as f32).unwrap_or(0.0);
+        let padding = style.glow_blur * 2.0;
+        let width = (text_width + padding * 2.0).ceil().max(1.0);
+        let height = (style.font_px * 1.4 + padding * 2.0).ceil().max(1.0);
+        self.canvas.set_width(width as u32);
+        self.canvas.set_height(height as u32);
+
+        // Resizing the canvas resets the 2D context state.
+        self.ctx.set_font(&font);
+        self.ctx.set_text_align("center");
+        self.ctx.set_text_baseline("middle");
+        self.ctx.clear_rect(0.0, 0.0, width as f64, height as f64);
+        self.ctx.set_shadow_color(&css_rgb(style.glow));
+        self.ctx.set_shadow_blur(style.glow_blur as f64);
+        self.ctx.set_fill_style_str(&css_rgb(style.color));
+        self.ctx
+            .fill_text(text, width as f64 / 2.0, height as f64 / 2.0)
+            .unwrap();
+
+        // Premultiplied texels filter without dark halos around the glow and
+        // match the starfield's premultiplied blending.
+        gl.bind_texture(GL::TEXTURE_2D, Some(&self.texture));
+        gl.pixel_storei(GL::UNPACK_PREMULTIPLY_ALPHA_WEBGL, 1);
+        gl.tex_image_2d_with_u32_and_u32_and_canvas(
+            GL::TEXTURE_2D, 0, GL::RGBA as i32, GL::RGBA, GL::UNSIGNED_BYTE, &self.canvas,
+        )
+        .unwrap();
+        gl.pixel_storei(GL::UNPACK_PREMULTIPLY_ALPHA_WEBGL, 0);
+
+        self.text = text.to_string();
+        self.style = Some(style);
+        self.size = (width, height);
+    }
+
+    pub fn size(&self) -> (f32, f32) {
+        self.size
+    }
+}
+
+impl TextRenderer {
+    pub fn new(gl: &GL) -> Result<TextRenderer, String> {
+        let vertex_shader_source = r#"
+            attribute vec2 a_position;
+            attribute vec2 a_texCoord;
+            uniform vec2 u_resolution;
+            varying vec2 v_texCoord;
+            void main() {
+                vec2 zeroToOne = a_position / u_resolution;
+                vec2 zeroToTwo = zeroToOne * 2.0;
+                vec2 clipSpace = zeroToTwo - 1.0;
+                clipSpace.y = -clipSpace.y;
+                gl_Position = vec4(clipSpace, 0.0, 1.0);
+                v_texCoord = a_texCoord;
+            }
+        "#;
+        let fragment_shader_source = r#"
+            precision mediump float;
+            uniform sampler2D u_texture;
+            uniform float u_alpha;
+            varying vec2 v_texCoord;
+            void main() {
+                gl_FragColor = texture2D(u_texture, v_texCoord) * u_alpha;
+            }
+        "#;
+        let vertex_shader = compile_shader(gl, GL::VERTEX_SHADER, vertex_shader_source)?;
+        let fragment_shader = compile_shader(gl, GL::FRAGMENT_SHADER, fragment_shader_source)?;
+        let program = link_program(gl, &vertex_shader, &fragment_shader)?;
+        let buffer = gl.create_buffer().ok_or("Failed to create text buffer")?;
+        Ok(TextRenderer { program, buffer })
+    }
+
+    pub fn draw(&self, gl: &GL, layer: &TextLayer, resolution: (f32, f32), center: (f32, f32), alpha: f32) {
+        if layer.text.is_empty() {
+            return;
+        }
+        let (w, h) = layer.size;
+        let x0 = center.0 - w / 2.0;
+        let y0 = center.1 - h / 2.0;
+        let x1 = x0 + w;
+        let y1 = y0 + h;
+        let vertices: [f32; 6 * 4] = [
+            x0, y0, 0.0, 0.0,
+            x1, y0, 1.0, 0.0,
+            x0, y1, 0.0, 1.0,
+            x1, y0, 1.0, 0.0,
+            x1, y1, 1.0, 1.0,
+            x0, y1, 0.0, 1.0,
+        ];
+        gl.use_program(Some(&self.program));
+        gl.bind_buffer(GL::ARRAY_BUFFER, Some(&self.buffer));
+        unsafe {
+            let vert_array = js_sys::Float32Array::view(&vertices);
+            gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &vert_array, GL::DYNAMIC_DRAW);
+        }
+        let stride = 4 * std::mem::size_of::<f32>() as i32;
+        let pos_loc = gl.get_attrib_location(&self.program, "a_position") as u32;
+        let tex_loc = gl.get_attrib_location(&self.program, "a_texCoord") as u32;
+        gl.enable_vertex_attrib_array(pos_loc);
+        gl.vertex_attrib_pointer_with_i32(pos_loc, 2, GL::FLOAT, false, stride, 0);
+        gl.enable_vertex_attrib_array(tex_loc);
+        gl.vertex_attrib_pointer_with_i32(tex_loc, 2, GL::FLOAT, false, stride, 2 * std::mem::size_of::<f32>() as i32);
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_resolution") {
+            gl.uniform2f(Some(&loc), resolution.0, resolution.1);
+        }
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_alpha") {
+            gl.uniform1f(Some(&loc), alpha);
+        }
+        gl.active_texture(GL::TEXTURE0);
+        gl.bind_texture(GL::TEXTURE_2D, Some(&layer.texture));
+        if let Some(loc) = gl.get_uniform_location(&self.program, "u_texture") {
+            gl.uniform1i(Some(&loc), 0);
+        }
+        gl.enable(GL::BLEND);
+        gl.blend_func(GL::ONE, GL::ONE_MINUS_SRC_ALPHA);
+        gl.draw_arrays(GL::TRIANGLES, 0, 6);
+        gl.disable(GL::BLEND);
+    }
+}
+
+pub(crate) fn css_rgb(color: [f32; 3]) -> String {
+    format!(
+        "rgb({}, {}, {})",
+        (color[0].clamp(0.0, 1.0) * 255.0).round() as u8,
+        (color[1].clamp(0.0, 1.0) * 255.0).round() as u8,
+        (color[2].clamp(0.0, 1.0) * 255.0).round() as u8,
+    )
+}
@@ -0,0 +1,133 @@
+use wasm_bindgen::prelude::*;
+
+use crate::options::invalid_option;
+use crate::stars::set_theme_tint;
+
+pub struct StarfieldPreset {
+    pub sky: [f32; 3],
+    pub sky_mix: f32,
+    pub star: [f32; 3],
+    pub star_mix: f32,
+}
+
+pub struct Palette {
+    pub tokens: Vec<(&'static str, [f32; 3])>,
+    pub starfield: StarfieldPreset,
+}
+
+/// Converts HSL (hue in degrees, saturation and lightness in 0..=1) to RGB.
+pub fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> [f32; 3] {
+    let hue = hue.rem_euclid(360.0) / 60.0;
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = lightness - chroma / 2.0;
+    [r + m, g + m, b + m]
+}
+
+fn relative_luminance(color: [f32; 3]) -> f32 {
+    let linear = color.map(|c| if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) });
+    0.2126 * linear[0] + 0.7152 * linear[1] + 0.0722 * linear[2]
+}
+
+fn hex(color: [f32; 3]) -> String {
+    let [r, g, b] = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Derives the site palette from one base hue. Neutrals carry a hint of the
+/// hue, the accent sits opposite it, and status colors keep fixed hues so
+/// verdicts read the same in every theme.
+pub fn palette(base_hue: f32, dark: bool) -> Palette {
+    let hue = base_hue.rem_euclid(360.0);
+    let accent_hue = hue + 150.0;
+    // (lightness in dark mode, lightness in light mode)
+    let l = |dark_value: f32, light_value: f32| if dark { dark_value } else { light_value };
+
+    let primary = hsl_to_rgb(hue, 0.75, l(0.62, 0.45));
+    let primary_contrast = if relative_luminance(primary) > 0.4 { [0.05, 0.05, 0.08] } else { [1.0, 1.0, 1.0] };
+    let tokens = vec![
+        ("background", hsl_to_rgb(hue, 0.3, l(0.07, 0.98))),
+        ("surface", hsl_to_rgb(hue, 0.25, l(0.11, 1.0))),
+        ("surface-raised", hsl_to_rgb(hue, 0.22, l(0.15, 0.96))),
+        ("border", hsl_to_rgb(hue, 0.18, l(0.24, 0.86))),
+        ("text", hsl_to_rgb(hue, 0.15, l(0.92, 0.12))),
+        ("text-muted", hsl_to_rgb(hue, 0.12, l(0.65, 0.4))),
+        ("primary", primary),
+        ("primary-hover", hsl_to_rgb(hue, 0.8, l(0.7, 0.38))),
+        ("primary-contrast", primary_contrast),
+        ("accent", hsl_to_rgb(accent_hue, 0.7, l(0.65, 0.45))),
+        ("success", hsl_to_rgb(140.0, 0.6, l(0.55, 0.38))),
+        ("warning", hsl_to_rgb(38.0, 0.9, l(0.58, 0.45))),
+        ("danger", hsl_to_rgb(355.0, 0.75, l(0.62, 0.47))),
+        ("info", hsl_to_rgb(205.0, 0.75, l(0.62, 0.45))),
+    ];
+
+    let starfield = StarfieldPreset {
+        sky: hsl_to_rgb(hue, 0.6, l(0.25, 0.55)),
+        sky_mix: l(0.2, 0.35),
+        star: hsl_to_rgb(hue, 0.5, 0.85),
+        star_mix: l(0.15, 0.3),
+    };
+    Palette { tokens, starfield }
+}
+
+fn validate_hue(base_hue: f32) -> Result<(), JsValue> {
+    if base_hue.is_finite() {
+        Ok(())
+    } else {
+        Err(invalid_option("baseHue", "a finite number of degrees", &base_hue.into()))
+    }
+}
+
+/// Generates the site theme from `base_hue` (degrees) for dark or light
+/// mode. Returns `{ css, variables, starfield }`: `css` is a block of
+/// `--soj-*` custom property declarations, `variables` the same as an
+/// object, and `starfield` the matching `{ sky, skyMix, star, starMix }`
+/// tint preset (hex colors) for `apply_theme_preset`.
+#[wasm_bindgen]
+pub fn theme_tokens(base_hue: f32, dark: bool) -> Result<JsValue, JsValue> {
+    validate_hue(base_hue)?;
+    let palette = palette(base_hue, dark);
+    let set = |object: &js_sys::Object, key: &str, value: JsValue| {
+        js_sys::Reflect::set(object, &JsValue::from_str(key), &value).unwrap();
+    };
+
+    let variables = js_sys::Object::new();
+    let mut css = String::new();
+    for (name, color) in &palette.tokens {
+        let property = format!("--soj-{}", name);
+        css.push_str(&format!("{}: {};\n", property, hex(*color)));
+        set(&variables, &property, JsValue::from_str(&hex(*color)));
+    }
+    css.push_str(&format!("color-scheme: {};\n", if dark { "dark" } else { "light" }));
+
+    let starfield = js_sys::Object::new();
+    set(&starfield, "sky", JsValue::from_str(&hex(palette.starfield.sky)));
+    set(&starfield, "skyMix", JsValue::from(palette.starfield.sky_mix));
+    set(&starfield, "star", JsValue::from_str(&hex(palette.starfield.star)));
+    set(&starfield, "starMix", JsValue::from(palette.starfield.star_mix));
+
+    let result = js_sys::Object::new();
+    set(&result, "css", JsValue::from_str(&css));
+    set(&result, "variables", variables.into());
+    set(&result, "starfield", starfield.into());
+    Ok(result.into())
+}
+
+/// Crossfades the starfield to the preset derived from the same `base_hue`
+/// and mode as `theme_tokens`, so canvas and page colors stay in step.
+#[wasm_bindgen]
+pub fn apply_theme_preset(base_hue: f32, dark: bool) -> Result<(), JsValue> {
+    validate_hue(base_hue)?;
+    let preset = palette(base_hue, dark).starfield;
+    set_theme_tint(preset.sky, preset.sky_mix, preset.star, preset.star_mix);
+    Ok(())
+}
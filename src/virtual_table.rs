@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+/// Virtual scrolling state for a large fixed-row-height table such as contest
+/// standings. The frontend pushes cell text with `set_row`, calls `scroll`
+/// on every scroll/resize, and patches only the cells reported back.
+#[wasm_bindgen]
+pub struct VirtualTable {
+    row_height: f32,
+    // Left edge of each column plus the total width as the last entry.
+    column_offsets: Vec<f32>,
+    sticky_columns: usize,
+    overscan: usize,
+    cells: Vec<Vec<String>>,
+    revisions: Vec<Vec<u32>>,
+    rendered: HashMap<(usize, usize), u32>,
+}
+
+pub struct Window {
+    pub rows: (usize, usize),
+    pub columns: (usize, usize),
+    pub offset: (f32, f32),
+}
+
+pub struct CellUpdate {
+    pub row: usize,
+    pub column: usize,
+}
+
+impl VirtualTable {
+    pub fn column_count(&self) -> usize {
+        self.column_offsets.len() - 1
+    }
+
+    pub fn total_size(&self) -> (f32, f32) {
+        (self.column_offsets[self.column_count()], self.cells.len() as f32 * self.row_height)
+    }
+
+    /// Visible rows `[start, end)` and scrolling columns `[start, end)` (the
+    /// sticky columns are always visible on top of these), with overscan.
+    pub fn window(&self, scroll_top: f32, scroll_left: f32, viewport_width: f32, viewport_height: f32) -> Window {
+        let rows = self.cells.len();
+        let first_row = (scroll_top.max(0.0) / self.row_height) as usize;
+        let last_row = ((scroll_top.max(0.0) + viewport_height) / self.row_height).ceil() as usize;
+        let row_start = first_row.saturating_sub(self.overscan).min(rows);
+        let row_end = (last_row + self.overscan).min(rows);
+
+        let columns = self.column_count();
+        let sticky_width = self.column_offsets[self.sticky_columns];
+        let left = scroll_left.max(0.0) + sticky_width;
+        let right = scroll_left.max(0.0) + viewport_width;
+        // Column `i` spans offsets[i]..offsets[i + 1]; find the first column
+        // ending after `left` and the first starting at or after `right`.
+        let first_column = self.column_offsets[1..]
+            .partition_point(|end| *end <= left)
+            .max(self.sticky_columns);
+        let end_column = self.column_offsets[..columns].partition_point(|start| *start < right);
+        let column_start = first_column.min(columns);
+        let column_end = end_column.max(column_start).min(columns);
+
+        Window {
+            rows: (row_start, row_end),
+            columns: (column_start, column_end),
+            offset: (scroll_left.max(0.0), row_start as f32 * self.row_height),
+        }
+    }
+
+    /// Returns the cells to (re)draw for `window` — newly visible or changed
+    /// since the last call — and the cells that left it.
+    pub fn diff(&mut self, window: &Window) -> (Vec<CellUpdate>, Vec<(usize, usize)>) {
+        let columns: Vec<usize> = (0..self.sticky_columns).chain(window.columns.0..window.columns.1).collect();
+        let mut visible = HashMap::with_capacity((window.rows.1 - window.rows.0) * columns.len());
+        let mut updates = Vec::new();
+        for row in window.rows.0..window.rows.1 {
+            for &column in &columns {
+                let revision = self.revisions[row][column];
+                if self.rendered.get(&(row, column)) != Some(&revision) {
+                    updates.push(CellUpdate { row, column });
+                }
+                visible.insert((row, column), revision);
+            }
+        }
+        let removed = self.rendered.keys().filter(|cell| !visible.contains_key(cell)).copied().collect();
+        self.rendered = visible;
+        (updates, removed)
+    }
+}
+
+#[wasm_bindgen]
+impl VirtualTable {
+    /// Creates a table of `row_count` empty rows. `column_widths` are in
+    /// pixels; the first `sticky_columns` stay pinned while scrolling
+    /// horizontally and `overscan` extra rows are kept above and below.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        row_count: u32,
+        row_height: f32,
+        column_widths: Vec<f32>,
+        sticky_columns: u32,
+        overscan: u32,
+    ) -> Result<VirtualTable, JsValue> {
+        if row_height <= 0.0 || !row_height.is_finite() {
+            return Err(JsValue::from_str("Row height must be positive"));
+        }
+        if column_widths.iter().any(|w| *w < 0.0 || !w.is_finite()) {
+            return Err(JsValue::from_str("Column widths must be non-negative"));
+        }
+        let mut column_offsets = Vec::with_capacity(column_widths.len() + 1);
+        let mut x = 0.0;
+        column_offsets.push(x);
+        for width in &column_widths {
+            x += width;
+            column_offsets.push(x);
+        }
+        let columns = column_widths.len();
+        Ok(VirtualTable {
+            row_height,
+            column_offsets,
+            sticky_columns: (sticky_columns as usize).min(columns),
+            overscan: overscan as usize,
+            cells: vec![vec![String::new(); columns]; row_count as usize],
+            revisions: vec![vec![0; columns]; row_count as usize],
+            rendered: HashMap::new(),
+        })
+    }
+
+    /// Grows or shrinks the table; new rows start empty.
+    pub fn set_row_count(&mut self, row_count: u32) {
+        let columns = self.column_count();
+        self.cells.resize(row_count as usize, vec![String::new(); columns]);
+        self.revisions.resize(row_count as usize, vec![0; columns]);
+    }
+
+    /// Replaces the text of one row. Only cells whose text actually changed
+    /// are reported by the next `scroll`.
+    pub fn set_row(&mut self, row: u32, cells: Vec<String>) -> Result<(), JsValue> {
+        let row = row as usize;
+        if row >= self.cells.len() {
+            return Err(JsValue::from_str(&format!("Row {} is out of range", row)));
+        }
+        for (column, text) in cells.into_iter().take(self.column_count()).enumerate() {
+            if self.cells[row][column] != text {
+                self.cells[row][column] = text;
+                self.revisions[row][column] = self.revisions[row][column].wrapping_add(1);
+            }
+        }
+        Ok(())
+    }
+
+    /// Forgets what has been rendered so the next `scroll` reports every
+    /// visible cell, e.g. after the frontend rebuilt its DOM.
+    pub fn invalidate(&mut self) {
+        self.rendered.clear();
+    }
+
+    /// Computes the visible window for a scroll position and viewport and
+    /// returns `{ rowStart, rowEnd, columnStart, columnEnd, stickyColumns,
+    /// offsetX, offsetY, totalWidth, totalHeight, updates, removed }` where
+    /// `updates` is `[{ row, column, text, x, y, width }]` for cells to draw
+    /// and `removed` is `[{ row, column }]` for cells to drop. Ranges are
+    /// half-open; `x` / `y` are positions within the full table.
+    pub fn scroll(&mut self, scroll_top: f32, scroll_left: f32, viewport_width: f32, viewport_height: f32) -> JsValue {
+        let window = self.window(scroll_top, scroll_left, viewport_width, viewport_height);
+        let (updates, removed) = self.diff(&window);
+        let (total_width, total_height) = self.total_size();
+
+        let set = |object: &js_sys::Object, key: &str, value: JsValue| {
+            js_sys::Reflect::set(object, &JsValue::from_str(key), &value).unwrap();
+        };
+        let updates: js_sys::Array = updates
+            .iter()
+            .map(|update| {
+                let object = js_sys::Object::new();
+                let x = self.column_offsets[update.column];
+                set(&object, "row", JsValue::from(update.row as u32));
+                set(&object, "column", JsValue::from(update.column as u32));
+                set(&object, "text", JsValue::from_str(&self.cells[update.row][update.column]));
+                set(&object, "x", JsValue::from(x));
+                set(&object, "y", JsValue::from(update.row as f32 * self.row_height));
+                set(&object, "width", JsValue::from(self.column_offsets[update.column + 1] - x));
+                JsValue::from(object)
+            })
+            .collect();
+        let removed: js_sys::Array = removed
+            .iter()
+            .map(|(row, column)| {
+                let object = js_sys::Object::new();
+                set(&object, "row", JsValue::from(*row as u32));
+                set(&object, "column", JsValue::from(*column as u32));
+                JsValue::from(object)
+            })
+            .collect();
+
+        let result = js_sys::Object::new();
+        set(&result, "rowStart", JsValue::from(window.rows.0 as u32));
+        set(&result, "rowEnd", JsValue::from(window.rows.1 as u32));
+        set(&result, "columnStart", JsValue::from(window.columns.0 as u32));
+        set(&result, "columnEnd", JsValue::from(window.columns.1 as u32));
+        set(&result, "stickyColumns", JsValue::from(self.sticky_columns as u32));
+        set(&result, "offsetX", JsValue::from(window.offset.0));
+        set(&result, "offsetY", JsValue::from(window.offset.1));
+        set(&result, "totalWidth", JsValue::from(total_width));
+        set(&result, "totalHeight", JsValue::from(total_height));
+        set(&result, "updates", updates.into());
+        set(&result, "removed", removed.into());
+        result.into()
+    }
+}
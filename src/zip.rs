@@ -0,0 +1,270 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const END_OF_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+const END_OF_DIRECTORY_SIZE: usize = 22;
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATED: u16 = 8;
+// General purpose flag bit 11: file names are UTF-8.
+const FLAG_UTF8: u16 = 1 << 11;
+const DEFLATE_LEVEL: u8 = 6;
+// 1980-01-01 00:00 in MS-DOS date format, the earliest representable time.
+const DOS_DATE_EPOCH: u16 = 0x0021;
+
+pub struct ZipEntry {
+    pub name: String,
+    pub data: Vec<u8>,
+    pub compressed_size: usize,
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Result<u16, String> {
+    bytes
+        .get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or_else(|| "truncated zip archive".to_string())
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "truncated zip archive".to_string())
+}
+
+fn find_end_of_directory(bytes: &[u8]) -> Result<usize, String> {
+    if bytes.len() < END_OF_DIRECTORY_SIZE {
+        return Err("not a zip archive".to_string());
+    }
+    // The record sits at the very end, followed by a comment of at most 64 KiB.
+    let lowest = bytes.len().saturating_sub(END_OF_DIRECTORY_SIZE + u16::MAX as usize);
+    (lowest..=bytes.len() - END_OF_DIRECTORY_SIZE)
+        .rev()
+        .find(|&offset| u32_at(bytes, offset) == Ok(END_OF_DIRECTORY_SIGNATURE))
+        .ok_or_else(|| "not a zip archive".to_string())
+}
+
+/// Reads every file in a ZIP archive (stored or deflated entries, no ZIP64
+/// or encryption). Directory entries are skipped and CRCs are verified.
+pub fn read_zip(bytes: &[u8]) -> Result<Vec<ZipEntry>, String> {
+    let end = find_end_of_directory(bytes)?;
+    let count = u16_at(bytes, end + 10)? as usize;
+    let mut offset = u32_at(bytes, end + 16)? as usize;
+    if count == u16::MAX as usize || offset == u32::MAX as usize {
+        return Err("ZIP64 archives are not supported".to_string());
+    }
+
+    let mut entries = Vec::with_capacity(count);
+    // Offsets and sizes come from the archive, so fields are read relative
+    // to sliced headers: sums of them could overflow on wasm32.
+    for _ in 0..count {
+        let header = bytes.get(offset..).ok_or("truncated zip archive")?;
+        if u32_at(header, 0)? != CENTRAL_HEADER_SIGNATURE {
+            return Err("corrupt central directory".to_string());
+        }
+        let flags = u16_at(header, 8)?;
+        let method = u16_at(header, 10)?;
+        let crc = u32_at(header, 16)?;
+        let compressed_size = u32_at(header, 20)? as usize;
+        let size = u32_at(header, 24)? as usize;
+        let name_len = u16_at(header, 28)? as usize;
+        let extra_len = u16_at(header, 30)? as usize;
+        let comment_len = u16_at(header, 32)? as usize;
+        let local = u32_at(header, 42)? as usize;
+        let name_bytes = header.get(46..46 + name_len).ok_or("truncated zip archive")?;
+        let name = String::from_utf8_lossy(name_bytes).into_owned();
+        offset = offset
+            .checked_add(46 + name_len + extra_len + comment_len)
+            .ok_or("corrupt central directory")?;
+
+        if name.ends_with('/') {
+            continue;
+        }
+        if flags & 1 != 0 {
+            return Err(format!("{}: encrypted entries are not supported", name));
+        }
+        let local_header = bytes.get(local..).ok_or_else(|| format!("{}: corrupt local header", name))?;
+        if u32_at(local_header, 0)? != LOCAL_HEADER_SIGNATURE {
+            return Err(format!("{}: corrupt local header", name));
+        }
+        let data_start = 30 + u16_at(local_header, 26)? as usize + u16_at(local_header, 28)? as usize;
+        let raw = local_header
+            .get(data_start..)
+            .and_then(|data| data.get(..compressed_size))
+            .ok_or_else(|| format!("{}: truncated data", name))?;
+        let data = match method {
+            METHOD_STORED => raw.to_vec(),
+            METHOD_DEFLATED => miniz_oxide::inflate::decompress_to_vec_with_limit(raw, size)
+                .map_err(|_| format!("{}: corrupt deflate stream", name))?,
+            _ => return Err(format!("{}: unsupported compression method {}", name, method)),
+        };
+        if data.len() != size || crc32fast::hash(&data) != crc {
+            return Err(format!("{}: checksum mismatch", name));
+        }
+        entries.push(ZipEntry { name, data, compressed_size });
+    }
+    Ok(entries)
+}
+
+/// Builds a ZIP archive, deflating each file unless storing it is smaller.
+pub fn write_zip(files: &[(String, Vec<u8>)]) -> Result<Vec<u8>, String> {
+    if files.len() >= u16::MAX as usize {
+        return Err("too many files for a zip archive".to_string());
+    }
+    let mut archive = Vec::new();
+    let mut directory = Vec::new();
+    for (name, data) in files {
+        if name.len() > u16::MAX as usize || data.len() >= u32::MAX as usize {
+            return Err(format!("{}: too large for a zip archive", name));
+        }
+        let deflated = miniz_oxide::deflate::compress_to_vec(data, DEFLATE_LEVEL);
+        let (method, payload) = if deflated.len() < data.len() {
+            (METHOD_DEFLATED, deflated.as_slice())
+        } else {
+            (METHOD_STORED, data.as_slice())
+        };
+        let local = archive.len();
+        if local >= u32::MAX as usize {
+            return Err("archive exceeds 4 GiB".to_string());
+        }
+        let crc = crc32fast::hash(data);
+
+        let mut header = Vec::with_capacity(46 + name.len());
+        header.extend_from_slice(&20u16.to_le_bytes());
+        header.extend_from_slice(&FLAG_UTF8.to_le_bytes());
+        header.extend_from_slice(&method.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&DOS_DATE_EPOCH.to_le_bytes());
+        header.extend_from_slice(&crc.to_le_bytes());
+        header.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+
+        archive.extend_from_slice(&LOCAL_HEADER_SIGNATURE.to_le_bytes());
+        archive.extend_from_slice(&header);
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(payload);
+
+        directory.extend_from_slice(&CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+        directory.extend_from_slice(&20u16.to_le_bytes());
+        directory.extend_from_slice(&header);
+        // Comment length, disk number, internal and external attributes.
+        directory.extend_from_slice(&[0; 10]);
+        directory.extend_from_slice(&(local as u32).to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let directory_offset = archive.len();
+    if directory_offset >= u32::MAX as usize {
+        return Err("archive exceeds 4 GiB".to_string());
+    }
+    archive.extend_from_slice(&directory);
+    archive.extend_from_slice(&END_OF_DIRECTORY_SIGNATURE.to_le_bytes());
+    archive.extend_from_slice(&[0; 4]);
+    archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    archive.extend_from_slice(&(directory_offset as u32).to_le_bytes());
+    archive.extend_from_slice(&0u16.to_le_bytes());
+    Ok(archive)
+}
+
+/// Unpacks a ZIP archive (e.g. an uploaded test-case bundle) into an array
+/// of `{ name, size, compressedSize, data }` where `data` is a
+/// `Uint8Array`.
+#[wasm_bindgen]
+pub fn unzip(bytes: &[u8]) -> Result<js_sys::Array, JsValue> {
+    let entries = read_zip(bytes).map_err(|e| JsValue::from_str(&e))?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let object = js_sys::Object::new();
+            let set = |key: &str, value: JsValue| {
+                js_sys::Reflect::set(&object, &JsValue::from_str(key), &value).unwrap();
+            };
+            set("name", entry.name.into());
+            set("size", JsValue::from(entry.data.len() as u32));
+            set("compressedSize", JsValue::from(entry.compressed_size as u32));
+            set("data", js_sys::Uint8Array::from(entry.data.as_slice()).into());
+            JsValue::from(object)
+        })
+        .collect())
+}
+
+/// Packs `files`, an array of `{ name, data }` where `data` is a string or
+/// `Uint8Array`, into ZIP archive bytes ready to wrap in a `Blob` for
+/// download.
+#[wasm_bindgen]
+pub fn create_zip(files: js_sys::Array) -> Result<Vec<u8>, JsValue> {
+    let mut entries = Vec::with_capacity(files.length() as usize);
+    for file in files.iter() {
+        let name = js_sys::Reflect::get(&file, &JsValue::from_str("name"))?
+            .as_string()
+            .ok_or_else(|| JsValue::from_str("zip entry needs a string name"))?;
+        let data = js_sys::Reflect::get(&file, &JsValue::from_str("data"))?;
+        let bytes = match data.as_string() {
+            Some(text) => text.into_bytes(),
+            None => data
+                .dyn_into::<js_sys::Uint8Array>()
+                .map_err(|_| JsValue::from_str(&format!("{}: data must be a string or Uint8Array", name)))?
+                .to_vec(),
+        };
+        entries.push((name, bytes));
+    }
+    write_zip(&entries).map_err(|e| JsValue::from_str(&e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<(String, Vec<u8>)> {
+        vec![
+            ("1.in".to_string(), b"3\n1 2 3\n".to_vec()),
+            ("1.out".to_string(), b"6\n".repeat(1000)),
+            ("题目/说明.txt".to_string(), Vec::new()),
+        ]
+    }
+
+    #[test]
+    fn round_trips() {
+        let files = sample();
+        let entries = read_zip(&write_zip(&files).unwrap()).unwrap();
+        assert_eq!(entries.len(), files.len());
+        for (entry, (name, data)) in entries.iter().zip(&files) {
+            assert_eq!(&entry.name, name);
+            assert_eq!(&entry.data, data);
+        }
+        // The repetitive file is deflated, the short one stored.
+        assert!(entries[1].compressed_size < entries[1].data.len());
+        assert_eq!(entries[0].compressed_size, entries[0].data.len());
+    }
+
+    #[test]
+    fn rejects_corrupt_archives() {
+        let mut archive = write_zip(&sample()).unwrap();
+        archive[0] ^= 0xff;
+        assert_eq!(read_zip(&archive).err().unwrap(), "1.in: corrupt local header");
+
+        let mut archive = write_zip(&sample()).unwrap();
+        // Flip a byte of the stored "1.in" payload, after its 30-byte header and name.
+        archive[30 + 4] ^= 1;
+        assert_eq!(read_zip(&archive).err().unwrap(), "1.in: checksum mismatch");
+
+        assert!(read_zip(b"PK").is_err());
+
+        // Sizes and offsets near the top of the address space must not wrap.
+        let archive = write_zip(&sample()).unwrap();
+        let directory = u32_at(&archive, archive.len() - END_OF_DIRECTORY_SIZE + 16).unwrap() as usize;
+        let mut oversized = archive.clone();
+        oversized[directory + 20..directory + 24].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(read_zip(&oversized).err().unwrap(), "1.in: truncated data");
+        let mut misplaced = archive.clone();
+        misplaced[directory + 42..directory + 46].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(read_zip(&misplaced).err().unwrap(), "1.in: corrupt local header");
+        let archive = write_zip(&sample()).unwrap();
+        assert!(read_zip(&archive[..archive.len() - 1]).is_err());
+    }
+}